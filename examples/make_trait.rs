@@ -1,10 +1,9 @@
 use parity_scale_codec::Encode;
-use protos::traits::Trait;
-use sp_core::twox_64;
+use protos::traits::{from_json_strict, Trait};
 use std::env;
 
 fn main() {
-  let json_file = env::args().skip(1).next();
+  let json_file = env::args().nth(1);
   let json_file = match json_file {
     Some(file) => file,
     None => {
@@ -17,19 +16,29 @@ fn main() {
 
   println!("JSON: {}", json);
 
-  let mut t: Trait = serde_json::from_str(&json).unwrap();
+  let mut t: Trait = from_json_strict(&json).unwrap();
+
+  if let Err(errors) = t.validate() {
+    for error in errors {
+      println!("Invalid trait: {}", error);
+    }
+    return;
+  }
 
   // THIS IS the way we reprocess the trait declaration before sorting it on chain and hashing it
-  t.records = t
-    .records
-    .into_iter()
-    .map(|r| (r.name.to_lowercase(), r.types).into())
-    .collect();
-  t.records.dedup_by(|a, b| a.name == b.name);
-  t.records.sort_by(|a, b| a.name.cmp(&b.name));
+  let report = t.canonicalize_with_report();
+  for (original, canonical) in &report.renamed {
+    println!("Record {} renamed to {}", original, canonical);
+  }
+  for name in &report.dropped_duplicates {
+    println!("Duplicate record {} dropped", name);
+  }
+  if report.types_normalized {
+    println!("Nested types normalized");
+  }
 
   let binary_trait = t.encode();
   println!("SCALE encoded trait: 0x{}", hex::encode(&binary_trait));
 
-  println!("Trait hash: 0x{}", hex::encode(&twox_64(&binary_trait)));
+  println!("Trait hash: 0x{}", hex::encode(t.shards_hash()));
 }
@@ -44,7 +44,7 @@ module.exports = {
         Categories: {
             _enum: {
                 "text": "TextCategories",
-                "trait": "Option<ShardsTrait>",
+                "trait": "TraitRef",
                 "shards": "ShardsScriptInfo",
                 "audio": "AudioCategories",
                 "texture": "TextureCategories",
@@ -108,6 +108,17 @@ module.exports = {
             implementing: "Vec<ShardsTrait>"
         },
         ShardsTrait: "Vec<u16>",
+        TraitRef: {
+            _enum: {
+                "any": null,
+                "hash": "ShardsTrait",
+                "full": "TraitRefFull",
+            }
+        },
+        TraitRefFull: {
+            hash: "ShardsTrait",
+            revision: "Option<u32>",
+        },
         ShardsFormat: {
             _enum: [
                 "edn",
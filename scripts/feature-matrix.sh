@@ -0,0 +1,25 @@
+#!/usr/bin/env bash
+# Checks that every optional capability builds on its own, without std and with the default
+# features, so gated modules never depend on each other by accident.
+set -euo pipefail
+cd "$(dirname "$0")/.."
+
+capabilities=(hashing registry values probe testgen)
+extra_args=("$@")
+
+check() {
+  echo "==> cargo check $*"
+  cargo check --lib "${extra_args[@]}" "$@"
+}
+
+check --no-default-features
+check --no-default-features --features serde
+for capability in "${capabilities[@]}"; do
+  check --no-default-features --features "$capability"
+  check --features "$capability"
+done
+check --no-default-features --features full
+check --all-features
+
+echo "==> cargo test --features full"
+cargo test "${extra_args[@]}" --features full
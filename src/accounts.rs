@@ -0,0 +1,239 @@
+use crate::fragments::Hash256;
+use parity_scale_codec::{Compact, Decode, Encode};
+use scale_info::prelude::vec::Vec;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Prefix of every account link message, so link signatures cannot be replayed as other messages.
+pub const LINK_DOMAIN: &[u8] = b"fragnova-protos:link-account";
+
+/// An identity of an external ecosystem that can be linked to a Fragnova account.
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub enum ExternalIdentity {
+  /// An EVM address (Ethereum and compatible chains)
+  Evm([u8; 20]),
+  /// An ed25519 public key (e.g. Solana, NEAR)
+  Ed25519([u8; 32]),
+  /// An sr25519 public key (Substrate based chains)
+  Sr25519([u8; 32]),
+}
+
+/// A request to link an external identity to a Fragnova account.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct AccountLink<TAccountId> {
+  /// The genesis hash of the chain the link is made on
+  pub genesis_hash: Hash256,
+  /// The Fragnova account
+  pub account: TAccountId,
+  /// The identity linked to the account
+  pub identity: ExternalIdentity,
+  /// Nonce preventing the link from being replayed
+  #[codec(compact)]
+  pub nonce: u64,
+}
+
+/// The chain state an `AccountLink` is verified against.
+#[derive(Clone, PartialEq, Debug, Eq)]
+pub struct LinkContext {
+  /// The genesis hash of the chain verifying the link
+  pub genesis_hash: Hash256,
+  /// The link nonce of the account, incremented by the chain on every link
+  pub nonce: u64,
+}
+
+/// Errors returned by `AccountLink::verify`.
+#[derive(Copy, Clone, PartialEq, Debug, Eq)]
+pub enum LinkError {
+  /// The link was signed for another chain
+  WrongChain,
+  /// The link was already used
+  NonceUsed,
+  /// The signature is not the one of the external identity
+  InvalidSignature,
+}
+
+impl core::fmt::Display for LinkError {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    match self {
+      Self::WrongChain => write!(f, "account link signed for another chain"),
+      Self::NonceUsed => write!(f, "account link already used"),
+      Self::InvalidSignature => write!(f, "account link not signed by the external identity"),
+    }
+  }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for LinkError {}
+
+impl<TAccountId: Encode> AccountLink<TAccountId> {
+  /// The message identifying the link: [`LINK_DOMAIN`] followed by every field of the link.
+  pub fn message(&self) -> Vec<u8> {
+    let mut message = LINK_DOMAIN.to_vec();
+    self.genesis_hash.encode_to(&mut message);
+    self.account.encode_to(&mut message);
+    self.identity.encode_to(&mut message);
+    Compact(self.nonce).encode_to(&mut message);
+    message
+  }
+
+  /// The bytes the external identity wallet actually signs for the link `message`.
+  ///
+  /// EVM wallets sign following EIP-191 (`personal_sign`), Substrate wallets wrap raw payloads in `<Bytes>` tags,
+  /// ed25519 wallets sign the message as is.
+  pub fn signing_payload(&self) -> Vec<u8> {
+    let message = self.message();
+    match self.identity {
+      ExternalIdentity::Evm(_) => {
+        let mut payload = b"\x19Ethereum Signed Message:\n".to_vec();
+        push_decimal(&mut payload, message.len());
+        payload.extend_from_slice(&message);
+        payload
+      }
+      ExternalIdentity::Sr25519(_) => {
+        let mut payload = b"<Bytes>".to_vec();
+        payload.extend_from_slice(&message);
+        payload.extend_from_slice(b"</Bytes>");
+        payload
+      }
+      ExternalIdentity::Ed25519(_) => message,
+    }
+  }
+
+  /// Verifies that the link can be made in `context` and that `signature` is the signature of
+  /// the external identity over the `signing_payload`.
+  ///
+  /// The crate has no signature schemes, `verify_signature` is called with the identity, the
+  /// signing payload and `signature` and returns whether the signature is valid. Making the link
+  /// must increment the nonce of the account, so the link cannot be replayed.
+  pub fn verify<F>(
+    &self,
+    context: &LinkContext,
+    signature: &[u8],
+    verify_signature: F,
+  ) -> Result<(), LinkError>
+  where
+    F: FnOnce(&ExternalIdentity, &[u8], &[u8]) -> bool,
+  {
+    if self.genesis_hash != context.genesis_hash {
+      return Err(LinkError::WrongChain);
+    }
+    if self.nonce != context.nonce {
+      return Err(LinkError::NonceUsed);
+    }
+    if !verify_signature(&self.identity, &self.signing_payload(), signature) {
+      return Err(LinkError::InvalidSignature);
+    }
+    Ok(())
+  }
+}
+
+fn push_decimal(dest: &mut Vec<u8>, mut value: usize) {
+  let start = dest.len();
+  loop {
+    dest.push(b'0' + (value % 10) as u8);
+    value /= 10;
+    if value == 0 {
+      break;
+    }
+  }
+  dest[start..].reverse();
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn link(identity: ExternalIdentity) -> AccountLink<[u8; 32]> {
+    AccountLink {
+      genesis_hash: [7; 32],
+      account: [1u8; 32],
+      identity,
+      nonce: 0,
+    }
+  }
+
+  #[test]
+  fn encode_decode_link() {
+    let link = link(ExternalIdentity::Evm([0xab; 20]));
+    let encoded = link.encode();
+    assert_eq!(AccountLink::decode(&mut encoded.as_slice()).unwrap(), link);
+
+    let json = serde_json::to_string(&link).unwrap();
+    assert_eq!(
+      serde_json::from_str::<AccountLink<[u8; 32]>>(&json).unwrap(),
+      link
+    );
+  }
+
+  #[test]
+  fn link_messages() {
+    let evm = link(ExternalIdentity::Evm([0xab; 20]));
+    let message = evm.message();
+    assert!(message.starts_with(LINK_DOMAIN));
+    assert_eq!(message[LINK_DOMAIN.len()..][..32], [7; 32]);
+
+    let payload = evm.signing_payload();
+    let header = format!("\x19Ethereum Signed Message:\n{}", message.len());
+    assert!(payload.starts_with(header.as_bytes()));
+    assert!(payload.ends_with(&message));
+
+    let sr25519 = link(ExternalIdentity::Sr25519([2; 32]));
+    let payload = sr25519.signing_payload();
+    assert!(payload.starts_with(b"<Bytes>") && payload.ends_with(b"</Bytes>"));
+
+    let ed25519 = link(ExternalIdentity::Ed25519([2; 32]));
+    assert_eq!(ed25519.signing_payload(), ed25519.message());
+    assert_ne!(ed25519.message(), sr25519.message());
+
+    let mut testnet = ed25519.clone();
+    testnet.genesis_hash = [8; 32];
+    assert_ne!(testnet.message(), ed25519.message());
+  }
+
+  #[test]
+  fn verify_links() {
+    let link = link(ExternalIdentity::Ed25519([2; 32]));
+    let context = LinkContext {
+      genesis_hash: [7; 32],
+      nonce: 0,
+    };
+    // a signature scheme where the signature is the signed payload
+    let verify = |identity: &ExternalIdentity, payload: &[u8], signature: &[u8]| {
+      *identity == ExternalIdentity::Ed25519([2; 32]) && payload == signature
+    };
+    let signature = link.signing_payload();
+    assert_eq!(link.verify(&context, &signature, verify), Ok(()));
+    assert_eq!(
+      link.verify(&context, b"forged", verify),
+      Err(LinkError::InvalidSignature)
+    );
+
+    let mainnet = LinkContext {
+      genesis_hash: [8; 32],
+      ..context.clone()
+    };
+    assert_eq!(
+      link.verify(&mainnet, &signature, verify),
+      Err(LinkError::WrongChain)
+    );
+    let used = LinkContext {
+      nonce: 1,
+      ..context
+    };
+    assert_eq!(
+      link.verify(&used, &signature, verify),
+      Err(LinkError::NonceUsed)
+    );
+  }
+}
@@ -0,0 +1,161 @@
+use crate::{fragments::Hash128, permissions::FragmentPerms};
+use parity_scale_codec::{Decode, Encode};
+use scale_info::prelude::vec::Vec;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Who a permission grant applies to.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, PartialOrd, Ord, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub enum Principal<TAccountId> {
+  /// Everybody
+  Anyone,
+  /// A single account
+  Account(TAccountId),
+  /// Any holder of an instance of the fragment definition
+  FragmentHolder(Hash128),
+}
+
+/// Permissions granted to a principal.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct PermissionGrant<TAccountId> {
+  pub principal: Principal<TAccountId>,
+  pub perms: FragmentPerms,
+}
+
+/// An access control list, gating access to proto content off-chain consistently with on-chain rights.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct Acl<TAccountId> {
+  pub grants: Vec<PermissionGrant<TAccountId>>,
+}
+
+impl<TAccountId> Default for Acl<TAccountId> {
+  fn default() -> Self {
+    Self { grants: Vec::new() }
+  }
+}
+
+impl<TAccountId: Ord + Clone> Acl<TAccountId> {
+  /// Adds a grant, permissions are merged with the ones already granted to the same principal.
+  pub fn grant(&mut self, principal: Principal<TAccountId>, perms: FragmentPerms) {
+    self.grants.push(PermissionGrant { principal, perms });
+    self.normalize();
+  }
+
+  /// Brings the list to its canonical form: a single grant per principal, sorted by principal,
+  /// without grants of no permissions.
+  pub fn normalize(&mut self) {
+    self.grants.sort_by(|a, b| a.principal.cmp(&b.principal));
+    self.grants.dedup_by(|next, kept| {
+      if next.principal == kept.principal {
+        kept.perms |= next.perms;
+        true
+      } else {
+        false
+      }
+    });
+    self.grants.retain(|grant| !grant.perms.is_empty());
+  }
+
+  /// The permissions granted to `principal`, including the ones granted to anyone.
+  pub fn perms_of(&self, principal: &Principal<TAccountId>) -> FragmentPerms {
+    self
+      .grants
+      .iter()
+      .filter(|grant| grant.principal == *principal || grant.principal == Principal::Anyone)
+      .fold(FragmentPerms::NONE, |perms, grant| perms | grant.perms)
+  }
+
+  /// Whether `principal` is granted all of `perms`.
+  pub fn allows(&self, principal: &Principal<TAccountId>, perms: FragmentPerms) -> bool {
+    self.perms_of(principal).contains(perms)
+  }
+
+  /// Whether `account`, holding instances of the `held` fragment definitions, is granted all of `perms`.
+  pub fn allows_account(
+    &self,
+    account: &TAccountId,
+    held: &[Hash128],
+    perms: FragmentPerms,
+  ) -> bool {
+    let granted = self
+      .grants
+      .iter()
+      .filter(|grant| match &grant.principal {
+        Principal::Anyone => true,
+        Principal::Account(granted) => granted == account,
+        Principal::FragmentHolder(definition) => held.contains(definition),
+      })
+      .fold(FragmentPerms::NONE, |perms, grant| perms | grant.perms);
+    granted.contains(perms)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn acl() -> Acl<u64> {
+    let mut acl = Acl::default();
+    acl.grant(Principal::Account(1), FragmentPerms::EDIT);
+    acl.grant(Principal::FragmentHolder([7; 16]), FragmentPerms::COPY);
+    acl.grant(Principal::Account(1), FragmentPerms::TRANSFER);
+    acl.grant(Principal::Account(2), FragmentPerms::NONE);
+    acl
+  }
+
+  #[test]
+  fn normalized_grants() {
+    let acl = acl();
+    assert_eq!(
+      acl.grants,
+      vec![
+        PermissionGrant {
+          principal: Principal::Account(1),
+          perms: FragmentPerms::EDIT | FragmentPerms::TRANSFER,
+        },
+        PermissionGrant {
+          principal: Principal::FragmentHolder([7; 16]),
+          perms: FragmentPerms::COPY,
+        },
+      ]
+    );
+
+    let encoded = acl.encode();
+    assert_eq!(Acl::decode(&mut encoded.as_slice()).unwrap(), acl);
+    let json = serde_json::to_string(&acl).unwrap();
+    assert_eq!(serde_json::from_str::<Acl<u64>>(&json).unwrap(), acl);
+  }
+
+  #[test]
+  fn evaluate_grants() {
+    let mut acl = acl();
+    assert!(acl.allows(
+      &Principal::Account(1),
+      FragmentPerms::EDIT | FragmentPerms::TRANSFER
+    ));
+    assert!(!acl.allows(&Principal::Account(1), FragmentPerms::ALL));
+    assert!(!acl.allows(&Principal::Account(2), FragmentPerms::EDIT));
+    assert!(acl.allows_account(&1, &[[7; 16]], FragmentPerms::ALL));
+    assert!(!acl.allows_account(&2, &[[8; 16]], FragmentPerms::COPY));
+
+    acl.grant(Principal::Anyone, FragmentPerms::COPY);
+    assert!(acl.allows(&Principal::Account(2), FragmentPerms::COPY));
+    assert!(acl.allows_account(&2, &[], FragmentPerms::COPY));
+  }
+}
@@ -0,0 +1,283 @@
+use crate::{cid::Cid, hashing::blake2_256};
+use core::{fmt, str::FromStr};
+use parity_scale_codec::{Decode, Encode};
+use scale_info::prelude::{string::String, vec::Vec};
+use sha2::{Digest, Sha256};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+const BASE64URL_ALPHABET: &[u8; 64] =
+  b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// An Arweave transaction id, represented in strings as 43 unpadded base64url characters.
+#[derive(
+  Encode, Decode, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, scale_info::TypeInfo,
+)]
+pub struct ArweaveTxId(pub [u8; 32]);
+
+/// Errors returned when parsing an invalid Arweave transaction id.
+#[derive(Copy, Clone, PartialEq, Debug, Eq)]
+pub enum ArweaveTxIdError {
+  /// The id is not 43 characters long
+  InvalidLength,
+  /// The id contains characters outside of the base64url alphabet, or is not canonically encoded
+  InvalidEncoding,
+}
+
+impl fmt::Display for ArweaveTxIdError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::InvalidLength => write!(f, "arweave transaction ids are 43 characters long"),
+      Self::InvalidEncoding => write!(f, "invalid base64url encoding"),
+    }
+  }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ArweaveTxIdError {}
+
+impl FromStr for ArweaveTxId {
+  type Err = ArweaveTxIdError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    if s.len() != 43 {
+      return Err(ArweaveTxIdError::InvalidLength);
+    }
+
+    let mut id = [0u8; 32];
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    let mut i = 0;
+    for c in s.bytes() {
+      let value = BASE64URL_ALPHABET
+        .iter()
+        .position(|a| *a == c)
+        .ok_or(ArweaveTxIdError::InvalidEncoding)? as u32;
+      buffer = (buffer << 6) | value;
+      bits += 6;
+      if bits >= 8 {
+        bits -= 8;
+        id[i] = (buffer >> bits) as u8;
+        buffer &= (1 << bits) - 1;
+        i += 1;
+      }
+    }
+    // 43 characters carry 2 bits more than 32 bytes, they must be zero
+    if buffer != 0 {
+      return Err(ArweaveTxIdError::InvalidEncoding);
+    }
+    Ok(Self(id))
+  }
+}
+
+impl fmt::Display for ArweaveTxId {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for byte in self.0 {
+      buffer = (buffer << 8) | byte as u32;
+      bits += 8;
+      while bits >= 6 {
+        bits -= 6;
+        write!(
+          f,
+          "{}",
+          BASE64URL_ALPHABET[(buffer >> bits) as usize & 63] as char
+        )?;
+      }
+      buffer &= (1 << bits) - 1;
+    }
+    write!(
+      f,
+      "{}",
+      BASE64URL_ALPHABET[(buffer << (6 - bits)) as usize & 63] as char
+    )
+  }
+}
+
+impl fmt::Debug for ArweaveTxId {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "ArweaveTxId({})", self)
+  }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for ArweaveTxId {
+  fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(self)
+  }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for ArweaveTxId {
+  fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    let s = <scale_info::prelude::borrow::Cow<'de, str> as Deserialize>::deserialize(deserializer)?;
+    s.parse().map_err(serde::de::Error::custom)
+  }
+}
+
+/// Hash functions that can be used to commit to off-chain data.
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub enum HashAlgorithm {
+  /// Blake2b with a 256 bits output, the same hash used for proto hashes
+  Blake2b256,
+  /// Sha2 with a 256 bits output, as used by Subresource Integrity
+  Sha256,
+}
+
+impl HashAlgorithm {
+  /// Hashes `bytes` with this algorithm.
+  pub fn digest(&self, bytes: &[u8]) -> [u8; 32] {
+    match self {
+      Self::Blake2b256 => blake2_256(bytes),
+      Self::Sha256 => Sha256::digest(bytes).into(),
+    }
+  }
+}
+
+/// A commitment to the content of off-chain data.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct Integrity {
+  /// The hash function used to compute the digest
+  pub algorithm: HashAlgorithm,
+  /// The digest of the data
+  pub digest: Vec<u8>,
+}
+
+impl Integrity {
+  /// Computes the integrity of `bytes` using `algorithm`.
+  pub fn new(algorithm: HashAlgorithm, bytes: &[u8]) -> Self {
+    Self {
+      algorithm,
+      digest: algorithm.digest(bytes).to_vec(),
+    }
+  }
+
+  /// Whether `bytes` match the committed digest.
+  pub fn verify(&self, bytes: &[u8]) -> bool {
+    self.digest[..] == self.algorithm.digest(bytes)[..]
+  }
+}
+
+/// How gateways are allowed to cache data fetched from a URL.
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub enum FetchPolicy {
+  /// The data never changes, it can be cached forever once verified
+  Immutable,
+  /// The data must be fetched and verified again before every use
+  Revalidate,
+}
+
+/// Proto data stored outside of the chain.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase", rename_all_fields = "camelCase")
+)]
+pub enum LinkedAsset {
+  /// Data stored on IPFS
+  Ipfs(Cid),
+  /// Data stored on Arweave
+  Arweave(ArweaveTxId),
+  /// Data served from a URL, verified against the integrity committed on chain
+  Url {
+    url: String,
+    integrity: Integrity,
+    fetch_policy: Option<FetchPolicy>,
+  },
+}
+
+impl LinkedAsset {
+  /// Verifies that `bytes` are the data committed to by this link.
+  ///
+  /// Returns `None` for content addressed links, that are verified by their own protocols.
+  pub fn verify(&self, bytes: &[u8]) -> Option<bool> {
+    match self {
+      Self::Url { integrity, .. } => Some(integrity.verify(bytes)),
+      Self::Ipfs(_) | Self::Arweave(_) => None,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const TX_ID: &str = "bNbA3TEQVL60xlgCcqdz4ZPHFZ711cZ3hmkpGttDt_U";
+
+  #[test]
+  fn parse_arweave_tx_id() {
+    let id: ArweaveTxId = TX_ID.parse().unwrap();
+    assert_eq!(id.to_string(), TX_ID);
+    assert_eq!(id.0[0], 0x6c);
+
+    assert_eq!(
+      TX_ID[1..].parse::<ArweaveTxId>(),
+      Err(ArweaveTxIdError::InvalidLength)
+    );
+    assert_eq!(
+      TX_ID.replace('_', "/").parse::<ArweaveTxId>(),
+      Err(ArweaveTxIdError::InvalidEncoding)
+    );
+    // the trailing bits of the last character must be zero
+    assert_eq!(
+      TX_ID.replace("_U", "_V").parse::<ArweaveTxId>(),
+      Err(ArweaveTxIdError::InvalidEncoding)
+    );
+  }
+
+  #[test]
+  fn url_integrity() {
+    let data = b"proto data";
+    let asset = LinkedAsset::Url {
+      url: "https://example.com/proto.bin".to_string(),
+      integrity: Integrity::new(HashAlgorithm::Sha256, data),
+      fetch_policy: Some(FetchPolicy::Immutable),
+    };
+    assert_eq!(asset.verify(data), Some(true));
+    assert_eq!(asset.verify(b"tampered data"), Some(false));
+    assert_eq!(
+      LinkedAsset::Arweave(TX_ID.parse().unwrap()).verify(data),
+      None
+    );
+
+    // known answer from `echo -n abc | sha256sum`
+    assert_eq!(
+      HashAlgorithm::Sha256.digest(b"abc")[..4],
+      [0xba, 0x78, 0x16, 0xbf]
+    );
+    assert!(Integrity::new(HashAlgorithm::Blake2b256, data).verify(data));
+
+    let json = serde_json::to_string(&asset).unwrap();
+    assert!(json.contains(r#""fetchPolicy":"#));
+    assert_eq!(serde_json::from_str::<LinkedAsset>(&json).unwrap(), asset);
+  }
+
+  #[test]
+  fn linked_asset_json() {
+    let asset = LinkedAsset::Arweave(TX_ID.parse().unwrap());
+    let json = serde_json::to_string(&asset).unwrap();
+    assert_eq!(json, format!("{{\"arweave\":\"{}\"}}", TX_ID));
+    assert_eq!(serde_json::from_str::<LinkedAsset>(&json).unwrap(), asset);
+
+    let encoded = asset.encode();
+    assert_eq!(LinkedAsset::decode(&mut encoded.as_slice()).unwrap(), asset);
+  }
+}
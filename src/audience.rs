@@ -0,0 +1,221 @@
+//! Machine-readable audience descriptors, so platforms filter protos for their audiences the same
+//! way instead of relying on free-form tags.
+
+use core::{fmt, str::FromStr};
+use parity_scale_codec::{Decode, Encode, Error, Input};
+use scale_info::prelude::vec::Vec;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The audience a proto is suitable for, ordered from the widest to the narrowest.
+#[derive(
+  Encode,
+  Decode,
+  Copy,
+  Clone,
+  PartialEq,
+  Debug,
+  Eq,
+  PartialOrd,
+  Ord,
+  Hash,
+  Default,
+  scale_info::TypeInfo,
+)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub enum ContentRating {
+  /// Suitable for every age
+  #[default]
+  Everyone,
+  /// Suitable for teenagers and adults
+  Teen,
+  /// Suitable for adults only
+  Mature,
+  /// Explicit content for adults only
+  Explicit,
+}
+
+/// An ISO 3166-1 alpha-2 country code, represented in strings as two uppercase letters.
+#[derive(Encode, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, scale_info::TypeInfo)]
+pub struct Region(pub [u8; 2]);
+
+/// Errors returned when parsing an invalid region code.
+#[derive(Copy, Clone, PartialEq, Debug, Eq)]
+pub struct RegionError;
+
+impl fmt::Display for RegionError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "regions are two uppercase ASCII letters")
+  }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RegionError {}
+
+impl TryFrom<[u8; 2]> for Region {
+  type Error = RegionError;
+
+  fn try_from(code: [u8; 2]) -> Result<Self, Self::Error> {
+    if code.iter().all(u8::is_ascii_uppercase) {
+      Ok(Self(code))
+    } else {
+      Err(RegionError)
+    }
+  }
+}
+
+impl FromStr for Region {
+  type Err = RegionError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match *s.as_bytes() {
+      [a, b] => Self::try_from([a, b]),
+      _ => Err(RegionError),
+    }
+  }
+}
+
+impl Decode for Region {
+  fn decode<I: Input>(input: &mut I) -> Result<Self, Error> {
+    Self::try_from(<[u8; 2]>::decode(input)?).map_err(|_| "Invalid region".into())
+  }
+}
+
+impl fmt::Display for Region {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}{}", self.0[0] as char, self.0[1] as char)
+  }
+}
+
+impl fmt::Debug for Region {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "Region({})", self)
+  }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for Region {
+  fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(self)
+  }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Region {
+  fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    let s = <scale_info::prelude::borrow::Cow<'de, str> as Deserialize>::deserialize(deserializer)?;
+    s.parse().map_err(serde::de::Error::custom)
+  }
+}
+
+/// The regions a proto can be shown in.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, Default, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub enum RegionRestriction {
+  /// Every region
+  #[default]
+  None,
+  /// Only these regions
+  AllowOnly(Vec<Region>),
+  /// Every region but these
+  Deny(Vec<Region>),
+}
+
+impl RegionRestriction {
+  /// Whether the proto can be shown in `region`, `None` when the region of the viewer is unknown.
+  ///
+  /// Viewers of unknown regions are only allowed when the proto is not restricted to some regions.
+  pub fn allows(&self, region: Option<Region>) -> bool {
+    match (self, region) {
+      (Self::None, _) => true,
+      (Self::AllowOnly(regions), Some(region)) => regions.contains(&region),
+      (Self::AllowOnly(_), None) => false,
+      (Self::Deny(regions), Some(region)) => !regions.contains(&region),
+      (Self::Deny(_), None) => true,
+    }
+  }
+}
+
+/// The audience of a proto, stored as proto metadata.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, Default, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct AudienceDescriptor {
+  /// The narrowest audience the proto is suitable for
+  pub rating: ContentRating,
+  /// The regions the proto can be shown in
+  pub regions: RegionRestriction,
+}
+
+impl AudienceDescriptor {
+  /// The metadata key under which the SCALE encoding of the descriptor is attached to a proto.
+  pub const METADATA_KEY: &'static str = "audience";
+
+  /// Whether the proto can be shown to a viewer accepting ratings up to `max_rating` in `region`.
+  pub fn allows(&self, max_rating: ContentRating, region: Option<Region>) -> bool {
+    self.rating <= max_rating && self.regions.allows(region)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn regions() {
+    let us: Region = "US".parse().unwrap();
+    assert_eq!(us, Region(*b"US"));
+    assert_eq!(us.to_string(), "US");
+    assert_eq!("us".parse::<Region>(), Err(RegionError));
+    assert_eq!("USA".parse::<Region>(), Err(RegionError));
+    assert_eq!(serde_json::to_string(&us).unwrap(), r#""US""#);
+    assert!(serde_json::from_str::<Region>(r#""U1""#).is_err());
+    assert_eq!(Region::decode(&mut &b"US"[..]), Ok(us));
+    assert!(Region::decode(&mut &b"us"[..]).is_err());
+
+    let de = Region(*b"DE");
+    assert!(RegionRestriction::None.allows(None));
+    assert!(RegionRestriction::AllowOnly(vec![us]).allows(Some(us)));
+    assert!(!RegionRestriction::AllowOnly(vec![us]).allows(Some(de)));
+    assert!(!RegionRestriction::AllowOnly(vec![us]).allows(None));
+    assert!(!RegionRestriction::Deny(vec![de]).allows(Some(de)));
+    assert!(RegionRestriction::Deny(vec![de]).allows(None));
+  }
+
+  #[test]
+  fn descriptors() {
+    let descriptor = AudienceDescriptor {
+      rating: ContentRating::Mature,
+      regions: RegionRestriction::Deny(vec![Region(*b"DE")]),
+    };
+    assert!(descriptor.allows(ContentRating::Explicit, Some(Region(*b"US"))));
+    assert!(!descriptor.allows(ContentRating::Teen, Some(Region(*b"US"))));
+    assert!(!descriptor.allows(ContentRating::Mature, Some(Region(*b"DE"))));
+    assert!(AudienceDescriptor::default().allows(ContentRating::Everyone, None));
+
+    let encoded = descriptor.encode();
+    assert_eq!(encoded, [2, 2, 4, b'D', b'E']);
+    assert_eq!(
+      AudienceDescriptor::decode(&mut &encoded[..]),
+      Ok(descriptor.clone())
+    );
+    let json = serde_json::to_string(&descriptor).unwrap();
+    assert_eq!(json, r#"{"rating":"mature","regions":{"deny":["DE"]}}"#);
+    assert_eq!(
+      serde_json::from_str::<AudienceDescriptor>(&json).unwrap(),
+      descriptor
+    );
+  }
+}
@@ -0,0 +1,106 @@
+//! Decoding of many traits at once, for indexers processing full storage snapshots.
+
+use crate::{categories::ShardsTrait, limits::DecodeLimits, traits::Trait};
+use parity_scale_codec::Error;
+use scale_info::prelude::vec::Vec;
+
+/// A decoded trait and its `shards_hash`.
+#[derive(Clone, PartialEq, Debug, Eq)]
+pub struct HashedTrait {
+  pub hash: ShardsTrait,
+  pub trait_: Trait,
+}
+
+/// Decodes and hashes one SCALE encoded trait within `limits`.
+pub fn decode_trait(bytes: &[u8], limits: &DecodeLimits) -> Result<HashedTrait, Error> {
+  let trait_: Trait = limits.decode(bytes)?;
+  Ok(HashedTrait {
+    hash: trait_.shards_hash(),
+    trait_,
+  })
+}
+
+/// Decodes and hashes every SCALE encoded trait of `iter` within `limits`, lazily and in order.
+pub fn decode_traits<'a>(
+  iter: impl Iterator<Item = Vec<u8>> + 'a,
+  limits: &'a DecodeLimits,
+) -> impl Iterator<Item = Result<HashedTrait, Error>> + 'a {
+  iter.map(move |bytes| decode_trait(&bytes, limits))
+}
+
+/// Same as [`decode_traits`] on every available core, the results are in the order of `items`.
+#[cfg(feature = "std")]
+pub fn decode_traits_parallel(
+  items: &[Vec<u8>],
+  limits: &DecodeLimits,
+) -> Vec<Result<HashedTrait, Error>> {
+  let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+  let chunk_len = items.len().div_ceil(threads).max(1);
+  std::thread::scope(|scope| {
+    let workers: Vec<_> = items
+      .chunks(chunk_len)
+      .map(|chunk| {
+        scope.spawn(move || {
+          chunk
+            .iter()
+            .map(|bytes| decode_trait(bytes, limits))
+            .collect::<Vec<_>>()
+        })
+      })
+      .collect();
+    workers
+      .into_iter()
+      .flat_map(|worker| worker.join().expect("decoding never panics"))
+      .collect()
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::traits::{Record, VariableType, VariableTypeInfo};
+  use parity_scale_codec::Encode;
+
+  fn encoded(n: usize) -> Vec<Vec<u8>> {
+    (0..n)
+      .map(|i| {
+        if i % 3 == 2 {
+          // truncated
+          return Vec::from([4, 1]);
+        }
+        let record = Record::from((
+          format!("record{}", i),
+          vec![VariableTypeInfo {
+            type_: VariableType::Int(None),
+            default: None,
+          }],
+        ));
+        Trait {
+          name: format!("Trait{}", i),
+          records: vec![record],
+        }
+        .encode()
+      })
+      .collect()
+  }
+
+  #[test]
+  fn batch_decoding() {
+    let items = encoded(10);
+    let limits = DecodeLimits::default();
+    let decoded: Vec<_> = decode_traits(items.clone().into_iter(), &limits).collect();
+    assert_eq!(decoded.len(), 10);
+    for (i, result) in decoded.iter().enumerate() {
+      match result {
+        Ok(hashed) => {
+          assert_eq!(hashed.trait_.name, format!("Trait{}", i));
+          assert_eq!(hashed.hash, hashed.trait_.shards_hash());
+        }
+        Err(_) => assert_eq!(i % 3, 2),
+      }
+    }
+
+    assert_eq!(decode_traits_parallel(&items, &limits), decoded);
+    assert!(decode_traits_parallel(&[], &limits).is_empty());
+  }
+}
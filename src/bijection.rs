@@ -0,0 +1,260 @@
+//! Checks that a JSON payload survives both the JSON and the SCALE pipelines unchanged, so
+//! integrators can certify their payloads with a single call.
+
+use parity_scale_codec::{Decode, Encode};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{Map, Value as Json};
+
+/// The step of the round trip losing a field.
+#[derive(Copy, Clone, PartialEq, Debug, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Stage {
+  /// Parsing the JSON into the type
+  Json,
+  /// Encoding the type to SCALE and decoding it back
+  Scale,
+}
+
+/// How a field differs after the round trip.
+#[derive(Clone, PartialEq, Debug, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Change {
+  /// The key is not part of the type, e.g. a misspelled or legacy field name
+  Dropped(Json),
+  /// The key was missing and got a value, `null` values are not reported
+  Added(Json),
+  /// The value is read differently, e.g. `default` bytes given in another representation
+  Changed { before: Json, after: Json },
+}
+
+/// A field not surviving the round trip.
+#[derive(Clone, PartialEq, Debug, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LossyField {
+  /// The JSON pointer of the field, `""` for the whole document
+  pub path: String,
+  pub stage: Stage,
+  pub change: Change,
+}
+
+/// The result of [`verify_bijection`].
+#[derive(Clone, PartialEq, Debug, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Equivalence {
+  /// The canonical JSON after the round trip, with sorted keys and no whitespace
+  pub canonical: String,
+  /// The fields differing between the input and `canonical`, in document order
+  pub lossy: Vec<LossyField>,
+}
+
+impl Equivalence {
+  /// Whether the payload survives the round trip without any loss.
+  pub fn is_exact(&self) -> bool {
+    self.lossy.is_empty()
+  }
+}
+
+/// Errors returned by `verify_bijection`.
+#[derive(Clone, PartialEq, Debug, Eq)]
+pub enum BijectionError {
+  /// The input is not well-formed JSON or does not describe the type
+  InvalidJson(String),
+  /// The SCALE encoding of the value cannot be decoded back
+  Codec(parity_scale_codec::Error),
+}
+
+impl core::fmt::Display for BijectionError {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    match self {
+      Self::InvalidJson(error) => write!(f, "invalid JSON: {}", error),
+      Self::Codec(error) => write!(f, "SCALE round trip failed: {}", error),
+    }
+  }
+}
+
+impl std::error::Error for BijectionError {}
+
+/// Parses `json` as a `T`, encodes it to SCALE, decodes it back and serializes it canonically,
+/// reporting every field lost or altered on the way.
+///
+/// ```
+/// use protos::{bijection::verify_bijection, traits::Trait};
+///
+/// let json = r#"{"name":"Door","records":[],"color":"red"}"#;
+/// let equivalence = verify_bijection::<Trait>(json).unwrap();
+/// assert_eq!(equivalence.canonical, r#"{"name":"Door","records":[]}"#);
+/// assert_eq!(equivalence.lossy[0].path, "/color");
+/// ```
+pub fn verify_bijection<T>(json: &str) -> Result<Equivalence, BijectionError>
+where
+  T: Serialize + DeserializeOwned + Encode + Decode,
+{
+  let invalid = |e: serde_json::Error| BijectionError::InvalidJson(e.to_string());
+  let input: Json = serde_json::from_str(json).map_err(invalid)?;
+  let parsed: T = serde_json::from_value(input.clone()).map_err(invalid)?;
+  let decoded = T::decode(&mut &parsed.encode()[..]).map_err(BijectionError::Codec)?;
+
+  let parsed = serde_json::to_value(&parsed).map_err(invalid)?;
+  let output = canonical(serde_json::to_value(&decoded).map_err(invalid)?);
+  let mut lossy = Vec::new();
+  diff(&input, &parsed, &mut String::new(), Stage::Json, &mut lossy);
+  diff(
+    &parsed,
+    &output,
+    &mut String::new(),
+    Stage::Scale,
+    &mut lossy,
+  );
+  Ok(Equivalence {
+    canonical: output.to_string(),
+    lossy,
+  })
+}
+
+/// `value` with the keys of every object sorted, whatever the map implementation of serde_json.
+fn canonical(value: Json) -> Json {
+  match value {
+    Json::Object(map) => {
+      let mut entries: Vec<_> = map.into_iter().collect();
+      entries.sort_by(|a, b| a.0.cmp(&b.0));
+      Json::Object(
+        entries
+          .into_iter()
+          .map(|(key, value)| (key, canonical(value)))
+          .collect::<Map<_, _>>(),
+      )
+    }
+    Json::Array(values) => Json::Array(values.into_iter().map(canonical).collect()),
+    value => value,
+  }
+}
+
+fn diff(before: &Json, after: &Json, path: &mut String, stage: Stage, out: &mut Vec<LossyField>) {
+  let len = path.len();
+  match (before, after) {
+    (Json::Object(before), Json::Object(after)) => {
+      for (key, value) in before {
+        push_key(path, key);
+        match after.get(key) {
+          Some(other) => diff(value, other, path, stage, out),
+          None => report(path, stage, Change::Dropped(value.clone()), out),
+        }
+        path.truncate(len);
+      }
+      for (key, value) in after {
+        if !before.contains_key(key) && !value.is_null() {
+          push_key(path, key);
+          report(path, stage, Change::Added(value.clone()), out);
+          path.truncate(len);
+        }
+      }
+    }
+    (Json::Array(before), Json::Array(after)) if before.len() == after.len() => {
+      for (index, (value, other)) in before.iter().zip(after).enumerate() {
+        push_key(path, &index.to_string());
+        diff(value, other, path, stage, out);
+        path.truncate(len);
+      }
+    }
+    _ if before == after => {}
+    _ => {
+      let change = Change::Changed {
+        before: before.clone(),
+        after: after.clone(),
+      };
+      report(path, stage, change, out)
+    }
+  }
+}
+
+fn report(path: &str, stage: Stage, change: Change, out: &mut Vec<LossyField>) {
+  out.push(LossyField {
+    path: path.into(),
+    stage,
+    change,
+  });
+}
+
+/// Appends `key` to the JSON pointer `path`, escaped as in RFC 6901.
+fn push_key(path: &mut String, key: &str) {
+  path.push('/');
+  path.push_str(&key.replace('~', "~0").replace('/', "~1"));
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::traits::{Record, Trait, VariableType, VariableTypeInfo};
+
+  #[test]
+  fn exact_round_trip() {
+    let trait_ = Trait {
+      name: "Door".into(),
+      records: vec![Record::from((
+        "open".to_string(),
+        vec![VariableTypeInfo {
+          type_: VariableType::Bool,
+          default: Some(vec![1]),
+        }],
+      ))],
+    };
+    let json = serde_json::to_string(&trait_).unwrap();
+    let equivalence = verify_bijection::<Trait>(&json).unwrap();
+    assert!(equivalence.is_exact());
+    assert_eq!(
+      serde_json::from_str::<Trait>(&equivalence.canonical).unwrap(),
+      trait_
+    );
+    // omitted optional fields are not losses
+    let json = r#"{"name":"Door","records":[{"name":"open","types":[{"type":"Bool"}]}]}"#;
+    assert!(verify_bijection::<Trait>(json).unwrap().is_exact());
+  }
+
+  #[test]
+  fn lossy_fields() {
+    let json = r#"{"name":"Door","records":[{"name":"open","types":[{"type_":"Bool"}]}],"x/y":1}"#;
+    let lossy = verify_bijection::<Trait>(json).unwrap().lossy;
+    let paths: Vec<_> = lossy.iter().map(|field| field.path.as_str()).collect();
+    assert_eq!(
+      paths,
+      [
+        "/records/0/types/0/type_",
+        "/records/0/types/0/type",
+        "/x~1y"
+      ]
+    );
+    assert_eq!(lossy[2].change, Change::Dropped(1.into()));
+    assert!(lossy.iter().all(|field| field.stage == Stage::Json));
+
+    assert!(matches!(
+      verify_bijection::<Trait>(r#"{"name":1}"#),
+      Err(BijectionError::InvalidJson(_))
+    ));
+    assert!(verify_bijection::<Trait>("{").is_err());
+  }
+
+  #[test]
+  fn scale_losses() {
+    #[derive(Encode, Decode, Serialize, Deserialize)]
+    struct Cached {
+      id: u32,
+      #[codec(skip)]
+      label: String,
+    }
+
+    let lossy = verify_bijection::<Cached>(r#"{"id":1,"label":"a"}"#)
+      .unwrap()
+      .lossy;
+    assert_eq!(
+      lossy,
+      [LossyField {
+        path: "/label".into(),
+        stage: Stage::Scale,
+        change: Change::Changed {
+          before: "a".into(),
+          after: "".into(),
+        },
+      }]
+    );
+  }
+}
@@ -0,0 +1,579 @@
+use crate::{
+  categories::{canonicalize_categories, canonicalize_hash_list, Categories, ShardsTrait},
+  proto::ProtoInfo,
+};
+use parity_scale_codec::{Decode, Encode};
+use scale_info::prelude::{collections::BTreeSet, string::String, vec::Vec};
+
+#[cfg(feature = "registry")]
+use crate::registry::TraitRegistry;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The role a proto plays inside a bundle.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub enum BundleEntryRole {
+  /// The main proto of the bundle, a bundle can have only one
+  Primary,
+  /// The image used to preview the bundle, a bundle can have only one
+  Thumbnail,
+  /// A proto needed by the other entries of the bundle
+  Dependency,
+  /// A named slot, names must be unique within a bundle
+  Slot(String),
+}
+
+/// A single proto included in a bundle.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct BundleEntry<THash> {
+  /// The hash of the proto included
+  pub proto: THash,
+  /// The role of the proto inside the bundle
+  pub role: BundleEntryRole,
+  /// The traits the proto must implement to fill this entry
+  pub required_traits: Vec<ShardsTrait>,
+  /// The categories accepted for this entry, any category is accepted if empty
+  pub categories: Vec<Categories>,
+}
+
+impl<THash> BundleEntry<THash> {
+  /// Whether a proto of `category` can fill this entry.
+  ///
+  /// Shards and Trait categories are matched by kind, as their payload describes the specific proto.
+  pub fn accepts(&self, category: &Categories) -> bool {
+    self.categories.is_empty()
+      || self
+        .categories
+        .iter()
+        .any(|accepted| match (accepted, category) {
+          (Categories::Shards(_), Categories::Shards(_)) => true,
+          (Categories::Trait(_), Categories::Trait(_)) => true,
+          _ => accepted == category,
+        })
+  }
+
+  /// Sorts and dedups `required_traits` and `categories`, which are sets, so equivalent entries
+  /// have the same encoding.
+  pub fn canonicalize(&mut self) {
+    canonicalize_hash_list(&mut self.required_traits);
+    canonicalize_categories(&mut self.categories);
+  }
+}
+
+/// The list of protos composing a `Categories::Bundle` proto.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct BundleManifest<THash> {
+  pub entries: Vec<BundleEntry<THash>>,
+}
+
+/// Errors returned when a bundle manifest cannot be satisfied.
+#[derive(Clone, PartialEq, Debug, Eq)]
+pub enum BundleError<THash> {
+  /// More than one entry has the `Primary` role
+  MultiplePrimary,
+  /// More than one entry has the `Thumbnail` role
+  MultipleThumbnails,
+  /// Two entries use the same named slot
+  DuplicateSlot(String),
+  /// A required trait is not known to the registry
+  UnknownTrait(ShardsTrait),
+  /// The proto of an entry could not be found
+  MissingProto(THash),
+  /// The proto of an entry does not implement a trait required by its slot
+  UnsatisfiedTrait { proto: THash, required: ShardsTrait },
+}
+
+impl<THash: core::fmt::Debug> core::fmt::Display for BundleError<THash> {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    match self {
+      Self::MultiplePrimary => write!(f, "bundle has more than one primary entry"),
+      Self::MultipleThumbnails => write!(f, "bundle has more than one thumbnail entry"),
+      Self::DuplicateSlot(name) => write!(f, "duplicate bundle slot {:?}", name),
+      Self::UnknownTrait(hash) => write!(f, "unknown trait {:?}", hash),
+      Self::MissingProto(proto) => write!(f, "proto {:?} not found", proto),
+      Self::UnsatisfiedTrait { proto, required } => {
+        write!(
+          f,
+          "proto {:?} does not implement trait {:?}",
+          proto, required
+        )
+      }
+    }
+  }
+}
+
+#[cfg(feature = "std")]
+impl<THash: core::fmt::Debug> std::error::Error for BundleError<THash> {}
+
+/// The result of checking a bundle manifest against the available protos.
+#[derive(Clone, PartialEq, Debug, Eq)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct BundleReport<THash> {
+  /// Protos that could not be resolved
+  pub missing: Vec<THash>,
+  /// Entries (by index) whose proto category is not accepted, with the category found
+  pub category_mismatches: Vec<(usize, Categories)>,
+  /// Chains of bundles including themselves, the first proto is repeated at the end
+  pub cycles: Vec<Vec<THash>>,
+  /// Entries (by index) repeating a previous entry with the same proto and role
+  pub duplicates: Vec<usize>,
+  /// Nested bundles deeper than `BundleManifest::MAX_NESTING_DEPTH`, which are not followed
+  pub too_deep: Vec<THash>,
+}
+
+impl<THash> Default for BundleReport<THash> {
+  fn default() -> Self {
+    Self {
+      missing: Vec::new(),
+      category_mismatches: Vec::new(),
+      cycles: Vec::new(),
+      duplicates: Vec::new(),
+      too_deep: Vec::new(),
+    }
+  }
+}
+
+impl<THash> BundleReport<THash> {
+  /// Whether the bundle can be listed as is.
+  pub fn is_ok(&self) -> bool {
+    self.missing.is_empty()
+      && self.category_mismatches.is_empty()
+      && self.cycles.is_empty()
+      && self.duplicates.is_empty()
+      && self.too_deep.is_empty()
+  }
+}
+
+/// The protos visited while following nested bundles.
+struct Walk<THash> {
+  /// The bundles from the entry to the current one
+  path: Vec<THash>,
+  /// The bundles whose references were all followed
+  explored: BTreeSet<THash>,
+  /// The cycles already reported
+  cycles: BTreeSet<Vec<THash>>,
+}
+
+impl<THash: Clone + Ord> BundleManifest<THash> {
+  /// How deep nested bundles are followed, deeper bundles are reported in `too_deep`.
+  pub const MAX_NESTING_DEPTH: usize = 16;
+
+  /// Checks the manifest against the available protos, collecting every problem found.
+  ///
+  /// `resolve` returns the information about a proto, or `None` if the proto is not available.
+  /// Nested bundles are followed through their references to detect circular inclusions. They are
+  /// resolved at most once and followed at most `MAX_NESTING_DEPTH` deep, so untrusted manifests
+  /// stay cheap to check.
+  pub fn check<TAccountId>(
+    &self,
+    resolve: impl Fn(&THash) -> Option<ProtoInfo<THash, TAccountId>>,
+  ) -> BundleReport<THash> {
+    let mut report = BundleReport::default();
+    let mut walk = Walk {
+      path: Vec::new(),
+      explored: BTreeSet::new(),
+      cycles: BTreeSet::new(),
+    };
+
+    for (index, entry) in self.entries.iter().enumerate() {
+      if self.entries[..index]
+        .iter()
+        .any(|previous| previous.proto == entry.proto && previous.role == entry.role)
+      {
+        report.duplicates.push(index);
+        continue;
+      }
+
+      match resolve(&entry.proto) {
+        None => {
+          if !report.missing.contains(&entry.proto) {
+            report.missing.push(entry.proto.clone());
+          }
+        }
+        Some(summary) => {
+          if !entry.accepts(&summary.category) {
+            report
+              .category_mismatches
+              .push((index, summary.category.clone()));
+          }
+          if !walk.explored.contains(&entry.proto) {
+            walk.path.push(entry.proto.clone());
+            Self::find_cycles(&summary, &resolve, &mut walk, &mut report);
+            walk.path.pop();
+          }
+        }
+      }
+    }
+
+    report
+  }
+
+  /// Follows the references of the last bundle of `walk.path`, which resolved to `summary`.
+  fn find_cycles<TAccountId>(
+    summary: &ProtoInfo<THash, TAccountId>,
+    resolve: &impl Fn(&THash) -> Option<ProtoInfo<THash, TAccountId>>,
+    walk: &mut Walk<THash>,
+    report: &mut BundleReport<THash>,
+  ) {
+    if summary.category != Categories::Bundle {
+      return;
+    }
+
+    for reference in &summary.references {
+      if let Some(start) = walk.path.iter().position(|hash| hash == reference) {
+        let mut cycle = walk.path[start..].to_vec();
+        cycle.push(reference.clone());
+        if walk.cycles.insert(cycle.clone()) {
+          report.cycles.push(cycle);
+        }
+        continue;
+      }
+      if walk.explored.contains(reference) {
+        continue;
+      }
+      if walk.path.len() > Self::MAX_NESTING_DEPTH {
+        if !report.too_deep.contains(reference) {
+          report.too_deep.push(reference.clone());
+        }
+        continue;
+      }
+
+      // missing nested protos are reported by the check of the bundle that includes them
+      if let Some(nested) = resolve(reference) {
+        walk.path.push(reference.clone());
+        Self::find_cycles(&nested, resolve, walk, report);
+        walk.path.pop();
+      }
+    }
+
+    if let Some(current) = walk.path.last() {
+      walk.explored.insert(current.clone());
+    }
+  }
+}
+
+impl<THash: Clone> BundleManifest<THash> {
+  /// Canonicalizes every entry, see [`BundleEntry::canonicalize`], before hashing or signing the
+  /// manifest. The entries keep their order.
+  pub fn canonicalize(&mut self) {
+    self.entries.iter_mut().for_each(BundleEntry::canonicalize);
+  }
+
+  /// The entry with the `Primary` role, if any.
+  pub fn primary(&self) -> Option<&BundleEntry<THash>> {
+    self
+      .entries
+      .iter()
+      .find(|entry| entry.role == BundleEntryRole::Primary)
+  }
+
+  /// Checks that every slot requirement of the manifest can be satisfied.
+  ///
+  /// `lookup` returns the traits implemented by a proto, or `None` if the proto is unknown.
+  #[cfg(feature = "registry")]
+  pub fn validate(
+    &self,
+    registry: &TraitRegistry,
+    lookup: impl Fn(&THash) -> Option<Vec<ShardsTrait>>,
+  ) -> Result<(), BundleError<THash>> {
+    let mut primary = false;
+    let mut thumbnail = false;
+    let mut slots: Vec<&String> = Vec::new();
+
+    for entry in &self.entries {
+      match &entry.role {
+        BundleEntryRole::Primary if primary => return Err(BundleError::MultiplePrimary),
+        BundleEntryRole::Primary => primary = true,
+        BundleEntryRole::Thumbnail if thumbnail => return Err(BundleError::MultipleThumbnails),
+        BundleEntryRole::Thumbnail => thumbnail = true,
+        BundleEntryRole::Slot(name) if slots.contains(&name) => {
+          return Err(BundleError::DuplicateSlot(name.clone()))
+        }
+        BundleEntryRole::Slot(name) => slots.push(name),
+        BundleEntryRole::Dependency => {}
+      }
+
+      if let Some(unknown) = entry
+        .required_traits
+        .iter()
+        .find(|hash| !registry.contains(hash))
+      {
+        return Err(BundleError::UnknownTrait(*unknown));
+      }
+
+      let implemented =
+        lookup(&entry.proto).ok_or_else(|| BundleError::MissingProto(entry.proto.clone()))?;
+      if let Some(required) = entry
+        .required_traits
+        .iter()
+        .find(|hash| !implemented.contains(hash))
+      {
+        return Err(BundleError::UnsatisfiedTrait {
+          proto: entry.proto.clone(),
+          required: *required,
+        });
+      }
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::categories::{TextCategories, TextureCategories};
+
+  const IMAGE_TRAIT: ShardsTrait = [1; 8];
+
+  #[cfg(feature = "registry")]
+  fn registry() -> TraitRegistry {
+    let mut registry = TraitRegistry::new();
+    registry.insert(
+      IMAGE_TRAIT,
+      crate::traits::Trait {
+        name: "Image".to_string(),
+        records: vec![],
+      },
+    );
+    registry
+  }
+
+  #[cfg(feature = "registry")]
+  fn lookup(proto: &u32) -> Option<Vec<ShardsTrait>> {
+    match proto {
+      1 => Some(vec![IMAGE_TRAIT]),
+      2 => Some(vec![]),
+      _ => None,
+    }
+  }
+
+  fn entry(
+    proto: u32,
+    role: BundleEntryRole,
+    required_traits: Vec<ShardsTrait>,
+  ) -> BundleEntry<u32> {
+    BundleEntry {
+      proto,
+      role,
+      required_traits,
+      categories: vec![],
+    }
+  }
+
+  fn resolve(proto: &u32) -> Option<ProtoInfo<u32, u64>> {
+    let (category, references) = match proto {
+      1 => (Categories::Texture(TextureCategories::PngFile), vec![]),
+      2 => (Categories::Text(TextCategories::Plain), vec![]),
+      // 10 -> 11 -> 10
+      10 => (Categories::Bundle, vec![1, 11]),
+      11 => (Categories::Bundle, vec![10]),
+      _ => return None,
+    };
+    Some(ProtoInfo {
+      hash: *proto,
+      owner: 1,
+      category,
+      tags: vec![],
+      references,
+    })
+  }
+
+  #[cfg(feature = "registry")]
+  #[test]
+  fn valid_bundle() {
+    let manifest = BundleManifest {
+      entries: vec![
+        entry(2, BundleEntryRole::Primary, vec![]),
+        entry(1, BundleEntryRole::Thumbnail, vec![IMAGE_TRAIT]),
+        entry(
+          1,
+          BundleEntryRole::Slot("skin".to_string()),
+          vec![IMAGE_TRAIT],
+        ),
+      ],
+    };
+    assert_eq!(manifest.validate(&registry(), lookup), Ok(()));
+    assert_eq!(manifest.primary().unwrap().proto, 2);
+  }
+
+  #[cfg(feature = "registry")]
+  #[test]
+  fn invalid_bundles() {
+    let manifest = BundleManifest {
+      entries: vec![
+        entry(2, BundleEntryRole::Primary, vec![]),
+        entry(1, BundleEntryRole::Primary, vec![]),
+      ],
+    };
+    assert_eq!(
+      manifest.validate(&registry(), lookup),
+      Err(BundleError::MultiplePrimary)
+    );
+
+    let manifest = BundleManifest {
+      entries: vec![entry(2, BundleEntryRole::Thumbnail, vec![IMAGE_TRAIT])],
+    };
+    assert_eq!(
+      manifest.validate(&registry(), lookup),
+      Err(BundleError::UnsatisfiedTrait {
+        proto: 2,
+        required: IMAGE_TRAIT
+      })
+    );
+
+    let manifest = BundleManifest {
+      entries: vec![entry(1, BundleEntryRole::Dependency, vec![[9; 8]])],
+    };
+    assert_eq!(
+      manifest.validate(&registry(), lookup),
+      Err(BundleError::UnknownTrait([9; 8]))
+    );
+
+    let manifest = BundleManifest {
+      entries: vec![entry(3, BundleEntryRole::Dependency, vec![])],
+    };
+    assert_eq!(
+      manifest.validate(&registry(), lookup),
+      Err(BundleError::MissingProto(3))
+    );
+  }
+
+  #[test]
+  fn check_bundle() {
+    let mut thumbnail = entry(1, BundleEntryRole::Thumbnail, vec![]);
+    thumbnail.categories = vec![Categories::Texture(TextureCategories::PngFile)];
+    let manifest = BundleManifest {
+      entries: vec![
+        entry(2, BundleEntryRole::Primary, vec![]),
+        thumbnail.clone(),
+      ],
+    };
+    assert!(manifest.check(resolve).is_ok());
+
+    let mut text_thumbnail = thumbnail.clone();
+    text_thumbnail.proto = 2;
+    let manifest = BundleManifest {
+      entries: vec![
+        text_thumbnail,
+        thumbnail.clone(),
+        thumbnail,
+        entry(3, BundleEntryRole::Dependency, vec![]),
+        entry(10, BundleEntryRole::Dependency, vec![]),
+      ],
+    };
+    let report = manifest.check(resolve);
+    assert!(!report.is_ok());
+    assert_eq!(report.missing, vec![3]);
+    assert_eq!(
+      report.category_mismatches,
+      vec![(0, Categories::Text(TextCategories::Plain))]
+    );
+    assert_eq!(report.duplicates, vec![2]);
+    assert_eq!(report.cycles, vec![vec![10, 11, 10]]);
+  }
+
+  #[test]
+  fn check_nested_bundles() {
+    use core::cell::Cell;
+
+    // bundle n references bundle n + 1 twice, down to bundle 40
+    let calls = Cell::new(0);
+    let resolve = |proto: &u32| {
+      calls.set(calls.get() + 1);
+      Some(ProtoInfo {
+        hash: *proto,
+        owner: 1u64,
+        category: Categories::Bundle,
+        tags: vec![],
+        references: if *proto < 40 {
+          vec![proto + 1, proto + 1]
+        } else {
+          vec![]
+        },
+      })
+    };
+    let manifest = BundleManifest {
+      entries: vec![entry(30, BundleEntryRole::Dependency, vec![])],
+    };
+    let report = manifest.check(resolve);
+    assert!(report.is_ok());
+    assert_eq!(calls.get(), 11);
+
+    calls.set(0);
+    let manifest = BundleManifest {
+      entries: vec![entry(0, BundleEntryRole::Dependency, vec![])],
+    };
+    let report = manifest.check(resolve);
+    let depth = BundleManifest::<u32>::MAX_NESTING_DEPTH as u32;
+    assert_eq!(report.too_deep, vec![depth + 1]);
+    assert!(!report.is_ok());
+    assert_eq!(calls.get(), depth + 1);
+  }
+
+  #[test]
+  fn encode_decode_manifest() {
+    let manifest = BundleManifest {
+      entries: vec![entry(
+        1,
+        BundleEntryRole::Slot("skin".to_string()),
+        vec![IMAGE_TRAIT],
+      )],
+    };
+    let encoded = manifest.encode();
+    assert_eq!(
+      BundleManifest::<u32>::decode(&mut encoded.as_slice()).unwrap(),
+      manifest
+    );
+
+    let json = serde_json::to_string(&manifest).unwrap();
+    assert_eq!(
+      serde_json::from_str::<BundleManifest<u32>>(&json).unwrap(),
+      manifest
+    );
+  }
+
+  #[test]
+  fn canonical_manifest() {
+    let mut a = BundleManifest {
+      entries: vec![entry(
+        1,
+        BundleEntryRole::Primary,
+        vec![[2; 8], IMAGE_TRAIT],
+      )],
+    };
+    a.entries[0].categories = vec![
+      Categories::Texture(TextureCategories::PngFile),
+      Categories::Text(TextCategories::Plain),
+    ];
+    let mut b = a.clone();
+    b.entries[0].required_traits = vec![IMAGE_TRAIT, [2; 8], IMAGE_TRAIT];
+    b.entries[0].categories.reverse();
+    assert_ne!(a.encode(), b.encode());
+
+    a.canonicalize();
+    b.canonicalize();
+    assert_eq!(a, b);
+    assert_eq!(a.entries[0].required_traits, [IMAGE_TRAIT, [2; 8]]);
+  }
+}
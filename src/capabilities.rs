@@ -0,0 +1,225 @@
+use crate::{
+  categories::{
+    AudioCategories, BinaryCategories, Categories, ModelCategories, TextCategories,
+    TextureCategories, TraitRef, VectorCategories, VideoCategories,
+  },
+  traits::{TableInfo, TableInfoV2, VariableType},
+};
+use parity_scale_codec::{Decode, Encode};
+use scale_info::prelude::vec::Vec;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Describes which categories and variable types a given chain runtime accepts,
+/// so clients can disable unsupported options instead of having extrinsics fail.
+///
+/// Variants are identified by their SCALE index.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, Default, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct RuntimeCapabilities {
+  /// Bit `i` is set if the `Categories` variant `i` is accepted
+  pub categories: u32,
+  /// Entry `i` has bit `j` set if the sub-category `j` of the `Categories` variant `i` is accepted
+  pub sub_categories: Vec<u64>,
+  /// Bit `i` is set if the `VariableType` variant `i` is accepted
+  pub variable_types: u64,
+}
+
+impl RuntimeCapabilities {
+  /// Capabilities accepting nothing.
+  pub fn none() -> Self {
+    Self::default()
+  }
+
+  /// Capabilities accepting every category and variable type known to this crate.
+  pub fn current() -> Self {
+    let mut capabilities = Self::none();
+    let categories = TextCategories::ALL
+      .iter()
+      .map(|sub| Categories::Text(*sub))
+      .chain([Categories::Trait(TraitRef::Any), Categories::Bundle])
+      .chain(
+        AudioCategories::ALL
+          .iter()
+          .map(|sub| Categories::Audio(*sub)),
+      )
+      .chain(
+        TextureCategories::ALL
+          .iter()
+          .map(|sub| Categories::Texture(*sub)),
+      )
+      .chain(
+        VectorCategories::ALL
+          .iter()
+          .map(|sub| Categories::Vector(*sub)),
+      )
+      .chain(
+        VideoCategories::ALL
+          .iter()
+          .map(|sub| Categories::Video(*sub)),
+      )
+      .chain(
+        ModelCategories::ALL
+          .iter()
+          .map(|sub| Categories::Model(*sub)),
+      )
+      .chain(
+        BinaryCategories::ALL
+          .iter()
+          .map(|sub| Categories::Binary(*sub)),
+      );
+    for category in categories {
+      capabilities.allow_category(&category);
+    }
+    // Shards carry a payload, it is enough to set the variant bit
+    capabilities.categories |= 1 << 2;
+    capabilities.variable_types = (1 << VariableType::VARIANT_COUNT) - 1;
+    capabilities
+  }
+
+  /// Accepts `category`, and its sub-category if it has one.
+  pub fn allow_category(&mut self, category: &Categories) {
+    let index = category.variant_index() as usize;
+    self.categories |= 1 << index;
+    if let Some(sub) = category.sub_index() {
+      if self.sub_categories.len() <= index {
+        self.sub_categories.resize(index + 1, 0);
+      }
+      self.sub_categories[index] |= 1 << sub;
+    }
+  }
+
+  /// Accepts the variant of `type_`, nested types are not affected.
+  pub fn allow_type(&mut self, type_: &VariableType) {
+    self.variable_types |= 1 << type_.variant_index();
+  }
+
+  /// Whether the runtime accepts protos of `category`.
+  pub fn supports(&self, category: &Categories) -> bool {
+    let index = category.variant_index() as usize;
+    if self.categories & (1 << index) == 0 {
+      return false;
+    }
+    match category.sub_index() {
+      Some(sub) => self
+        .sub_categories
+        .get(index)
+        .is_some_and(|mask| mask & (1 << sub) != 0),
+      None => true,
+    }
+  }
+
+  /// Whether the runtime accepts traits using `type_`, including every type nested in it.
+  pub fn supports_type(&self, type_: &VariableType) -> bool {
+    if self.variable_types & (1 << type_.variant_index()) == 0 {
+      return false;
+    }
+    match type_ {
+      VariableType::Bytes(Some(sub)) => self.supports(&Categories::Binary(*sub)),
+      VariableType::String(Some(sub)) => self.supports(&Categories::Text(*sub)),
+      VariableType::Seq { types, .. } => types.iter().all(|t| self.supports_type(t)),
+      VariableType::Table(TableInfo { types, .. })
+      | VariableType::TableV2(TableInfoV2 { types, .. }) => {
+        types.iter().flatten().all(|t| self.supports_type(t))
+      }
+      VariableType::Code(code) => code
+        .requires
+        .iter()
+        .chain(code.exposes.iter())
+        .map(|(_, t)| t)
+        .chain(code.inputs.iter())
+        .chain([&code.output])
+        .all(|t| self.supports_type(t)),
+      other => other
+        .carried()
+        .is_none_or(|inner| self.supports_type(inner)),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::categories::ShardsFormat;
+  use crate::categories::ShardsScriptInfo;
+
+  #[test]
+  fn indexes_match_encoding() {
+    let categories = [
+      Categories::Text(TextCategories::Markdown),
+      Categories::Trait(TraitRef::Hash([0; 8])),
+      Categories::Shards(ShardsScriptInfo {
+        format: ShardsFormat::Edn,
+        shards_version: 1,
+        requiring: vec![],
+        implementing: vec![],
+      }),
+      Categories::Binary(BinaryCategories::RareDomain),
+      Categories::Bundle,
+    ];
+    for category in categories {
+      let encoded = category.encode();
+      assert_eq!(encoded[0], category.variant_index());
+      if let Some(sub) = category.sub_index() {
+        assert_eq!(encoded[1], sub);
+      }
+    }
+
+    let types = [
+      VariableType::None,
+      VariableType::Int16(Default::default()),
+      VariableType::Event(Box::new(VariableType::Any)),
+      VariableType::EventV2(Default::default()),
+      VariableType::TableV2(Default::default()),
+    ];
+    for type_ in types {
+      assert_eq!(type_.encode()[0], type_.variant_index());
+    }
+    assert_eq!(
+      VariableType::TableV2(Default::default()).variant_index(),
+      VariableType::VARIANT_COUNT - 1
+    );
+  }
+
+  #[test]
+  fn supported_categories() {
+    let current = RuntimeCapabilities::current();
+    assert!(current.supports(&Categories::Model(ModelCategories::Sdf)));
+    assert!(current.supports(&Categories::Bundle));
+    assert!(current.supports_type(&VariableType::Any));
+
+    let mut capabilities = RuntimeCapabilities::none();
+    capabilities.allow_category(&Categories::Texture(TextureCategories::PngFile));
+    assert!(capabilities.supports(&Categories::Texture(TextureCategories::PngFile)));
+    assert!(!capabilities.supports(&Categories::Texture(TextureCategories::JpgFile)));
+    assert!(!capabilities.supports(&Categories::Bundle));
+
+    let encoded = capabilities.encode();
+    assert_eq!(
+      RuntimeCapabilities::decode(&mut encoded.as_slice()).unwrap(),
+      capabilities
+    );
+  }
+
+  #[test]
+  fn supported_types() {
+    let mut capabilities = RuntimeCapabilities::none();
+    let seq = VariableType::Seq {
+      types: vec![VariableType::String(Some(TextCategories::Wgsl))],
+      length_limits: None,
+    };
+    capabilities.allow_type(&seq);
+    assert!(!capabilities.supports_type(&seq));
+
+    capabilities.allow_type(&VariableType::String(None));
+    assert!(!capabilities.supports_type(&seq));
+
+    capabilities.allow_category(&Categories::Text(TextCategories::Wgsl));
+    assert!(capabilities.supports_type(&seq));
+  }
+}
@@ -1,31 +1,30 @@
-use parity_scale_codec::{Decode, Encode};
+use crate::hex::{HexBytes, HexList};
+use parity_scale_codec::{Decode, Encode, Error, Input};
 use scale_info::prelude::vec::Vec;
 
-#[cfg(not(feature = "std"))]
-type String = Vec<u8>;
-
-#[cfg(feature = "std")]
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 /// A XX64 hash of the trait interface.
 pub type ShardsTrait = [u8; 8];
 
-#[derive(Encode, Decode, Copy, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[derive(Encode, Decode, Default, Copy, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
 #[cfg_attr(
-  feature = "std",
+  feature = "serde",
   derive(Serialize, Deserialize),
   serde(rename_all = "camelCase")
 )]
 pub enum ShardsFormat {
   /// Canonical textual format interpreted by the Shards runtime
+  #[default]
   Edn,
   /// Serialized binary format
   Binary,
 }
 
-#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[derive(Encode, Decode, Default, Clone, PartialEq, Eq, scale_info::TypeInfo)]
 #[cfg_attr(
-  feature = "std",
+  feature = "serde",
   derive(Serialize, Deserialize),
   serde(rename_all = "camelCase")
 )]
@@ -37,14 +36,208 @@ pub struct ShardsScriptInfo {
   pub implementing: Vec<ShardsTrait>,
 }
 
+impl core::fmt::Debug for ShardsScriptInfo {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("ShardsScriptInfo")
+      .field("format", &self.format)
+      .field("shards_version", &self.shards_version)
+      .field("requiring", &HexList(&self.requiring))
+      .field("implementing", &HexList(&self.implementing))
+      .finish()
+  }
+}
+
+/// Errors returned when validating a `ShardsScriptInfo`.
+#[derive(Copy, Clone, PartialEq, Debug, Eq)]
+pub enum ScriptInfoError {
+  /// The `shards_version` is 0
+  ZeroVersion,
+  /// A trait is listed twice in `requiring` or in `implementing`
+  DuplicateTrait(ShardsTrait),
+  /// A trait is both required and implemented by the script, only returned by
+  /// [`ShardsScriptInfoBuilder::build`]
+  RequiredAndImplemented(ShardsTrait),
+}
+
+impl core::fmt::Display for ScriptInfoError {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    match self {
+      Self::ZeroVersion => write!(f, "shards version must not be 0"),
+      Self::DuplicateTrait(hash) => write!(f, "trait {} is listed twice", HexBytes(hash)),
+      Self::RequiredAndImplemented(hash) => {
+        write!(
+          f,
+          "trait {} is both required and implemented",
+          HexBytes(hash)
+        )
+      }
+    }
+  }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ScriptInfoError {}
+
+/// Sorts `hashes` and drops the repeated ones, so lists declaring the same hashes in any order
+/// encode, hash and sign identically.
+pub fn canonicalize_hash_list<THash: Ord>(hashes: &mut Vec<THash>) {
+  hashes.sort_unstable();
+  hashes.dedup();
+}
+
+/// Canonicalizes every category (see [`Categories::canonicalize`]), then sorts them by SCALE
+/// encoding and drops the repeated ones, so sets of categories declared in any order encode,
+/// hash and sign identically.
+pub fn canonicalize_categories(categories: &mut Vec<Categories>) {
+  categories.iter_mut().for_each(Categories::canonicalize);
+  categories.sort_by_cached_key(Encode::encode);
+  categories.dedup();
+}
+
+impl ShardsScriptInfo {
+  /// Sorts `requiring` and `implementing` and drops repeated traits, see
+  /// [`canonicalize_hash_list`].
+  pub fn canonicalize(&mut self) {
+    canonicalize_hash_list(&mut self.requiring);
+    canonicalize_hash_list(&mut self.implementing);
+  }
+
+  /// A builder of script infos.
+  pub fn builder() -> ShardsScriptInfoBuilder {
+    ShardsScriptInfoBuilder::default()
+  }
+
+  /// Checks that the `shards_version` is set and that no trait is listed twice.
+  ///
+  /// A trait both required and implemented is accepted: whether it was intended is not part of
+  /// the info, [`ShardsScriptInfoBuilder::build`] rejects it unless allowed.
+  pub fn validate(&self) -> Result<(), ScriptInfoError> {
+    if self.shards_version == 0 {
+      return Err(ScriptInfoError::ZeroVersion);
+    }
+    for list in [&self.requiring, &self.implementing] {
+      for (i, hash) in list.iter().enumerate() {
+        if list[..i].contains(hash) {
+          return Err(ScriptInfoError::DuplicateTrait(*hash));
+        }
+      }
+    }
+    Ok(())
+  }
+
+  /// The first trait both required and implemented, if any.
+  pub fn overlap(&self) -> Option<&ShardsTrait> {
+    self
+      .requiring
+      .iter()
+      .find(|hash| self.implementing.contains(hash))
+  }
+}
+
+/// Builds a valid `ShardsScriptInfo`, see [`ShardsScriptInfo::validate`].
+#[derive(Default, Clone, Debug)]
+pub struct ShardsScriptInfoBuilder {
+  info: ShardsScriptInfo,
+  allow_overlap: bool,
+}
+
+impl ShardsScriptInfoBuilder {
+  /// The script also requires the trait `hash`, requiring a trait twice is a no-op.
+  pub fn require(mut self, hash: ShardsTrait) -> Self {
+    if !self.info.requiring.contains(&hash) {
+      self.info.requiring.push(hash);
+    }
+    self
+  }
+
+  /// The script also implements the trait `hash`, implementing a trait twice is a no-op.
+  pub fn implement(mut self, hash: ShardsTrait) -> Self {
+    if !self.info.implementing.contains(&hash) {
+      self.info.implementing.push(hash);
+    }
+    self
+  }
+
+  /// The `shards_version` of the script.
+  pub fn version(mut self, shards_version: u32) -> Self {
+    self.info.shards_version = shards_version;
+    self
+  }
+
+  /// The format of the script, `Edn` by default.
+  pub fn format(mut self, format: ShardsFormat) -> Self {
+    self.info.format = format;
+    self
+  }
+
+  /// Allows traits both required and implemented, for scripts extending a trait they implement.
+  pub fn allow_overlap(mut self) -> Self {
+    self.allow_overlap = true;
+    self
+  }
+
+  /// The canonical script info, if valid (see [`ShardsScriptInfo::validate`]) and, unless
+  /// [`ShardsScriptInfoBuilder::allow_overlap`] was called, without traits both required and
+  /// implemented.
+  pub fn build(self) -> Result<ShardsScriptInfo, ScriptInfoError> {
+    let mut info = self.info;
+    info.validate()?;
+    match info.overlap() {
+      Some(hash) if !self.allow_overlap => Err(ScriptInfoError::RequiredAndImplemented(*hash)),
+      _ => {
+        info.canonicalize();
+        Ok(info)
+      }
+    }
+  }
+}
+
 // serde(rename_all = "camelCase") is needed or polkadot.js will not be able to deserialize
 
+/// Compares a category name ignoring ASCII case, `_`, `-` and spaces, so `"pngFile"`, `"PngFile"`,
+/// `"png_file"` and `"pngfile"` all name the same variant.
+pub(crate) fn loose_eq(input: &str, canonical: &str) -> bool {
+  let significant = |c: &char| !matches!(c, '_' | '-' | ' ');
+  let mut input = input.chars().filter(significant);
+  let mut canonical = canonical.chars().filter(significant);
+  loop {
+    match (input.next(), canonical.next()) {
+      (None, None) => return true,
+      (Some(a), Some(b)) if a.eq_ignore_ascii_case(&b) => {}
+      _ => return false,
+    }
+  }
+}
+
+/// Implements lenient name parsing for a sub-category enum, and a `Deserialize` accepting any
+/// spelling `loose_eq` tolerates. Serialization stays derived and canonical (camelCase).
+macro_rules! loose_sub_category {
+  ($name:ident { $($variant:ident),* $(,)? }) => {
+    impl $name {
+      /// Parses a variant name, ignoring case and word separators.
+      pub fn from_str_loose(name: &str) -> Option<Self> {
+        $(
+          if loose_eq(name, stringify!($variant)) {
+            return Some(Self::$variant);
+          }
+        )*
+        None
+      }
+    }
+
+    #[cfg(feature = "serde")]
+    impl<'de> Deserialize<'de> for $name {
+      fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        const VARIANTS: &[&str] = &[$(stringify!($variant)),*];
+        let name = scale_info::prelude::string::String::deserialize(deserializer)?;
+        Self::from_str_loose(&name).ok_or_else(|| serde::de::Error::unknown_variant(&name, VARIANTS))
+      }
+    }
+  };
+}
+
 #[derive(Encode, Decode, Copy, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
-#[cfg_attr(
-  feature = "std",
-  derive(Serialize, Deserialize),
-  serde(rename_all = "camelCase")
-)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "camelCase"))]
 pub enum AudioCategories {
   /// A compressed audio file in the ogg container format
   OggFile,
@@ -52,12 +245,13 @@ pub enum AudioCategories {
   Mp3File,
 }
 
+impl AudioCategories {
+  /// Every sub-category, ordered by SCALE index.
+  pub const ALL: &'static [Self] = &[Self::OggFile, Self::Mp3File];
+}
+
 #[derive(Encode, Decode, Copy, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
-#[cfg_attr(
-  feature = "std",
-  derive(Serialize, Deserialize),
-  serde(rename_all = "camelCase")
-)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "camelCase"))]
 pub enum ModelCategories {
   /// A GLTF binary model
   GltfFile,
@@ -67,23 +261,25 @@ pub enum ModelCategories {
   PhysicsCollider,
 }
 
+impl ModelCategories {
+  /// Every sub-category, ordered by SCALE index.
+  pub const ALL: &'static [Self] = &[Self::GltfFile, Self::Sdf, Self::PhysicsCollider];
+}
+
 #[derive(Encode, Decode, Copy, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
-#[cfg_attr(
-  feature = "std",
-  derive(Serialize, Deserialize),
-  serde(rename_all = "camelCase")
-)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "camelCase"))]
 pub enum TextureCategories {
   PngFile,
   JpgFile,
 }
 
+impl TextureCategories {
+  /// Every sub-category, ordered by SCALE index.
+  pub const ALL: &'static [Self] = &[Self::PngFile, Self::JpgFile];
+}
+
 #[derive(Encode, Decode, Copy, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
-#[cfg_attr(
-  feature = "std",
-  derive(Serialize, Deserialize),
-  serde(rename_all = "camelCase")
-)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "camelCase"))]
 pub enum VectorCategories {
   /// A Scalable Vector Graphics file
   SvgFile,
@@ -93,12 +289,13 @@ pub enum VectorCategories {
   OtfFile,
 }
 
+impl VectorCategories {
+  /// Every sub-category, ordered by SCALE index.
+  pub const ALL: &'static [Self] = &[Self::SvgFile, Self::TtfFile, Self::OtfFile];
+}
+
 #[derive(Encode, Decode, Copy, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
-#[cfg_attr(
-  feature = "std",
-  derive(Serialize, Deserialize),
-  serde(rename_all = "camelCase")
-)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "camelCase"))]
 pub enum VideoCategories {
   /// A compressed video file in the mkv container format
   MkvFile,
@@ -106,12 +303,13 @@ pub enum VideoCategories {
   Mp4File,
 }
 
+impl VideoCategories {
+  /// Every sub-category, ordered by SCALE index.
+  pub const ALL: &'static [Self] = &[Self::MkvFile, Self::Mp4File];
+}
+
 #[derive(Encode, Decode, Copy, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
-#[cfg_attr(
-  feature = "std",
-  derive(Serialize, Deserialize),
-  serde(rename_all = "camelCase")
-)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "camelCase"))]
 pub enum TextCategories {
   /// Plain Text
   Plain,
@@ -123,12 +321,13 @@ pub enum TextCategories {
   Markdown,
 }
 
+impl TextCategories {
+  /// Every sub-category, ordered by SCALE index.
+  pub const ALL: &'static [Self] = &[Self::Plain, Self::Json, Self::Wgsl, Self::Markdown];
+}
+
 #[derive(Encode, Decode, Copy, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
-#[cfg_attr(
-  feature = "std",
-  derive(Serialize, Deserialize),
-  serde(rename_all = "camelCase")
-)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "camelCase"))]
 pub enum BinaryCategories {
   /// A generic wasm program, compiled to run on a WASI runtime
   WasmProgram,
@@ -144,19 +343,169 @@ pub enum BinaryCategories {
   RareDomain,
 }
 
-/// Types of categories that can be attached to a Proto-Fragment to describe it (e.g Code, Audio, Video etc.)
-#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+impl BinaryCategories {
+  /// Every sub-category, ordered by SCALE index.
+  pub const ALL: &'static [Self] = &[
+    Self::WasmProgram,
+    Self::WasmReactor,
+    Self::BlendFile,
+    Self::OnnxModel,
+    Self::SafeTensors,
+    Self::RareDomain,
+  ];
+}
+
+loose_sub_category!(AudioCategories { OggFile, Mp3File });
+loose_sub_category!(ModelCategories {
+  GltfFile,
+  Sdf,
+  PhysicsCollider
+});
+loose_sub_category!(TextureCategories { PngFile, JpgFile });
+loose_sub_category!(VectorCategories {
+  SvgFile,
+  TtfFile,
+  OtfFile
+});
+loose_sub_category!(VideoCategories { MkvFile, Mp4File });
+loose_sub_category!(TextCategories {
+  Plain,
+  Json,
+  Wgsl,
+  Markdown
+});
+loose_sub_category!(BinaryCategories {
+  WasmProgram,
+  WasmReactor,
+  BlendFile,
+  OnnxModel,
+  SafeTensors,
+  RareDomain
+});
+
+/// A trait interface along with the revision of it a proto declares.
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
 #[cfg_attr(
-  feature = "std",
+  feature = "serde",
   derive(Serialize, Deserialize),
   serde(rename_all = "camelCase")
 )]
+pub struct TraitRefFull {
+  /// The hash of the trait
+  pub hash: ShardsTrait,
+  /// The revision of the trait interface, any revision if unspecified
+  pub revision: Option<u32>,
+}
+
+/// The trait declared by a `Categories::Trait` proto.
+///
+/// `Any` and `Hash` are encoded like the `Option<ShardsTrait>` this payload used to be, so older
+/// encodings decode unchanged, in SCALE and in JSON (`null` or the hash bytes).
+///
+/// A `Full` reference without a revision decodes as `Hash`, so each reference has one form.
+#[derive(Encode, Default, Copy, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+pub enum TraitRef {
+  /// No specific trait, meant to be used in Fragnova protos when uploading and RPCs mainly
+  #[default]
+  Any,
+  /// A trait, in any revision
+  Hash(ShardsTrait),
+  /// A trait in a specific revision, construct it with `TraitRef::from` to keep `Hash` canonical
+  Full(TraitRefFull),
+}
+
+impl TraitRef {
+  /// The hash of the trait, if any.
+  pub fn hash(&self) -> Option<&ShardsTrait> {
+    match self {
+      Self::Any => None,
+      Self::Hash(hash) | Self::Full(TraitRefFull { hash, .. }) => Some(hash),
+    }
+  }
+
+  /// The revision of the trait, if specified.
+  pub fn revision(&self) -> Option<u32> {
+    match self {
+      Self::Full(full) => full.revision,
+      _ => None,
+    }
+  }
+
+  /// Whether the trait `hash` in the revision `revision` (if known) is the declared trait.
+  pub fn matches(&self, hash: &ShardsTrait, revision: Option<u32>) -> bool {
+    match self {
+      Self::Any => true,
+      Self::Hash(ours) => ours == hash,
+      Self::Full(ours) => {
+        ours.hash == *hash && ours.revision.is_none_or(|ours| revision == Some(ours))
+      }
+    }
+  }
+}
+
+impl From<Option<ShardsTrait>> for TraitRef {
+  fn from(hash: Option<ShardsTrait>) -> Self {
+    hash.map_or(Self::Any, Self::Hash)
+  }
+}
+
+impl From<TraitRefFull> for TraitRef {
+  /// Uses the shorter `Hash` form when the revision is unspecified.
+  fn from(full: TraitRefFull) -> Self {
+    match full.revision {
+      None => Self::Hash(full.hash),
+      Some(_) => Self::Full(full),
+    }
+  }
+}
+
+impl Decode for TraitRef {
+  fn decode<I: Input>(input: &mut I) -> Result<Self, Error> {
+    match input.read_byte()? {
+      0 => Ok(Self::Any),
+      1 => Ok(Self::Hash(ShardsTrait::decode(input)?)),
+      2 => Ok(TraitRefFull::decode(input)?.into()),
+      _ => Err("Invalid TraitRef variant".into()),
+    }
+  }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for TraitRef {
+  fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    match self {
+      Self::Any => serializer.serialize_none(),
+      Self::Hash(hash) => hash.serialize(serializer),
+      Self::Full(full) => full.serialize(serializer),
+    }
+  }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for TraitRef {
+  fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Repr {
+      Legacy(Option<ShardsTrait>),
+      Full(TraitRefFull),
+    }
+    Ok(match Repr::deserialize(deserializer)? {
+      Repr::Legacy(hash) => hash.into(),
+      Repr::Full(full) => full.into(),
+    })
+  }
+}
+
+/// Types of categories that can be attached to a Proto-Fragment to describe it (e.g Code, Audio, Video etc.)
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "camelCase"))]
 pub enum Categories {
   /// Text of the supported sub-categories
   Text(TextCategories),
   /// A Scripting Trait declaration, traits are unique, and are used to describe how Shards work (Scripts)
-  /// None is meant to be used in Fragnova protos when uploading and RPCs mainly.
-  Trait(Option<ShardsTrait>),
+  /// `TraitRef::Any` is meant to be used in Fragnova protos when uploading and RPCs mainly.
+  Trait(TraitRef),
   /// Shards scripts of various sub-categories
   /// Shards use interoperability traits to describe how they can be used in other shards
   Shards(ShardsScriptInfo),
@@ -175,3 +524,567 @@ pub enum Categories {
   /// A bundle of many protos
   Bundle,
 }
+
+/// Accepts the category names in any spelling `loose_eq` tolerates, like the sub-categories.
+/// Serialization stays derived and canonical (camelCase).
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Categories {
+  fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    use serde::de::{EnumAccess, Error, Unexpected, VariantAccess, Visitor};
+
+    const VARIANTS: &[&str] = &[
+      "text", "trait", "shards", "audio", "texture", "vector", "video", "model", "binary", "bundle",
+    ];
+
+    /// The SCALE index of a category variant.
+    struct Tag(u8);
+
+    impl<'a> Deserialize<'a> for Tag {
+      fn deserialize<D: serde::Deserializer<'a>>(deserializer: D) -> Result<Self, D::Error> {
+        struct TagVisitor;
+
+        impl Visitor<'_> for TagVisitor {
+          type Value = Tag;
+
+          fn expecting(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            write!(f, "a category name")
+          }
+
+          fn visit_str<E: Error>(self, name: &str) -> Result<Tag, E> {
+            Categories::VARIANT_NAMES
+              .iter()
+              .position(|variant| loose_eq(name, variant))
+              .map(|index| Tag(index as u8))
+              .ok_or_else(|| E::unknown_variant(name, VARIANTS))
+          }
+
+          fn visit_u64<E: Error>(self, index: u64) -> Result<Tag, E> {
+            match u8::try_from(index) {
+              Ok(index) if usize::from(index) < VARIANTS.len() => Ok(Tag(index)),
+              _ => Err(E::invalid_value(Unexpected::Unsigned(index), &self)),
+            }
+          }
+        }
+
+        deserializer.deserialize_identifier(TagVisitor)
+      }
+    }
+
+    struct CategoriesVisitor;
+
+    impl<'a> Visitor<'a> for CategoriesVisitor {
+      type Value = Categories;
+
+      fn expecting(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "a category")
+      }
+
+      fn visit_enum<A: EnumAccess<'a>>(self, data: A) -> Result<Categories, A::Error> {
+        let (Tag(index), variant) = data.variant()?;
+        Ok(match index {
+          0 => Categories::Text(variant.newtype_variant()?),
+          1 => Categories::Trait(variant.newtype_variant()?),
+          2 => Categories::Shards(variant.newtype_variant()?),
+          3 => Categories::Audio(variant.newtype_variant()?),
+          4 => Categories::Texture(variant.newtype_variant()?),
+          5 => Categories::Vector(variant.newtype_variant()?),
+          6 => Categories::Video(variant.newtype_variant()?),
+          7 => Categories::Model(variant.newtype_variant()?),
+          8 => Categories::Binary(variant.newtype_variant()?),
+          _ => {
+            variant.unit_variant()?;
+            Categories::Bundle
+          }
+        })
+      }
+    }
+
+    deserializer.deserialize_enum("Categories", VARIANTS, CategoriesVisitor)
+  }
+}
+
+impl Categories {
+  /// Canonicalizes the trait lists of `Shards` categories, the other categories have a single
+  /// encoding.
+  pub fn canonicalize(&mut self) {
+    if let Self::Shards(info) = self {
+      info.canonicalize();
+    }
+  }
+
+  /// Parses a category from a loosely spelled `"<category>"` or `"<category>/<sub-category>"`
+  /// (`.` and `:` are accepted as separators too), e.g. `"Texture/png_file"` or `"text:json"`.
+  ///
+  /// Case and word separators are ignored. `Shards` carries script info and cannot be parsed,
+  /// `"trait"` yields `Trait(TraitRef::Any)`.
+  pub fn from_str_loose(name: &str) -> Option<Self> {
+    let (category, sub) = match name.find(['/', '.', ':']) {
+      Some(at) => (&name[..at], Some(&name[at + 1..])),
+      None => (name, None),
+    };
+    let category = category.trim();
+    let sub = sub.map(str::trim);
+    if loose_eq(category, "Bundle") {
+      return sub.is_none().then_some(Self::Bundle);
+    }
+    if loose_eq(category, "Trait") {
+      return sub.is_none().then_some(Self::Trait(TraitRef::Any));
+    }
+    let sub = sub?;
+    if loose_eq(category, "Text") {
+      TextCategories::from_str_loose(sub).map(Self::Text)
+    } else if loose_eq(category, "Audio") {
+      AudioCategories::from_str_loose(sub).map(Self::Audio)
+    } else if loose_eq(category, "Texture") {
+      TextureCategories::from_str_loose(sub).map(Self::Texture)
+    } else if loose_eq(category, "Vector") {
+      VectorCategories::from_str_loose(sub).map(Self::Vector)
+    } else if loose_eq(category, "Video") {
+      VideoCategories::from_str_loose(sub).map(Self::Video)
+    } else if loose_eq(category, "Model") {
+      ModelCategories::from_str_loose(sub).map(Self::Model)
+    } else if loose_eq(category, "Binary") {
+      BinaryCategories::from_str_loose(sub).map(Self::Binary)
+    } else {
+      None
+    }
+  }
+
+  /// The names of the category variants, indexed by SCALE index.
+  pub const VARIANT_NAMES: [&'static str; 10] = [
+    "Text", "Trait", "Shards", "Audio", "Texture", "Vector", "Video", "Model", "Binary", "Bundle",
+  ];
+
+  /// The SCALE index of the category variant.
+  pub fn variant_index(&self) -> u8 {
+    match self {
+      Self::Text(_) => 0,
+      Self::Trait(_) => 1,
+      Self::Shards(_) => 2,
+      Self::Audio(_) => 3,
+      Self::Texture(_) => 4,
+      Self::Vector(_) => 5,
+      Self::Video(_) => 6,
+      Self::Model(_) => 7,
+      Self::Binary(_) => 8,
+      Self::Bundle => 9,
+    }
+  }
+
+  /// The SCALE index of the sub-category, `None` for categories without sub-categories.
+  pub fn sub_index(&self) -> Option<u8> {
+    match self {
+      Self::Text(sub) => Some(*sub as u8),
+      Self::Audio(sub) => Some(*sub as u8),
+      Self::Texture(sub) => Some(*sub as u8),
+      Self::Vector(sub) => Some(*sub as u8),
+      Self::Video(sub) => Some(*sub as u8),
+      Self::Model(sub) => Some(*sub as u8),
+      Self::Binary(sub) => Some(*sub as u8),
+      Self::Trait(_) | Self::Shards(_) | Self::Bundle => None,
+    }
+  }
+
+  /// A fixed-width id of the category and sub-category, ignoring payloads, for database indexes.
+  ///
+  /// The high byte is the `variant_index` and the low byte the `sub_index` (0 when the category
+  /// has none), e.g. `0x0400` for `Texture(PngFile)`. SCALE indexes are never reused (see
+  /// [`crate::codec_check`]), so neither are ids.
+  pub fn stable_id(&self) -> u16 {
+    u16::from_be_bytes([self.variant_index(), self.sub_index().unwrap_or(0)])
+  }
+
+  /// The category of `id` (see [`Categories::stable_id`]) carrying `payload`, if `id` is known and
+  /// `payload` is the one its category carries.
+  pub fn from_stable_id(id: u16, payload: CategoryPayload) -> Option<Self> {
+    fn sub<T: Decode>(index: u8) -> Option<T> {
+      T::decode(&mut &[index][..]).ok()
+    }
+    let [variant, index] = id.to_be_bytes();
+    match (variant, payload) {
+      (0, CategoryPayload::None) => sub(index).map(Self::Text),
+      (1, CategoryPayload::Trait(trait_ref)) if index == 0 => Some(Self::Trait(trait_ref)),
+      (2, CategoryPayload::Shards(info)) if index == 0 => Some(Self::Shards(info)),
+      (3, CategoryPayload::None) => sub(index).map(Self::Audio),
+      (4, CategoryPayload::None) => sub(index).map(Self::Texture),
+      (5, CategoryPayload::None) => sub(index).map(Self::Vector),
+      (6, CategoryPayload::None) => sub(index).map(Self::Video),
+      (7, CategoryPayload::None) => sub(index).map(Self::Model),
+      (8, CategoryPayload::None) => sub(index).map(Self::Binary),
+      (9, CategoryPayload::None) if index == 0 => Some(Self::Bundle),
+      _ => None,
+    }
+  }
+
+  /// The payload of the category, the part not covered by [`Categories::stable_id`].
+  pub fn payload(&self) -> CategoryPayload {
+    match self {
+      Self::Trait(trait_ref) => CategoryPayload::Trait(*trait_ref),
+      Self::Shards(info) => CategoryPayload::Shards(info.clone()),
+      _ => CategoryPayload::None,
+    }
+  }
+
+  /// The IANA media type of the files of the category, if it has a registered one.
+  pub fn mime_type(&self) -> Option<&'static str> {
+    Some(match self {
+      Self::Text(TextCategories::Plain) => "text/plain",
+      Self::Text(TextCategories::Json) => "application/json",
+      Self::Text(TextCategories::Markdown) => "text/markdown",
+      Self::Audio(AudioCategories::OggFile) => "audio/ogg",
+      Self::Audio(AudioCategories::Mp3File) => "audio/mpeg",
+      Self::Texture(TextureCategories::PngFile) => "image/png",
+      Self::Texture(TextureCategories::JpgFile) => "image/jpeg",
+      Self::Vector(VectorCategories::SvgFile) => "image/svg+xml",
+      Self::Vector(VectorCategories::TtfFile) => "font/ttf",
+      Self::Vector(VectorCategories::OtfFile) => "font/otf",
+      Self::Video(VideoCategories::MkvFile) => "video/matroska",
+      Self::Video(VideoCategories::Mp4File) => "video/mp4",
+      Self::Model(ModelCategories::GltfFile) => "model/gltf-binary",
+      Self::Binary(BinaryCategories::WasmProgram | BinaryCategories::WasmReactor) => {
+        "application/wasm"
+      }
+      _ => return None,
+    })
+  }
+
+  /// The usual file extensions of the category, without dot, the preferred one first.
+  pub fn extensions(&self) -> &'static [&'static str] {
+    match self {
+      Self::Text(TextCategories::Plain) => &["txt"],
+      Self::Text(TextCategories::Json) => &["json"],
+      Self::Text(TextCategories::Wgsl) => &["wgsl"],
+      Self::Text(TextCategories::Markdown) => &["md", "markdown"],
+      Self::Audio(AudioCategories::OggFile) => &["ogg", "oga"],
+      Self::Audio(AudioCategories::Mp3File) => &["mp3"],
+      Self::Texture(TextureCategories::PngFile) => &["png"],
+      Self::Texture(TextureCategories::JpgFile) => &["jpg", "jpeg"],
+      Self::Vector(VectorCategories::SvgFile) => &["svg"],
+      Self::Vector(VectorCategories::TtfFile) => &["ttf"],
+      Self::Vector(VectorCategories::OtfFile) => &["otf"],
+      Self::Video(VideoCategories::MkvFile) => &["mkv"],
+      Self::Video(VideoCategories::Mp4File) => &["mp4"],
+      Self::Model(ModelCategories::GltfFile) => &["glb"],
+      Self::Binary(BinaryCategories::WasmProgram | BinaryCategories::WasmReactor) => &["wasm"],
+      Self::Binary(BinaryCategories::BlendFile) => &["blend"],
+      Self::Binary(BinaryCategories::OnnxModel) => &["onnx"],
+      Self::Binary(BinaryCategories::SafeTensors) => &["safetensors"],
+      _ => &[],
+    }
+  }
+}
+
+/// The data carried by a category besides its [`Categories::stable_id`].
+#[derive(Clone, PartialEq, Debug, Eq, Default)]
+pub enum CategoryPayload {
+  /// Categories carrying no data
+  #[default]
+  None,
+  /// The trait of `Categories::Trait`
+  Trait(TraitRef),
+  /// The script info of `Categories::Shards`
+  Shards(ShardsScriptInfo),
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn stable_ids() {
+    assert_eq!(Categories::Text(TextCategories::Plain).stable_id(), 0x0000);
+    assert_eq!(
+      Categories::Trait(TraitRef::Hash([1; 8])).stable_id(),
+      0x0100
+    );
+    assert_eq!(
+      Categories::Texture(TextureCategories::PngFile).stable_id(),
+      0x0400
+    );
+    assert_eq!(
+      Categories::Binary(BinaryCategories::SafeTensors).stable_id(),
+      0x0804
+    );
+    assert_eq!(Categories::Bundle.stable_id(), 0x0900);
+
+    let all = [
+      Categories::Text(TextCategories::Markdown),
+      Categories::Trait(TraitRef::Hash([1; 8])),
+      Categories::Shards(ShardsScriptInfo::default()),
+      Categories::Audio(AudioCategories::Mp3File),
+      Categories::Texture(TextureCategories::JpgFile),
+      Categories::Vector(VectorCategories::OtfFile),
+      Categories::Video(VideoCategories::Mp4File),
+      Categories::Model(ModelCategories::PhysicsCollider),
+      Categories::Binary(BinaryCategories::RareDomain),
+      Categories::Bundle,
+    ];
+    for category in all {
+      assert_eq!(
+        Categories::from_stable_id(category.stable_id(), category.payload()),
+        Some(category)
+      );
+    }
+    assert_eq!(
+      Categories::from_stable_id(0x0100, CategoryPayload::None),
+      None
+    );
+    assert_eq!(
+      Categories::from_stable_id(0x04ff, CategoryPayload::None),
+      None
+    );
+    assert_eq!(
+      Categories::from_stable_id(0x0a00, CategoryPayload::None),
+      None
+    );
+  }
+
+  #[test]
+  fn script_info_builder() {
+    let info = ShardsScriptInfo::builder()
+      .version(2)
+      .format(ShardsFormat::Binary)
+      .require([1; 8])
+      .require([1; 8])
+      .implement([2; 8])
+      .build()
+      .unwrap();
+    assert_eq!(
+      info,
+      ShardsScriptInfo {
+        format: ShardsFormat::Binary,
+        shards_version: 2,
+        requiring: vec![[1; 8]],
+        implementing: vec![[2; 8]],
+      }
+    );
+    assert_eq!(info.validate(), Ok(()));
+
+    assert_eq!(
+      ShardsScriptInfo::builder().build(),
+      Err(ScriptInfoError::ZeroVersion)
+    );
+    let overlapping = ShardsScriptInfo::builder()
+      .version(1)
+      .require([3; 8])
+      .implement([3; 8]);
+    assert_eq!(
+      overlapping.clone().build(),
+      Err(ScriptInfoError::RequiredAndImplemented([3; 8]))
+    );
+    let overlapping = overlapping.allow_overlap().build().unwrap();
+    assert_eq!(overlapping.overlap(), Some(&[3; 8]));
+    assert_eq!(overlapping.validate(), Ok(()));
+
+    let unordered = ShardsScriptInfo::builder()
+      .version(1)
+      .require([5; 8])
+      .require([3; 8])
+      .build()
+      .unwrap();
+    assert_eq!(unordered.requiring, [[3; 8], [5; 8]]);
+
+    let duplicated = ShardsScriptInfo {
+      implementing: vec![[2; 8], [4; 8], [2; 8]],
+      ..info
+    };
+    assert_eq!(
+      duplicated.validate(),
+      Err(ScriptInfoError::DuplicateTrait([2; 8]))
+    );
+  }
+
+  #[test]
+  fn loose_names() {
+    for name in ["pngFile", "PngFile", "png_file", "pngfile", "PNG-FILE"] {
+      assert_eq!(
+        TextureCategories::from_str_loose(name),
+        Some(TextureCategories::PngFile)
+      );
+    }
+    assert_eq!(TextureCategories::from_str_loose("png"), None);
+
+    assert_eq!(
+      Categories::from_str_loose("Texture/png_file"),
+      Some(Categories::Texture(TextureCategories::PngFile))
+    );
+    assert_eq!(
+      Categories::from_str_loose("text:json"),
+      Some(Categories::Text(TextCategories::Json))
+    );
+    assert_eq!(
+      Categories::from_str_loose("bundle"),
+      Some(Categories::Bundle)
+    );
+    assert_eq!(
+      Categories::from_str_loose("trait"),
+      Some(Categories::Trait(TraitRef::Any))
+    );
+    assert_eq!(Categories::from_str_loose("texture"), None);
+    assert_eq!(Categories::from_str_loose("shards"), None);
+  }
+
+  #[cfg(feature = "std")]
+  #[test]
+  fn json_aliases() {
+    let expected = Categories::Binary(BinaryCategories::SafeTensors);
+    for json in [
+      r#"{"binary":"safeTensors"}"#,
+      r#"{"Binary":"SafeTensors"}"#,
+      r#"{"binary":"safe_tensors"}"#,
+      r#"{"binary":"safetensors"}"#,
+    ] {
+      assert_eq!(serde_json::from_str::<Categories>(json).unwrap(), expected);
+    }
+    assert_eq!(
+      serde_json::to_string(&expected).unwrap(),
+      r#"{"binary":"safeTensors"}"#
+    );
+    assert!(serde_json::from_str::<Categories>(r#"{"binary":"safe"}"#).is_err());
+
+    // the category names are as loose as the sub-category ones
+    for json in [
+      r#"{"BINARY":"safe_tensors"}"#,
+      r#"{"bin-ary":"SafeTensors"}"#,
+      r#"{"Binary ":"safetensors"}"#,
+    ] {
+      assert_eq!(serde_json::from_str::<Categories>(json).unwrap(), expected);
+    }
+    for json in [
+      r#""bundle""#,
+      r#""Bundle""#,
+      r#""BUNDLE""#,
+      r#"{"bundle":null}"#,
+    ] {
+      assert_eq!(
+        serde_json::from_str::<Categories>(json).unwrap(),
+        Categories::Bundle
+      );
+    }
+    assert_eq!(
+      serde_json::from_str::<Categories>(r#"{"TRAIT":null}"#).unwrap(),
+      Categories::Trait(TraitRef::Any)
+    );
+    assert_eq!(
+      serde_json::from_str::<Categories>(r#"{"texture":"png_file"}"#).unwrap(),
+      Categories::Texture(TextureCategories::PngFile)
+    );
+    assert!(serde_json::from_str::<Categories>(r#"{"binaries":"safeTensors"}"#).is_err());
+    assert!(serde_json::from_str::<Categories>(r#""binary""#).is_err());
+    assert!(serde_json::from_str::<Categories>(r#"{"bundle":"x"}"#).is_err());
+  }
+
+  #[test]
+  fn script_info_debug() {
+    let script = ShardsScriptInfo {
+      requiring: vec![[0xab; 8]],
+      ..Default::default()
+    };
+    assert_eq!(
+      format!("{:?}", script),
+      "ShardsScriptInfo { format: Edn, shards_version: 0, requiring: [0xabababababababab], implementing: [] }"
+    );
+  }
+
+  #[test]
+  fn trait_ref_compat() {
+    let hash = [7u8; 8];
+    for (legacy, current) in [(None, TraitRef::Any), (Some(hash), TraitRef::Hash(hash))] {
+      let encoded = Categories::Trait(current).encode();
+      assert_eq!(encoded[1..], legacy.encode());
+      assert_eq!(
+        Categories::decode(&mut &encoded[..]).unwrap(),
+        Categories::Trait(current)
+      );
+    }
+
+    let full = TraitRef::from(TraitRefFull {
+      hash,
+      revision: Some(3),
+    });
+    let encoded = full.encode();
+    assert_eq!(encoded[0], 2);
+    assert_eq!(TraitRef::decode(&mut &encoded[..]).unwrap(), full);
+    assert_eq!(
+      TraitRef::from(TraitRefFull {
+        hash,
+        revision: None
+      }),
+      TraitRef::Hash(hash)
+    );
+    let unrevised = TraitRef::Full(TraitRefFull {
+      hash,
+      revision: None,
+    });
+    assert_eq!(
+      TraitRef::decode(&mut &unrevised.encode()[..]).unwrap(),
+      TraitRef::Hash(hash)
+    );
+    assert!(TraitRef::decode(&mut &[3u8][..]).is_err());
+
+    assert!(full.matches(&hash, Some(3)));
+    assert!(!full.matches(&hash, Some(2)));
+    assert!(!full.matches(&hash, None));
+    assert!(TraitRef::Hash(hash).matches(&hash, Some(2)));
+    assert!(!TraitRef::Hash(hash).matches(&[0; 8], None));
+    assert!(TraitRef::Any.matches(&[0; 8], None));
+    assert_eq!(full.revision(), Some(3));
+    assert_eq!(full.hash(), Some(&hash));
+  }
+
+  #[cfg(feature = "std")]
+  #[test]
+  fn trait_ref_json() {
+    let hash = [1u8, 2, 3, 4, 5, 6, 7, 8];
+    for (json, trait_ref) in [
+      (r#"{"trait":null}"#, TraitRef::Any),
+      (r#"{"trait":[1,2,3,4,5,6,7,8]}"#, TraitRef::Hash(hash)),
+      (
+        r#"{"trait":{"hash":[1,2,3,4,5,6,7,8],"revision":2}}"#,
+        TraitRef::Full(TraitRefFull {
+          hash,
+          revision: Some(2),
+        }),
+      ),
+    ] {
+      let category = Categories::Trait(trait_ref);
+      assert_eq!(serde_json::to_string(&category).unwrap(), json);
+      assert_eq!(serde_json::from_str::<Categories>(json).unwrap(), category);
+    }
+    assert_eq!(
+      serde_json::from_str::<TraitRef>(r#"{"hash":[1,2,3,4,5,6,7,8],"revision":null}"#).unwrap(),
+      TraitRef::Hash(hash)
+    );
+  }
+
+  #[test]
+  fn canonical_lists() {
+    let mut hashes = vec![[3u8; 8], [1; 8], [3; 8]];
+    canonicalize_hash_list(&mut hashes);
+    assert_eq!(hashes, [[1; 8], [3; 8]]);
+
+    let script = |requiring: Vec<ShardsTrait>| {
+      Categories::Shards(ShardsScriptInfo {
+        requiring,
+        ..Default::default()
+      })
+    };
+    let mut a = vec![
+      Categories::Bundle,
+      script(vec![[2; 8], [1; 8]]),
+      Categories::Text(TextCategories::Json),
+      Categories::Bundle,
+    ];
+    let mut b = vec![
+      script(vec![[1; 8], [2; 8]]),
+      Categories::Text(TextCategories::Json),
+      Categories::Bundle,
+    ];
+    canonicalize_categories(&mut a);
+    canonicalize_categories(&mut b);
+    assert_eq!(a, b);
+    assert_eq!(a.encode(), b.encode());
+    assert_eq!(a.len(), 3);
+    assert!(a.windows(2).all(|w| w[0].encode() < w[1].encode()));
+  }
+}
@@ -0,0 +1,205 @@
+use crate::hashing::blake2_256;
+use core::fmt;
+use parity_scale_codec::{Decode, Encode};
+use scale_info::prelude::vec::Vec;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Describes proto data split in fixed size chunks, so it can be uploaded and verified incrementally.
+///
+/// Every chunk is `chunk_size` bytes long except the last one, which holds the remainder.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct ChunkedData {
+  /// The size of the whole data in bytes
+  #[codec(compact)]
+  pub total_size: u64,
+  /// The size of every chunk in bytes
+  #[codec(compact)]
+  pub chunk_size: u32,
+  /// The blake2-256 hash of every chunk, in order
+  pub chunk_hashes: Vec<[u8; 32]>,
+  /// The merkle root of `chunk_hashes`
+  pub root: [u8; 32],
+}
+
+/// Errors returned when a chunked data descriptor is inconsistent.
+#[derive(Copy, Clone, PartialEq, Debug, Eq)]
+pub enum ChunkError {
+  /// The chunk size is zero
+  ZeroChunkSize,
+  /// The amount of chunk hashes does not match the total and chunk sizes
+  ChunkCountMismatch,
+  /// The root is not the merkle root of the chunk hashes
+  RootMismatch,
+}
+
+impl fmt::Display for ChunkError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::ZeroChunkSize => write!(f, "chunk size cannot be zero"),
+      Self::ChunkCountMismatch => write!(f, "chunk count does not match the data size"),
+      Self::RootMismatch => write!(f, "merkle root does not match the chunk hashes"),
+    }
+  }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ChunkError {}
+
+/// The prefix of the hashed leaves of the merkle tree.
+const LEAF_PREFIX: u8 = 0x00;
+
+/// The prefix of the hashed inner nodes of the merkle tree.
+const NODE_PREFIX: u8 = 0x01;
+
+fn hash_prefixed(prefix: u8, parts: &[&[u8; 32]]) -> [u8; 32] {
+  let mut bytes = Vec::with_capacity(1 + 32 * parts.len());
+  bytes.push(prefix);
+  parts
+    .iter()
+    .for_each(|part| bytes.extend_from_slice(&part[..]));
+  blake2_256(&bytes)
+}
+
+/// Computes the merkle root of a list of chunk hashes.
+///
+/// Leaves are the blake2-256 of `0x00` followed by a chunk hash, and pairs of nodes are hashed
+/// together with blake2-256 behind `0x01`, so an inner node can never be passed off as a leaf.
+/// An odd node is carried to the next level as is. The root of no chunks is the hash of empty
+/// data.
+pub fn merkle_root(chunk_hashes: &[[u8; 32]]) -> [u8; 32] {
+  if chunk_hashes.is_empty() {
+    return blake2_256(&[]);
+  }
+
+  let mut level: Vec<[u8; 32]> = chunk_hashes
+    .iter()
+    .map(|hash| hash_prefixed(LEAF_PREFIX, &[hash]))
+    .collect();
+  while level.len() > 1 {
+    level = level
+      .chunks(2)
+      .map(|pair| match pair {
+        [left, right] => hash_prefixed(NODE_PREFIX, &[left, right]),
+        [single] => *single,
+        _ => unreachable!(),
+      })
+      .collect();
+  }
+  level[0]
+}
+
+impl ChunkedData {
+  /// Splits `data` in chunks of `chunk_size` bytes and builds its descriptor.
+  ///
+  /// Panics if `chunk_size` is zero.
+  pub fn new(data: &[u8], chunk_size: u32) -> Self {
+    assert!(chunk_size > 0, "chunk size cannot be zero");
+    let chunk_hashes: Vec<[u8; 32]> = data.chunks(chunk_size as usize).map(blake2_256).collect();
+    Self {
+      total_size: data.len() as u64,
+      chunk_size,
+      root: merkle_root(&chunk_hashes),
+      chunk_hashes,
+    }
+  }
+
+  /// The amount of chunks the data must be split in.
+  pub fn chunk_count(&self) -> u64 {
+    if self.chunk_size == 0 {
+      return 0;
+    }
+    self.total_size.div_ceil(self.chunk_size as u64)
+  }
+
+  /// The expected size of the chunk at `index`, `None` if out of bounds.
+  pub fn chunk_len(&self, index: usize) -> Option<u64> {
+    let index = index as u64;
+    if index >= self.chunk_count() {
+      return None;
+    }
+    let start = index * self.chunk_size as u64;
+    Some((self.total_size - start).min(self.chunk_size as u64))
+  }
+
+  /// Whether `bytes` are the chunk at `index`.
+  pub fn verify_chunk(&self, index: usize, bytes: &[u8]) -> bool {
+    self.chunk_len(index) == Some(bytes.len() as u64)
+      && self.chunk_hashes.get(index) == Some(&blake2_256(bytes))
+  }
+
+  /// Checks that the descriptor is consistent: sizes match the chunk hashes, and the root matches them.
+  pub fn validate(&self) -> Result<(), ChunkError> {
+    if self.chunk_size == 0 {
+      return Err(ChunkError::ZeroChunkSize);
+    }
+    if self.chunk_hashes.len() as u64 != self.chunk_count() {
+      return Err(ChunkError::ChunkCountMismatch);
+    }
+    if merkle_root(&self.chunk_hashes) != self.root {
+      return Err(ChunkError::RootMismatch);
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn chunk_and_verify() {
+    let data: Vec<u8> = (0..=255u8).cycle().take(1000).collect();
+    let chunked = ChunkedData::new(&data, 300);
+    assert_eq!(chunked.chunk_count(), 4);
+    assert_eq!(chunked.chunk_len(3), Some(100));
+    assert_eq!(chunked.chunk_len(4), None);
+    assert_eq!(chunked.validate(), Ok(()));
+
+    for (index, chunk) in data.chunks(300).enumerate() {
+      assert!(chunked.verify_chunk(index, chunk));
+    }
+    assert!(!chunked.verify_chunk(0, &data[1..301]));
+    assert!(!chunked.verify_chunk(3, &data[..100]));
+  }
+
+  #[test]
+  fn merkle_roots() {
+    let prefixed = |prefix: u8, parts: &[[u8; 32]]| {
+      let mut bytes = vec![prefix];
+      parts.iter().for_each(|part| bytes.extend_from_slice(part));
+      blake2_256(&bytes)
+    };
+    let [a, b, c] = [b"a", b"b", b"c"].map(|data| blake2_256(data));
+    let [leaf_a, leaf_b, leaf_c] = [a, b, c].map(|hash| prefixed(0, &[hash]));
+    let ab = prefixed(1, &[leaf_a, leaf_b]);
+
+    assert_eq!(merkle_root(&[]), blake2_256(&[]));
+    assert_eq!(merkle_root(&[a]), leaf_a);
+    assert_eq!(merkle_root(&[a, b]), ab);
+    assert_eq!(merkle_root(&[a, b, c]), prefixed(1, &[ab, leaf_c]));
+
+    // an inner node is not the root of a leaf
+    assert_ne!(merkle_root(&[ab]), merkle_root(&[a, b]));
+  }
+
+  #[test]
+  fn invalid_descriptors() {
+    let data = [1u8; 64];
+    let mut chunked = ChunkedData::new(&data, 16);
+    chunked.root[0] ^= 1;
+    assert_eq!(chunked.validate(), Err(ChunkError::RootMismatch));
+
+    chunked.chunk_hashes.pop();
+    assert_eq!(chunked.validate(), Err(ChunkError::ChunkCountMismatch));
+
+    chunked.chunk_size = 0;
+    assert_eq!(chunked.validate(), Err(ChunkError::ZeroChunkSize));
+  }
+}
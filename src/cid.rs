@@ -0,0 +1,415 @@
+use core::{fmt, str::FromStr};
+use parity_scale_codec::{Decode, Encode, Input};
+use scale_info::prelude::vec::Vec;
+
+// For more info refer to:
+// https://github.com/multiformats/cid
+// https://github.com/multiformats/multihash
+
+const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+const BASE32_ALPHABET: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+
+/// Multihash code of sha2-256, the only hash function allowed in CIDv0.
+const SHA2_256: u64 = 0x12;
+/// Multicodec of dag-pb, the implicit codec of CIDv0.
+const DAG_PB: u64 = 0x70;
+/// The longest base58 string decoded, base58 decoding is quadratic in the length of its input.
+const MAX_BASE58_LEN: usize = 256;
+
+/// An IPFS content identifier, stored in its binary form.
+///
+/// Both CIDv0 (`Qm...`) and CIDv1 (multibase, `b...` by default) string forms are supported.
+#[derive(Encode, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, scale_info::TypeInfo)]
+pub struct Cid(Vec<u8>);
+
+/// Errors returned when parsing an invalid CID.
+#[derive(Copy, Clone, PartialEq, Debug, Eq)]
+pub enum CidError {
+  /// The multibase prefix of the string is not supported
+  UnsupportedBase,
+  /// The string contains characters outside of its base alphabet
+  InvalidEncoding,
+  /// The CID version is not 0 or 1
+  UnsupportedVersion,
+  /// A varint is malformed or truncated
+  InvalidVarint,
+  /// The multihash is malformed
+  InvalidMultihash,
+  /// The digest length does not match the one declared or required by the hash function
+  DigestLengthMismatch,
+  /// The string is longer than any supported CID
+  TooLong,
+}
+
+impl fmt::Display for CidError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::UnsupportedBase => write!(f, "unsupported multibase"),
+      Self::InvalidEncoding => write!(f, "invalid base encoding"),
+      Self::UnsupportedVersion => write!(f, "unsupported CID version"),
+      Self::InvalidVarint => write!(f, "invalid varint"),
+      Self::InvalidMultihash => write!(f, "invalid multihash"),
+      Self::DigestLengthMismatch => write!(f, "multihash digest length mismatch"),
+      Self::TooLong => write!(f, "CID too long"),
+    }
+  }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CidError {}
+
+impl Cid {
+  /// Creates a CID from its binary form, validating it.
+  pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, CidError> {
+    let cid = Self(bytes);
+    cid.parts()?;
+    Ok(cid)
+  }
+
+  /// Builds a CIDv1 from a content multicodec and a multihash.
+  pub fn new_v1(codec: u64, multihash: &[u8]) -> Result<Self, CidError> {
+    let mut bytes = Vec::with_capacity(multihash.len() + 4);
+    push_varint(&mut bytes, 1);
+    push_varint(&mut bytes, codec);
+    bytes.extend_from_slice(multihash);
+    Self::from_bytes(bytes)
+  }
+
+  pub fn as_bytes(&self) -> &[u8] {
+    &self.0
+  }
+
+  pub fn into_bytes(self) -> Vec<u8> {
+    self.0
+  }
+
+  /// The CID version, either 0 or 1.
+  pub fn version(&self) -> u8 {
+    self
+      .parts()
+      .map(|(version, _, _)| version)
+      .unwrap_or_default()
+  }
+
+  /// The multicodec of the content, dag-pb for CIDv0.
+  pub fn codec(&self) -> u64 {
+    self.parts().map(|(_, codec, _)| codec).unwrap_or_default()
+  }
+
+  /// The multihash of the content.
+  pub fn multihash(&self) -> &[u8] {
+    self.parts().map(|(_, _, hash)| hash).unwrap_or_default()
+  }
+
+  fn parts(&self) -> Result<(u8, u64, &[u8]), CidError> {
+    let bytes = self.0.as_slice();
+    // a CIDv0 is a bare sha2-256 multihash
+    if bytes.len() == 34 && bytes[0] == SHA2_256 as u8 && bytes[1] == 32 {
+      return Ok((0, DAG_PB, bytes));
+    }
+
+    let (version, rest) = read_varint(bytes)?;
+    if version != 1 {
+      return Err(CidError::UnsupportedVersion);
+    }
+    let (codec, multihash) = read_varint(rest)?;
+    validate_multihash(multihash)?;
+    Ok((1, codec, multihash))
+  }
+}
+
+fn read_varint(bytes: &[u8]) -> Result<(u64, &[u8]), CidError> {
+  let mut value = 0u64;
+  // multiformats varints are limited to 9 bytes
+  for (i, byte) in bytes.iter().enumerate().take(9) {
+    value |= ((byte & 0x7f) as u64) << (7 * i);
+    if byte & 0x80 == 0 {
+      // a trailing zero byte is a non-minimal encoding of a shorter varint
+      if *byte == 0 && i > 0 {
+        return Err(CidError::InvalidVarint);
+      }
+      return Ok((value, &bytes[i + 1..]));
+    }
+  }
+  Err(CidError::InvalidVarint)
+}
+
+fn push_varint(dest: &mut Vec<u8>, mut value: u64) {
+  while value >= 0x80 {
+    dest.push((value as u8) | 0x80);
+    value >>= 7;
+  }
+  dest.push(value as u8);
+}
+
+fn validate_multihash(multihash: &[u8]) -> Result<(), CidError> {
+  let (code, rest) = read_varint(multihash).map_err(|_| CidError::InvalidMultihash)?;
+  let (length, digest) = read_varint(rest).map_err(|_| CidError::InvalidMultihash)?;
+  if digest.len() as u64 != length {
+    return Err(CidError::DigestLengthMismatch);
+  }
+
+  let expected = match code {
+    // sha2-256, sha3-256, keccak-256, blake2b-256
+    0x12 | 0x16 | 0x1b | 0xb220 => Some(32),
+    // sha2-512, sha3-512, blake2b-512
+    0x13 | 0x14 | 0xb240 => Some(64),
+    _ => None,
+  };
+  match expected {
+    Some(expected) if expected != length => Err(CidError::DigestLengthMismatch),
+    _ => Ok(()),
+  }
+}
+
+fn base58_decode(input: &str) -> Result<Vec<u8>, CidError> {
+  if input.len() > MAX_BASE58_LEN {
+    return Err(CidError::TooLong);
+  }
+  let mut bytes: Vec<u8> = Vec::new();
+  for c in input.bytes() {
+    let mut carry = BASE58_ALPHABET
+      .iter()
+      .position(|a| *a == c)
+      .ok_or(CidError::InvalidEncoding)? as u32;
+    for byte in bytes.iter_mut().rev() {
+      carry += (*byte as u32) * 58;
+      *byte = carry as u8;
+      carry >>= 8;
+    }
+    while carry > 0 {
+      bytes.insert(0, carry as u8);
+      carry >>= 8;
+    }
+  }
+  let zeros = input.bytes().take_while(|c| *c == b'1').count();
+  let mut result = Vec::with_capacity(zeros + bytes.len());
+  result.resize(zeros, 0);
+  result.extend(bytes);
+  Ok(result)
+}
+
+fn base58_encode(input: &[u8], f: &mut fmt::Formatter<'_>) -> fmt::Result {
+  let mut digits: Vec<u8> = Vec::new();
+  for byte in input {
+    let mut carry = *byte as u32;
+    for digit in digits.iter_mut().rev() {
+      carry += (*digit as u32) << 8;
+      *digit = (carry % 58) as u8;
+      carry /= 58;
+    }
+    while carry > 0 {
+      digits.insert(0, (carry % 58) as u8);
+      carry /= 58;
+    }
+  }
+  for _ in input.iter().take_while(|b| **b == 0) {
+    f.write_str("1")?;
+  }
+  for digit in digits {
+    write!(f, "{}", BASE58_ALPHABET[digit as usize] as char)?;
+  }
+  Ok(())
+}
+
+fn base32_decode(input: &str) -> Result<Vec<u8>, CidError> {
+  let mut result = Vec::with_capacity(input.len() * 5 / 8);
+  let mut buffer = 0u32;
+  let mut bits = 0;
+  for c in input.bytes() {
+    let value = BASE32_ALPHABET
+      .iter()
+      .position(|a| *a == c.to_ascii_lowercase())
+      .ok_or(CidError::InvalidEncoding)? as u32;
+    buffer = (buffer << 5) | value;
+    bits += 5;
+    if bits >= 8 {
+      bits -= 8;
+      result.push((buffer >> bits) as u8);
+      buffer &= (1 << bits) - 1;
+    }
+  }
+  // the padding bits of the last character must be zero, and a whole character cannot be padding
+  if bits >= 5 || buffer != 0 {
+    return Err(CidError::InvalidEncoding);
+  }
+  Ok(result)
+}
+
+fn base32_encode(input: &[u8], f: &mut fmt::Formatter<'_>) -> fmt::Result {
+  let mut buffer = 0u32;
+  let mut bits = 0;
+  for byte in input {
+    buffer = (buffer << 8) | *byte as u32;
+    bits += 8;
+    while bits >= 5 {
+      bits -= 5;
+      write!(
+        f,
+        "{}",
+        BASE32_ALPHABET[(buffer >> bits) as usize & 31] as char
+      )?;
+    }
+    buffer &= (1 << bits) - 1;
+  }
+  if bits > 0 {
+    write!(
+      f,
+      "{}",
+      BASE32_ALPHABET[(buffer << (5 - bits)) as usize & 31] as char
+    )?;
+  }
+  Ok(())
+}
+
+impl FromStr for Cid {
+  type Err = CidError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    if s.len() == 46 && s.starts_with("Qm") {
+      return Self::from_bytes(base58_decode(s)?);
+    }
+
+    let mut chars = s.chars();
+    let bytes = match chars.next() {
+      Some('b') | Some('B') => base32_decode(chars.as_str())?,
+      Some('z') => base58_decode(chars.as_str())?,
+      _ => return Err(CidError::UnsupportedBase),
+    };
+    let cid = Self::from_bytes(bytes)?;
+    // the binary form of a CIDv0 is not prefixed, so it cannot be multibase encoded
+    if cid.version() == 0 {
+      return Err(CidError::UnsupportedVersion);
+    }
+    Ok(cid)
+  }
+}
+
+impl fmt::Display for Cid {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if self.version() == 0 {
+      base58_encode(&self.0, f)
+    } else {
+      f.write_str("b")?;
+      base32_encode(&self.0, f)
+    }
+  }
+}
+
+impl fmt::Debug for Cid {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "Cid({})", self)
+  }
+}
+
+impl Decode for Cid {
+  fn decode<I: Input>(input: &mut I) -> Result<Self, parity_scale_codec::Error> {
+    Self::from_bytes(Vec::<u8>::decode(input)?).map_err(|_| "Invalid CID".into())
+  }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Cid {
+  fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(self)
+  }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Cid {
+  fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    let s = <scale_info::prelude::borrow::Cow<'de, str> as serde::Deserialize>::deserialize(
+      deserializer,
+    )?;
+    s.parse().map_err(serde::de::Error::custom)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const V0: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+  const V1: &str = "bafybeie5nqv6kd3qnfjupgvz34woh3oksc3iau6abmyajn7qvtf6d2ho34";
+
+  #[test]
+  fn parse_cid_v0() {
+    let cid: Cid = V0.parse().unwrap();
+    assert_eq!(cid.version(), 0);
+    assert_eq!(cid.codec(), DAG_PB);
+    assert_eq!(cid.as_bytes().len(), 34);
+    assert_eq!(cid.to_string(), V0);
+  }
+
+  #[test]
+  fn parse_cid_v1() {
+    let v0: Cid = V0.parse().unwrap();
+    let cid: Cid = V1.parse().unwrap();
+    assert_eq!(cid.version(), 1);
+    assert_eq!(cid.codec(), DAG_PB);
+    assert_eq!(cid.multihash(), v0.multihash());
+    assert_eq!(cid.to_string(), V1);
+    assert_eq!(Cid::new_v1(DAG_PB, v0.multihash()).unwrap(), cid);
+    assert_eq!(V1.to_uppercase().parse::<Cid>().unwrap(), cid);
+  }
+
+  #[test]
+  fn reject_invalid_cids() {
+    assert_eq!("".parse::<Cid>(), Err(CidError::UnsupportedBase));
+    assert_eq!("Qm0000".parse::<Cid>(), Err(CidError::UnsupportedBase));
+    assert_eq!(
+      "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbd0".parse::<Cid>(),
+      Err(CidError::InvalidEncoding)
+    );
+    // truncated digest
+    let bytes = V1.parse::<Cid>().unwrap().into_bytes();
+    assert_eq!(
+      Cid::from_bytes(bytes[..bytes.len() - 1].to_vec()),
+      Err(CidError::DigestLengthMismatch)
+    );
+    assert_eq!(
+      V1[..V1.len() - 4].parse::<Cid>(),
+      Err(CidError::InvalidEncoding)
+    );
+    // sha2-256 with a 16 bytes digest
+    let mut multihash = vec![0x12, 16];
+    multihash.extend([0u8; 16]);
+    assert_eq!(
+      Cid::new_v1(DAG_PB, &multihash),
+      Err(CidError::DigestLengthMismatch)
+    );
+    assert_eq!(
+      Cid::from_bytes(vec![2, 0x70, 0x12, 0]),
+      Err(CidError::UnsupportedVersion)
+    );
+
+    // every CID has a single binary and string form
+    let cid: Cid = V1.parse().unwrap();
+    let mut padded = vec![0x81, 0x00];
+    padded.extend_from_slice(&cid.as_bytes()[1..]);
+    assert_eq!(Cid::from_bytes(padded), Err(CidError::InvalidVarint));
+    let trailing_bits = format!("{}5", &V1[..V1.len() - 1]);
+    assert_eq!(trailing_bits.parse::<Cid>(), Err(CidError::InvalidEncoding));
+    assert_eq!(
+      format!("{}a", V1).parse::<Cid>(),
+      Err(CidError::InvalidEncoding)
+    );
+    assert_eq!(
+      format!("z{}", "2".repeat(10_000)).parse::<Cid>(),
+      Err(CidError::TooLong)
+    );
+  }
+
+  #[test]
+  fn encode_decode_cid() {
+    let cid: Cid = V1.parse().unwrap();
+    let encoded = cid.encode();
+    assert_eq!(Cid::decode(&mut encoded.as_slice()).unwrap(), cid);
+    assert!(Cid::decode(&mut vec![0u8; 3].encode().as_slice()).is_err());
+
+    let json = serde_json::to_string(&cid).unwrap();
+    assert_eq!(json, format!("\"{}\"", V1));
+    assert_eq!(serde_json::from_str::<Cid>(&json).unwrap(), cid);
+    assert!(serde_json::from_str::<Cid>("\"bafy\"").is_err());
+  }
+}
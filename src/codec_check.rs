@@ -0,0 +1,464 @@
+//! The SCALE variant indexes of every enum of the crate, so chains can snapshot the wire format
+//! and detect accidental reorderings when upgrading the crate.
+
+use crate::{
+  accounts::ExternalIdentity,
+  acl::Principal,
+  audience::{ContentRating, RegionRestriction},
+  bundles::BundleEntryRole,
+  categories::{
+    AudioCategories, BinaryCategories, Categories, ModelCategories, ShardsFormat, TextCategories,
+    TextureCategories, TraitRef, VectorCategories, VideoCategories,
+  },
+  compression::Compression,
+  detach::SupportedChains,
+  filter::FilterExpr,
+  fragments::{InstanceDataRef, UniqueDataError},
+  hashing::HashScheme,
+  limits_wire::LimitsEncoding,
+  pagination::SortOrder,
+  policy::{CategoryFilter, PolicyDecision},
+  rpc::ProtoField,
+  sales::SaleTerms,
+  signing::PayloadKind,
+  traits::{CodeType, EventDelivery, KeyPattern, VariableType, VectorLimits},
+  transfer::TransferTarget,
+};
+use scale_info::{
+  prelude::{string::String, vec::Vec},
+  TypeDef, TypeInfo,
+};
+
+#[cfg(feature = "wgsl")]
+use crate::content::wgsl::{BindingKind, ShaderStage};
+#[cfg(feature = "registry")]
+use crate::registry::{IndexUpdate, TraitStatus};
+#[cfg(feature = "hashing")]
+use crate::{
+  assets::{FetchPolicy, HashAlgorithm, LinkedAsset},
+  encryption::EncryptionScheme,
+};
+
+/// The name of an enum, without its module path.
+pub type TypeName = &'static str;
+/// The name of a variant.
+pub type VariantName = &'static str;
+
+/// A difference between the current variant table and a snapshot.
+#[derive(Clone, PartialEq, Debug, Eq)]
+pub enum CodecMismatch {
+  /// The variant is now encoded with another index
+  Moved {
+    type_name: String,
+    variant: String,
+    expected: u8,
+    actual: u8,
+  },
+  /// The variant, or its whole enum, no longer exists
+  Removed { type_name: String, variant: String },
+}
+
+impl core::fmt::Display for CodecMismatch {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    match self {
+      Self::Moved {
+        type_name,
+        variant,
+        expected,
+        actual,
+      } => write!(
+        f,
+        "{}::{} moved from index {} to {}",
+        type_name, variant, expected, actual
+      ),
+      Self::Removed { type_name, variant } => write!(f, "{}::{} was removed", type_name, variant),
+    }
+  }
+}
+
+fn push_variants<T: TypeInfo + 'static>(table: &mut Vec<(TypeName, VariantName, u8)>) {
+  let ty = T::type_info();
+  let name = ty.path.ident().unwrap_or_default();
+  if let TypeDef::Variant(def) = ty.type_def {
+    table.extend(def.variants.iter().map(|v| (name, v.name, v.index)));
+  }
+}
+
+/// Generates `variant_table` from the listed enums, generic enums are listed with any parameters
+/// and enums of optional modules with their `cfg`.
+macro_rules! codec_enums {
+  ($($(#[$meta:meta])* $ty:ty),* $(,)?) => {
+    /// Every `(enum, variant, index)` of the SCALE encoded enums of the crate.
+    pub fn variant_table() -> Vec<(TypeName, VariantName, u8)> {
+      let mut table = Vec::new();
+      $($(#[$meta])* push_variants::<$ty>(&mut table);)*
+      table
+    }
+  };
+}
+
+// New enums must be added here.
+codec_enums!(
+  ExternalIdentity,
+  Principal<u64>,
+  ContentRating,
+  RegionRestriction,
+  #[cfg(feature = "hashing")]
+  HashAlgorithm,
+  #[cfg(feature = "hashing")]
+  FetchPolicy,
+  #[cfg(feature = "hashing")]
+  LinkedAsset,
+  BundleEntryRole,
+  ShardsFormat,
+  AudioCategories,
+  ModelCategories,
+  TextureCategories,
+  VectorCategories,
+  VideoCategories,
+  TextCategories,
+  BinaryCategories,
+  TraitRef,
+  Categories,
+  Compression,
+  SupportedChains,
+  #[cfg(feature = "hashing")]
+  EncryptionScheme,
+  FilterExpr<u64>,
+  InstanceDataRef<[u8; 32]>,
+  UniqueDataError,
+  HashScheme,
+  LimitsEncoding,
+  SortOrder,
+  CategoryFilter,
+  PolicyDecision,
+  #[cfg(feature = "registry")]
+  TraitStatus,
+  #[cfg(feature = "registry")]
+  IndexUpdate,
+  ProtoField,
+  SaleTerms<u32, u128>,
+  PayloadKind,
+  CodeType,
+  EventDelivery,
+  KeyPattern,
+  VariableType,
+  // Encoded by hand, with the variants described by its `TypeInfo`.
+  VectorLimits<2>,
+  TransferTarget,
+  #[cfg(feature = "wgsl")]
+  ShaderStage,
+  #[cfg(feature = "wgsl")]
+  BindingKind,
+);
+
+/// Compares the current variant table with `snapshot`, a table saved from an earlier version.
+///
+/// Variants added since the snapshot are not reported, they do not change existing encodings.
+pub fn verify_against<S: AsRef<str>>(snapshot: &[(S, S, u8)]) -> Result<(), Vec<CodecMismatch>> {
+  let table = variant_table();
+  let mismatches: Vec<_> = snapshot
+    .iter()
+    .filter_map(|(type_name, variant, expected)| {
+      let (type_name, variant) = (type_name.as_ref(), variant.as_ref());
+      let current = table
+        .iter()
+        .find(|(t, v, _)| *t == type_name && *v == variant);
+      match current {
+        Some((_, _, actual)) if actual == expected => None,
+        Some((_, _, actual)) => Some(CodecMismatch::Moved {
+          type_name: type_name.into(),
+          variant: variant.into(),
+          expected: *expected,
+          actual: *actual,
+        }),
+        None => Some(CodecMismatch::Removed {
+          type_name: type_name.into(),
+          variant: variant.into(),
+        }),
+      }
+    })
+    .collect();
+  if mismatches.is_empty() {
+    Ok(())
+  } else {
+    Err(mismatches)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// The wire format layout, any change here breaks existing encodings.
+  const SNAPSHOT: &[(TypeName, VariantName, u8)] = &[
+    ("ExternalIdentity", "Evm", 0),
+    ("ExternalIdentity", "Ed25519", 1),
+    ("ExternalIdentity", "Sr25519", 2),
+    ("Principal", "Anyone", 0),
+    ("Principal", "Account", 1),
+    ("Principal", "FragmentHolder", 2),
+    ("ContentRating", "Everyone", 0),
+    ("ContentRating", "Teen", 1),
+    ("ContentRating", "Mature", 2),
+    ("ContentRating", "Explicit", 3),
+    ("RegionRestriction", "None", 0),
+    ("RegionRestriction", "AllowOnly", 1),
+    ("RegionRestriction", "Deny", 2),
+    ("HashAlgorithm", "Blake2b256", 0),
+    ("HashAlgorithm", "Sha256", 1),
+    ("FetchPolicy", "Immutable", 0),
+    ("FetchPolicy", "Revalidate", 1),
+    ("LinkedAsset", "Ipfs", 0),
+    ("LinkedAsset", "Arweave", 1),
+    ("LinkedAsset", "Url", 2),
+    ("BundleEntryRole", "Primary", 0),
+    ("BundleEntryRole", "Thumbnail", 1),
+    ("BundleEntryRole", "Dependency", 2),
+    ("BundleEntryRole", "Slot", 3),
+    ("ShardsFormat", "Edn", 0),
+    ("ShardsFormat", "Binary", 1),
+    ("AudioCategories", "OggFile", 0),
+    ("AudioCategories", "Mp3File", 1),
+    ("ModelCategories", "GltfFile", 0),
+    ("ModelCategories", "Sdf", 1),
+    ("ModelCategories", "PhysicsCollider", 2),
+    ("TextureCategories", "PngFile", 0),
+    ("TextureCategories", "JpgFile", 1),
+    ("VectorCategories", "SvgFile", 0),
+    ("VectorCategories", "TtfFile", 1),
+    ("VectorCategories", "OtfFile", 2),
+    ("VideoCategories", "MkvFile", 0),
+    ("VideoCategories", "Mp4File", 1),
+    ("TextCategories", "Plain", 0),
+    ("TextCategories", "Json", 1),
+    ("TextCategories", "Wgsl", 2),
+    ("TextCategories", "Markdown", 3),
+    ("BinaryCategories", "WasmProgram", 0),
+    ("BinaryCategories", "WasmReactor", 1),
+    ("BinaryCategories", "BlendFile", 2),
+    ("BinaryCategories", "OnnxModel", 3),
+    ("BinaryCategories", "SafeTensors", 4),
+    ("BinaryCategories", "RareDomain", 5),
+    ("TraitRef", "Any", 0),
+    ("TraitRef", "Hash", 1),
+    ("TraitRef", "Full", 2),
+    ("Categories", "Text", 0),
+    ("Categories", "Trait", 1),
+    ("Categories", "Shards", 2),
+    ("Categories", "Audio", 3),
+    ("Categories", "Texture", 4),
+    ("Categories", "Vector", 5),
+    ("Categories", "Video", 6),
+    ("Categories", "Model", 7),
+    ("Categories", "Binary", 8),
+    ("Categories", "Bundle", 9),
+    ("Compression", "None", 0),
+    ("Compression", "Zstd", 1),
+    ("Compression", "Brotli", 2),
+    ("SupportedChains", "EthereumMainnet", 0),
+    ("SupportedChains", "EthereumRinkeby", 1),
+    ("SupportedChains", "EthereumGoerli", 2),
+    ("EncryptionScheme", "XChaCha20Poly1305", 0),
+    ("EncryptionScheme", "Aes256Gcm", 1),
+    ("FilterExpr", "And", 0),
+    ("FilterExpr", "Or", 1),
+    ("FilterExpr", "Not", 2),
+    ("FilterExpr", "Category", 3),
+    ("FilterExpr", "Tag", 4),
+    ("FilterExpr", "Owner", 5),
+    ("FilterExpr", "Implements", 6),
+    ("InstanceDataRef", "Immutable", 0),
+    ("InstanceDataRef", "Mutable", 1),
+    ("UniqueDataError", "MutableReference", 0),
+    ("UniqueDataError", "HashMismatch", 1),
+    ("HashScheme", "Xx64", 0),
+    ("LimitsEncoding", "V1", 1),
+    ("LimitsEncoding", "V2", 2),
+    ("SortOrder", "Ascending", 0),
+    ("SortOrder", "Descending", 1),
+    ("CategoryFilter", "Any", 0),
+    ("CategoryFilter", "Variant", 1),
+    ("CategoryFilter", "Exact", 2),
+    ("PolicyDecision", "Allow", 0),
+    ("PolicyDecision", "Review", 1),
+    ("PolicyDecision", "Block", 2),
+    ("TraitStatus", "Active", 0),
+    ("TraitStatus", "Deprecated", 1),
+    ("IndexUpdate", "Insert", 0),
+    ("IndexUpdate", "Remove", 1),
+    ("IndexUpdate", "SetStatus", 2),
+    ("ProtoField", "Block", 0),
+    ("ProtoField", "Creator", 1),
+    ("ProtoField", "Owner", 2),
+    ("ProtoField", "References", 3),
+    ("ProtoField", "Category", 4),
+    ("ProtoField", "Tags", 5),
+    ("SaleTerms", "FixedPrice", 0),
+    ("SaleTerms", "Auction", 1),
+    ("SaleTerms", "OpenEdition", 2),
+    ("PayloadKind", "Trait", 0),
+    ("PayloadKind", "Proto", 1),
+    ("PayloadKind", "Bundle", 2),
+    ("PayloadKind", "Transfer", 3),
+    ("CodeType", "Shards", 0),
+    ("CodeType", "Wire", 1),
+    ("CodeType", "WireV2", 2),
+    ("EventDelivery", "Latest", 0),
+    ("EventDelivery", "AtMostOnce", 1),
+    ("EventDelivery", "AtLeastOnce", 2),
+    ("EventDelivery", "ExactlyOnce", 3),
+    ("KeyPattern", "Exact", 0),
+    ("KeyPattern", "Prefix", 1),
+    ("KeyPattern", "Any", 2),
+    ("VariableType", "None", 0),
+    ("VariableType", "Any", 1),
+    ("VariableType", "Bool", 2),
+    ("VariableType", "Color", 3),
+    ("VariableType", "Bytes", 4),
+    ("VariableType", "String", 5),
+    ("VariableType", "Image", 6),
+    ("VariableType", "Audio", 7),
+    ("VariableType", "Mesh", 8),
+    ("VariableType", "Enum", 9),
+    ("VariableType", "Int", 10),
+    ("VariableType", "Int2", 11),
+    ("VariableType", "Int3", 12),
+    ("VariableType", "Int4", 13),
+    ("VariableType", "Int8", 14),
+    ("VariableType", "Int16", 15),
+    ("VariableType", "Float", 16),
+    ("VariableType", "Float2", 17),
+    ("VariableType", "Float3", 18),
+    ("VariableType", "Float4", 19),
+    ("VariableType", "Seq", 20),
+    ("VariableType", "Table", 21),
+    ("VariableType", "Object", 22),
+    ("VariableType", "Code", 23),
+    ("VariableType", "Channel", 24),
+    ("VariableType", "Event", 25),
+    ("VariableType", "ChannelV2", 26),
+    ("VariableType", "EventV2", 27),
+    ("VariableType", "TableV2", 28),
+    ("VectorLimits", "PerLaneFirstUnlimited", 0),
+    ("VectorLimits", "PerLaneFirstLimited", 1),
+    ("VectorLimits", "Shared", 2),
+    ("TransferTarget", "Proto", 0),
+    ("TransferTarget", "Definition", 1),
+    ("TransferTarget", "Instance", 2),
+    ("ShaderStage", "Vertex", 0),
+    ("ShaderStage", "Fragment", 1),
+    ("ShaderStage", "Compute", 2),
+    ("ShaderStage", "Task", 3),
+    ("ShaderStage", "Mesh", 4),
+    ("BindingKind", "Uniform", 0),
+    ("BindingKind", "Storage", 1),
+    ("BindingKind", "Handle", 2),
+  ];
+
+  /// Whether the enum is in the table of this build, enums of disabled features are not.
+  fn enabled(type_name: &str) -> bool {
+    let hashing = [
+      "HashAlgorithm",
+      "FetchPolicy",
+      "LinkedAsset",
+      "EncryptionScheme",
+    ];
+    let registry = ["TraitStatus", "IndexUpdate"];
+    let wgsl = ["ShaderStage", "BindingKind"];
+    (cfg!(feature = "hashing") || !hashing.contains(&type_name))
+      && (cfg!(feature = "registry") || !registry.contains(&type_name))
+      && (cfg!(feature = "wgsl") || !wgsl.contains(&type_name))
+  }
+
+  /// The public enums deriving `Encode` in the sources under `dir`.
+  fn derived_enums(dir: &std::path::Path, found: &mut Vec<String>) {
+    for entry in std::fs::read_dir(dir).unwrap() {
+      let path = entry.unwrap().path();
+      if path.is_dir() {
+        derived_enums(&path, found);
+        continue;
+      }
+      let source = std::fs::read_to_string(&path).unwrap();
+      // The attributes and docs since the end of the previous item.
+      let mut attributes = String::new();
+      for line in source.lines().map(str::trim) {
+        if let Some(name) = line.strip_prefix("pub enum ") {
+          if attributes.contains("Encode") {
+            found.push(
+              name
+                .split(|c: char| !c.is_alphanumeric())
+                .next()
+                .unwrap()
+                .into(),
+            );
+          }
+        }
+        if line.is_empty() || line.ends_with('}') || line.ends_with(';') {
+          attributes.clear();
+        } else {
+          attributes.push_str(line);
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn every_enum_listed() {
+    let mut found = Vec::new();
+    derived_enums(
+      &std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src"),
+      &mut found,
+    );
+    assert!(found.len() > 30);
+    for name in found {
+      assert!(
+        SNAPSHOT.iter().any(|(type_name, _, _)| *type_name == name),
+        "{} is missing from `codec_enums!` and the snapshot",
+        name
+      );
+    }
+  }
+
+  #[test]
+  fn stable_layout() {
+    let snapshot: Vec<_> = SNAPSHOT
+      .iter()
+      .copied()
+      .filter(|(type_name, _, _)| enabled(type_name))
+      .collect();
+    assert_eq!(verify_against(&snapshot), Ok(()));
+    assert_eq!(variant_table(), snapshot);
+  }
+
+  #[test]
+  fn mismatches() {
+    let snapshot = [
+      ("VariableType", "Bool", 3),
+      ("VariableType", "Boolean", 2),
+      ("Missing", "Variant", 0),
+    ];
+    assert_eq!(
+      verify_against(&snapshot),
+      Err(vec![
+        CodecMismatch::Moved {
+          type_name: "VariableType".into(),
+          variant: "Bool".into(),
+          expected: 3,
+          actual: 2,
+        },
+        CodecMismatch::Removed {
+          type_name: "VariableType".into(),
+          variant: "Boolean".into(),
+        },
+        CodecMismatch::Removed {
+          type_name: "Missing".into(),
+          variant: "Variant".into(),
+        },
+      ])
+    );
+
+    let owned = vec![("Compression".to_string(), "Brotli".to_string(), 2)];
+    assert!(verify_against(&owned).is_ok());
+  }
+}
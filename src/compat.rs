@@ -0,0 +1,265 @@
+use crate::{
+  categories::ShardsScriptInfo,
+  traits::{CodeInfo, CodeType, TableInfo, TableInfoV2, VariableType},
+};
+use scale_info::prelude::{string::String, vec::Vec};
+
+/// The first `shards_version` understanding each `VariableType` variant, indexed by SCALE index.
+///
+/// New variants must be appended here with the version of the runtime that introduced them.
+///
+/// Versions above 1, here and in the constants below, are provisional: they number the revisions
+/// of the trait format made by this crate (2: channels and events, 3: constrained channels and
+/// events, shared vector limits and wire scheduling, 4: key patterns) and are not yet tied to
+/// shards runtime releases. Each must be confirmed against the `shards_version` of the runtime
+/// shipping the revision before release.
+pub(crate) const MIN_SHARDS_VERSIONS: [u32; VariableType::VARIANT_COUNT as usize] = [
+  1, // None
+  1, // Any
+  1, // Bool
+  1, // Color
+  1, // Bytes
+  1, // String
+  1, // Image
+  1, // Audio
+  1, // Mesh
+  1, // Enum
+  1, // Int
+  1, // Int2
+  1, // Int3
+  1, // Int4
+  1, // Int8
+  1, // Int16
+  1, // Float
+  1, // Float2
+  1, // Float3
+  1, // Float4
+  1, // Seq
+  1, // Table
+  1, // Object
+  1, // Code
+  2, // Channel
+  2, // Event
+  3, // ChannelV2
+  3, // EventV2
+  4, // TableV2
+];
+
+/// The first `shards_version` understanding shared vector limits (`VectorLimits::Shared`),
+/// provisional (see [`MIN_SHARDS_VERSIONS`]).
+const SHARED_VECTOR_LIMITS_VERSION: u32 = 3;
+
+/// The first `shards_version` understanding wire scheduling metadata (`CodeType::WireV2`),
+/// provisional (see [`MIN_SHARDS_VERSIONS`]).
+const WIRE_SCHEDULING_VERSION: u32 = 3;
+
+/// The minimum `shards_version` able to understand the kind of code `kind`.
+pub fn min_kind_version(kind: &CodeType) -> u32 {
+  match kind {
+    CodeType::WireV2 { .. } => WIRE_SCHEDULING_VERSION,
+    CodeType::Shards | CodeType::Wire { .. } => 1,
+  }
+}
+
+/// The minimum `shards_version` able to understand `type_`, including every type nested in it.
+pub fn min_shards_version(type_: &VariableType) -> u32 {
+  let shared_limits = match type_ {
+    VariableType::Int2(l) | VariableType::Float2(l) => l.is_shared(),
+    VariableType::Int3(l) | VariableType::Float3(l) => l.is_shared(),
+    VariableType::Int4(l) | VariableType::Float4(l) => l.is_shared(),
+    VariableType::Int8(l) => l.is_shared(),
+    VariableType::Int16(l) => l.is_shared(),
+    _ => false,
+  };
+  let own = match type_ {
+    _ if shared_limits => SHARED_VECTOR_LIMITS_VERSION,
+    _ => MIN_SHARDS_VERSIONS[type_.variant_index() as usize],
+  };
+  let nested = match type_ {
+    VariableType::Seq { types, .. } => types.iter().map(min_shards_version).max(),
+    VariableType::Table(TableInfo { types, .. })
+    | VariableType::TableV2(TableInfoV2 { types, .. }) => {
+      types.iter().flatten().map(min_shards_version).max()
+    }
+    VariableType::Code(code) => Some(min_code_version(code)),
+    other => other.carried().map(min_shards_version),
+  };
+  own.max(nested.unwrap_or_default())
+}
+
+fn min_code_version(code: &CodeInfo) -> u32 {
+  code
+    .requires
+    .iter()
+    .chain(code.exposes.iter())
+    .map(|(_, t)| t)
+    .chain(code.inputs.iter())
+    .chain([&code.output])
+    .map(min_shards_version)
+    .max()
+    .unwrap_or_default()
+    .max(min_kind_version(&code.kind))
+}
+
+/// Where a type is declared inside a `CodeInfo`.
+#[derive(Clone, PartialEq, Debug, Eq)]
+pub enum CodeLocation {
+  Kind,
+  Requires(String),
+  Exposes(String),
+  Input(usize),
+  Output,
+}
+
+/// A type that is not understood by the declared `shards_version`.
+#[derive(Clone, PartialEq, Debug, Eq)]
+pub struct VersionMismatch {
+  pub location: CodeLocation,
+  /// The minimum `shards_version` understanding the type
+  pub required: u32,
+}
+
+impl ShardsScriptInfo {
+  /// Checks that every type used by `code` is understood by the declared `shards_version`.
+  pub fn validate_types_against_version(
+    &self,
+    code: &CodeInfo,
+  ) -> Result<(), Vec<VersionMismatch>> {
+    let locations = code
+      .requires
+      .iter()
+      .map(|(name, t)| (CodeLocation::Requires(name.clone()), t))
+      .chain(
+        code
+          .exposes
+          .iter()
+          .map(|(name, t)| (CodeLocation::Exposes(name.clone()), t)),
+      )
+      .chain(
+        code
+          .inputs
+          .iter()
+          .enumerate()
+          .map(|(i, t)| (CodeLocation::Input(i), t)),
+      )
+      .chain([(CodeLocation::Output, &code.output)]);
+
+    let mismatches: Vec<VersionMismatch> = [(CodeLocation::Kind, min_kind_version(&code.kind))]
+      .into_iter()
+      .chain(locations.map(|(location, t)| (location, min_shards_version(t))))
+      .filter(|(_, required)| *required > self.shards_version)
+      .map(|(location, required)| VersionMismatch { location, required })
+      .collect();
+
+    if mismatches.is_empty() {
+      Ok(())
+    } else {
+      Err(mismatches)
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    categories::ShardsFormat,
+    traits::{EventDelivery, EventInfo, VectorLimits},
+  };
+
+  fn script(shards_version: u32) -> ShardsScriptInfo {
+    ShardsScriptInfo {
+      format: ShardsFormat::Edn,
+      shards_version,
+      requiring: vec![],
+      implementing: vec![],
+    }
+  }
+
+  #[test]
+  fn nested_versions() {
+    assert_eq!(min_shards_version(&VariableType::Int(None)), 1);
+    let channel = VariableType::Channel(Box::new(VariableType::Int(None)));
+    assert_eq!(min_shards_version(&channel), 2);
+    let seq = VariableType::Seq {
+      types: vec![VariableType::Bool, channel],
+      length_limits: None,
+    };
+    assert_eq!(min_shards_version(&seq), 2);
+
+    let limits = crate::traits::Limits {
+      min: 0,
+      max: 1,
+      scale: 0,
+    };
+    let per_lane = VariableType::Float3([Some(limits.clone()), None, None].into());
+    assert_eq!(min_shards_version(&per_lane), 1);
+    let shared = VariableType::Float3(VectorLimits::Shared(limits));
+    assert_eq!(min_shards_version(&shared), 3);
+
+    let event = VariableType::EventV2(Box::new(EventInfo {
+      type_: shared,
+      delivery: Some(EventDelivery::Latest),
+    }));
+    assert_eq!(min_shards_version(&event), 3);
+
+    let table = TableInfo {
+      keys: vec!["anim_*".to_string()],
+      types: vec![vec![VariableType::Float(None)]],
+    };
+    assert_eq!(min_shards_version(&VariableType::Table(table.clone())), 1);
+    let patterns = VariableType::TableV2(table.into());
+    assert_eq!(min_shards_version(&patterns), 4);
+  }
+
+  #[test]
+  fn validate_code_types() {
+    let code = CodeInfo {
+      kind: CodeType::Shards,
+      requires: vec![(
+        "events".to_string(),
+        VariableType::Event(Box::new(VariableType::Any)),
+      )],
+      exposes: vec![("count".to_string(), VariableType::Int(None))],
+      inputs: vec![VariableType::Any],
+      output: VariableType::Channel(Box::new(VariableType::Any)),
+    };
+
+    assert_eq!(script(2).validate_types_against_version(&code), Ok(()));
+    assert_eq!(
+      script(1).validate_types_against_version(&code),
+      Err(vec![
+        VersionMismatch {
+          location: CodeLocation::Requires("events".to_string()),
+          required: 2
+        },
+        VersionMismatch {
+          location: CodeLocation::Output,
+          required: 2
+        },
+      ])
+    );
+
+    let wire = CodeInfo {
+      kind: CodeType::WireV2 {
+        looped: Some(true),
+        pure: None,
+        priority: Some(10),
+        detached: Some(true),
+        rate_limit_hz: Some(60),
+      },
+      ..code
+    };
+    assert_eq!(
+      min_shards_version(&VariableType::Code(Box::new(wire.clone()))),
+      3
+    );
+    assert_eq!(
+      script(2).validate_types_against_version(&wire),
+      Err(vec![VersionMismatch {
+        location: CodeLocation::Kind,
+        required: 3
+      }])
+    );
+  }
+}
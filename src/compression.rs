@@ -0,0 +1,149 @@
+use parity_scale_codec::{Decode, Encode};
+
+#[cfg(feature = "compression")]
+use scale_info::prelude::vec::Vec;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The compression applied to proto data before it was stored.
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Debug, Eq, Default, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub enum Compression {
+  /// The data is stored as is
+  #[default]
+  None,
+  /// The data is compressed with zstd at the given level
+  Zstd { level: i8 },
+  /// The data is compressed with brotli at the maximum quality
+  Brotli,
+}
+
+/// Errors returned by the compression helpers.
+#[cfg(feature = "compression")]
+#[derive(Debug)]
+pub enum CompressionError {
+  /// The compressor failed, or the data is not valid for the decompressor
+  Io(std::io::Error),
+  /// The decompressed data is larger than the allowed limit
+  SizeLimitExceeded { limit: usize },
+}
+
+#[cfg(feature = "compression")]
+impl core::fmt::Display for CompressionError {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    match self {
+      Self::Io(e) => write!(f, "compression failed: {}", e),
+      Self::SizeLimitExceeded { limit } => {
+        write!(f, "decompressed data exceeds the limit of {} bytes", limit)
+      }
+    }
+  }
+}
+
+#[cfg(feature = "compression")]
+impl std::error::Error for CompressionError {}
+
+#[cfg(feature = "compression")]
+impl From<std::io::Error> for CompressionError {
+  fn from(e: std::io::Error) -> Self {
+    Self::Io(e)
+  }
+}
+
+#[cfg(feature = "compression")]
+impl Compression {
+  /// Brotli parameters used when compressing, decompression works with any.
+  const BROTLI_QUALITY: u32 = 11;
+  const BROTLI_WINDOW: u32 = 22;
+
+  /// Compresses `data` with this compression.
+  pub fn compress(&self, data: &[u8]) -> Result<Vec<u8>, CompressionError> {
+    match self {
+      Self::None => Ok(data.to_vec()),
+      Self::Zstd { level } => Ok(zstd::encode_all(data, *level as i32)?),
+      Self::Brotli => {
+        let mut output = Vec::new();
+        let mut reader =
+          brotli::CompressorReader::new(data, 4096, Self::BROTLI_QUALITY, Self::BROTLI_WINDOW);
+        std::io::Read::read_to_end(&mut reader, &mut output)?;
+        Ok(output)
+      }
+    }
+  }
+
+  /// Decompresses `data`, failing if the decompressed data is larger than `max_size` bytes.
+  ///
+  /// The limit is enforced while decompressing, so compression bombs are never fully inflated.
+  pub fn decompress(&self, data: &[u8], max_size: usize) -> Result<Vec<u8>, CompressionError> {
+    use std::io::Read;
+
+    let reader: Box<dyn Read + '_> = match self {
+      Self::None => Box::new(data),
+      Self::Zstd { .. } => Box::new(zstd::Decoder::new(data)?),
+      Self::Brotli => Box::new(brotli::Decompressor::new(data, 4096)),
+    };
+
+    let mut output = Vec::new();
+    reader.take(max_size as u64 + 1).read_to_end(&mut output)?;
+    if output.len() > max_size {
+      return Err(CompressionError::SizeLimitExceeded { limit: max_size });
+    }
+    Ok(output)
+  }
+}
+
+#[cfg(all(test, feature = "compression"))]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn round_trip() {
+    let data = b"fragnova ".repeat(100);
+    for compression in [
+      Compression::None,
+      Compression::Zstd { level: 3 },
+      Compression::Brotli,
+    ] {
+      let compressed = compression.compress(&data).unwrap();
+      if compression != Compression::None {
+        assert!(compressed.len() < data.len());
+      }
+      assert_eq!(
+        compression.decompress(&compressed, data.len()).unwrap(),
+        data
+      );
+    }
+  }
+
+  #[test]
+  fn size_limit() {
+    let data = vec![0u8; 1 << 20];
+    let compression = Compression::Zstd { level: 1 };
+    let compressed = compression.compress(&data).unwrap();
+    assert!(matches!(
+      compression.decompress(&compressed, 1000),
+      Err(CompressionError::SizeLimitExceeded { limit: 1000 })
+    ));
+    assert!(Compression::Brotli.decompress(b"not brotli", 1000).is_err());
+  }
+
+  #[test]
+  fn encode_descriptor() {
+    let compression = Compression::Zstd { level: -3 };
+    let encoded = compression.encode();
+    assert_eq!(encoded, vec![1, 0xfd]);
+    assert_eq!(
+      Compression::decode(&mut encoded.as_slice()).unwrap(),
+      compression
+    );
+    assert_eq!(
+      serde_json::to_string(&compression).unwrap(),
+      r#"{"zstd":{"level":-3}}"#
+    );
+  }
+}
@@ -0,0 +1,276 @@
+//! Checks that a proto's declared category and traits match its payload, in one report for
+//! moderation queues and explorers.
+
+use crate::{
+  categories::{BinaryCategories, Categories, ModelCategories, ShardsTrait, TextCategories},
+  content::json::validate_json_payload,
+  error::{ErrorCode, ProtosError},
+  limits::DecodeLimits,
+  probe,
+  proto::Proto,
+  registry::TraitRegistry,
+  traits::Trait,
+};
+use serde::{Deserialize, Serialize};
+
+/// A mismatch between what a proto declares and what it is.
+#[derive(Clone, PartialEq, Debug, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConformanceIssue {
+  /// The payload looks like another category than the declared one
+  CategoryMismatch { sniffed: Categories },
+  /// The payload is not valid for the declared category
+  InvalidPayload(ProtosError),
+  /// The script info of a Shards proto is invalid
+  InvalidScriptInfo(ProtosError),
+  /// A trait used by the proto is not in the registry
+  UnknownTrait(ShardsTrait),
+  /// A trait used by the proto is deprecated
+  DeprecatedTrait(ShardsTrait),
+  /// The payload of a trait proto is not the declared trait
+  TraitHashMismatch {
+    declared: ShardsTrait,
+    actual: ShardsTrait,
+  },
+}
+
+/// The result of [`check`].
+#[derive(Clone, PartialEq, Debug, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConformanceReport {
+  /// The category the payload looks like, when it has a recognizable format
+  pub sniffed: Option<Categories>,
+  /// Whether the payload was validated against the declared category
+  pub payload_checked: bool,
+  /// The mismatches found, empty when the proto conforms
+  pub issues: Vec<ConformanceIssue>,
+}
+
+impl ConformanceReport {
+  /// Whether no mismatch was found.
+  pub fn is_conformant(&self) -> bool {
+    self.issues.is_empty()
+  }
+}
+
+/// Checks `proto` against its `payload`, when available, and the traits of `registry`.
+///
+/// Only the payloads of categories with a validator are checked, see
+/// [`ConformanceReport::payload_checked`].
+pub fn check<TAccountId, TBlockNumber>(
+  proto: &Proto<TAccountId, TBlockNumber>,
+  payload: Option<&[u8]>,
+  registry: &TraitRegistry,
+) -> ConformanceReport {
+  let mut report = ConformanceReport::default();
+  let category = &proto.category;
+
+  match category {
+    Categories::Trait(trait_ref) => match (payload, trait_ref.hash().copied()) {
+      (Some(bytes), declared) => {
+        match DecodeLimits::default().decode::<Trait>(bytes) {
+          Ok(trait_) => {
+            let actual = trait_.shards_hash();
+            if let Some(declared) = declared.filter(|declared| *declared != actual) {
+              report
+                .issues
+                .push(ConformanceIssue::TraitHashMismatch { declared, actual });
+            }
+          }
+          Err(error) => report
+            .issues
+            .push(ConformanceIssue::InvalidPayload(error.into())),
+        }
+        report.payload_checked = true;
+      }
+      (None, Some(declared)) => check_trait(&declared, registry, &mut report),
+      (None, None) => {}
+    },
+    Categories::Shards(info) => {
+      if let Err(error) = info.validate() {
+        report
+          .issues
+          .push(ConformanceIssue::InvalidScriptInfo(error.into()));
+      }
+      for hash in info.requiring.iter().chain(&info.implementing) {
+        check_trait(hash, registry, &mut report);
+      }
+    }
+    _ => {}
+  }
+
+  if let Some(bytes) = payload {
+    report.sniffed = probe::sniff(bytes);
+    if let Some(sniffed) = &report.sniffed {
+      if sniffed.stable_id() != category.stable_id() {
+        report.issues.push(ConformanceIssue::CategoryMismatch {
+          sniffed: sniffed.clone(),
+        });
+      }
+    }
+    if let Some(result) = validate_payload(category, bytes) {
+      report.payload_checked = true;
+      if let Err(error) = result {
+        report.issues.push(ConformanceIssue::InvalidPayload(error));
+      }
+    }
+  }
+  report
+}
+
+fn check_trait(hash: &ShardsTrait, registry: &TraitRegistry, report: &mut ConformanceReport) {
+  if !registry.contains(hash) {
+    report.issues.push(ConformanceIssue::UnknownTrait(*hash));
+  } else if registry.status(hash).is_deprecated() {
+    report.issues.push(ConformanceIssue::DeprecatedTrait(*hash));
+  }
+}
+
+/// Validates `bytes` as a payload of `category`, `None` when the category has no validator.
+fn validate_payload(category: &Categories, bytes: &[u8]) -> Option<Result<(), ProtosError>> {
+  let result = match category {
+    Categories::Texture(_) => probe::texture(bytes).map(drop).map_err(Into::into),
+    Categories::Audio(sub) => probe::audio(bytes, *sub).map(drop).map_err(Into::into),
+    Categories::Model(ModelCategories::GltfFile) => {
+      probe::model_gltf(bytes).map(drop).map_err(Into::into)
+    }
+    Categories::Binary(sub @ (BinaryCategories::OnnxModel | BinaryCategories::SafeTensors)) => {
+      probe::ml_model(bytes, *sub).map(drop).map_err(Into::into)
+    }
+    Categories::Text(TextCategories::Json) => {
+      validate_json_payload(bytes, None).map_err(Into::into)
+    }
+    #[cfg(feature = "wgsl")]
+    Categories::Text(TextCategories::Wgsl) => crate::content::wgsl::validate_wgsl(bytes)
+      .map(drop)
+      .map_err(Into::into),
+    Categories::Text(_) => core::str::from_utf8(bytes)
+      .map(drop)
+      .map_err(|_| ProtosError {
+        code: ErrorCode::Malformed,
+        message: "text payload is not valid UTF-8".into(),
+      }),
+    _ => return None,
+  };
+  Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    categories::{AudioCategories, ShardsFormat, ShardsScriptInfo, TextureCategories, TraitRef},
+    registry::TraitStatus,
+    traits::{Record, VariableType, VariableTypeInfo},
+  };
+  use parity_scale_codec::Encode;
+
+  fn proto(category: Categories) -> Proto<u64, u32> {
+    Proto {
+      block: 1,
+      creator: 1,
+      owner: 1,
+      references: vec![],
+      category,
+      tags: vec![],
+    }
+  }
+
+  fn trait1() -> Trait {
+    Trait {
+      name: "Flag".to_string(),
+      records: vec![Record::from((
+        "on".to_string(),
+        vec![VariableTypeInfo {
+          type_: VariableType::Bool,
+          default: None,
+        }],
+      ))],
+    }
+  }
+
+  fn png() -> Vec<u8> {
+    let mut bytes = b"\x89PNG\r\n\x1a\n".to_vec();
+    bytes.extend_from_slice(&13u32.to_be_bytes());
+    bytes.extend_from_slice(b"IHDR");
+    bytes.extend_from_slice(&[0, 0, 0, 2, 0, 0, 0, 3, 8, 6, 0, 0, 0]);
+    bytes
+  }
+
+  #[test]
+  fn payloads() {
+    let registry = TraitRegistry::new();
+    let texture = proto(Categories::Texture(TextureCategories::PngFile));
+    let report = check(&texture, Some(&png()), &registry);
+    assert!(report.is_conformant(), "{:?}", report);
+    assert!(report.payload_checked);
+    assert_eq!(report.sniffed, Some(texture.category.clone()));
+
+    let audio = proto(Categories::Audio(AudioCategories::OggFile));
+    let report = check(&audio, Some(&png()), &registry);
+    assert!(report.payload_checked);
+    assert!(matches!(
+      &report.issues[..],
+      [
+        ConformanceIssue::CategoryMismatch { .. },
+        ConformanceIssue::InvalidPayload(_)
+      ]
+    ));
+
+    let text = proto(Categories::Text(TextCategories::Plain));
+    let report = check(&text, Some(&[0xc3, 0x28]), &registry);
+    assert!(matches!(
+      &report.issues[..],
+      [ConformanceIssue::InvalidPayload(error)] if error.code == ErrorCode::Malformed
+    ));
+
+    let report = check(&proto(Categories::Bundle), Some(b"data"), &registry);
+    assert!(report.is_conformant());
+    assert!(!report.payload_checked);
+  }
+
+  #[test]
+  fn traits() {
+    let mut registry = TraitRegistry::new();
+    let hash = trait1().shards_hash();
+
+    let declared = proto(Categories::Trait(TraitRef::Hash(hash)));
+    assert!(check(&declared, Some(&trait1().encode()), &registry).is_conformant());
+    let wrong = proto(Categories::Trait(TraitRef::Hash([1; 8])));
+    assert_eq!(
+      check(&wrong, Some(&trait1().encode()), &registry).issues,
+      [ConformanceIssue::TraitHashMismatch {
+        declared: [1; 8],
+        actual: hash
+      }]
+    );
+    assert_eq!(
+      check(&declared, None, &registry).issues,
+      [ConformanceIssue::UnknownTrait(hash)]
+    );
+
+    registry.insert(hash, trait1());
+    registry.set_status(
+      &hash,
+      TraitStatus::Deprecated {
+        superseded_by: None,
+        note_hash: None,
+      },
+    );
+    let script = proto(Categories::Shards(ShardsScriptInfo {
+      format: ShardsFormat::Edn,
+      shards_version: 0,
+      requiring: vec![hash],
+      implementing: vec![[2; 8]],
+    }));
+    let issues = check(&script, None, &registry).issues;
+    assert!(matches!(
+      &issues[..],
+      [
+        ConformanceIssue::InvalidScriptInfo(error),
+        ConformanceIssue::DeprecatedTrait(deprecated),
+        ConformanceIssue::UnknownTrait([2, ..]),
+      ] if error.code == ErrorCode::ZeroShardsVersion && *deprecated == hash
+    ));
+  }
+}
@@ -0,0 +1,120 @@
+/// Errors returned when validating the payload of a `Text(Json)` proto.
+#[derive(Clone, PartialEq, Debug, Eq)]
+pub enum JsonPayloadError {
+  /// The payload is not well-formed JSON
+  Malformed { line: usize, column: usize },
+  /// The schema is not valid JSON or not a valid JSON Schema
+  InvalidSchema,
+  /// Schema validation was requested but the crate was built without the `json-schema` feature
+  SchemaUnsupported,
+  /// The payload does not satisfy the schema, one message per violation
+  SchemaViolations(Vec<String>),
+}
+
+impl core::fmt::Display for JsonPayloadError {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    match self {
+      Self::Malformed { line, column } => {
+        write!(f, "malformed JSON at line {}, column {}", line, column)
+      }
+      Self::InvalidSchema => write!(f, "invalid JSON schema"),
+      Self::SchemaUnsupported => write!(f, "JSON schema validation is not enabled"),
+      Self::SchemaViolations(violations) => {
+        write!(
+          f,
+          "JSON does not match the schema: {}",
+          violations.join("; ")
+        )
+      }
+    }
+  }
+}
+
+impl std::error::Error for JsonPayloadError {}
+
+/// Checks that `bytes` is well-formed JSON and, when given, that it satisfies the JSON Schema
+/// `schema` (usually the payload of a proto referenced by the JSON proto).
+///
+/// Schema validation requires the `json-schema` feature.
+pub fn validate_json_payload(bytes: &[u8], schema: Option<&str>) -> Result<(), JsonPayloadError> {
+  let value: serde_json::Value =
+    serde_json::from_slice(bytes).map_err(|e| JsonPayloadError::Malformed {
+      line: e.line(),
+      column: e.column(),
+    })?;
+
+  match schema {
+    None => Ok(()),
+    Some(schema) => validate_against_schema(&value, schema),
+  }
+}
+
+#[cfg(feature = "json-schema")]
+fn validate_against_schema(
+  value: &serde_json::Value,
+  schema: &str,
+) -> Result<(), JsonPayloadError> {
+  let schema: serde_json::Value =
+    serde_json::from_str(schema).map_err(|_| JsonPayloadError::InvalidSchema)?;
+  let validator =
+    jsonschema::validator_for(&schema).map_err(|_| JsonPayloadError::InvalidSchema)?;
+
+  let violations: Vec<String> = validator
+    .iter_errors(value)
+    .map(|e| format!("{} at '{}'", e, e.instance_path))
+    .collect();
+  if violations.is_empty() {
+    Ok(())
+  } else {
+    Err(JsonPayloadError::SchemaViolations(violations))
+  }
+}
+
+#[cfg(not(feature = "json-schema"))]
+fn validate_against_schema(
+  _value: &serde_json::Value,
+  _schema: &str,
+) -> Result<(), JsonPayloadError> {
+  Err(JsonPayloadError::SchemaUnsupported)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn well_formed() {
+    assert_eq!(validate_json_payload(br#"{"a":[1,2]}"#, None), Ok(()));
+    assert_eq!(
+      validate_json_payload(b"{\n\"a\":", None),
+      Err(JsonPayloadError::Malformed { line: 2, column: 4 })
+    );
+  }
+
+  #[cfg(feature = "json-schema")]
+  #[test]
+  fn schema() {
+    let schema = r#"{"type":"object","required":["name"],"properties":{"name":{"type":"string"}}}"#;
+    assert_eq!(
+      validate_json_payload(br#"{"name":"frag"}"#, Some(schema)),
+      Ok(())
+    );
+    assert!(matches!(
+      validate_json_payload(br#"{"name":1}"#, Some(schema)),
+      Err(JsonPayloadError::SchemaViolations(v)) if v.len() == 1
+    ));
+    assert_eq!(
+      validate_json_payload(b"{}", Some("{")),
+      Err(JsonPayloadError::InvalidSchema)
+    );
+  }
+
+  #[cfg(not(feature = "json-schema"))]
+  #[test]
+  fn schema_disabled() {
+    assert_eq!(
+      validate_json_payload(b"{}", Some("{}")),
+      Err(JsonPayloadError::SchemaUnsupported)
+    );
+  }
+}
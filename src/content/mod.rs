@@ -0,0 +1,10 @@
+//! Validation of proto payloads against what their category declares.
+//!
+//! Each validator only understands the formats of one category and is gated behind the feature
+//! pulling in its parser.
+
+#[cfg(feature = "std")]
+pub mod json;
+
+#[cfg(feature = "wgsl")]
+pub mod wgsl;
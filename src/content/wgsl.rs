@@ -0,0 +1,222 @@
+use parity_scale_codec::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+
+/// The pipeline stage an entry point runs in.
+#[derive(
+  Encode, Decode, Copy, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo, Serialize, Deserialize,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum ShaderStage {
+  Vertex,
+  Fragment,
+  Compute,
+  Task,
+  Mesh,
+}
+
+/// An entry point of a shader module.
+#[derive(
+  Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo, Serialize, Deserialize,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct EntryPoint {
+  pub name: String,
+  pub stage: ShaderStage,
+  /// Workgroup size of compute (and task/mesh) entry points, `[0, 0, 0]` for other stages
+  pub workgroup_size: [u32; 3],
+}
+
+/// How a bound resource is accessed.
+#[derive(
+  Encode, Decode, Copy, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo, Serialize, Deserialize,
+)]
+#[serde(rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum BindingKind {
+  /// A uniform buffer
+  Uniform,
+  /// A storage buffer
+  Storage { read_only: bool },
+  /// A texture or a sampler
+  Handle,
+}
+
+/// A resource bound through `@group(g) @binding(b)`.
+#[derive(
+  Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo, Serialize, Deserialize,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct ShaderBinding {
+  pub group: u32,
+  pub binding: u32,
+  pub name: Option<String>,
+  pub kind: BindingKind,
+}
+
+/// The interface a WGSL shader exposes to the pipelines using it.
+#[derive(
+  Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo, Serialize, Deserialize,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct ShaderInterface {
+  pub entry_points: Vec<EntryPoint>,
+  /// Bindings ordered by group then binding
+  pub bindings: Vec<ShaderBinding>,
+}
+
+/// Errors returned when validating a `Text(Wgsl)` payload.
+#[derive(Clone, PartialEq, Debug, Eq)]
+pub enum ShaderError {
+  /// The payload is not UTF-8
+  InvalidUtf8,
+  /// The source does not parse, `line` and `column` are 1-based when known
+  Parse {
+    message: String,
+    line: Option<u32>,
+    column: Option<u32>,
+  },
+  /// The module parses but is not valid
+  Validation(String),
+}
+
+impl core::fmt::Display for ShaderError {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    match self {
+      Self::InvalidUtf8 => write!(f, "shader source is not valid UTF-8"),
+      Self::Parse {
+        message,
+        line: Some(line),
+        column: Some(column),
+      } => write!(f, "{} at line {}, column {}", message, line, column),
+      Self::Parse { message, .. } => write!(f, "{}", message),
+      Self::Validation(message) => write!(f, "invalid shader: {}", message),
+    }
+  }
+}
+
+impl std::error::Error for ShaderError {}
+
+/// Parses and validates a WGSL shader, returning its entry points and resource bindings.
+pub fn validate_wgsl(bytes: &[u8]) -> Result<ShaderInterface, ShaderError> {
+  let source = core::str::from_utf8(bytes).map_err(|_| ShaderError::InvalidUtf8)?;
+  let module = naga::front::wgsl::parse_str(source).map_err(|e| {
+    let location = e.location(source);
+    ShaderError::Parse {
+      message: e.message().to_string(),
+      line: location.map(|l| l.line_number),
+      column: location.map(|l| l.line_position),
+    }
+  })?;
+  naga::valid::Validator::new(
+    naga::valid::ValidationFlags::all(),
+    naga::valid::Capabilities::all(),
+  )
+  .validate(&module)
+  .map_err(|e| ShaderError::Validation(e.into_inner().to_string()))?;
+
+  let entry_points = module
+    .entry_points
+    .iter()
+    .map(|ep| EntryPoint {
+      name: ep.name.clone(),
+      stage: match ep.stage {
+        naga::ShaderStage::Vertex => ShaderStage::Vertex,
+        naga::ShaderStage::Fragment => ShaderStage::Fragment,
+        naga::ShaderStage::Compute => ShaderStage::Compute,
+        naga::ShaderStage::Task => ShaderStage::Task,
+        naga::ShaderStage::Mesh => ShaderStage::Mesh,
+      },
+      workgroup_size: ep.workgroup_size,
+    })
+    .collect();
+
+  let mut bindings: Vec<ShaderBinding> = module
+    .global_variables
+    .iter()
+    .filter_map(|(_, var)| {
+      let binding = var.binding.as_ref()?;
+      let kind = match var.space {
+        naga::AddressSpace::Uniform => BindingKind::Uniform,
+        naga::AddressSpace::Storage { access } => BindingKind::Storage {
+          read_only: !access.contains(naga::StorageAccess::STORE),
+        },
+        naga::AddressSpace::Handle => BindingKind::Handle,
+        _ => return None,
+      };
+      Some(ShaderBinding {
+        group: binding.group,
+        binding: binding.binding,
+        name: var.name.clone(),
+        kind,
+      })
+    })
+    .collect();
+  bindings.sort_by_key(|b| (b.group, b.binding));
+
+  Ok(ShaderInterface {
+    entry_points,
+    bindings,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const SHADER: &str = r#"
+@group(1) @binding(0) var<storage, read> input: array<f32>;
+@group(0) @binding(2) var<uniform> scale: vec4<f32>;
+@group(0) @binding(0) var<storage, read_write> output: array<f32>;
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+  output[id.x] = input[id.x] * scale.x;
+}
+"#;
+
+  #[test]
+  fn interface() {
+    let interface = validate_wgsl(SHADER.as_bytes()).unwrap();
+    assert_eq!(
+      interface.entry_points,
+      vec![EntryPoint {
+        name: "main".to_string(),
+        stage: ShaderStage::Compute,
+        workgroup_size: [64, 1, 1],
+      }]
+    );
+    let bindings: Vec<_> = interface
+      .bindings
+      .iter()
+      .map(|b| (b.group, b.binding, b.name.as_deref().unwrap(), b.kind))
+      .collect();
+    assert_eq!(
+      bindings,
+      vec![
+        (0, 0, "output", BindingKind::Storage { read_only: false }),
+        (0, 2, "scale", BindingKind::Uniform),
+        (1, 0, "input", BindingKind::Storage { read_only: true }),
+      ]
+    );
+    assert_eq!(
+      serde_json::to_string(&BindingKind::Storage { read_only: true }).unwrap(),
+      r#"{"storage":{"readOnly":true}}"#
+    );
+  }
+
+  #[test]
+  fn errors() {
+    assert_eq!(validate_wgsl(&[0xff]), Err(ShaderError::InvalidUtf8));
+    assert!(matches!(
+      validate_wgsl(b"fn broken( {"),
+      Err(ShaderError::Parse {
+        line: Some(1),
+        column: Some(12),
+        ..
+      })
+    ));
+    assert!(matches!(
+      validate_wgsl(b"fn f() -> f32 { return 1u; }"),
+      Err(ShaderError::Validation(_))
+    ));
+  }
+}
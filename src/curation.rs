@@ -0,0 +1,168 @@
+//! Curated collections of protos, so galleries are represented the same way by every front-end.
+
+use parity_scale_codec::{Decode, Encode};
+use scale_info::prelude::{string::String, vec::Vec};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A proto in a collection.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct CollectionEntry<THash> {
+  /// The hash of the proto
+  pub proto: THash,
+  /// The note of the curator about the proto, at most `Collection::MAX_NOTE_LEN` bytes
+  pub note: Option<String>,
+}
+
+/// An ordered list of protos picked by a curator.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct Collection<THash, TAccountId> {
+  /// The account curating the collection
+  pub curator: TAccountId,
+  /// The hash of the title, stored with the other texts of the collection
+  pub title_hash: THash,
+  /// The protos of the collection, in display order
+  pub entries: Vec<CollectionEntry<THash>>,
+  /// Whether the collection is listed publicly, unlisted collections are only reachable by hash
+  pub public: bool,
+}
+
+/// Errors returned by `Collection::validate`.
+#[derive(Clone, PartialEq, Debug, Eq)]
+pub enum CollectionError<THash> {
+  /// The collection has more than `Collection::MAX_ENTRIES` entries
+  TooManyEntries(usize),
+  /// The proto is in the collection several times
+  DuplicateEntry(THash),
+  /// The note of the entry at this index is longer than `Collection::MAX_NOTE_LEN` bytes
+  NoteTooLong(usize),
+}
+
+impl<THash: core::fmt::Debug> core::fmt::Display for CollectionError<THash> {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    match self {
+      Self::TooManyEntries(len) => write!(
+        f,
+        "collection has {} entries, at most {} are allowed",
+        len,
+        Collection::<THash, ()>::MAX_ENTRIES
+      ),
+      Self::DuplicateEntry(proto) => write!(f, "proto {:?} is in the collection twice", proto),
+      Self::NoteTooLong(index) => write!(f, "note of entry {} is too long", index),
+    }
+  }
+}
+
+#[cfg(feature = "std")]
+impl<THash: core::fmt::Debug> std::error::Error for CollectionError<THash> {}
+
+impl<THash, TAccountId> Collection<THash, TAccountId> {
+  /// The largest amount of entries of a collection.
+  pub const MAX_ENTRIES: usize = 1000;
+  /// The longest note of an entry, in bytes.
+  pub const MAX_NOTE_LEN: usize = 280;
+
+  /// The hashes of the protos of the collection, in order.
+  pub fn protos(&self) -> impl Iterator<Item = &THash> {
+    self.entries.iter().map(|entry| &entry.proto)
+  }
+}
+
+impl<THash: Ord + Clone, TAccountId> Collection<THash, TAccountId> {
+  /// Checks that the collection is within bounds and has every proto at most once.
+  pub fn validate(&self) -> Result<(), CollectionError<THash>> {
+    if self.entries.len() > Self::MAX_ENTRIES {
+      return Err(CollectionError::TooManyEntries(self.entries.len()));
+    }
+    if let Some(index) = self.entries.iter().position(|entry| {
+      entry
+        .note
+        .as_ref()
+        .is_some_and(|note| note.len() > Self::MAX_NOTE_LEN)
+    }) {
+      return Err(CollectionError::NoteTooLong(index));
+    }
+    let mut protos: Vec<&THash> = self.protos().collect();
+    protos.sort();
+    if let Some(pair) = protos.windows(2).find(|pair| pair[0] == pair[1]) {
+      return Err(CollectionError::DuplicateEntry(pair[0].clone()));
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn entry(proto: u32, note: Option<&str>) -> CollectionEntry<u32> {
+    CollectionEntry {
+      proto,
+      note: note.map(Into::into),
+    }
+  }
+
+  #[test]
+  fn validation() {
+    let mut collection = Collection {
+      curator: 7u64,
+      title_hash: 1,
+      entries: vec![entry(3, Some("the original")), entry(2, None)],
+      public: true,
+    };
+    assert_eq!(collection.validate(), Ok(()));
+    assert_eq!(collection.protos().collect::<Vec<_>>(), [&3, &2]);
+
+    collection.entries.push(entry(3, None));
+    assert_eq!(
+      collection.validate(),
+      Err(CollectionError::DuplicateEntry(3))
+    );
+
+    collection.entries[2] = entry(4, Some(&"a".repeat(281)));
+    assert_eq!(collection.validate(), Err(CollectionError::NoteTooLong(2)));
+
+    collection.entries = (0..1001).map(|proto| entry(proto, None)).collect();
+    assert_eq!(
+      collection.validate(),
+      Err(CollectionError::TooManyEntries(1001))
+    );
+  }
+
+  #[test]
+  fn encoding() {
+    let collection = Collection {
+      curator: 7u64,
+      title_hash: [1u8; 32],
+      entries: vec![CollectionEntry {
+        proto: [2u8; 32],
+        note: None,
+      }],
+      public: false,
+    };
+    let encoded = collection.encode();
+    assert_eq!(
+      Collection::decode(&mut &encoded[..]),
+      Ok(collection.clone())
+    );
+
+    let json = serde_json::to_string(&collection).unwrap();
+    assert!(json.contains(r#""public":false"#));
+    assert!(json.contains(r#""titleHash":"#));
+    assert_eq!(
+      serde_json::from_str::<Collection<[u8; 32], u64>>(&json).unwrap(),
+      collection
+    );
+  }
+}
@@ -0,0 +1,121 @@
+use parity_scale_codec::{Compact, Decode, Encode};
+use scale_info::prelude::vec::Vec;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// External chains protos and fragments can be detached to.
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub enum SupportedChains {
+  EthereumMainnet,
+  EthereumRinkeby,
+  EthereumGoerli,
+}
+
+impl SupportedChains {
+  /// The EIP-155 chain id of the chain.
+  pub fn chain_id(&self) -> u64 {
+    match self {
+      Self::EthereumMainnet => 1,
+      Self::EthereumRinkeby => 4,
+      Self::EthereumGoerli => 5,
+    }
+  }
+}
+
+/// Domain separator prefixed to the message of a [`DetachedProof`], so validator signatures of a
+/// detach cannot be passed off as signatures of another payload.
+pub const DETACH_DOMAIN: &[u8] = b"fragnova-protos:detach";
+
+/// Proof that a proto was detached from Fragnova to be owned on an external chain.
+///
+/// Validators sign the `message` of the proof, the external chain contract verifies the signatures.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct DetachedProof<THash> {
+  /// The hash of the proto detached
+  pub proto_hash: THash,
+  /// The chain the proto is detached to
+  pub target_chain: SupportedChains,
+  /// The account owning the proto on the target chain, in the chain native format
+  pub remote_owner: Vec<u8>,
+  /// Signatures of the proof `message`, in the target chain native format
+  pub signatures: Vec<Vec<u8>>,
+  /// Nonce preventing the proof from being replayed
+  #[codec(compact)]
+  pub nonce: u64,
+}
+
+impl<THash: Encode> DetachedProof<THash> {
+  /// The bytes signed by the validators: [`DETACH_DOMAIN`] followed by every field of the proof
+  /// except the signatures.
+  pub fn message(&self) -> Vec<u8> {
+    let mut message = DETACH_DOMAIN.to_vec();
+    self.proto_hash.encode_to(&mut message);
+    self.target_chain.encode_to(&mut message);
+    self.remote_owner.encode_to(&mut message);
+    Compact(self.nonce).encode_to(&mut message);
+    message
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn proof() -> DetachedProof<[u8; 32]> {
+    DetachedProof {
+      proto_hash: [3u8; 32],
+      target_chain: SupportedChains::EthereumGoerli,
+      remote_owner: vec![0xaa; 20],
+      signatures: vec![vec![0x11; 65]],
+      nonce: 7,
+    }
+  }
+
+  #[test]
+  fn encode_decode_proof() {
+    let proof = proof();
+    let encoded = proof.encode();
+    assert_eq!(
+      DetachedProof::decode(&mut encoded.as_slice()).unwrap(),
+      proof
+    );
+
+    let json = serde_json::to_string(&proof).unwrap();
+    assert!(json.contains("\"targetChain\":\"ethereumGoerli\""));
+    assert_eq!(
+      serde_json::from_str::<DetachedProof<[u8; 32]>>(&json).unwrap(),
+      proof
+    );
+  }
+
+  #[test]
+  fn message_excludes_signatures() {
+    let proof = proof();
+    let mut resigned = proof.clone();
+    resigned.signatures.push(vec![0x22; 65]);
+    assert_eq!(proof.message(), resigned.message());
+
+    let mut replayed = proof.clone();
+    replayed.nonce += 1;
+    assert_ne!(proof.message(), replayed.message());
+
+    let fields = (
+      proof.proto_hash,
+      proof.target_chain,
+      &proof.remote_owner,
+      Compact(proof.nonce),
+    );
+    assert_eq!(proof.message(), [DETACH_DOMAIN, &fields.encode()].concat());
+  }
+}
@@ -0,0 +1,104 @@
+//! Runtime access to the doc comments captured in the `scale_info` metadata.
+//!
+//! Doc comments are only captured when the `docs` feature is enabled, otherwise every lookup is
+//! empty.
+
+use scale_info::{prelude::vec::Vec, TypeDef, TypeInfo};
+
+/// The documentation of a type and of its fields or variants.
+#[derive(Clone, PartialEq, Debug, Eq, Default)]
+pub struct TypeDocs {
+  /// The lines of the doc comment of the type itself
+  pub docs: Vec<&'static str>,
+  /// The doc comments of the named fields or of the variants, in declaration order
+  pub items: Vec<(&'static str, Vec<&'static str>)>,
+}
+
+impl TypeDocs {
+  /// The doc comment lines of the field or variant named `name`.
+  pub fn item(&self, name: &str) -> Option<&[&'static str]> {
+    self
+      .items
+      .iter()
+      .find(|(item, _)| *item == name)
+      .map(|(_, docs)| docs.as_slice())
+  }
+}
+
+/// Collects the documentation of `T` from its `TypeInfo`.
+pub fn for_type<T: TypeInfo + 'static>() -> TypeDocs {
+  let ty = T::type_info();
+  let items = match ty.type_def {
+    TypeDef::Composite(composite) => composite
+      .fields
+      .into_iter()
+      .filter_map(|field| Some((field.name?, field.docs)))
+      .collect(),
+    TypeDef::Variant(variant) => variant
+      .variants
+      .into_iter()
+      .map(|variant| (variant.name, variant.docs))
+      .collect(),
+    _ => Vec::new(),
+  };
+  TypeDocs {
+    docs: ty.docs,
+    items,
+  }
+}
+
+/// The first doc comment line of the variant of `T` with the SCALE index `index`.
+pub(crate) fn variant_summary<T: TypeInfo + 'static>(index: u8) -> &'static str {
+  match T::type_info().type_def {
+    TypeDef::Variant(variant) => variant
+      .variants
+      .into_iter()
+      .find(|variant| variant.index == index)
+      .and_then(|variant| variant.docs.first().copied())
+      .unwrap_or_default(),
+    _ => "",
+  }
+}
+
+/// The name of the variant of `T` with the SCALE index `index`, captured with or without `docs`.
+pub(crate) fn variant_name<T: TypeInfo + 'static>(index: u8) -> &'static str {
+  match T::type_info().type_def {
+    TypeDef::Variant(variant) => variant
+      .variants
+      .into_iter()
+      .find(|variant| variant.index == index)
+      .map(|variant| variant.name)
+      .unwrap_or_default(),
+    _ => "",
+  }
+}
+
+#[cfg(all(test, feature = "docs"))]
+mod tests {
+  use super::*;
+  use crate::traits::{Trait, VariableType};
+
+  #[test]
+  fn captured() {
+    let docs = for_type::<Trait>();
+    assert_eq!(docs.item("name"), Some(&["Name of the Trait"][..]));
+    assert!(docs.item("unknown").is_none());
+
+    let docs = for_type::<VariableType>();
+    assert_eq!(
+      docs.docs,
+      vec!["Enum represents all the possible types that a variable can be"]
+    );
+    assert_eq!(docs.items.len(), VariableType::VARIANT_COUNT as usize);
+    assert_eq!(docs.item("Bool"), Some(&["Boolean type"][..]));
+  }
+
+  #[test]
+  fn describe() {
+    assert_eq!(VariableType::Bool.describe(), "Boolean type");
+    assert_eq!(
+      VariableType::Channel(Box::new(VariableType::Any)).describe(),
+      "Channel type with variable type"
+    );
+  }
+}
@@ -0,0 +1,362 @@
+//! Decoding of SCALE data into a generic tree, driven by `scale_info` metadata.
+//!
+//! The metadata can come from a newer build than the decoding one (e.g. fetched from a node), so
+//! data using types or variants unknown to this build can still be rendered.
+
+use crate::trace;
+use parity_scale_codec::{Compact, Decode, Error, Input};
+use scale_info::{
+  form::PortableForm,
+  prelude::{string::String, vec::Vec},
+  Field, PortableRegistry, Registry, TypeDef, TypeDefPrimitive, TypeInfo,
+};
+
+/// Maximum nesting of composites, variants and sequences while decoding.
+pub const MAX_DEPTH: u32 = 128;
+
+/// A decoded value of any type described by the metadata.
+#[derive(Clone, PartialEq, Debug, Eq)]
+pub enum DynValue {
+  Bool(bool),
+  Char(char),
+  Str(String),
+  /// Any unsigned integer up to 128 bits, compact or not
+  UInt(u128),
+  /// Any signed integer up to 128 bits
+  Int(i128),
+  /// A 256 bits integer as little endian bytes
+  Int256([u8; 32]),
+  /// Sequences and arrays of `u8`
+  Bytes(Vec<u8>),
+  /// Any other sequence or array
+  Seq(Vec<DynValue>),
+  /// Structs and tuples, unnamed fields have no name
+  Composite(Vec<(Option<String>, DynValue)>),
+  /// Enums
+  Variant {
+    index: u8,
+    name: String,
+    fields: Vec<(Option<String>, DynValue)>,
+  },
+}
+
+impl DynValue {
+  /// The field named `name` of a composite or a variant.
+  pub fn field(&self, name: &str) -> Option<&DynValue> {
+    match self {
+      Self::Composite(fields) | Self::Variant { fields, .. } => fields
+        .iter()
+        .find(|(field, _)| field.as_deref() == Some(name))
+        .map(|(_, value)| value),
+      _ => None,
+    }
+  }
+}
+
+/// Errors returned by the dynamic decoder.
+#[derive(Clone, PartialEq, Debug, Eq)]
+pub enum DynError {
+  /// The type id is not in the registry
+  UnknownType(u32),
+  /// The variant index is not a variant of the type
+  UnknownVariant { type_id: u32, index: u8 },
+  /// The type cannot be decoded dynamically
+  Unsupported(&'static str),
+  /// The data nests deeper than `MAX_DEPTH`
+  TooDeep,
+  /// The data is not a valid encoding of the type
+  Codec(Error),
+}
+
+impl core::fmt::Display for DynError {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    match self {
+      Self::UnknownType(id) => write!(f, "unknown type id {}", id),
+      Self::UnknownVariant { type_id, index } => {
+        write!(f, "unknown variant {} of type id {}", index, type_id)
+      }
+      Self::Unsupported(what) => write!(f, "unsupported type: {}", what),
+      Self::TooDeep => write!(f, "data nested too deeply"),
+      Self::Codec(e) => write!(f, "invalid data: {}", e),
+    }
+  }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DynError {}
+
+impl From<Error> for DynError {
+  fn from(e: Error) -> Self {
+    Self::Codec(e)
+  }
+}
+
+/// The metadata of `T` (and of every type it uses) along with the id of `T` in it.
+pub fn registry_for<T: TypeInfo + 'static>() -> (PortableRegistry, u32) {
+  let mut registry = Registry::new();
+  let id = registry.register_type(&scale_info::meta_type::<T>()).id;
+  (registry.into(), id)
+}
+
+/// Decodes the whole of `bytes` as the crate type `T`, using its own metadata.
+pub fn decode_as<T: TypeInfo + 'static>(bytes: &[u8]) -> Result<DynValue, DynError> {
+  trace::span!(
+    "decode_as",
+    type_name = core::any::type_name::<T>(),
+    len = bytes.len()
+  );
+  trace::malformed((|| {
+    let (registry, id) = registry_for::<T>();
+    let mut input = bytes;
+    let value = decode(&registry, id, &mut input)?;
+    if !input.is_empty() {
+      return Err(Error::from("Trailing bytes after the decoded data").into());
+    }
+    Ok(value)
+  })())
+}
+
+/// Decodes a value of the type `type_id` of `registry` from `input`.
+pub fn decode<I: Input>(
+  registry: &PortableRegistry,
+  type_id: u32,
+  input: &mut I,
+) -> Result<DynValue, DynError> {
+  Decoder { registry, depth: 0 }.value(type_id, input)
+}
+
+struct Decoder<'a> {
+  registry: &'a PortableRegistry,
+  depth: u32,
+}
+
+impl<'a> Decoder<'a> {
+  fn resolve(&self, id: u32) -> Result<&'a TypeDef<PortableForm>, DynError> {
+    self
+      .registry
+      .resolve(id)
+      .map(|ty| &ty.type_def)
+      .ok_or(DynError::UnknownType(id))
+  }
+
+  fn is_u8(&self, id: u32) -> Result<bool, DynError> {
+    Ok(matches!(
+      self.resolve(id)?,
+      TypeDef::Primitive(TypeDefPrimitive::U8)
+    ))
+  }
+
+  fn value<I: Input>(&mut self, id: u32, input: &mut I) -> Result<DynValue, DynError> {
+    match self.resolve(id)? {
+      TypeDef::Primitive(primitive) => primitive_value(primitive, input),
+      TypeDef::Compact(compact) => self.compact(compact.type_param.id, input),
+      _ => {
+        if self.depth >= MAX_DEPTH {
+          return Err(DynError::TooDeep);
+        }
+        self.depth += 1;
+        input.descend_ref()?;
+        let value = self.nested(id, input);
+        input.ascend_ref();
+        self.depth -= 1;
+        value
+      }
+    }
+  }
+
+  fn nested<I: Input>(&mut self, id: u32, input: &mut I) -> Result<DynValue, DynError> {
+    match self.resolve(id)? {
+      TypeDef::Composite(composite) => {
+        Ok(DynValue::Composite(self.fields(&composite.fields, input)?))
+      }
+      TypeDef::Variant(variant) => {
+        let index = input.read_byte()?;
+        let variant = variant
+          .variants
+          .iter()
+          .find(|variant| variant.index == index)
+          .ok_or(DynError::UnknownVariant { type_id: id, index })?;
+        Ok(DynValue::Variant {
+          index,
+          name: owned(&variant.name),
+          fields: self.fields(&variant.fields, input)?,
+        })
+      }
+      TypeDef::Sequence(sequence) if self.is_u8(sequence.type_param.id)? => {
+        Ok(DynValue::Bytes(Vec::<u8>::decode(input)?))
+      }
+      TypeDef::Sequence(sequence) => {
+        let len = <Compact<u32>>::decode(input)?.0 as usize;
+        self.elements(sequence.type_param.id, len, input)
+      }
+      TypeDef::Array(array) => self.elements(array.type_param.id, array.len as usize, input),
+      TypeDef::Tuple(tuple) => Ok(DynValue::Composite(
+        tuple
+          .fields
+          .iter()
+          .map(|field| Ok((None, self.value(field.id, input)?)))
+          .collect::<Result<_, DynError>>()?,
+      )),
+      TypeDef::BitSequence(_) => Err(DynError::Unsupported("bit sequence")),
+      TypeDef::Primitive(_) | TypeDef::Compact(_) => unreachable!("not nested"),
+    }
+  }
+
+  fn fields<I: Input>(
+    &mut self,
+    fields: &[Field<PortableForm>],
+    input: &mut I,
+  ) -> Result<Vec<(Option<String>, DynValue)>, DynError> {
+    fields
+      .iter()
+      .map(|field| {
+        let name = field.name.as_ref().map(owned);
+        Ok((name, self.value(field.ty.id, input)?))
+      })
+      .collect()
+  }
+
+  fn elements<I: Input>(
+    &mut self,
+    element: u32,
+    len: usize,
+    input: &mut I,
+  ) -> Result<DynValue, DynError> {
+    if self.is_u8(element)? {
+      input.on_before_alloc_mem(len)?;
+      let bytes = (0..len)
+        .map(|_| input.read_byte())
+        .collect::<Result<_, _>>()?;
+      return Ok(DynValue::Bytes(bytes));
+    }
+    input.on_before_alloc_mem(len.saturating_mul(core::mem::size_of::<DynValue>()))?;
+    (0..len)
+      .map(|_| self.value(element, input))
+      .collect::<Result<_, _>>()
+      .map(DynValue::Seq)
+  }
+
+  fn compact<I: Input>(&mut self, id: u32, input: &mut I) -> Result<DynValue, DynError> {
+    let value = match self.resolve(id)? {
+      TypeDef::Primitive(TypeDefPrimitive::U8) => <Compact<u8>>::decode(input)?.0 as u128,
+      TypeDef::Primitive(TypeDefPrimitive::U16) => <Compact<u16>>::decode(input)?.0 as u128,
+      TypeDef::Primitive(TypeDefPrimitive::U32) => <Compact<u32>>::decode(input)?.0 as u128,
+      TypeDef::Primitive(TypeDefPrimitive::U64) => <Compact<u64>>::decode(input)?.0 as u128,
+      TypeDef::Primitive(TypeDefPrimitive::U128) => <Compact<u128>>::decode(input)?.0,
+      // a compact single field struct is encoded as its compact field
+      TypeDef::Composite(composite) if composite.fields.len() == 1 => {
+        let field = &composite.fields[0];
+        let value = self.compact(field.ty.id, input)?;
+        let name = field.name.as_ref().map(owned);
+        return Ok(DynValue::Composite(Vec::from([(name, value)])));
+      }
+      _ => return Err(DynError::Unsupported("compact of a non integer type")),
+    };
+    Ok(DynValue::UInt(value))
+  }
+}
+
+// metadata names are `&'static str` without std
+fn owned(name: &impl AsRef<str>) -> String {
+  String::from(name.as_ref())
+}
+
+fn primitive_value<I: Input>(
+  primitive: &TypeDefPrimitive,
+  input: &mut I,
+) -> Result<DynValue, DynError> {
+  Ok(match primitive {
+    TypeDefPrimitive::Bool => DynValue::Bool(bool::decode(input)?),
+    TypeDefPrimitive::Char => {
+      DynValue::Char(char::from_u32(u32::decode(input)?).ok_or(Error::from("Invalid char"))?)
+    }
+    TypeDefPrimitive::Str => DynValue::Str(String::decode(input)?),
+    TypeDefPrimitive::U8 => DynValue::UInt(u8::decode(input)? as u128),
+    TypeDefPrimitive::U16 => DynValue::UInt(u16::decode(input)? as u128),
+    TypeDefPrimitive::U32 => DynValue::UInt(u32::decode(input)? as u128),
+    TypeDefPrimitive::U64 => DynValue::UInt(u64::decode(input)? as u128),
+    TypeDefPrimitive::U128 => DynValue::UInt(u128::decode(input)?),
+    TypeDefPrimitive::I8 => DynValue::Int(i8::decode(input)? as i128),
+    TypeDefPrimitive::I16 => DynValue::Int(i16::decode(input)? as i128),
+    TypeDefPrimitive::I32 => DynValue::Int(i32::decode(input)? as i128),
+    TypeDefPrimitive::I64 => DynValue::Int(i64::decode(input)? as i128),
+    TypeDefPrimitive::I128 => DynValue::Int(i128::decode(input)?),
+    TypeDefPrimitive::U256 | TypeDefPrimitive::I256 => DynValue::Int256(<[u8; 32]>::decode(input)?),
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::traits::{Record, Trait, VariableType, VariableTypeInfo};
+  use parity_scale_codec::Encode;
+
+  #[test]
+  fn decode_trait() {
+    let trait1 = Trait {
+      name: "Flag".into(),
+      records: vec![Record::from((
+        "on".to_string(),
+        vec![VariableTypeInfo {
+          type_: VariableType::Enum {
+            vendor_id: 300,
+            type_id: 1,
+          },
+          default: Some(vec![1, 2]),
+        }],
+      ))],
+    };
+    let value = decode_as::<Trait>(&trait1.encode()).unwrap();
+    assert_eq!(value.field("name"), Some(&DynValue::Str("Flag".into())));
+
+    let DynValue::Seq(records) = value.field("records").unwrap() else {
+      panic!("records is a sequence");
+    };
+    let DynValue::Seq(types) = records[0].field("types").unwrap() else {
+      panic!("types is a sequence");
+    };
+    let info = &types[0];
+    assert_eq!(
+      info.field("type"),
+      Some(&DynValue::Variant {
+        index: 9,
+        name: "Enum".into(),
+        fields: vec![
+          (Some("vendor_id".into()), DynValue::UInt(300)),
+          (Some("type_id".into()), DynValue::UInt(1)),
+        ],
+      })
+    );
+    assert_eq!(
+      info.field("default"),
+      Some(&DynValue::Variant {
+        index: 1,
+        name: "Some".into(),
+        fields: vec![(None, DynValue::Bytes(vec![1, 2]))],
+      })
+    );
+  }
+
+  #[test]
+  fn errors() {
+    assert!(matches!(
+      decode_as::<VariableType>(&[VariableType::VARIANT_COUNT]),
+      Err(DynError::UnknownVariant { index: 29, .. })
+    ));
+    assert!(matches!(
+      decode_as::<VariableType>(&[2, 0]),
+      Err(DynError::Codec(_))
+    ));
+
+    let (registry, _) = registry_for::<VariableType>();
+    assert_eq!(
+      decode(&registry, 1000, &mut &[][..]),
+      Err(DynError::UnknownType(1000))
+    );
+
+    // Channel(Channel(...)) deeper than the limit
+    let mut deep = vec![24; MAX_DEPTH as usize];
+    deep.push(2);
+    assert_eq!(decode_as::<VariableType>(&deep), Err(DynError::TooDeep));
+  }
+}
@@ -0,0 +1,190 @@
+use crate::hashing::blake2_256;
+use core::fmt;
+use parity_scale_codec::{Decode, Encode};
+use scale_info::prelude::vec::Vec;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The authenticated encryption scheme used to encrypt proto data.
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub enum EncryptionScheme {
+  XChaCha20Poly1305,
+  Aes256Gcm,
+}
+
+impl EncryptionScheme {
+  /// The length in bytes of the nonce used by the scheme.
+  pub fn nonce_len(&self) -> usize {
+    match self {
+      Self::XChaCha20Poly1305 => 24,
+      Self::Aes256Gcm => 12,
+    }
+  }
+}
+
+/// The data key, wrapped for a single recipient.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct RecipientKey {
+  /// The x25519 public key of the recipient
+  pub public_key: [u8; 32],
+  /// The data key sealed to `public_key`
+  pub wrapped_key: Vec<u8>,
+}
+
+/// Describes encrypted proto data and who can decrypt it.
+///
+/// No cryptography is performed by this crate, this is only the descriptor shared by the runtime and clients.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct EncryptedData {
+  /// The scheme used to encrypt the data
+  pub scheme: EncryptionScheme,
+  /// The nonce used to encrypt the data
+  pub nonce: Vec<u8>,
+  /// The blake2-256 hash of the ciphertext
+  pub ciphertext_hash: [u8; 32],
+  /// The recipients able to decrypt the data
+  pub recipients: Vec<RecipientKey>,
+}
+
+/// Errors returned when an encrypted data descriptor is malformed.
+#[derive(Copy, Clone, PartialEq, Debug, Eq)]
+pub enum EncryptionError {
+  /// The nonce length does not match the scheme
+  InvalidNonceLength { expected: usize, found: usize },
+  /// Nobody can decrypt the data
+  NoRecipients,
+  /// The same public key is listed more than once
+  DuplicateRecipient([u8; 32]),
+}
+
+impl fmt::Display for EncryptionError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::InvalidNonceLength { expected, found } => {
+        write!(f, "invalid nonce length {}, expected {}", found, expected)
+      }
+      Self::NoRecipients => write!(f, "encrypted data has no recipients"),
+      Self::DuplicateRecipient(_) => write!(f, "duplicate recipient"),
+    }
+  }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for EncryptionError {}
+
+impl EncryptedData {
+  /// Checks that the descriptor is well formed.
+  pub fn validate(&self) -> Result<(), EncryptionError> {
+    if self.nonce.len() != self.scheme.nonce_len() {
+      return Err(EncryptionError::InvalidNonceLength {
+        expected: self.scheme.nonce_len(),
+        found: self.nonce.len(),
+      });
+    }
+    if self.recipients.is_empty() {
+      return Err(EncryptionError::NoRecipients);
+    }
+    for (i, recipient) in self.recipients.iter().enumerate() {
+      if self.recipients[..i]
+        .iter()
+        .any(|other| other.public_key == recipient.public_key)
+      {
+        return Err(EncryptionError::DuplicateRecipient(recipient.public_key));
+      }
+    }
+    Ok(())
+  }
+
+  /// The wrapped key of the recipient owning `public_key`, if any.
+  pub fn recipient(&self, public_key: &[u8; 32]) -> Option<&RecipientKey> {
+    self
+      .recipients
+      .iter()
+      .find(|recipient| recipient.public_key == *public_key)
+  }
+
+  /// Whether `ciphertext` is the ciphertext described.
+  pub fn verify_ciphertext(&self, ciphertext: &[u8]) -> bool {
+    blake2_256(ciphertext) == self.ciphertext_hash
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn encrypted() -> EncryptedData {
+    EncryptedData {
+      scheme: EncryptionScheme::XChaCha20Poly1305,
+      nonce: vec![0; 24],
+      ciphertext_hash: blake2_256(b"ciphertext"),
+      recipients: vec![
+        RecipientKey {
+          public_key: [1; 32],
+          wrapped_key: vec![1; 48],
+        },
+        RecipientKey {
+          public_key: [2; 32],
+          wrapped_key: vec![2; 48],
+        },
+      ],
+    }
+  }
+
+  #[test]
+  fn valid_descriptor() {
+    let data = encrypted();
+    assert_eq!(data.validate(), Ok(()));
+    assert!(data.verify_ciphertext(b"ciphertext"));
+    assert!(!data.verify_ciphertext(b"plaintext"));
+    assert_eq!(data.recipient(&[2; 32]).unwrap().wrapped_key, vec![2; 48]);
+    assert!(data.recipient(&[3; 32]).is_none());
+
+    let encoded = data.encode();
+    assert_eq!(
+      EncryptedData::decode(&mut encoded.as_slice()).unwrap(),
+      data
+    );
+    let json = serde_json::to_string(&data).unwrap();
+    assert_eq!(serde_json::from_str::<EncryptedData>(&json).unwrap(), data);
+  }
+
+  #[test]
+  fn invalid_descriptors() {
+    let mut data = encrypted();
+    data.scheme = EncryptionScheme::Aes256Gcm;
+    assert_eq!(
+      data.validate(),
+      Err(EncryptionError::InvalidNonceLength {
+        expected: 12,
+        found: 24
+      })
+    );
+
+    let mut data = encrypted();
+    data.recipients[1].public_key = [1; 32];
+    assert_eq!(
+      data.validate(),
+      Err(EncryptionError::DuplicateRecipient([1; 32]))
+    );
+
+    data.recipients.clear();
+    assert_eq!(data.validate(), Err(EncryptionError::NoRecipients));
+  }
+}
@@ -0,0 +1,745 @@
+//! Machine-readable codes for every error of the crate.
+//!
+//! Codes are stable: once released a code keeps its number and meaning forever, new errors get
+//! new codes. Codes are grouped by hundreds, one group per area of the crate.
+
+use crate::{
+  accounts::LinkError,
+  audience::RegionError,
+  bundles::BundleError,
+  categories::ScriptInfoError,
+  cid::CidError,
+  curation::CollectionError,
+  dynamic::DynError,
+  fragments::UniqueDataError,
+  localization::LocalizationError,
+  names::VariableNameError,
+  pagination::PageError,
+  proto::TagSetError,
+  provenance::{AttributionError, PatchError},
+  sales::{AmountError, SaleTermsError},
+  trait_docs::TraitDocsError,
+  traits::{MissingRequirement, TraitError, TraitStreamError, TypeIssue},
+  transfer::TransferError,
+};
+use core::fmt;
+use scale_info::prelude::string::{String, ToString};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+macro_rules! error_codes {
+  ($($(#[$meta:meta])* $name:ident = $code:literal,)*) => {
+    /// The stable code of an error, (de)serialized as its number.
+    #[non_exhaustive]
+    #[derive(Copy, Clone, PartialEq, Debug, Eq, Hash, PartialOrd, Ord)]
+    #[repr(u16)]
+    pub enum ErrorCode {
+      $($(#[$meta])* $name = $code,)*
+    }
+
+    impl ErrorCode {
+      /// Every code, in increasing order.
+      pub const ALL: &'static [ErrorCode] = &[$(Self::$name,)*];
+
+      /// The code with the number `code`, if it exists.
+      pub fn from_code(code: u16) -> Option<Self> {
+        match code {
+          $($code => Some(Self::$name),)*
+          _ => None,
+        }
+      }
+    }
+  };
+}
+
+error_codes! {
+  /// Invalid SCALE data
+  Codec = 100,
+  /// Data nested deeper than allowed
+  TooDeep = 101,
+  /// A type is unknown to the metadata
+  UnknownType = 102,
+  /// A variant is unknown to the metadata
+  UnknownVariant = 103,
+  /// A type cannot be decoded dynamically
+  UnsupportedType = 104,
+
+  /// A value is not of the expected type
+  ValueTypeMismatch = 200,
+  /// A value is outside of its limits
+  ValueOutOfLimits = 201,
+  /// A collection or string value has a length outside of its limits
+  ValueLengthOutOfLimits = 202,
+  /// A table value has a key its type does not allow
+  ValueUnknownKey = 203,
+  /// An instance belongs to another trait
+  TraitMismatch = 210,
+  /// A record is not part of the trait
+  UnknownRecord = 211,
+  /// A record required by the trait has no value
+  MissingRecord = 212,
+  /// A record value does not match the record types
+  InvalidRecordValue = 213,
+  /// The example value of a record allocates more than a runtime decoder allows
+  ExampleTooLarge = 214,
+  /// A trait documentation does not match the trait
+  DocsHashMismatch = 220,
+  /// A trait documentation is not valid markdown
+  InvalidMarkdown = 221,
+  /// A localization does not match the locale it is merged into
+  LocaleMismatch = 230,
+  /// A record path cannot be parsed
+  InvalidRecordPath = 240,
+  /// A trait has no records
+  NoRecords = 250,
+  /// A record has an empty name
+  EmptyRecordName = 251,
+  /// Several records share a name
+  DuplicateRecord = 252,
+  /// A record has no types
+  NoRecordTypes = 253,
+  /// A table type has a key several times
+  DuplicateTableKey = 254,
+  /// A table type has not as many type lists as keys
+  TableLengthMismatch = 255,
+  /// Limits have a minimum above their maximum
+  InvalidLimits = 256,
+  /// A code type has a variable several times
+  DuplicateVariable = 257,
+  /// A streamed record does not follow the previous one in canonical order
+  RecordOutOfOrder = 258,
+  /// A trait stream did not get as many records as announced
+  RecordCountMismatch = 259,
+  /// A variable required by some code is not exposed by the host
+  MissingRequirement = 260,
+  /// A variable required by some code is exposed by the host with an incompatible type
+  IncompatibleRequirement = 261,
+  /// A variable name is empty
+  EmptyVariableName = 270,
+  /// A variable name is too long
+  VariableNameTooLong = 271,
+  /// A variable name has an invalid character
+  InvalidVariableNameChar = 272,
+  /// A script declares another shards version than its lock
+  LockedVersionMismatch = 280,
+  /// A script uses a trait missing from its lock
+  UnlockedTrait = 281,
+  /// A script lock has a trait the script no longer uses
+  StaleLockedTrait = 282,
+  /// A script info has a shards version of 0
+  ZeroShardsVersion = 290,
+  /// A script info lists a trait twice
+  DuplicateScriptTrait = 291,
+  /// A script info both requires and implements a trait
+  RequiredAndImplementedTrait = 292,
+
+  /// An Arweave transaction id has an invalid length
+  InvalidArweaveTxIdLength = 300,
+  /// An Arweave transaction id is not valid base64url
+  InvalidArweaveTxIdEncoding = 301,
+  /// A CID uses an unsupported multibase
+  UnsupportedCidBase = 310,
+  /// A CID is not valid for its multibase
+  InvalidCidEncoding = 311,
+  /// A CID uses an unsupported version
+  UnsupportedCidVersion = 312,
+  /// A CID holds an invalid varint
+  InvalidCidVarint = 313,
+  /// A CID holds an invalid multihash
+  InvalidCidMultihash = 314,
+  /// A CID digest does not have the length of its hash function
+  CidDigestLengthMismatch = 315,
+  /// A CID string is longer than any supported CID
+  CidTooLong = 316,
+  /// Chunks cannot have a size of zero
+  ZeroChunkSize = 320,
+  /// The amount of chunks does not match the manifest
+  ChunkCountMismatch = 321,
+  /// The chunks do not hash to the manifest root
+  ChunkRootMismatch = 322,
+  /// Unique data holds a mutable reference
+  MutableUniqueData = 330,
+  /// Unique data does not match its hash
+  UniqueDataHashMismatch = 331,
+  /// A trait package has a trait not in its canonical form
+  NonCanonicalPackageTrait = 340,
+  /// A trait package has dependencies not ordered by hash
+  UnorderedPackageDependencies = 341,
+  /// A region is not an ISO 3166-1 alpha-2 code
+  InvalidRegion = 350,
+
+  /// A bundle has several primary entries
+  MultiplePrimary = 400,
+  /// A bundle has several thumbnails
+  MultipleThumbnails = 401,
+  /// A bundle slot is used several times
+  DuplicateSlot = 402,
+  /// A bundle references an unknown trait
+  UnknownTrait = 403,
+  /// A bundle references a missing proto
+  MissingProto = 404,
+  /// A bundle proto does not implement a required trait
+  UnsatisfiedTrait = 405,
+  /// Attribution weights exceed the total
+  AttributionWeightOverflow = 410,
+  /// An upstream is attributed several times
+  DuplicateUpstream = 411,
+  /// The provenance graph has a cycle
+  ProvenanceCycle = 412,
+  /// Patches are not in order
+  PatchOutOfOrder = 413,
+  /// A patch does not change the data
+  UnchangedPatch = 414,
+  /// A collection has more entries than allowed
+  TooManyCollectionEntries = 420,
+  /// A collection has a proto several times
+  DuplicateCollectionEntry = 421,
+  /// A collection entry has a note longer than allowed
+  CollectionNoteTooLong = 422,
+  /// A tag set has more tags than allowed
+  TooManyTags = 430,
+  /// A tag is longer than allowed
+  TagTooLong = 431,
+
+  /// Amounts of different assets cannot be combined
+  AssetMismatch = 500,
+  /// An amount overflows
+  AmountOverflow = 501,
+  /// A sale has a zero duration
+  ZeroSaleDuration = 502,
+  /// A sale has a zero per account limit
+  ZeroAccountLimit = 503,
+  /// A transfer intent expired
+  TransferExpired = 510,
+  /// A fragment without the transfer permission is transferred
+  NotTransferable = 511,
+  /// A transfer grants permissions its owner does not have
+  TransferPermissionEscalation = 512,
+  /// A transfer intent was signed for another chain
+  TransferWrongChain = 513,
+  /// A transfer intent was not signed by the current owner
+  TransferNotOwner = 514,
+  /// A transfer intent was already executed or cancelled
+  TransferNonceUsed = 515,
+  /// An account link was signed for another chain
+  LinkWrongChain = 520,
+  /// An account link was already used
+  LinkNonceUsed = 521,
+  /// An account link was not signed by its external identity
+  InvalidLinkSignature = 522,
+
+  /// An encryption nonce has an invalid length
+  InvalidNonceLength = 600,
+  /// An encrypted payload has no recipients
+  NoRecipients = 601,
+  /// An encrypted payload lists a recipient several times
+  DuplicateRecipient = 602,
+  /// Data could not be compressed or decompressed
+  Compression = 610,
+  /// Decompressed data exceeds the size limit
+  DecompressionLimitExceeded = 611,
+
+  /// The format of the data is not recognized
+  UnknownFormat = 700,
+  /// The data is truncated
+  Truncated = 701,
+  /// The data is malformed
+  Malformed = 702,
+  /// A glTF binary has an unsupported version
+  UnsupportedGltfVersion = 703,
+  /// A JSON payload violates its schema
+  SchemaViolation = 710,
+  /// A JSON schema is invalid or unsupported
+  InvalidSchema = 711,
+  /// A shader failed to parse
+  ShaderParse = 720,
+  /// A shader failed to validate
+  ShaderValidation = 721,
+
+  /// A page request has a zero limit
+  ZeroPageLimit = 800,
+  /// A page request has a limit above the maximum
+  PageLimitTooLarge = 801,
+}
+
+impl ErrorCode {
+  /// The number of the code.
+  pub fn code(self) -> u16 {
+    self as u16
+  }
+}
+
+impl fmt::Display for ErrorCode {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "E{:03}", self.code())
+  }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for ErrorCode {
+  fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u16(self.code())
+  }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for ErrorCode {
+  fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    let code = u16::deserialize(deserializer)?;
+    Self::from_code(code).ok_or_else(|| {
+      serde::de::Error::custom(scale_info::prelude::format!("unknown error code: {}", code))
+    })
+  }
+}
+
+/// Errors with an `ErrorCode`.
+pub trait HasErrorCode {
+  /// The code of the error.
+  fn error_code(&self) -> ErrorCode;
+}
+
+/// Any error of the crate, as its code and its message.
+#[derive(Clone, PartialEq, Debug, Eq)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct ProtosError {
+  /// The code of the error
+  pub code: ErrorCode,
+  /// The human-readable description of the error
+  pub message: String,
+}
+
+impl ProtosError {
+  /// The code and the message of `error`.
+  pub fn from_error<E: HasErrorCode + fmt::Display>(error: &E) -> Self {
+    Self {
+      code: error.error_code(),
+      message: error.to_string(),
+    }
+  }
+}
+
+impl fmt::Display for ProtosError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "[{}] {}", self.code, self.message)
+  }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ProtosError {}
+
+macro_rules! impl_error_code {
+  ($(#[$meta:meta])* $error:ty, |$e:ident| $code:expr) => {
+    $(#[$meta])*
+    impl HasErrorCode for $error {
+      fn error_code(&self) -> ErrorCode {
+        let $e = self;
+        $code
+      }
+    }
+
+    $(#[$meta])*
+    impl From<$error> for ProtosError {
+      fn from(error: $error) -> Self {
+        Self::from_error(&error)
+      }
+    }
+  };
+}
+
+impl_error_code!(parity_scale_codec::Error, |_e| ErrorCode::Codec);
+
+impl_error_code!(DynError, |e| match e {
+  DynError::UnknownType(_) => ErrorCode::UnknownType,
+  DynError::UnknownVariant { .. } => ErrorCode::UnknownVariant,
+  DynError::Unsupported(_) => ErrorCode::UnsupportedType,
+  DynError::TooDeep => ErrorCode::TooDeep,
+  DynError::Codec(_) => ErrorCode::Codec,
+});
+
+impl_error_code!(
+  #[cfg(feature = "values")]
+  crate::values::ValueError,
+  |e| {
+    use crate::values::ValueError;
+    match e {
+      ValueError::TypeMismatch => ErrorCode::ValueTypeMismatch,
+      ValueError::OutOfLimits => ErrorCode::ValueOutOfLimits,
+      ValueError::LengthOutOfLimits => ErrorCode::ValueLengthOutOfLimits,
+      ValueError::UnknownKey(_) => ErrorCode::ValueUnknownKey,
+    }
+  }
+);
+
+impl_error_code!(
+  #[cfg(feature = "values")]
+  crate::instance::InstanceError,
+  |e| {
+    use crate::instance::InstanceError;
+    match e {
+      InstanceError::TraitMismatch => ErrorCode::TraitMismatch,
+      InstanceError::UnknownRecord(_) => ErrorCode::UnknownRecord,
+      InstanceError::MissingRecord(_) => ErrorCode::MissingRecord,
+      InstanceError::InvalidValue { .. } => ErrorCode::InvalidRecordValue,
+      InstanceError::TooLarge(_) => ErrorCode::ExampleTooLarge,
+    }
+  }
+);
+
+impl_error_code!(
+  #[cfg(feature = "values")]
+  crate::instance::PayloadError,
+  |e| {
+    use crate::instance::PayloadError;
+    match e {
+      PayloadError::Codec(_) => ErrorCode::Codec,
+      PayloadError::Invalid(e) => e.error_code(),
+    }
+  }
+);
+
+impl_error_code!(TraitDocsError, |e| match e {
+  TraitDocsError::HashMismatch { .. } => ErrorCode::DocsHashMismatch,
+  TraitDocsError::InvalidMarkdown => ErrorCode::InvalidMarkdown,
+});
+
+impl_error_code!(LocalizationError, |e| match e {
+  LocalizationError::UnknownRecord(_) => ErrorCode::UnknownRecord,
+  LocalizationError::LocaleMismatch => ErrorCode::LocaleMismatch,
+});
+
+impl_error_code!(TraitError, |e| match e {
+  TraitError::NoRecords => ErrorCode::NoRecords,
+  TraitError::EmptyRecordName => ErrorCode::EmptyRecordName,
+  TraitError::DuplicateRecord(_) => ErrorCode::DuplicateRecord,
+  TraitError::NoTypes(_) => ErrorCode::NoRecordTypes,
+  TraitError::InvalidType { issue, .. } => match issue {
+    TypeIssue::DuplicateTableKey(_) => ErrorCode::DuplicateTableKey,
+    TypeIssue::TableLengthMismatch { .. } => ErrorCode::TableLengthMismatch,
+    TypeIssue::InvalidLimits { .. } => ErrorCode::InvalidLimits,
+    TypeIssue::DuplicateVariable(_) => ErrorCode::DuplicateVariable,
+  },
+});
+
+impl_error_code!(MissingRequirement, |e| match e.exposed {
+  None => ErrorCode::MissingRequirement,
+  Some(_) => ErrorCode::IncompatibleRequirement,
+});
+
+impl_error_code!(VariableNameError, |e| match e {
+  VariableNameError::Empty => ErrorCode::EmptyVariableName,
+  VariableNameError::TooLong(_) => ErrorCode::VariableNameTooLong,
+  VariableNameError::InvalidChar(_) => ErrorCode::InvalidVariableNameChar,
+});
+
+impl_error_code!(
+  #[cfg(feature = "hashing")]
+  crate::script_lock::LockMismatch,
+  |e| {
+    use crate::script_lock::LockMismatch;
+    match e {
+      LockMismatch::ShardsVersion { .. } => ErrorCode::LockedVersionMismatch,
+      LockMismatch::UnlockedTrait(_) => ErrorCode::UnlockedTrait,
+      LockMismatch::StaleTrait(_) => ErrorCode::StaleLockedTrait,
+    }
+  }
+);
+
+impl_error_code!(ScriptInfoError, |e| match e {
+  ScriptInfoError::ZeroVersion => ErrorCode::ZeroShardsVersion,
+  ScriptInfoError::DuplicateTrait(_) => ErrorCode::DuplicateScriptTrait,
+  ScriptInfoError::RequiredAndImplemented(_) => ErrorCode::RequiredAndImplementedTrait,
+});
+
+impl_error_code!(
+  #[cfg(feature = "std")]
+  crate::path::PathParseError,
+  |_e| ErrorCode::InvalidRecordPath
+);
+
+impl_error_code!(
+  #[cfg(feature = "hashing")]
+  crate::assets::ArweaveTxIdError,
+  |e| {
+    use crate::assets::ArweaveTxIdError;
+    match e {
+      ArweaveTxIdError::InvalidLength => ErrorCode::InvalidArweaveTxIdLength,
+      ArweaveTxIdError::InvalidEncoding => ErrorCode::InvalidArweaveTxIdEncoding,
+    }
+  }
+);
+
+impl_error_code!(CidError, |e| match e {
+  CidError::UnsupportedBase => ErrorCode::UnsupportedCidBase,
+  CidError::InvalidEncoding => ErrorCode::InvalidCidEncoding,
+  CidError::UnsupportedVersion => ErrorCode::UnsupportedCidVersion,
+  CidError::InvalidVarint => ErrorCode::InvalidCidVarint,
+  CidError::InvalidMultihash => ErrorCode::InvalidCidMultihash,
+  CidError::DigestLengthMismatch => ErrorCode::CidDigestLengthMismatch,
+  CidError::TooLong => ErrorCode::CidTooLong,
+});
+
+impl_error_code!(
+  #[cfg(feature = "hashing")]
+  crate::chunks::ChunkError,
+  |e| {
+    use crate::chunks::ChunkError;
+    match e {
+      ChunkError::ZeroChunkSize => ErrorCode::ZeroChunkSize,
+      ChunkError::ChunkCountMismatch => ErrorCode::ChunkCountMismatch,
+      ChunkError::RootMismatch => ErrorCode::ChunkRootMismatch,
+    }
+  }
+);
+
+impl_error_code!(UniqueDataError, |e| match e {
+  UniqueDataError::MutableReference => ErrorCode::MutableUniqueData,
+  UniqueDataError::HashMismatch => ErrorCode::UniqueDataHashMismatch,
+});
+
+impl_error_code!(
+  #[cfg(all(feature = "hashing", feature = "registry"))]
+  crate::export::PackageError,
+  |e| {
+    use crate::export::PackageError;
+    match e {
+      PackageError::NonCanonical(_) => ErrorCode::NonCanonicalPackageTrait,
+      PackageError::UnorderedDependencies => ErrorCode::UnorderedPackageDependencies,
+      PackageError::InvalidTrait(_, error) => error.error_code(),
+      PackageError::Docs(error) => error.error_code(),
+    }
+  }
+);
+
+impl<THash> HasErrorCode for BundleError<THash> {
+  fn error_code(&self) -> ErrorCode {
+    match self {
+      BundleError::MultiplePrimary => ErrorCode::MultiplePrimary,
+      BundleError::MultipleThumbnails => ErrorCode::MultipleThumbnails,
+      BundleError::DuplicateSlot(_) => ErrorCode::DuplicateSlot,
+      BundleError::UnknownTrait(_) => ErrorCode::UnknownTrait,
+      BundleError::MissingProto(_) => ErrorCode::MissingProto,
+      BundleError::UnsatisfiedTrait { .. } => ErrorCode::UnsatisfiedTrait,
+    }
+  }
+}
+
+impl<THash: fmt::Debug> From<BundleError<THash>> for ProtosError {
+  fn from(error: BundleError<THash>) -> Self {
+    Self::from_error(&error)
+  }
+}
+
+impl<THash> HasErrorCode for AttributionError<THash> {
+  fn error_code(&self) -> ErrorCode {
+    match self {
+      AttributionError::WeightOverflow { .. } => ErrorCode::AttributionWeightOverflow,
+      AttributionError::DuplicateUpstream(_) => ErrorCode::DuplicateUpstream,
+      AttributionError::Cycle(_) => ErrorCode::ProvenanceCycle,
+    }
+  }
+}
+
+impl<THash: fmt::Debug> From<AttributionError<THash>> for ProtosError {
+  fn from(error: AttributionError<THash>) -> Self {
+    Self::from_error(&error)
+  }
+}
+
+impl<THash> HasErrorCode for CollectionError<THash> {
+  fn error_code(&self) -> ErrorCode {
+    match self {
+      CollectionError::TooManyEntries(_) => ErrorCode::TooManyCollectionEntries,
+      CollectionError::DuplicateEntry(_) => ErrorCode::DuplicateCollectionEntry,
+      CollectionError::NoteTooLong(_) => ErrorCode::CollectionNoteTooLong,
+    }
+  }
+}
+
+impl<THash: fmt::Debug> From<CollectionError<THash>> for ProtosError {
+  fn from(error: CollectionError<THash>) -> Self {
+    Self::from_error(&error)
+  }
+}
+
+impl_error_code!(TraitStreamError, |e| match e {
+  TraitStreamError::RecordOutOfOrder(_) => ErrorCode::RecordOutOfOrder,
+  TraitStreamError::RecordCountMismatch(_) => ErrorCode::RecordCountMismatch,
+});
+
+impl_error_code!(TagSetError, |e| match e {
+  TagSetError::TooManyTags(_) => ErrorCode::TooManyTags,
+  TagSetError::TagTooLong(_) => ErrorCode::TagTooLong,
+});
+
+impl_error_code!(RegionError, |_e| ErrorCode::InvalidRegion);
+
+impl_error_code!(PatchError, |e| match e {
+  PatchError::OutOfOrder(_) => ErrorCode::PatchOutOfOrder,
+  PatchError::UnchangedData(_) => ErrorCode::UnchangedPatch,
+});
+
+impl_error_code!(AmountError, |e| match e {
+  AmountError::AssetMismatch => ErrorCode::AssetMismatch,
+  AmountError::Overflow => ErrorCode::AmountOverflow,
+});
+
+impl_error_code!(SaleTermsError, |e| match e {
+  SaleTermsError::ZeroDuration => ErrorCode::ZeroSaleDuration,
+  SaleTermsError::ZeroAccountLimit => ErrorCode::ZeroAccountLimit,
+});
+
+impl_error_code!(TransferError, |e| match e {
+  TransferError::WrongChain => ErrorCode::TransferWrongChain,
+  TransferError::NotOwner => ErrorCode::TransferNotOwner,
+  TransferError::NonceUsed => ErrorCode::TransferNonceUsed,
+  TransferError::Expired => ErrorCode::TransferExpired,
+  TransferError::NotTransferable => ErrorCode::NotTransferable,
+  TransferError::PermissionEscalation(_) => ErrorCode::TransferPermissionEscalation,
+});
+
+impl_error_code!(LinkError, |e| match e {
+  LinkError::WrongChain => ErrorCode::LinkWrongChain,
+  LinkError::NonceUsed => ErrorCode::LinkNonceUsed,
+  LinkError::InvalidSignature => ErrorCode::InvalidLinkSignature,
+});
+
+impl_error_code!(
+  #[cfg(feature = "hashing")]
+  crate::encryption::EncryptionError,
+  |e| {
+    use crate::encryption::EncryptionError;
+    match e {
+      EncryptionError::InvalidNonceLength { .. } => ErrorCode::InvalidNonceLength,
+      EncryptionError::NoRecipients => ErrorCode::NoRecipients,
+      EncryptionError::DuplicateRecipient(_) => ErrorCode::DuplicateRecipient,
+    }
+  }
+);
+
+impl_error_code!(PageError, |e| match e {
+  PageError::ZeroLimit => ErrorCode::ZeroPageLimit,
+  PageError::LimitTooLarge(_) => ErrorCode::PageLimitTooLarge,
+});
+
+impl_error_code!(
+  #[cfg(feature = "compression")]
+  crate::compression::CompressionError,
+  |e| match e {
+    crate::compression::CompressionError::Io(_) => ErrorCode::Compression,
+    crate::compression::CompressionError::SizeLimitExceeded { .. } => {
+      ErrorCode::DecompressionLimitExceeded
+    }
+  }
+);
+
+impl_error_code!(
+  #[cfg(feature = "probe")]
+  crate::probe::ProbeError,
+  |e| match e {
+    crate::probe::ProbeError::UnknownFormat => ErrorCode::UnknownFormat,
+    crate::probe::ProbeError::Truncated => ErrorCode::Truncated,
+    crate::probe::ProbeError::Malformed(_) => ErrorCode::Malformed,
+  }
+);
+
+impl_error_code!(
+  #[cfg(feature = "probe")]
+  crate::probe::GltfError,
+  |e| match e {
+    crate::probe::GltfError::NotGlb => ErrorCode::UnknownFormat,
+    crate::probe::GltfError::UnsupportedVersion(_) => ErrorCode::UnsupportedGltfVersion,
+    crate::probe::GltfError::LengthMismatch { .. } => ErrorCode::Truncated,
+    crate::probe::GltfError::InvalidChunk(_) | crate::probe::GltfError::InvalidJson(_) => {
+      ErrorCode::Malformed
+    }
+  }
+);
+
+impl_error_code!(
+  #[cfg(feature = "std")]
+  crate::bijection::BijectionError,
+  |e| {
+    use crate::bijection::BijectionError;
+    match e {
+      BijectionError::InvalidJson(_) => ErrorCode::Malformed,
+      BijectionError::Codec(_) => ErrorCode::Codec,
+    }
+  }
+);
+
+impl_error_code!(
+  #[cfg(feature = "std")]
+  crate::content::json::JsonPayloadError,
+  |e| {
+    use crate::content::json::JsonPayloadError;
+    match e {
+      JsonPayloadError::Malformed { .. } => ErrorCode::Malformed,
+      JsonPayloadError::InvalidSchema | JsonPayloadError::SchemaUnsupported => {
+        ErrorCode::InvalidSchema
+      }
+      JsonPayloadError::SchemaViolations(_) => ErrorCode::SchemaViolation,
+    }
+  }
+);
+
+impl_error_code!(
+  #[cfg(feature = "wgsl")]
+  crate::content::wgsl::ShaderError,
+  |e| {
+    use crate::content::wgsl::ShaderError;
+    match e {
+      ShaderError::InvalidUtf8 => ErrorCode::Malformed,
+      ShaderError::Parse { .. } => ErrorCode::ShaderParse,
+      ShaderError::Validation(_) => ErrorCode::ShaderValidation,
+    }
+  }
+);
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn stable_codes() {
+    assert!(ErrorCode::ALL.windows(2).all(|w| w[0].code() < w[1].code()));
+    for code in ErrorCode::ALL {
+      assert_eq!(ErrorCode::from_code(code.code()), Some(*code));
+    }
+    assert_eq!(ErrorCode::from_code(0), None);
+    // released codes must never change
+    assert_eq!(ErrorCode::Codec.code(), 100);
+    assert_eq!(ErrorCode::ValueTypeMismatch.code(), 200);
+    assert_eq!(ErrorCode::MultiplePrimary.code(), 400);
+  }
+
+  #[test]
+  fn protos_error() {
+    let error = ProtosError::from(crate::pagination::PageError::ZeroLimit);
+    assert_eq!(error.code, ErrorCode::ZeroPageLimit);
+    assert_eq!(error.to_string(), "[E800] page limit must not be 0");
+
+    #[cfg(feature = "values")]
+    {
+      use crate::instance::{InstanceError, PayloadError};
+      let error = ProtosError::from(InstanceError::MissingRecord("speed".into()));
+      assert_eq!(error.code, ErrorCode::MissingRecord);
+      assert_eq!(
+        error.to_string(),
+        format!("[E212] {}", InstanceError::MissingRecord("speed".into()))
+      );
+
+      let payload = PayloadError::Invalid(InstanceError::TraitMismatch);
+      assert_eq!(payload.error_code(), ErrorCode::TraitMismatch);
+    }
+
+    let json = serde_json::to_string(&error).unwrap();
+    assert!(json.starts_with(r#"{"code":800,"message":"#));
+    assert_eq!(serde_json::from_str::<ProtosError>(&json).unwrap(), error);
+    assert!(serde_json::from_str::<ErrorCode>("1").is_err());
+  }
+}
@@ -0,0 +1,104 @@
+//! The lifecycle events deposited by the Fragnova chain for protos and fragments.
+//!
+//! The fields mirror the ones of the runtime events, in the same order, so indexers can decode and
+//! store them without hand-written mirrors.
+
+use crate::fragments::InstanceUnit;
+use parity_scale_codec::{Decode, Encode};
+use scale_info::prelude::vec::Vec;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A proto was uploaded.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct ProtoUploaded<THash> {
+  /// The hash of the proto data
+  pub proto_hash: THash,
+  /// The CID of the proto data
+  pub cid: Vec<u8>,
+}
+
+/// A proto was patched, its data now includes the patch.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct ProtoPatched<THash> {
+  /// The hash of the patched proto
+  pub proto_hash: THash,
+  /// The CID of the patch data
+  pub cid: Vec<u8>,
+}
+
+/// A proto changed owner.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct ProtoTransferred<THash, TAccountId> {
+  /// The hash of the transferred proto
+  pub proto_hash: THash,
+  /// The new owner of the proto
+  pub owner_id: TAccountId,
+}
+
+/// A fragment instance was minted to an account.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct FragmentMinted<THash, TAccountId> {
+  /// The account receiving the instance
+  pub account_id: TAccountId,
+  /// The hash of the fragment definition
+  pub definition_hash: THash,
+  /// The edition and copy ids of the instance
+  pub fragment_id: (InstanceUnit, InstanceUnit),
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::fragments::{Hash128, Hash256};
+
+  #[test]
+  fn runtime_layout() {
+    let uploaded = ProtoUploaded::<Hash256> {
+      proto_hash: [1; 32],
+      cid: b"z".to_vec(),
+    };
+    assert_eq!(uploaded.encode(), ([1u8; 32], b"z".to_vec()).encode());
+
+    let minted = FragmentMinted::<Hash128, u64> {
+      account_id: 7,
+      definition_hash: [2; 16],
+      fragment_id: (1, 3),
+    };
+    let encoded = minted.encode();
+    assert_eq!(encoded, (7u64, [2u8; 16], 1u64, 3u64).encode());
+    assert_eq!(FragmentMinted::decode(&mut &encoded[..]).unwrap(), minted);
+
+    let transferred = ProtoTransferred {
+      proto_hash: [1u8; 32],
+      owner_id: 9u64,
+    };
+    let json = serde_json::to_string(&transferred).unwrap();
+    assert!(json.contains("\"ownerId\":9"));
+    assert_eq!(
+      serde_json::from_str::<ProtoTransferred<Hash256, u64>>(&json).unwrap(),
+      transferred
+    );
+  }
+}
@@ -0,0 +1,274 @@
+//! A trait with its dependencies and documentation as a single verifiable artifact, to share
+//! traits between environments.
+
+use crate::{
+  categories::ShardsTrait,
+  fragments::Hash256,
+  hashing::blake2_256,
+  limits::{CheckLimits, DecodeLimits},
+  registry::TraitRegistry,
+  trait_docs::{TraitDocsBundle, TraitDocsError},
+  traits::{Trait, TraitError},
+};
+use parity_scale_codec::{Decode, Encode, Error};
+use scale_info::prelude::vec::Vec;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A trait, the traits it depends on and its documentation.
+///
+/// Packages built with [`TraitPackage::new`] are canonical: every trait is canonicalized and the
+/// dependencies are ordered by hash without repetitions, so a package has a single encoding.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct TraitPackage {
+  /// The packaged trait
+  pub root: Trait,
+  /// The traits the root depends on, ordered by hash
+  pub dependencies: Vec<Trait>,
+  /// The documentation of the root
+  pub docs: Option<TraitDocsBundle>,
+}
+
+/// Errors returned by `TraitPackage::verify`.
+#[derive(Clone, PartialEq, Debug, Eq)]
+pub enum PackageError {
+  /// The trait with this hash is not in its canonical form
+  NonCanonical(ShardsTrait),
+  /// The dependencies are not strictly ordered by hash, or include the root
+  UnorderedDependencies,
+  /// The trait with this hash is invalid
+  InvalidTrait(ShardsTrait, TraitError),
+  /// The documentation does not match the root
+  Docs(TraitDocsError),
+}
+
+impl core::fmt::Display for PackageError {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    match self {
+      Self::NonCanonical(hash) => write!(f, "packaged trait {:?} is not canonical", hash),
+      Self::UnorderedDependencies => {
+        write!(f, "package dependencies are not ordered by hash")
+      }
+      Self::InvalidTrait(hash, error) => {
+        write!(f, "packaged trait {:?} is invalid: {:?}", hash, error)
+      }
+      Self::Docs(error) => write!(f, "{}", error),
+    }
+  }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PackageError {}
+
+impl TraitPackage {
+  /// The bytes starting every binary package.
+  pub const MAGIC: [u8; 4] = *b"PTPK";
+
+  /// A canonical package of `root` and its `dependencies`, repeated dependencies and the root
+  /// itself are dropped from `dependencies`.
+  pub fn new(root: Trait, dependencies: Vec<Trait>, docs: Option<TraitDocsBundle>) -> Self {
+    let mut root = root;
+    root.canonicalize();
+    let root_hash = root.shards_hash();
+    let mut hashed: Vec<(ShardsTrait, Trait)> = dependencies
+      .into_iter()
+      .map(|mut trait_| {
+        trait_.canonicalize();
+        (trait_.shards_hash(), trait_)
+      })
+      .filter(|(hash, _)| *hash != root_hash)
+      .collect();
+    hashed.sort_by_key(|(hash, _)| *hash);
+    hashed.dedup_by(|a, b| a.0 == b.0);
+    Self {
+      root,
+      dependencies: hashed.into_iter().map(|(_, trait_)| trait_).collect(),
+      docs,
+    }
+  }
+
+  /// The hash of the root.
+  pub fn root_hash(&self) -> ShardsTrait {
+    self.root.shards_hash()
+  }
+
+  /// The hashes of the dependencies, in order.
+  pub fn dependency_hashes(&self) -> Vec<ShardsTrait> {
+    self.dependencies.iter().map(Trait::shards_hash).collect()
+  }
+
+  /// The Blake2b-256 of the encoding of the package, stored in its binary form.
+  pub fn digest(&self) -> Hash256 {
+    blake2_256(&self.encode())
+  }
+
+  /// Checks that the package is canonical, that every trait is valid and that the documentation
+  /// matches the root.
+  pub fn verify(&self) -> Result<(), PackageError> {
+    let root_hash = self.root_hash();
+    let hashes = self.dependency_hashes();
+    if hashes.windows(2).any(|w| w[0] >= w[1]) || hashes.contains(&root_hash) {
+      return Err(PackageError::UnorderedDependencies);
+    }
+    for (hash, trait_) in
+      core::iter::once((&root_hash, &self.root)).chain(hashes.iter().zip(&self.dependencies))
+    {
+      let mut canonical = trait_.clone();
+      canonical.canonicalize();
+      if canonical != *trait_ {
+        return Err(PackageError::NonCanonical(*hash));
+      }
+      if let Err(mut errors) = trait_.validate() {
+        return Err(PackageError::InvalidTrait(*hash, errors.remove(0)));
+      }
+    }
+    if let Some(docs) = &self.docs {
+      docs.verify(&self.root).map_err(PackageError::Docs)?;
+    }
+    Ok(())
+  }
+
+  /// Verifies the package and registers its traits in `registry`, returning the hash of the root.
+  ///
+  /// Nothing is registered when the package is not valid.
+  pub fn import(&self, registry: &mut TraitRegistry) -> Result<ShardsTrait, PackageError> {
+    self.verify()?;
+    for trait_ in &self.dependencies {
+      registry.insert(trait_.shards_hash(), trait_.clone());
+    }
+    let root_hash = self.root_hash();
+    registry.insert(root_hash, self.root.clone());
+    Ok(root_hash)
+  }
+
+  /// The binary form of the package: `TraitPackage::MAGIC`, the digest and the encoding.
+  pub fn to_bytes(&self) -> Vec<u8> {
+    let mut bytes = Vec::from(Self::MAGIC);
+    bytes.extend_from_slice(&self.digest());
+    self.encode_to(&mut bytes);
+    bytes
+  }
+
+  /// Parses the binary form of a package within `limits`, rejecting packages whose digest does
+  /// not match. The package still has to be verified.
+  pub fn from_bytes(bytes: &[u8], limits: &DecodeLimits) -> Result<Self, Error> {
+    let input = bytes
+      .strip_prefix(&Self::MAGIC[..])
+      .ok_or("Not a trait package")?;
+    if input.len() < 32 {
+      return Err("Trait package is truncated".into());
+    }
+    let (digest, encoded) = input.split_at(32);
+    if blake2_256(encoded) != digest {
+      return Err("Trait package digest mismatch".into());
+    }
+    limits.decode(encoded)
+  }
+}
+
+impl CheckLimits for TraitPackage {
+  fn check_limits(&self, limits: &DecodeLimits) -> Result<(), Error> {
+    self.root.check_limits(limits)?;
+    self.dependencies.check_limits(limits)?;
+    self
+      .docs
+      .as_ref()
+      .map_or(Ok(()), |docs| docs.check_limits(limits))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::traits::{Record, VariableType, VariableTypeInfo};
+
+  fn trait_(name: &str, record: &str) -> Trait {
+    Trait {
+      name: name.to_string(),
+      records: vec![Record::from((
+        record.to_string(),
+        vec![VariableTypeInfo {
+          type_: VariableType::Bool,
+          default: None,
+        }],
+      ))],
+    }
+  }
+
+  fn package() -> TraitPackage {
+    let root = trait_("Door", "Open");
+    let docs = TraitDocsBundle {
+      trait_hash: root.shards_hash(),
+      revision: 1,
+      markdown: b"# Door".to_vec(),
+      examples: vec![],
+    };
+    let dependencies = vec![
+      trait_("Lock", "locked"),
+      trait_("Hinge", "broken"),
+      trait_("Lock", "locked"),
+      root.clone(),
+    ];
+    TraitPackage::new(root, dependencies, Some(docs))
+  }
+
+  #[test]
+  fn canonical_packages() {
+    let package = package();
+    assert_eq!(package.dependencies.len(), 2);
+    assert_eq!(package.root.records[0].name, "open");
+    assert_eq!(package.verify(), Ok(()));
+
+    let mut unordered = package.clone();
+    unordered.dependencies.reverse();
+    assert_eq!(unordered.verify(), Err(PackageError::UnorderedDependencies));
+
+    let mut raw = package.clone();
+    raw.root.records[0].name = "Open".into();
+    assert!(matches!(raw.verify(), Err(PackageError::NonCanonical(_))));
+
+    let mut other_docs = package.clone();
+    other_docs.docs.as_mut().unwrap().trait_hash = [0; 8];
+    assert!(matches!(
+      other_docs.verify(),
+      Err(PackageError::Docs(TraitDocsError::HashMismatch { .. }))
+    ));
+  }
+
+  #[test]
+  fn binary_form() {
+    let package = package();
+    let bytes = package.to_bytes();
+    let limits = DecodeLimits::default();
+    assert_eq!(
+      TraitPackage::from_bytes(&bytes, &limits),
+      Ok(package.clone())
+    );
+
+    let mut tampered = bytes.clone();
+    *tampered.last_mut().unwrap() ^= 1;
+    assert!(TraitPackage::from_bytes(&tampered, &limits).is_err());
+    assert!(TraitPackage::from_bytes(&bytes[..20], &limits).is_err());
+    assert!(TraitPackage::from_bytes(&bytes[4..], &limits).is_err());
+
+    let mut registry = TraitRegistry::new();
+    let root_hash = package.import(&mut registry).unwrap();
+    assert_eq!(root_hash, package.root_hash());
+    assert_eq!(registry.len(), 3);
+    for hash in package.dependency_hashes() {
+      assert!(registry.contains(&hash));
+    }
+
+    let mut invalid = package;
+    invalid.dependencies.reverse();
+    let mut registry = TraitRegistry::new();
+    assert!(invalid.import(&mut registry).is_err());
+    assert!(registry.is_empty());
+  }
+}
@@ -0,0 +1,160 @@
+//! Filter expressions over the information about protos, so complex searches can be sent to
+//! indexers as data.
+
+use crate::{
+  categories::{Categories, ShardsTrait},
+  policy::CategoryFilter,
+  proto::ProtoInfo,
+};
+use parity_scale_codec::{Compact, Decode, Encode, Error, Input};
+use scale_info::prelude::{boxed::Box, string::String, vec::Vec};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The maximum nesting of `And`, `Or` and `Not` accepted when decoding a `FilterExpr`.
+pub const MAX_FILTER_DEPTH: u32 = 16;
+
+/// A boolean expression over the information about a proto.
+#[derive(Encode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub enum FilterExpr<TAccountId> {
+  /// Every expression matches, true when empty
+  And(Vec<FilterExpr<TAccountId>>),
+  /// Any expression matches, false when empty
+  Or(Vec<FilterExpr<TAccountId>>),
+  /// The expression does not match
+  Not(Box<FilterExpr<TAccountId>>),
+  /// The proto is in a category matched by the filter
+  Category(CategoryFilter),
+  /// The proto has the tag
+  Tag(String),
+  /// The proto is owned by the account
+  Owner(TAccountId),
+  /// The proto is a Shards script implementing the trait
+  Implements(ShardsTrait),
+}
+
+impl<TAccountId: PartialEq> FilterExpr<TAccountId> {
+  /// Whether the proto of `info` matches the expression.
+  pub fn evaluate<THash>(&self, info: &ProtoInfo<THash, TAccountId>) -> bool {
+    match self {
+      Self::And(exprs) => exprs.iter().all(|expr| expr.evaluate(info)),
+      Self::Or(exprs) => exprs.iter().any(|expr| expr.evaluate(info)),
+      Self::Not(expr) => !expr.evaluate(info),
+      Self::Category(filter) => filter.matches(&info.category),
+      Self::Tag(tag) => info.tags.contains(tag),
+      Self::Owner(owner) => info.owner == *owner,
+      Self::Implements(hash) => {
+        matches!(&info.category, Categories::Shards(info) if info.implementing.contains(hash))
+      }
+    }
+  }
+}
+
+fn decode_exprs<TAccountId: Decode, I: Input>(
+  input: &mut I,
+  depth: u32,
+) -> Result<Vec<FilterExpr<TAccountId>>, Error> {
+  let len = Compact::<u32>::decode(input)?.0;
+  input.on_before_alloc_mem(
+    (len as usize).saturating_mul(core::mem::size_of::<FilterExpr<TAccountId>>()),
+  )?;
+  let mut exprs = Vec::new();
+  for _ in 0..len {
+    exprs.push(decode_expr(input, depth + 1)?);
+  }
+  Ok(exprs)
+}
+
+fn decode_expr<TAccountId: Decode, I: Input>(
+  input: &mut I,
+  depth: u32,
+) -> Result<FilterExpr<TAccountId>, Error> {
+  if depth > MAX_FILTER_DEPTH {
+    return Err("Filter expression nested too deeply".into());
+  }
+  Ok(match input.read_byte()? {
+    0 => FilterExpr::And(decode_exprs(input, depth)?),
+    1 => FilterExpr::Or(decode_exprs(input, depth)?),
+    2 => FilterExpr::Not(Box::new(decode_expr(input, depth + 1)?)),
+    3 => FilterExpr::Category(Decode::decode(input)?),
+    4 => FilterExpr::Tag(Decode::decode(input)?),
+    5 => FilterExpr::Owner(Decode::decode(input)?),
+    6 => FilterExpr::Implements(Decode::decode(input)?),
+    _ => return Err("Invalid FilterExpr variant".into()),
+  })
+}
+
+impl<TAccountId: Decode> Decode for FilterExpr<TAccountId> {
+  fn decode<I: Input>(input: &mut I) -> Result<Self, Error> {
+    decode_expr(input, 0)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::categories::{ShardsFormat, ShardsScriptInfo, TextureCategories};
+
+  fn info(category: Categories, owner: u64, tags: &[&str]) -> ProtoInfo<u32, u64> {
+    ProtoInfo {
+      hash: 1,
+      owner,
+      category,
+      tags: tags.iter().map(|tag| tag.to_string()).collect(),
+      references: vec![],
+    }
+  }
+
+  #[test]
+  fn evaluation() {
+    let png = Categories::Texture(TextureCategories::PngFile);
+    let script = Categories::Shards(ShardsScriptInfo {
+      format: ShardsFormat::Edn,
+      shards_version: 1,
+      requiring: vec![],
+      implementing: vec![[7; 8]],
+    });
+    // textures tagged "cat" not owned by 2, or any script implementing [7; 8]
+    let expr = FilterExpr::Or(vec![
+      FilterExpr::And(vec![
+        FilterExpr::Category(CategoryFilter::Variant(4)),
+        FilterExpr::Tag("cat".into()),
+        FilterExpr::Not(Box::new(FilterExpr::Owner(2))),
+      ]),
+      FilterExpr::Implements([7; 8]),
+    ]);
+    assert!(expr.evaluate(&info(png.clone(), 1, &["cat", "meme"])));
+    assert!(!expr.evaluate(&info(png.clone(), 2, &["cat"])));
+    assert!(!expr.evaluate(&info(png, 1, &["dog"])));
+    assert!(expr.evaluate(&info(script, 2, &[])));
+    assert!(FilterExpr::<u64>::And(vec![]).evaluate(&info(Categories::Bundle, 1, &[])));
+    assert!(!FilterExpr::<u64>::Or(vec![]).evaluate(&info(Categories::Bundle, 1, &[])));
+
+    let encoded = expr.encode();
+    assert_eq!(FilterExpr::decode(&mut &encoded[..]), Ok(expr.clone()));
+    let json = serde_json::to_string(&expr).unwrap();
+    assert!(json.starts_with(r#"{"or":[{"and":[{"category":{"variant":4}},{"tag":"cat"}"#));
+    assert_eq!(
+      serde_json::from_str::<FilterExpr<u64>>(&json).unwrap(),
+      expr
+    );
+  }
+
+  #[test]
+  fn nesting_limit() {
+    let mut expr = FilterExpr::<u64>::Tag("cat".into());
+    for _ in 0..MAX_FILTER_DEPTH {
+      expr = FilterExpr::Not(Box::new(expr));
+    }
+    let encoded = expr.encode();
+    assert_eq!(FilterExpr::decode(&mut &encoded[..]), Ok(expr.clone()));
+    let encoded = FilterExpr::And(vec![expr]).encode();
+    assert!(FilterExpr::<u64>::decode(&mut &encoded[..]).is_err());
+  }
+}
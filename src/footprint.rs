@@ -0,0 +1,140 @@
+//! Estimation of the on-chain storage used by traits and protos, so wallets can show the deposit
+//! an upload will reserve before submitting it.
+
+use crate::traits::Trait;
+use parity_scale_codec::{Decode, Encode};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Computes the deposit reserved for storing data on chain.
+pub trait DepositModel {
+  /// The deposit, in units of the chain currency, for `encoded_bytes` bytes split into `items`
+  /// storage items.
+  fn deposit(&self, encoded_bytes: u32, items: u32) -> u128;
+}
+
+/// A deposit growing linearly with the size and the amount of items stored.
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct LinearDeposit {
+  /// The deposit for each encoded byte
+  pub per_byte: u128,
+  /// The deposit for each storage item
+  pub per_item: u128,
+}
+
+impl LinearDeposit {
+  /// One unit per byte, each item costing as much as a 32 bytes storage key.
+  pub const DEFAULT: Self = Self {
+    per_byte: 1,
+    per_item: 32,
+  };
+}
+
+impl Default for LinearDeposit {
+  fn default() -> Self {
+    Self::DEFAULT
+  }
+}
+
+impl DepositModel for LinearDeposit {
+  fn deposit(&self, encoded_bytes: u32, items: u32) -> u128 {
+    self
+      .per_byte
+      .saturating_mul(encoded_bytes as u128)
+      .saturating_add(self.per_item.saturating_mul(items as u128))
+  }
+}
+
+/// The estimated storage used by some data.
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct FootprintEstimate {
+  /// The size of the SCALE encoding of the data
+  pub encoded_bytes: u32,
+  /// The amount of records of the data, or 0 if it has none
+  pub record_count: u32,
+  /// The deposit estimated by the deposit model
+  pub est_deposit_units: u128,
+}
+
+impl FootprintEstimate {
+  /// The footprint of `value`, stored as a single item.
+  pub fn of<T: Encode>(value: &T, model: &impl DepositModel) -> Self {
+    let encoded_bytes = value.encoded_size() as u32;
+    Self {
+      encoded_bytes,
+      record_count: 0,
+      est_deposit_units: model.deposit(encoded_bytes, 1),
+    }
+  }
+}
+
+impl Trait {
+  /// The footprint of the canonical form of the trait, the form stored on chain, with the
+  /// [`LinearDeposit::DEFAULT`] deposit model.
+  pub fn storage_footprint(&self) -> FootprintEstimate {
+    self.storage_footprint_with(&LinearDeposit::DEFAULT)
+  }
+
+  /// The footprint of the canonical form of the trait with the deposit model `model`.
+  ///
+  /// The trait is stored as a single item, its records are counted but not charged as items.
+  pub fn storage_footprint_with(&self, model: &impl DepositModel) -> FootprintEstimate {
+    let mut canonical = self.clone();
+    canonical.canonicalize();
+    FootprintEstimate {
+      record_count: canonical.records.len() as u32,
+      ..FootprintEstimate::of(&canonical, model)
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::traits::{Record, VariableType, VariableTypeInfo};
+
+  #[test]
+  fn trait_footprint() {
+    let info = VariableTypeInfo {
+      type_: VariableType::Bool,
+      default: None,
+    };
+    let trait1 = Trait {
+      name: "Flag".to_string(),
+      records: vec![
+        Record::from(("on".to_string(), vec![info.clone()])),
+        Record::from(("ON".to_string(), vec![info])),
+      ],
+    };
+    let estimate = trait1.storage_footprint();
+    // the duplicate record is dropped by canonicalization
+    assert_eq!(estimate.record_count, 1);
+    assert_eq!(estimate.encoded_bytes, 12);
+    assert_eq!(estimate.est_deposit_units, 12 + 32);
+
+    struct Flat;
+    impl DepositModel for Flat {
+      fn deposit(&self, _: u32, items: u32) -> u128 {
+        1000 * items as u128
+      }
+    }
+    assert_eq!(trait1.storage_footprint_with(&Flat).est_deposit_units, 1000);
+
+    let huge = LinearDeposit {
+      per_byte: u128::MAX,
+      per_item: 1,
+    };
+    assert_eq!(huge.deposit(2, 1), u128::MAX);
+  }
+}
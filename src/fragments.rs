@@ -0,0 +1,232 @@
+use crate::permissions::FragmentPerms;
+use parity_scale_codec::{Compact, Decode, Encode, MaxEncodedLen};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A 256-bit hash, used to reference proto and fragment data.
+pub type Hash256 = [u8; 32];
+
+/// A 128-bit hash, used to identify fragment definitions.
+pub type Hash128 = [u8; 16];
+
+/// The unit used to count edition, copy and stack amounts of fragment instances.
+pub type InstanceUnit = u64;
+
+/// Struct representing a single instance (an edition or a copy) of a Fragment Definition.
+///
+/// This mirrors the layout of the instance storage entries of the Fragnova chain,
+/// so explorers can decode them without depending on the pallet.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, MaxEncodedLen, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct FragmentInstance<TBlockNum> {
+  /// Permissions of the instance, these can only be restricted in respect to the definition's ones
+  pub permissions: FragmentPerms,
+  /// The block number at which the instance was created
+  pub created_at: TBlockNum,
+  /// Hash of the custom data attached to the instance, if any
+  pub custom_data: Option<Hash256>,
+  /// The block number at which the instance expires, if it is a temporary instance
+  pub expiring_at: Option<TBlockNum>,
+  /// The amount of units stacked in this instance, only used by stackable fragments
+  #[cfg_attr(feature = "serde", serde(with = "compact_option"))]
+  pub stack_amount: Option<Compact<InstanceUnit>>,
+}
+
+impl<TBlockNum> FragmentInstance<TBlockNum> {
+  /// Returns the amount of units stacked in the instance, `None` if the fragment is not stackable.
+  pub fn stack_amount_value(&self) -> Option<InstanceUnit> {
+    self.stack_amount.map(|amount| amount.0)
+  }
+}
+
+impl<TBlockNum: PartialOrd> FragmentInstance<TBlockNum> {
+  /// Whether the instance is expired at block `now`.
+  pub fn is_expired(&self, now: &TBlockNum) -> bool {
+    self.expiring_at.as_ref().is_some_and(|at| now >= at)
+  }
+}
+
+/// Struct describing the uniqueness rules of the instances of a Fragment Definition.
+#[derive(
+  Encode, Decode, Copy, Clone, PartialEq, Debug, Eq, MaxEncodedLen, scale_info::TypeInfo,
+)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct UniqueOptions<THash> {
+  /// Whether the data of the instances can be changed after minting
+  pub mutable: bool,
+  /// Hash of the data that makes the instance unique
+  pub unique_data_hash: THash,
+}
+
+/// A reference to the data attached to a fragment instance.
+#[derive(
+  Encode, Decode, Copy, Clone, PartialEq, Debug, Eq, MaxEncodedLen, scale_info::TypeInfo,
+)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub enum InstanceDataRef<THash> {
+  /// Content addressed data, the hash is the hash of the data itself and can never change
+  Immutable(THash),
+  /// Data that can be patched after minting, the hash identifies its current version
+  Mutable(THash),
+}
+
+impl<THash> InstanceDataRef<THash> {
+  /// The hash referenced, regardless of the mutability of the data.
+  pub fn hash(&self) -> &THash {
+    match self {
+      Self::Immutable(hash) | Self::Mutable(hash) => hash,
+    }
+  }
+
+  pub fn is_mutable(&self) -> bool {
+    matches!(self, Self::Mutable(_))
+  }
+}
+
+/// Errors returned when instance data does not follow the rules of its definition.
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum UniqueDataError {
+  /// An immutable instance references data that can change
+  MutableReference,
+  /// The data referenced is not the unique data of the instance
+  HashMismatch,
+}
+
+impl core::fmt::Display for UniqueDataError {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    match self {
+      Self::MutableReference => write!(f, "immutable instances cannot reference mutable data"),
+      Self::HashMismatch => write!(f, "referenced data does not match the unique data hash"),
+    }
+  }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UniqueDataError {}
+
+impl<THash: PartialEq> UniqueOptions<THash> {
+  /// Checks that `data` can be attached to an instance following these options.
+  ///
+  /// Immutable instances must reference their unique data through an immutable reference,
+  /// mutable instances can reference any data as it will be allowed to change anyway.
+  pub fn validate(&self, data: &InstanceDataRef<THash>) -> Result<(), UniqueDataError> {
+    if self.mutable {
+      return Ok(());
+    }
+
+    match data {
+      InstanceDataRef::Mutable(_) => Err(UniqueDataError::MutableReference),
+      InstanceDataRef::Immutable(hash) if *hash != self.unique_data_hash => {
+        Err(UniqueDataError::HashMismatch)
+      }
+      InstanceDataRef::Immutable(_) => Ok(()),
+    }
+  }
+}
+
+/// `Compact` does not implement serde, so we (de)serialize it as its inner value.
+#[cfg(feature = "serde")]
+mod compact_option {
+  use super::InstanceUnit;
+  use parity_scale_codec::Compact;
+  use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+  pub fn serialize<S: Serializer>(
+    value: &Option<Compact<InstanceUnit>>,
+    serializer: S,
+  ) -> Result<S::Ok, S::Error> {
+    value.map(|v| v.0).serialize(serializer)
+  }
+
+  pub fn deserialize<'de, D: Deserializer<'de>>(
+    deserializer: D,
+  ) -> Result<Option<Compact<InstanceUnit>>, D::Error> {
+    Ok(Option::<InstanceUnit>::deserialize(deserializer)?.map(Compact))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn instance() -> FragmentInstance<u32> {
+    FragmentInstance {
+      permissions: FragmentPerms::EDIT | FragmentPerms::TRANSFER,
+      created_at: 10,
+      custom_data: Some([7u8; 32]),
+      expiring_at: Some(100),
+      stack_amount: Some(Compact(1_000)),
+    }
+  }
+
+  #[test]
+  fn encode_decode_instance() {
+    let instance = instance();
+    let encoded = instance.encode();
+    assert!(encoded.len() <= FragmentInstance::<u32>::max_encoded_len());
+
+    let decoded = FragmentInstance::<u32>::decode(&mut encoded.as_slice()).unwrap();
+    assert_eq!(instance, decoded);
+    assert_eq!(decoded.stack_amount_value(), Some(1_000));
+  }
+
+  #[test]
+  fn json_instance() {
+    let instance = instance();
+    let json = serde_json::to_string(&instance).unwrap();
+    assert!(json.contains("\"stackAmount\":1000"));
+
+    let decoded: FragmentInstance<u32> = serde_json::from_str(&json).unwrap();
+    assert_eq!(instance, decoded);
+  }
+
+  #[test]
+  fn unique_options_validation() {
+    let immutable = UniqueOptions {
+      mutable: false,
+      unique_data_hash: [1u8; 32],
+    };
+    assert_eq!(
+      immutable.validate(&InstanceDataRef::Immutable([1u8; 32])),
+      Ok(())
+    );
+    assert_eq!(
+      immutable.validate(&InstanceDataRef::Mutable([1u8; 32])),
+      Err(UniqueDataError::MutableReference)
+    );
+    assert_eq!(
+      immutable.validate(&InstanceDataRef::Immutable([2u8; 32])),
+      Err(UniqueDataError::HashMismatch)
+    );
+
+    let mutable = UniqueOptions {
+      mutable: true,
+      ..immutable
+    };
+    assert_eq!(
+      mutable.validate(&InstanceDataRef::Mutable([2u8; 32])),
+      Ok(())
+    );
+  }
+
+  #[test]
+  fn instance_expiration() {
+    let instance = instance();
+    assert!(!instance.is_expired(&99));
+    assert!(instance.is_expired(&100));
+  }
+}
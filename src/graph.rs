@@ -0,0 +1,437 @@
+//! Graphviz (DOT) and GraphML exports of the relationships between traits and between protos, to
+//! visualize ecosystems and spot tangled clusters with off-the-shelf graph tools.
+
+use crate::{categories::Categories, hex::HexBytes, proto::ProtoInfo};
+use core::fmt::Write;
+use scale_info::prelude::{
+  collections::BTreeSet,
+  string::{String, ToString},
+  vec::Vec,
+};
+
+#[cfg(feature = "registry")]
+use crate::registry::TraitRegistry;
+
+/// What a node of a [`Graph`] stands for.
+#[derive(Copy, Clone, PartialEq, Debug, Eq)]
+pub enum NodeKind {
+  /// A registered trait
+  Trait,
+  /// A registered trait that should no longer be implemented
+  DeprecatedTrait,
+  /// A proto
+  Proto,
+  /// A trait or proto referenced by the graph but not part of its source
+  Missing,
+}
+
+impl NodeKind {
+  fn name(&self) -> &'static str {
+    match self {
+      Self::Trait => "trait",
+      Self::DeprecatedTrait => "deprecatedTrait",
+      Self::Proto => "proto",
+      Self::Missing => "missing",
+    }
+  }
+}
+
+/// The relationship an edge of a [`Graph`] stands for.
+#[derive(Copy, Clone, PartialEq, Debug, Eq)]
+pub enum EdgeKind {
+  /// The source trait is deprecated in favor of the target trait
+  SupersededBy,
+  /// The source proto references the target proto
+  References,
+  /// The source shards proto requires the target trait
+  Requires,
+  /// The source shards proto implements the target trait
+  Implements,
+}
+
+impl EdgeKind {
+  fn name(&self) -> &'static str {
+    match self {
+      Self::SupersededBy => "supersededBy",
+      Self::References => "references",
+      Self::Requires => "requires",
+      Self::Implements => "implements",
+    }
+  }
+
+  fn label(&self) -> &'static str {
+    match self {
+      Self::SupersededBy => "superseded by",
+      Self::References => "references",
+      Self::Requires => "requires",
+      Self::Implements => "implements",
+    }
+  }
+}
+
+/// A trait or proto of a [`Graph`].
+#[derive(Clone, PartialEq, Debug, Eq)]
+pub struct Node {
+  /// The `0x` hex hash of the trait or proto
+  pub id: String,
+  /// The trait name, or the start of the hash for protos
+  pub label: String,
+  /// What the node stands for
+  pub kind: NodeKind,
+}
+
+/// A relationship between two nodes of a [`Graph`].
+#[derive(Clone, PartialEq, Debug, Eq)]
+pub struct Edge {
+  /// The id of the source node
+  pub from: String,
+  /// The id of the target node, a `Missing` node if the source of the graph lacks it
+  pub to: String,
+  /// The relationship
+  pub kind: EdgeKind,
+}
+
+/// A directed graph of traits or protos, built from a `TraitRegistry` or a list of `ProtoInfo`.
+///
+/// Nodes are in source order, followed by the missing nodes in the order they are first
+/// referenced, so exports of the same source are identical.
+#[derive(Clone, PartialEq, Debug, Eq, Default)]
+pub struct Graph {
+  /// Every node, each id once
+  pub nodes: Vec<Node>,
+  /// Every edge, in the order of their source nodes
+  pub edges: Vec<Edge>,
+}
+
+impl Graph {
+  /// Adds `Missing` nodes for the edge targets that are not nodes of the graph.
+  fn add_missing(&mut self) {
+    let mut known: BTreeSet<String> = self.nodes.iter().map(|node| node.id.clone()).collect();
+    for edge in &self.edges {
+      if known.insert(edge.to.clone()) {
+        self.nodes.push(Node {
+          id: edge.to.clone(),
+          label: short_label(&edge.to),
+          kind: NodeKind::Missing,
+        });
+      }
+    }
+  }
+
+  /// The graph in the Graphviz DOT language.
+  ///
+  /// Deprecated traits are dashed, missing nodes are dotted.
+  pub fn to_dot(&self) -> String {
+    let mut dot = String::from("digraph protos {\n");
+    for node in &self.nodes {
+      let style = match node.kind {
+        NodeKind::Trait | NodeKind::Proto => "",
+        NodeKind::DeprecatedTrait => ", style=dashed",
+        NodeKind::Missing => ", style=dotted",
+      };
+      let _ = writeln!(
+        dot,
+        "  \"{}\" [label=\"{}\"{}];",
+        dot_escape(&node.id),
+        dot_escape(&node.label),
+        style
+      );
+    }
+    for edge in &self.edges {
+      let _ = writeln!(
+        dot,
+        "  \"{}\" -> \"{}\" [label=\"{}\"];",
+        dot_escape(&edge.from),
+        dot_escape(&edge.to),
+        edge.kind.label()
+      );
+    }
+    dot.push_str("}\n");
+    dot
+  }
+
+  /// The graph as a GraphML document, with the label and kind of every node and the relationship
+  /// of every edge as data attributes.
+  pub fn to_graphml(&self) -> String {
+    let mut xml = String::from(concat!(
+      "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+      "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+      "  <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>\n",
+      "  <key id=\"kind\" for=\"node\" attr.name=\"kind\" attr.type=\"string\"/>\n",
+      "  <key id=\"relation\" for=\"edge\" attr.name=\"relation\" attr.type=\"string\"/>\n",
+      "  <graph id=\"protos\" edgedefault=\"directed\">\n",
+    ));
+    for node in &self.nodes {
+      let _ = writeln!(
+        xml,
+        "    <node id=\"{}\"><data key=\"label\">{}</data><data key=\"kind\">{}</data></node>",
+        xml_escape(&node.id),
+        xml_escape(&node.label),
+        node.kind.name()
+      );
+    }
+    for edge in &self.edges {
+      let _ = writeln!(
+        xml,
+        "    <edge source=\"{}\" target=\"{}\"><data key=\"relation\">{}</data></edge>",
+        xml_escape(&edge.from),
+        xml_escape(&edge.to),
+        edge.kind.name()
+      );
+    }
+    xml.push_str("  </graph>\n</graphml>\n");
+    xml
+  }
+}
+
+/// The registered traits, with an edge from every deprecated trait to the trait superseding it.
+#[cfg(feature = "registry")]
+impl From<&TraitRegistry> for Graph {
+  fn from(registry: &TraitRegistry) -> Self {
+    let mut graph = Graph::default();
+    for (hash, trait_) in registry.iter() {
+      let id = HexBytes(hash).to_string();
+      let status = registry.status(hash);
+      if let Some(successor) = status.superseded_by() {
+        graph.edges.push(Edge {
+          from: id.clone(),
+          to: HexBytes(successor).to_string(),
+          kind: EdgeKind::SupersededBy,
+        });
+      }
+      graph.nodes.push(Node {
+        id,
+        label: trait_.name.clone(),
+        kind: if status.is_deprecated() {
+          NodeKind::DeprecatedTrait
+        } else {
+          NodeKind::Trait
+        },
+      });
+    }
+    graph.add_missing();
+    graph
+  }
+}
+
+/// The protos, with an edge from every proto to each proto it references and from every shards
+/// proto to each trait it requires or implements.
+impl<THash: AsRef<[u8]>, TAccountId> From<&[ProtoInfo<THash, TAccountId>]> for Graph {
+  fn from(protos: &[ProtoInfo<THash, TAccountId>]) -> Self {
+    let mut graph = Graph::default();
+    for proto in protos {
+      let id = HexBytes(proto.hash.as_ref()).to_string();
+      for reference in &proto.references {
+        graph.edges.push(Edge {
+          from: id.clone(),
+          to: HexBytes(reference.as_ref()).to_string(),
+          kind: EdgeKind::References,
+        });
+      }
+      if let Categories::Shards(info) = &proto.category {
+        let traits = info
+          .requiring
+          .iter()
+          .map(|hash| (hash, EdgeKind::Requires))
+          .chain(
+            info
+              .implementing
+              .iter()
+              .map(|hash| (hash, EdgeKind::Implements)),
+          );
+        for (hash, kind) in traits {
+          graph.edges.push(Edge {
+            from: id.clone(),
+            to: HexBytes(hash).to_string(),
+            kind,
+          });
+        }
+      }
+      graph.nodes.push(Node {
+        label: short_label(&id),
+        id,
+        kind: NodeKind::Proto,
+      });
+    }
+    graph.add_missing();
+    graph
+  }
+}
+
+/// Exports the trait graph of a `TraitRegistry` or the reference graph of protos to DOT.
+///
+/// ```
+/// use protos::{
+///   categories::{BinaryCategories, Categories},
+///   graph::export_dot,
+///   proto::ProtoInfo,
+/// };
+///
+/// let protos = [ProtoInfo {
+///   hash: [1u8; 4],
+///   owner: (),
+///   category: Categories::Binary(BinaryCategories::BlendFile),
+///   tags: vec![],
+///   references: vec![[2u8; 4]],
+/// }];
+/// let dot = export_dot(&protos[..]);
+/// assert!(dot.contains(r#""0x01010101" -> "0x02020202""#));
+/// ```
+pub fn export_dot(source: impl Into<Graph>) -> String {
+  source.into().to_dot()
+}
+
+/// Exports the trait graph of a `TraitRegistry` or the reference graph of protos to GraphML.
+pub fn export_graphml(source: impl Into<Graph>) -> String {
+  source.into().to_graphml()
+}
+
+/// The first 4 bytes of a `0x` hex hash, enough to tell nodes apart in a drawing.
+fn short_label(id: &str) -> String {
+  match id.get(..10) {
+    Some(start) if id.len() > 10 => [start, "…"].concat(),
+    _ => id.into(),
+  }
+}
+
+fn dot_escape(s: &str) -> String {
+  s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Escapes the markup characters of `s` and replaces the characters XML 1.0 forbids, the control
+/// characters but tab and line breaks and the `U+FFFE`/`U+FFFF` non-characters, with `U+FFFD`.
+fn xml_escape(s: &str) -> String {
+  let mut escaped = String::with_capacity(s.len());
+  for c in s.chars() {
+    match c {
+      '&' => escaped.push_str("&amp;"),
+      '<' => escaped.push_str("&lt;"),
+      '>' => escaped.push_str("&gt;"),
+      '"' => escaped.push_str("&quot;"),
+      '\t' | '\n' | '\r' => escaped.push(c),
+      '\u{0}'..='\u{1f}' | '\u{fffe}' | '\u{ffff}' => escaped.push(char::REPLACEMENT_CHARACTER),
+      _ => escaped.push(c),
+    }
+  }
+  escaped
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::categories::{BinaryCategories, ShardsScriptInfo};
+
+  fn info(hash: u8, references: &[u8]) -> ProtoInfo<[u8; 32], ()> {
+    ProtoInfo {
+      hash: [hash; 32],
+      owner: (),
+      category: Categories::Binary(BinaryCategories::BlendFile),
+      tags: vec![],
+      references: references.iter().map(|r| [*r; 32]).collect(),
+    }
+  }
+
+  #[test]
+  fn proto_graph() {
+    let protos = [info(1, &[2, 3]), info(2, &[3])];
+    let graph = Graph::from(&protos[..]);
+    let kinds: Vec<_> = graph.nodes.iter().map(|node| node.kind).collect();
+    assert_eq!(kinds, [NodeKind::Proto, NodeKind::Proto, NodeKind::Missing]);
+    assert_eq!(graph.edges.len(), 3);
+    assert_eq!(graph.nodes[0].label, "0x01010101…");
+
+    let dot = export_dot(&protos[..]);
+    assert!(dot.starts_with("digraph protos {\n"));
+    assert!(dot.contains(&format!(
+      "\"{}\" [label=\"0x03030303…\", style=dotted];",
+      HexBytes(&[3; 32])
+    )));
+    assert_eq!(dot.matches(" -> ").count(), 3);
+
+    let graphml = export_graphml(&protos[..]);
+    assert!(graphml.contains(r#"<data key="relation">references</data>"#));
+    assert_eq!(graphml.matches("<node ").count(), 3);
+    assert!(graphml.ends_with("</graphml>\n"));
+  }
+
+  #[test]
+  fn escaping() {
+    let graph = Graph {
+      nodes: vec![Node {
+        id: "a".into(),
+        label: r#"<"Door" & \>"#.into(),
+        kind: NodeKind::Trait,
+      }],
+      edges: vec![],
+    };
+    assert!(graph.to_dot().contains(r#""a" [label="<\"Door\" & \\>"];"#));
+    assert!(graph
+      .to_graphml()
+      .contains("<data key=\"label\">&lt;&quot;Door&quot; &amp; \\&gt;</data>"));
+
+    assert_eq!(
+      xml_escape("a\u{0}\tb\u{1b}\u{ffff}\n"),
+      "a\u{fffd}\tb\u{fffd}\u{fffd}\n"
+    );
+  }
+
+  #[test]
+  fn shards_traits() {
+    let mut script = info(1, &[]);
+    script.category = Categories::Shards(ShardsScriptInfo {
+      requiring: vec![[7; 8]],
+      implementing: vec![[8; 8]],
+      ..Default::default()
+    });
+    let graph = Graph::from(&[script][..]);
+    assert_eq!(
+      graph.edges.iter().map(|edge| edge.kind).collect::<Vec<_>>(),
+      [EdgeKind::Requires, EdgeKind::Implements]
+    );
+    assert_eq!(graph.edges[0].to, HexBytes(&[7; 8]).to_string());
+    assert_eq!(graph.nodes.len(), 3);
+    assert_eq!(graph.nodes[1].kind, NodeKind::Missing);
+    assert!(graph.to_dot().contains("[label=\"implements\"]"));
+  }
+
+  #[cfg(feature = "registry")]
+  #[test]
+  fn trait_graph() {
+    use crate::{
+      registry::TraitStatus,
+      traits::{Record, Trait},
+    };
+
+    let trait_ = |name: &str| Trait {
+      name: name.into(),
+      records: vec![Record::from(("a".to_string(), vec![Default::default()]))],
+    };
+    let mut registry = TraitRegistry::new();
+    let (old, new) = (trait_("Old"), trait_("New"));
+    let (old_hash, new_hash) = (old.shards_hash(), new.shards_hash());
+    registry.insert(old_hash, old);
+    registry.insert(new_hash, new);
+    registry.set_status(
+      &old_hash,
+      TraitStatus::Deprecated {
+        superseded_by: Some(new_hash),
+        note_hash: None,
+      },
+    );
+
+    let graph = Graph::from(&registry);
+    assert_eq!(graph.nodes.len(), 2);
+    assert_eq!(
+      graph.edges,
+      [Edge {
+        from: HexBytes(&old_hash).to_string(),
+        to: HexBytes(&new_hash).to_string(),
+        kind: EdgeKind::SupersededBy,
+      }]
+    );
+    let dot = export_dot(&registry);
+    assert!(dot.contains("[label=\"Old\", style=dashed];"));
+    assert!(dot.contains("[label=\"New\"];"));
+    assert!(export_graphml(&registry).contains(r#"<data key="kind">deprecatedTrait</data>"#));
+  }
+}
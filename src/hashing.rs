@@ -0,0 +1,341 @@
+use crate::categories::ShardsTrait;
+#[cfg(feature = "hashing")]
+use blake2::{digest::consts::U32, Blake2b, Digest};
+use parity_scale_codec::{Decode, Encode};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Blake2b with a 256 bits output, equivalent to `sp_core::blake2_256`.
+#[cfg(feature = "hashing")]
+pub fn blake2_256(data: &[u8]) -> [u8; 32] {
+  Blake2b::<U32>::digest(data).into()
+}
+
+/// Computes the hash of proto data exactly like the chain does when the proto is uploaded.
+#[cfg(feature = "hashing")]
+pub fn proto_hash(data: &[u8]) -> [u8; 32] {
+  crate::trace::span!("proto_hash", len = data.len());
+  blake2_256(data)
+}
+
+/// Whether `data` is the data of the proto identified by `hash`.
+#[cfg(feature = "hashing")]
+pub fn verify(data: &[u8], hash: &[u8; 32]) -> bool {
+  proto_hash(data) == *hash
+}
+
+const PRIME64_1: u64 = 0x9E3779B185EBCA87;
+const PRIME64_2: u64 = 0xC2B2AE3D27D4EB4F;
+const PRIME64_3: u64 = 0x165667B19E3779F9;
+const PRIME64_4: u64 = 0x85EBCA77C2B2AE63;
+const PRIME64_5: u64 = 0x27D4EB2F165667C5;
+
+const fn read_u64(data: &[u8], at: usize) -> u64 {
+  u64::from_le_bytes([
+    data[at],
+    data[at + 1],
+    data[at + 2],
+    data[at + 3],
+    data[at + 4],
+    data[at + 5],
+    data[at + 6],
+    data[at + 7],
+  ])
+}
+
+const fn read_u32(data: &[u8], at: usize) -> u64 {
+  u32::from_le_bytes([data[at], data[at + 1], data[at + 2], data[at + 3]]) as u64
+}
+
+const fn xxh64_round(acc: u64, input: u64) -> u64 {
+  acc
+    .wrapping_add(input.wrapping_mul(PRIME64_2))
+    .rotate_left(31)
+    .wrapping_mul(PRIME64_1)
+}
+
+const fn xxh64_merge(acc: u64, value: u64) -> u64 {
+  (acc ^ xxh64_round(0, value))
+    .wrapping_mul(PRIME64_1)
+    .wrapping_add(PRIME64_4)
+}
+
+/// XXHash64 with the given seed.
+///
+/// A `const fn` so trait hashes can be computed and checked at compile time.
+pub const fn xxh64(data: &[u8], seed: u64) -> u64 {
+  let len = data.len();
+  let mut at = 0;
+  let hash = if len >= 32 {
+    let mut v1 = seed.wrapping_add(PRIME64_1).wrapping_add(PRIME64_2);
+    let mut v2 = seed.wrapping_add(PRIME64_2);
+    let mut v3 = seed;
+    let mut v4 = seed.wrapping_sub(PRIME64_1);
+    while at + 32 <= len {
+      v1 = xxh64_round(v1, read_u64(data, at));
+      v2 = xxh64_round(v2, read_u64(data, at + 8));
+      v3 = xxh64_round(v3, read_u64(data, at + 16));
+      v4 = xxh64_round(v4, read_u64(data, at + 24));
+      at += 32;
+    }
+    let hash = v1
+      .rotate_left(1)
+      .wrapping_add(v2.rotate_left(7))
+      .wrapping_add(v3.rotate_left(12))
+      .wrapping_add(v4.rotate_left(18));
+    xxh64_merge(xxh64_merge(xxh64_merge(xxh64_merge(hash, v1), v2), v3), v4)
+  } else {
+    seed.wrapping_add(PRIME64_5)
+  };
+
+  xxh64_tail(hash.wrapping_add(len as u64), data, at)
+}
+
+/// Mixes the last bytes of `data`, from `at`, into `hash` and finalizes it.
+const fn xxh64_tail(mut hash: u64, data: &[u8], mut at: usize) -> u64 {
+  let len = data.len();
+  while at + 8 <= len {
+    hash = (hash ^ xxh64_round(0, read_u64(data, at)))
+      .rotate_left(27)
+      .wrapping_mul(PRIME64_1)
+      .wrapping_add(PRIME64_4);
+    at += 8;
+  }
+  if at + 4 <= len {
+    hash = (hash ^ read_u32(data, at).wrapping_mul(PRIME64_1))
+      .rotate_left(23)
+      .wrapping_mul(PRIME64_2)
+      .wrapping_add(PRIME64_3);
+    at += 4;
+  }
+  while at < len {
+    hash = (hash ^ (data[at] as u64).wrapping_mul(PRIME64_5))
+      .rotate_left(11)
+      .wrapping_mul(PRIME64_1);
+    at += 1;
+  }
+
+  hash ^= hash >> 33;
+  hash = hash.wrapping_mul(PRIME64_2);
+  hash ^= hash >> 29;
+  hash = hash.wrapping_mul(PRIME64_3);
+  hash ^ (hash >> 32)
+}
+
+/// Incremental [`xxh64`], for data arriving in pieces.
+///
+/// Implements `parity_scale_codec::Output`, so values can be hashed while they are encoded.
+#[derive(Clone, Debug)]
+pub struct XxHash64 {
+  seed: u64,
+  lanes: [u64; 4],
+  buffer: [u8; 32],
+  buffered: usize,
+  len: u64,
+}
+
+impl XxHash64 {
+  pub fn new(seed: u64) -> Self {
+    Self {
+      seed,
+      lanes: [
+        seed.wrapping_add(PRIME64_1).wrapping_add(PRIME64_2),
+        seed.wrapping_add(PRIME64_2),
+        seed,
+        seed.wrapping_sub(PRIME64_1),
+      ],
+      buffer: [0; 32],
+      buffered: 0,
+      len: 0,
+    }
+  }
+
+  fn consume(&mut self, stripe: &[u8]) {
+    for (i, lane) in self.lanes.iter_mut().enumerate() {
+      *lane = xxh64_round(*lane, read_u64(stripe, i * 8));
+    }
+  }
+
+  pub fn update(&mut self, mut data: &[u8]) {
+    self.len += data.len() as u64;
+    if self.buffered > 0 {
+      let take = data.len().min(32 - self.buffered);
+      self.buffer[self.buffered..self.buffered + take].copy_from_slice(&data[..take]);
+      self.buffered += take;
+      data = &data[take..];
+      if self.buffered < 32 {
+        return;
+      }
+      let stripe = self.buffer;
+      self.consume(&stripe);
+      self.buffered = 0;
+    }
+    while data.len() >= 32 {
+      self.consume(&data[..32]);
+      data = &data[32..];
+    }
+    self.buffer[..data.len()].copy_from_slice(data);
+    self.buffered = data.len();
+  }
+
+  /// The hash of every byte written so far, equal to `xxh64` of their concatenation.
+  pub fn finish(&self) -> u64 {
+    let [v1, v2, v3, v4] = self.lanes;
+    let hash = if self.len >= 32 {
+      let hash = v1
+        .rotate_left(1)
+        .wrapping_add(v2.rotate_left(7))
+        .wrapping_add(v3.rotate_left(12))
+        .wrapping_add(v4.rotate_left(18));
+      xxh64_merge(xxh64_merge(xxh64_merge(xxh64_merge(hash, v1), v2), v3), v4)
+    } else {
+      self.seed.wrapping_add(PRIME64_5)
+    };
+    xxh64_tail(
+      hash.wrapping_add(self.len),
+      &self.buffer[..self.buffered],
+      0,
+    )
+  }
+}
+
+impl parity_scale_codec::Output for XxHash64 {
+  fn write(&mut self, bytes: &[u8]) {
+    self.update(bytes);
+  }
+}
+
+/// XXHash64 with seed 0 as little endian bytes, equivalent to `sp_core::twox_64`.
+///
+/// This is the hash identifying traits (`ShardsTrait`).
+pub const fn twox_64(data: &[u8]) -> [u8; 8] {
+  xxh64(data, 0).to_le_bytes()
+}
+
+/// Computes the identity of a trait from the SCALE encoding of its canonical form.
+pub trait TraitHasher {
+  /// The digest, of any length so hashes wider than `ShardsTrait` can be expressed
+  type Output: AsRef<[u8]>;
+
+  /// The digest of `canonical`, the SCALE encoding of the canonical form of a trait (see
+  /// [`Trait::canonicalize`](crate::traits::Trait::canonicalize)).
+  fn hash_trait(&self, canonical: &[u8]) -> Self::Output;
+}
+
+/// The hasher of the current chain, [`twox_64`].
+#[derive(Copy, Clone, PartialEq, Debug, Eq, Default)]
+pub struct Xx64;
+
+impl TraitHasher for Xx64 {
+  type Output = ShardsTrait;
+
+  fn hash_trait(&self, canonical: &[u8]) -> ShardsTrait {
+    twox_64(canonical)
+  }
+}
+
+/// The hash scheme identifying traits, so a chain upgrade to another hash can be expressed.
+///
+/// Every scheme produces a `ShardsTrait`, the identity `ShardsScriptInfo` lists, a scheme with a
+/// wider digest is a [`TraitHasher`] of its own.
+#[derive(Encode, Decode, Default, Copy, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub enum HashScheme {
+  /// [`Xx64`]
+  #[default]
+  Xx64,
+}
+
+impl TraitHasher for HashScheme {
+  type Output = ShardsTrait;
+
+  fn hash_trait(&self, canonical: &[u8]) -> ShardsTrait {
+    match self {
+      Self::Xx64 => Xx64.hash_trait(canonical),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[cfg(feature = "hashing")]
+  #[test]
+  fn proto_hash_matches_chain() {
+    let data = b"Hello Fragnova";
+    assert_eq!(proto_hash(data), sp_core::blake2_256(data));
+    assert_eq!(proto_hash(&[]), sp_core::blake2_256(&[]));
+  }
+
+  #[cfg(feature = "hashing")]
+  #[test]
+  fn verify_data() {
+    let data = b"Hello Fragnova".to_vec();
+    let hash = proto_hash(&data);
+    assert!(verify(&data, &hash));
+
+    let mut corrupted = data;
+    corrupted[0] ^= 1;
+    assert!(!verify(&corrupted, &hash));
+  }
+
+  #[test]
+  fn twox_64_matches_chain() {
+    let data: Vec<u8> = (0..=255u8).cycle().take(1000).collect();
+    // cover every tail length and the 32 bytes stripes
+    for len in 0..100 {
+      assert_eq!(twox_64(&data[..len]), sp_core::twox_64(&data[..len]));
+    }
+    assert_eq!(twox_64(&data), sp_core::twox_64(&data));
+
+    const HASH: [u8; 8] = twox_64(b"Hello Fragnova");
+    assert_eq!(HASH, sp_core::twox_64(b"Hello Fragnova"));
+  }
+
+  #[test]
+  fn incremental_xxh64() {
+    let data: Vec<u8> = (0..=255u8).cycle().take(200).collect();
+    for len in [0, 5, 31, 32, 33, 64, 100, 200] {
+      for chunk in [1, 3, 7, 32, 50] {
+        let mut stream = XxHash64::new(7);
+        data[..len]
+          .chunks(chunk)
+          .for_each(|piece| stream.update(piece));
+        assert_eq!(stream.finish(), xxh64(&data[..len], 7), "{} {}", len, chunk);
+      }
+    }
+  }
+
+  #[test]
+  fn trait_hashers() {
+    let data = b"Hello Fragnova";
+    assert_eq!(Xx64.hash_trait(data), twox_64(data));
+    assert_eq!(HashScheme::default().hash_trait(data), twox_64(data));
+
+    struct Wide;
+    impl TraitHasher for Wide {
+      type Output = [u8; 16];
+
+      fn hash_trait(&self, canonical: &[u8]) -> [u8; 16] {
+        let mut hash = [0; 16];
+        hash[..8].copy_from_slice(&twox_64(canonical));
+        hash[8..].copy_from_slice(&xxh64(canonical, 1).to_le_bytes());
+        hash
+      }
+    }
+    let trait1 = crate::traits::Trait {
+      name: "Empty".to_string(),
+      records: vec![],
+    };
+    assert_eq!(trait1.hash_with(&Xx64), trait1.shards_hash());
+    let wide = trait1.hash_with(&Wide);
+    assert_eq!(wide.len(), 16);
+    assert_eq!(wide[..8], trait1.shards_hash());
+  }
+}
@@ -0,0 +1,48 @@
+//! Readable formatting of raw bytes and hashes.
+
+use core::fmt;
+
+/// Formats bytes as a `0x` prefixed lowercase hex string, in `Debug` and `Display`.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct HexBytes<'a>(pub &'a [u8]);
+
+impl fmt::Display for HexBytes<'_> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str("0x")?;
+    self.0.iter().try_for_each(|byte| write!(f, "{:02x}", byte))
+  }
+}
+
+impl fmt::Debug for HexBytes<'_> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fmt::Display::fmt(self, f)
+  }
+}
+
+/// Formats a list of byte strings (e.g. hashes) as a `Debug` list of [`HexBytes`].
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct HexList<'a, T>(pub &'a [T]);
+
+impl<T: AsRef<[u8]>> fmt::Debug for HexList<'_, T> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_list()
+      .entries(self.0.iter().map(|bytes| HexBytes(bytes.as_ref())))
+      .finish()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn format() {
+    assert_eq!(HexBytes(&[0, 0xab, 16]).to_string(), "0x00ab10");
+    assert_eq!(format!("{:?}", HexBytes(&[])), "0x");
+    assert_eq!(format!("{:?}", Some(HexBytes(&[1, 2]))), "Some(0x0102)");
+    assert_eq!(
+      format!("{:?}", HexList(&[[1u8, 2], [3, 4]])),
+      "[0x0102, 0x0304]"
+    );
+  }
+}
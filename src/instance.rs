@@ -0,0 +1,618 @@
+use crate::{
+  categories::ShardsTrait,
+  limits::DecodeLimits,
+  traits::{Limits, Record, Trait, VariableType},
+  values::{check_any_of, decode_typed, float_within, Value, ValueError},
+};
+use parity_scale_codec::{Decode, Encode, Input};
+use scale_info::prelude::{collections::BTreeMap, string::String, vec::Vec};
+
+#[cfg(feature = "std")]
+use crate::hex::HexBytes;
+
+/// The values of the records of a trait, the runtime counterpart of a `Trait`.
+///
+/// Values are keyed by canonical record name and encoded in canonical record order. Decoded
+/// instances are not validated, use [`TraitInstance::validate`] against the trait.
+#[derive(Encode, Decode, Clone, PartialEq, Debug)]
+pub struct TraitInstance {
+  trait_hash: ShardsTrait,
+  values: BTreeMap<String, Value>,
+}
+
+/// Errors returned when values do not match the trait of an instance.
+#[derive(Clone, PartialEq, Debug, Eq)]
+pub enum InstanceError {
+  /// The instance was created for another trait
+  TraitMismatch,
+  /// A value is keyed by a name that is not a record of the trait
+  UnknownRecord(String),
+  /// A record that is not optional (`None` type) and has no default has no value
+  MissingRecord(String),
+  /// A value does not match any of the types of its record
+  InvalidValue { record: String, error: ValueError },
+  /// The example value of a record allocates more than a runtime decoder allows
+  TooLarge(String),
+}
+
+impl core::fmt::Display for InstanceError {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    match self {
+      Self::TraitMismatch => write!(f, "instance of another trait"),
+      Self::UnknownRecord(name) => write!(f, "unknown record {:?}", name),
+      Self::MissingRecord(name) => write!(f, "missing value for record {:?}", name),
+      Self::InvalidValue { record, error } => {
+        write!(f, "invalid value for record {:?}: {}", record, error)
+      }
+      Self::TooLarge(name) => write!(f, "example value of record {:?} is too large", name),
+    }
+  }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InstanceError {}
+
+/// Errors returned by `TraitInstance::decode_payload`.
+#[derive(Clone, PartialEq, Debug, Eq)]
+pub enum PayloadError {
+  /// The payload is not a valid encoding for the trait
+  Codec(parity_scale_codec::Error),
+  /// The decoded values do not match the trait
+  Invalid(InstanceError),
+}
+
+impl core::fmt::Display for PayloadError {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    match self {
+      Self::Codec(e) => write!(f, "invalid payload: {}", e),
+      Self::Invalid(e) => write!(f, "{}", e),
+    }
+  }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PayloadError {}
+
+impl TraitInstance {
+  /// Creates an instance of `trait_`, checking every value against the types of its record.
+  pub fn new(trait_: &Trait, values: BTreeMap<String, Value>) -> Result<Self, InstanceError> {
+    let instance = Self {
+      trait_hash: trait_.shards_hash(),
+      values,
+    };
+    instance.validate(trait_)?;
+    Ok(instance)
+  }
+
+  /// Checks that the instance is an instance of `trait_` and that its values match their records.
+  pub fn validate(&self, trait_: &Trait) -> Result<(), InstanceError> {
+    if trait_.shards_hash() != self.trait_hash {
+      return Err(InstanceError::TraitMismatch);
+    }
+    let mut canonical = trait_.clone();
+    canonical.canonicalize();
+
+    if let Some(name) = self
+      .values
+      .keys()
+      .find(|name| !canonical.records.iter().any(|r| r.name == **name))
+    {
+      return Err(InstanceError::UnknownRecord(name.clone()));
+    }
+
+    for record in &canonical.records {
+      let types: Vec<_> = record.types.iter().map(|info| info.type_.clone()).collect();
+      match self.values.get(&record.name) {
+        Some(value) => {
+          check_any_of(value, &types).map_err(|error| InstanceError::InvalidValue {
+            record: record.name.clone(),
+            error,
+          })?
+        }
+        None => {
+          let optional = record
+            .types
+            .iter()
+            .any(|info| info.default.is_some() || info.type_ == VariableType::None);
+          if !optional {
+            return Err(InstanceError::MissingRecord(record.name.clone()));
+          }
+        }
+      }
+    }
+    Ok(())
+  }
+
+  /// Encodes only the values, in the canonical record order of `trait_`.
+  ///
+  /// Every record is prefixed by a byte: `0` when it has no value, otherwise `1 + ` the index of
+  /// the record type the value is encoded as. Holders of the trait decode it with
+  /// [`TraitInstance::decode_payload`].
+  pub fn encode_payload(&self, trait_: &Trait) -> Result<Vec<u8>, InstanceError> {
+    self.validate(trait_)?;
+    let mut canonical = trait_.clone();
+    canonical.canonicalize();
+
+    let mut payload = Vec::new();
+    for record in &canonical.records {
+      let Some(value) = self.values.get(&record.name) else {
+        payload.push(0);
+        continue;
+      };
+      // validated, so some type matches
+      let index = record
+        .types
+        .iter()
+        .position(|info| value.check(&info.type_).is_ok())
+        .unwrap_or_default();
+      payload.push(index as u8 + 1);
+      value
+        .encode_typed_to(&record.types[index].type_, &mut payload)
+        .map_err(|error| InstanceError::InvalidValue {
+          record: record.name.clone(),
+          error,
+        })?;
+    }
+    Ok(payload)
+  }
+
+  /// Decodes a payload produced by [`TraitInstance::encode_payload`] and validates it.
+  pub fn decode_payload(trait_: &Trait, payload: &[u8]) -> Result<Self, PayloadError> {
+    let mut canonical = trait_.clone();
+    canonical.canonicalize();
+
+    let mut input = payload;
+    let mut values = BTreeMap::new();
+    for record in &canonical.records {
+      let tag = input.read_byte().map_err(PayloadError::Codec)?;
+      let Some(index) = (tag as usize).checked_sub(1) else {
+        continue;
+      };
+      let info = record
+        .types
+        .get(index)
+        .ok_or(PayloadError::Codec("Invalid record type index".into()))?;
+      let value = decode_typed(&info.type_, &mut input, 0).map_err(PayloadError::Codec)?;
+      values.insert(record.name.clone(), value);
+    }
+    if !input.is_empty() {
+      return Err(PayloadError::Codec("Trailing payload bytes".into()));
+    }
+    Self::new(trait_, values).map_err(PayloadError::Invalid)
+  }
+
+  pub fn trait_hash(&self) -> &ShardsTrait {
+    &self.trait_hash
+  }
+
+  /// The values, in canonical record order.
+  pub fn values(&self) -> &BTreeMap<String, Value> {
+    &self.values
+  }
+
+  pub fn get(&self, record: &String) -> Option<&Value> {
+    self.values.get(record)
+  }
+
+  /// The value of `record` converted to `T`, `None` if there is no value or it is of another type.
+  pub fn get_typed<T>(&self, record: &String) -> Option<T>
+  where
+    T: for<'a> TryFrom<&'a Value>,
+  {
+    self.get(record).and_then(|v| T::try_from(v).ok())
+  }
+}
+
+/// The integer closest to 0 within `limits`.
+fn example_int(limits: &Option<Limits>) -> i64 {
+  limits.as_ref().map_or(0, |l| 0.max(l.min).min(l.max))
+}
+
+/// The float closest to 0 within `limits`.
+fn example_float(limits: &Option<Limits>) -> f64 {
+  limits.as_ref().map_or(0.0, |l| {
+    let divisor = (0..l.scale).fold(1f64, |d, _| d * 10.0);
+    0f64.max(l.min as f64 / divisor).min(l.max as f64 / divisor)
+  })
+}
+
+/// `example_float` as a 32-bit lane, moved inside the limits when the bound is not representable.
+fn example_f32(limits: &Option<Limits>) -> f32 {
+  let value = example_float(limits) as f32;
+  match limits {
+    Some(l) if !float_within(limits, value.into()) => {
+      let divisor = (0..l.scale).fold(1f64, |d, _| d * 10.0);
+      ((l.min as f64 + l.max as f64) / 2.0 / divisor) as f32
+    }
+    _ => value,
+  }
+}
+
+/// `example_int` as a narrower lane, out of range values are left to the type check to report.
+fn example_lane<T: TryFrom<i64> + Default>(limits: &Option<Limits>) -> T {
+  T::try_from(example_int(limits)).unwrap_or_default()
+}
+
+/// The smallest value of type `ty`: zeros, empty strings and the shortest sequences its limits
+/// allow.
+///
+/// `budget` is the amount of bytes the sequences of the value may still allocate, `None` is
+/// returned once it is exhausted instead of building values no runtime could decode.
+fn example_value(ty: &VariableType, budget: &mut u32) -> Option<Value> {
+  Some(match ty {
+    VariableType::Any
+    | VariableType::None
+    | VariableType::Channel(_)
+    | VariableType::Event(_)
+    | VariableType::ChannelV2(_)
+    | VariableType::EventV2(_) => Value::None,
+    VariableType::Bool => Value::Bool(false),
+    VariableType::Color => Value::Color([0; 4]),
+    VariableType::Bytes(_) | VariableType::Image | VariableType::Audio | VariableType::Mesh => {
+      Value::Bytes(Vec::new())
+    }
+    VariableType::String(_) => Value::String(String::new()),
+    VariableType::Enum { .. } => Value::Enum(0),
+    VariableType::Int(limits) => Value::Int(example_int(limits)),
+    VariableType::Int2(limits) => Value::Int2(limits.to_lanes().map(|l| example_int(&l))),
+    VariableType::Int3(limits) => Value::Int3(limits.to_lanes().map(|l| example_lane(&l))),
+    VariableType::Int4(limits) => Value::Int4(limits.to_lanes().map(|l| example_lane(&l))),
+    VariableType::Int8(limits) => Value::Int8(limits.to_lanes().map(|l| example_lane(&l))),
+    VariableType::Int16(limits) => Value::Int16(limits.to_lanes().map(|l| example_lane(&l))),
+    VariableType::Float(limits) => Value::Float(example_float(limits)),
+    VariableType::Float2(limits) => Value::Float2(limits.to_lanes().map(|l| example_float(&l))),
+    VariableType::Float3(limits) => Value::Float3(limits.to_lanes().map(|l| example_f32(&l))),
+    VariableType::Float4(limits) => Value::Float4(limits.to_lanes().map(|l| example_f32(&l))),
+    VariableType::Seq {
+      types,
+      length_limits,
+    } => {
+      // sequences longer than any decodable one are left to the type check to report
+      let len = example_int(length_limits)
+        .clamp(0, DecodeLimits::RUNTIME_SAFE.max_collection_len as i64 + 1) as u32;
+      let bytes = len.checked_mul(core::mem::size_of::<Value>() as u32)?;
+      *budget = budget.checked_sub(bytes)?;
+      let item = types.first();
+      Value::Seq(
+        (0..len)
+          .map(|_| item.map_or(Some(Value::None), |ty| example_value(ty, budget)))
+          .collect::<Option<_>>()?,
+      )
+    }
+    VariableType::Table(_) | VariableType::TableV2(_) => Value::Table(BTreeMap::new()),
+    VariableType::Object { .. } => Value::Object(Vec::new()),
+    VariableType::Code(_) => Value::Code(Vec::new()),
+  })
+}
+
+/// `example_value` within the allocations allowed by [`DecodeLimits::RUNTIME_SAFE`].
+fn example_record_value(record: &str, ty: &VariableType) -> Result<Value, InstanceError> {
+  let mut budget = DecodeLimits::RUNTIME_SAFE.max_alloc_bytes;
+  example_value(ty, &mut budget).ok_or_else(|| InstanceError::TooLarge(record.into()))
+}
+
+/// The decoded default of the first type of `record` with a valid default.
+fn record_default(record: &Record) -> Option<Value> {
+  record.types.iter().find_map(|info| {
+    let mut input = &info.default.as_ref()?[..];
+    let value = Value::decode_typed(&info.type_, &mut input).ok()?;
+    (input.is_empty() && value.check(&info.type_).is_ok()).then_some(value)
+  })
+}
+
+impl Trait {
+  /// A valid instance of the trait with a value for every record: its default when it has one,
+  /// otherwise the smallest value of its first type (see [`TraitInstance::validate`]).
+  ///
+  /// Fails when a record has no value within its limits, e.g. an `Int` with `min > max`, or when
+  /// its smallest value allocates more than [`DecodeLimits::RUNTIME_SAFE`] allows.
+  pub fn example_instance(&self) -> Result<TraitInstance, InstanceError> {
+    let mut canonical = self.clone();
+    canonical.canonicalize();
+    let mut values = BTreeMap::new();
+    for record in &canonical.records {
+      let value = match (record_default(record), record.types.first()) {
+        (Some(value), _) => value,
+        (None, Some(info)) => example_record_value(&record.name, &info.type_)?,
+        (None, None) => continue,
+      };
+      values.insert(record.name.clone(), value);
+    }
+    TraitInstance::new(self, values)
+  }
+}
+
+/// Encode/decode test vectors of a trait, produced by [`conformance_vectors`].
+///
+/// Bytes are `0x` hex strings, so the vectors can be checked in as JSON by SDKs in any language.
+#[cfg(feature = "std")]
+#[derive(Clone, PartialEq, Debug, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConformanceVectors {
+  /// The canonical trait
+  #[serde(rename = "trait")]
+  pub trait_: Trait,
+  /// The SCALE encoding of the canonical trait
+  pub encoded_trait: String,
+  /// The `Trait::shards_hash` of the trait
+  pub trait_hash: String,
+  pub vectors: Vec<ConformanceVector>,
+}
+
+/// An instance of the trait of [`ConformanceVectors`] in each of its representations.
+#[cfg(feature = "std")]
+#[derive(Clone, PartialEq, Debug, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConformanceVector {
+  /// What the vector covers: `example`, `absent` or `<record>/<type index>`
+  pub name: String,
+  /// The values in their JSON form (see `Value::to_json`), keyed by canonical record name
+  pub values: serde_json::Value,
+  /// The payload, see [`TraitInstance::encode_payload`]
+  pub payload: String,
+  /// The SCALE encoding of the [`TraitInstance`], with self-describing values
+  pub encoded: String,
+}
+
+/// Test vectors of `trait_`, for implementations in other languages to check their encoding and
+/// decoding of instances against.
+///
+/// The vectors are the [`Trait::example_instance`], then the same instance with a record set to
+/// the smallest value of each of its other types, then without the values of optional records.
+/// Vectors with the same payload as an earlier one are skipped.
+#[cfg(feature = "std")]
+pub fn conformance_vectors(trait_: &Trait) -> Result<ConformanceVectors, InstanceError> {
+  let mut canonical = trait_.clone();
+  canonical.canonicalize();
+  let example = trait_.example_instance()?;
+
+  let mut instances = vec![(String::from("example"), example.clone())];
+  for record in &canonical.records {
+    for (index, info) in record.types.iter().enumerate() {
+      let mut values = example.values.clone();
+      values.insert(
+        record.name.clone(),
+        example_record_value(&record.name, &info.type_)?,
+      );
+      if let Ok(instance) = TraitInstance::new(trait_, values) {
+        instances.push((format!("{}/{}", record.name, index), instance));
+      }
+    }
+  }
+  let mut values = example.values.clone();
+  values.retain(|name, _| {
+    !canonical.records.iter().any(|record| {
+      record.name == *name
+        && record
+          .types
+          .iter()
+          .any(|info| info.default.is_some() || info.type_ == VariableType::None)
+    })
+  });
+  instances.push((String::from("absent"), TraitInstance::new(trait_, values)?));
+
+  let mut vectors: Vec<ConformanceVector> = Vec::new();
+  for (name, instance) in instances {
+    let payload = HexBytes(&instance.encode_payload(trait_)?).to_string();
+    if vectors.iter().any(|vector| vector.payload == payload) {
+      continue;
+    }
+    vectors.push(ConformanceVector {
+      name,
+      values: serde_json::Value::Object(
+        instance
+          .values
+          .iter()
+          .map(|(name, value)| (name.clone(), value.to_json()))
+          .collect(),
+      ),
+      payload,
+      encoded: HexBytes(&instance.encode()).to_string(),
+    });
+  }
+  Ok(ConformanceVectors {
+    encoded_trait: HexBytes(&canonical.encode()).to_string(),
+    trait_hash: HexBytes(&canonical.shards_hash()).to_string(),
+    trait_: canonical,
+    vectors,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::traits::{VariableTypeInfo, VectorLimits};
+
+  fn test_trait() -> Trait {
+    let info = |type_, default| VariableTypeInfo { type_, default };
+    Trait {
+      name: "Instance".to_string(),
+      records: vec![
+        Record::from((
+          "Health".to_string(),
+          vec![info(
+            VariableType::Int(Some(Limits {
+              min: 0,
+              max: 100,
+              scale: 0,
+            })),
+            None,
+          )],
+        )),
+        Record::from((
+          "name".to_string(),
+          vec![
+            info(VariableType::String(None), None),
+            info(VariableType::None, None),
+          ],
+        )),
+        Record::from((
+          "speed".to_string(),
+          vec![info(VariableType::Float(None), Some(vec![0; 8]))],
+        )),
+      ],
+    }
+  }
+
+  #[test]
+  fn validate_values() {
+    let trait1 = test_trait();
+    let mut values = BTreeMap::new();
+    values.insert("health".to_string(), Value::Int(80));
+
+    let instance = TraitInstance::new(&trait1, values.clone()).unwrap();
+    assert_eq!(instance.get_typed::<i64>(&"health".to_string()), Some(80));
+    assert_eq!(instance.get_typed::<bool>(&"health".to_string()), None);
+    assert_eq!(instance.trait_hash(), &trait1.shards_hash());
+
+    let encoded = instance.encode();
+    let decoded = TraitInstance::decode(&mut &encoded[..]).unwrap();
+    assert_eq!(decoded.validate(&trait1), Ok(()));
+
+    values.insert("health".to_string(), Value::Int(101));
+    assert_eq!(
+      TraitInstance::new(&trait1, values.clone()),
+      Err(InstanceError::InvalidValue {
+        record: "health".to_string(),
+        error: ValueError::OutOfLimits,
+      })
+    );
+    assert_eq!(
+      TraitInstance::new(&trait1, BTreeMap::new()),
+      Err(InstanceError::MissingRecord("health".to_string()))
+    );
+    values.insert("health".to_string(), Value::Int(1));
+    values.insert("mana".to_string(), Value::Int(1));
+    assert_eq!(
+      TraitInstance::new(&trait1, values),
+      Err(InstanceError::UnknownRecord("mana".to_string()))
+    );
+
+    let mut other = test_trait();
+    other.name = "Other".to_string();
+    assert_eq!(decoded.validate(&other), Err(InstanceError::TraitMismatch));
+  }
+
+  #[test]
+  fn payload() {
+    let trait1 = test_trait();
+    let mut values = BTreeMap::new();
+    values.insert("health".to_string(), Value::Int(80));
+    values.insert("name".to_string(), Value::None);
+    let instance = TraitInstance::new(&trait1, values).unwrap();
+
+    let payload = instance.encode_payload(&trait1).unwrap();
+    let mut expected = vec![1];
+    expected.extend(80i64.encode());
+    // `name` has its second type, `speed` has no value
+    expected.extend([2, 0]);
+    assert_eq!(payload, expected);
+    assert_eq!(
+      TraitInstance::decode_payload(&trait1, &payload).unwrap(),
+      instance
+    );
+
+    assert!(matches!(
+      TraitInstance::decode_payload(&trait1, &payload[..4]),
+      Err(PayloadError::Codec(_))
+    ));
+    assert!(matches!(
+      TraitInstance::decode_payload(&trait1, &[0, 0, 0]),
+      Err(PayloadError::Invalid(InstanceError::MissingRecord(_)))
+    ));
+  }
+
+  #[test]
+  fn example_instance() {
+    let trait1 = test_trait();
+    let instance = trait1.example_instance().unwrap();
+    assert_eq!(instance.get(&"health".to_string()), Some(&Value::Int(0)));
+    assert_eq!(
+      instance.get(&"name".to_string()),
+      Some(&Value::String(String::new()))
+    );
+    assert_eq!(instance.get(&"speed".to_string()), Some(&Value::Float(0.0)));
+
+    let mut limited = test_trait();
+    limited.records[0].types[0].type_ = VariableType::Seq {
+      types: vec![VariableType::Float3(VectorLimits::Shared(Limits {
+        min: 1,
+        max: 3,
+        scale: 1,
+      }))],
+      length_limits: Some(Limits {
+        min: 2,
+        max: 4,
+        scale: 0,
+      }),
+    };
+    let instance = limited.example_instance().unwrap();
+    assert_eq!(
+      instance.get(&"health".to_string()),
+      Some(&Value::Seq(vec![Value::Float3([0.1; 3]); 2]))
+    );
+
+    limited.records[0].types[0].type_ = VariableType::Int(Some(Limits {
+      min: 5,
+      max: 1,
+      scale: 0,
+    }));
+    assert!(matches!(
+      limited.example_instance(),
+      Err(InstanceError::InvalidValue { .. })
+    ));
+
+    // 1000^3 values if built, over the allocation limits
+    let seq = |type_| VariableType::Seq {
+      types: vec![type_],
+      length_limits: Some(Limits {
+        min: 1000,
+        max: 1000,
+        scale: 0,
+      }),
+    };
+    limited.records[0].types[0].type_ = seq(seq(seq(VariableType::Int(None))));
+    assert_eq!(
+      limited.example_instance(),
+      Err(InstanceError::TooLarge("health".to_string()))
+    );
+    assert_eq!(
+      conformance_vectors(&limited),
+      Err(InstanceError::TooLarge("health".to_string()))
+    );
+  }
+
+  #[test]
+  fn vectors() {
+    let vectors = conformance_vectors(&test_trait()).unwrap();
+    let names: Vec<_> = vectors.vectors.iter().map(|v| v.name.as_str()).collect();
+    assert_eq!(names, ["example", "name/1", "absent"]);
+    assert_eq!(
+      vectors.trait_hash,
+      HexBytes(&test_trait().shards_hash()).to_string()
+    );
+    assert_eq!(vectors.vectors[2].payload, "0x0100000000000000000000");
+    assert_eq!(
+      vectors.vectors[1].values,
+      serde_json::json!({"health": 0, "name": null, "speed": 0.0})
+    );
+
+    let bytes = |hex: &str| -> Vec<u8> {
+      (2..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+    };
+    for vector in &vectors.vectors {
+      let instance =
+        TraitInstance::decode_payload(&vectors.trait_, &bytes(&vector.payload)).unwrap();
+      assert_eq!(HexBytes(&instance.encode()).to_string(), vector.encoded);
+    }
+    let json = serde_json::to_string(&vectors).unwrap();
+    assert!(json.starts_with(r#"{"trait":{"name":"Instance""#));
+    assert_eq!(
+      serde_json::from_str::<ConformanceVectors>(&json).unwrap(),
+      vectors
+    );
+  }
+}
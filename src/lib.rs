@@ -1,5 +1,70 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
+pub mod accounts;
+pub mod acl;
+#[cfg(feature = "hashing")]
+pub mod assets;
+pub mod audience;
+pub mod batch;
+#[cfg(feature = "std")]
+pub mod bijection;
+pub mod bundles;
+pub mod capabilities;
 pub mod categories;
+#[cfg(feature = "hashing")]
+pub mod chunks;
+pub mod cid;
+pub mod codec_check;
+pub mod compat;
+pub mod compression;
+#[cfg(all(feature = "probe", feature = "registry"))]
+pub mod conformance;
+pub mod content;
+pub mod curation;
+pub mod detach;
+pub mod docs;
+pub mod dynamic;
+#[cfg(feature = "hashing")]
+pub mod encryption;
+pub mod error;
+pub mod events;
+#[cfg(all(feature = "hashing", feature = "registry"))]
+pub mod export;
+pub mod filter;
+pub mod footprint;
+pub mod fragments;
+pub mod graph;
+pub mod hashing;
+pub mod hex;
+#[cfg(feature = "values")]
+pub mod instance;
+pub mod limits;
+pub mod limits_wire;
+pub mod localization;
+pub mod names;
+pub mod pagination;
+pub mod path;
 pub mod permissions;
+pub mod policy;
+#[cfg(feature = "probe")]
+pub mod probe;
+pub mod proto;
+pub mod provenance;
+#[cfg(feature = "registry")]
+pub mod registry;
+pub mod rpc;
+pub mod sales;
+#[cfg(feature = "hashing")]
+pub mod script_lock;
+pub mod signing;
+pub mod standard;
+pub mod tables;
+#[cfg(feature = "testgen")]
+pub mod testgen;
+mod trace;
+pub mod trait_docs;
 pub mod traits;
+pub mod transfer;
+pub mod ui;
+#[cfg(feature = "values")]
+pub mod values;
@@ -0,0 +1,399 @@
+//! Hardening knobs applied when decoding untrusted SCALE data.
+
+use crate::{
+  categories::Categories,
+  dynamic::{self, DynError, DynValue},
+  trace,
+  trait_docs::TraitDocsBundle,
+  traits::{
+    CodeInfo, KeyPattern, Record, TableInfo, TableInfoV2, Trait, VariableType, VariableTypeInfo,
+  },
+};
+use parity_scale_codec::{Decode, Encode, Error, Input};
+use scale_info::PortableRegistry;
+
+#[cfg(feature = "values")]
+use crate::values::Value;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Limits enforced by [`DecodeLimits::decode`].
+///
+/// Depth and allocation are enforced while decoding, so malicious input fails before exhausting
+/// the stack or the memory, lengths are checked once decoded.
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct DecodeLimits {
+  /// Maximum nesting of boxes, collections and values
+  pub max_depth: u32,
+  /// Maximum amount of elements of any collection
+  pub max_collection_len: u32,
+  /// Maximum length in bytes of any string or byte blob
+  pub max_string_len: u32,
+  /// Maximum amount of memory allocated by the decoded collections
+  pub max_alloc_bytes: u32,
+}
+
+impl DecodeLimits {
+  /// Limits fit for on-chain decoding of user submitted data.
+  pub const RUNTIME_SAFE: Self = Self {
+    max_depth: 32,
+    max_collection_len: 1024,
+    max_string_len: 4096,
+    max_alloc_bytes: 1 << 20,
+  };
+
+  /// Limits for off-chain tools working with large, trusted data.
+  pub const TOOLING_RELAXED: Self = Self {
+    max_depth: 128,
+    max_collection_len: 1 << 20,
+    max_string_len: 16 << 20,
+    max_alloc_bytes: 256 << 20,
+  };
+
+  /// Decodes a `T` from the whole of `bytes` within these limits.
+  pub fn decode<T: Decode + CheckLimits>(&self, bytes: &[u8]) -> Result<T, Error> {
+    trace::span!(
+      "decode",
+      type_name = core::any::type_name::<T>(),
+      len = bytes.len()
+    );
+    trace::malformed((|| {
+      let mut input = LimitedInput::new(bytes, self);
+      let value = T::decode(&mut input)?;
+      input.finish()?;
+      value.check_limits(self)?;
+      Ok(value)
+    })())
+  }
+
+  /// Decodes a value of type `ty` (see [`Value::encode_typed`]) within these limits.
+  #[cfg(feature = "values")]
+  pub fn decode_value(&self, ty: &VariableType, bytes: &[u8]) -> Result<Value, Error> {
+    trace::span!(
+      "decode_value",
+      variant = ty.variant_index(),
+      len = bytes.len()
+    );
+    trace::malformed((|| {
+      let mut input = LimitedInput::new(bytes, self);
+      let value = crate::values::decode_typed(ty, &mut input, 0)?;
+      input.finish()?;
+      value.check_limits(self)?;
+      Ok(value)
+    })())
+  }
+
+  /// Decodes the whole of `bytes` as the type `type_id` of `registry`, see [`crate::dynamic`].
+  pub fn decode_dynamic(
+    &self,
+    registry: &PortableRegistry,
+    type_id: u32,
+    bytes: &[u8],
+  ) -> Result<DynValue, DynError> {
+    trace::span!("decode_dynamic", type_id, len = bytes.len());
+    trace::malformed((|| {
+      let mut input = LimitedInput::new(bytes, self);
+      let value = dynamic::decode(registry, type_id, &mut input)?;
+      input.finish()?;
+      Ok(value)
+    })())
+  }
+
+  fn collection(&self, len: usize) -> Result<(), Error> {
+    if len > self.max_collection_len as usize {
+      return Err("Collection exceeds the decode limits".into());
+    }
+    Ok(())
+  }
+
+  fn string(&self, len: usize) -> Result<(), Error> {
+    if len > self.max_string_len as usize {
+      return Err("String exceeds the decode limits".into());
+    }
+    Ok(())
+  }
+}
+
+impl Default for DecodeLimits {
+  fn default() -> Self {
+    Self::RUNTIME_SAFE
+  }
+}
+
+/// An input tracking nesting depth and allocations against `DecodeLimits`.
+struct LimitedInput<'a> {
+  input: &'a [u8],
+  limits: &'a DecodeLimits,
+  depth: u32,
+  allocated: usize,
+}
+
+impl<'a> LimitedInput<'a> {
+  fn new(input: &'a [u8], limits: &'a DecodeLimits) -> Self {
+    Self {
+      input,
+      limits,
+      depth: 0,
+      allocated: 0,
+    }
+  }
+
+  fn finish(&self) -> Result<(), Error> {
+    if !self.input.is_empty() {
+      return Err("Trailing bytes after the decoded data".into());
+    }
+    Ok(())
+  }
+}
+
+impl Input for LimitedInput<'_> {
+  fn remaining_len(&mut self) -> Result<Option<usize>, Error> {
+    self.input.remaining_len()
+  }
+
+  fn read(&mut self, into: &mut [u8]) -> Result<(), Error> {
+    self.input.read(into)
+  }
+
+  fn descend_ref(&mut self) -> Result<(), Error> {
+    self.depth += 1;
+    if self.depth > self.limits.max_depth {
+      return Err("Data nested deeper than the decode limits".into());
+    }
+    Ok(())
+  }
+
+  fn ascend_ref(&mut self) {
+    self.depth = self.depth.saturating_sub(1);
+  }
+
+  fn on_before_alloc_mem(&mut self, size: usize) -> Result<(), Error> {
+    self.allocated = self.allocated.saturating_add(size);
+    if self.allocated > self.limits.max_alloc_bytes as usize {
+      return Err("Data allocates more than the decode limits".into());
+    }
+    Ok(())
+  }
+}
+
+/// Types whose collection and string lengths can be checked against `DecodeLimits`.
+pub trait CheckLimits {
+  fn check_limits(&self, limits: &DecodeLimits) -> Result<(), Error>;
+}
+
+impl<T: CheckLimits> CheckLimits for [T] {
+  fn check_limits(&self, limits: &DecodeLimits) -> Result<(), Error> {
+    limits.collection(self.len())?;
+    self.iter().try_for_each(|item| item.check_limits(limits))
+  }
+}
+
+impl CheckLimits for VariableType {
+  fn check_limits(&self, limits: &DecodeLimits) -> Result<(), Error> {
+    match self {
+      VariableType::Seq { types, .. } => types.check_limits(limits),
+      VariableType::Table(table) => table.check_limits(limits),
+      VariableType::TableV2(table) => table.check_limits(limits),
+      VariableType::Code(code) => code.check_limits(limits),
+      other => other
+        .carried()
+        .map_or(Ok(()), |inner| inner.check_limits(limits)),
+    }
+  }
+}
+
+impl CheckLimits for TableInfo {
+  fn check_limits(&self, limits: &DecodeLimits) -> Result<(), Error> {
+    limits.collection(self.keys.len())?;
+    self
+      .keys
+      .iter()
+      .try_for_each(|key| limits.string(key.len()))?;
+    limits.collection(self.types.len())?;
+    self.types.iter().try_for_each(|t| t.check_limits(limits))
+  }
+}
+
+impl CheckLimits for TableInfoV2 {
+  fn check_limits(&self, limits: &DecodeLimits) -> Result<(), Error> {
+    limits.collection(self.keys.len())?;
+    self.keys.iter().try_for_each(|key| match key {
+      KeyPattern::Exact(key) | KeyPattern::Prefix(key) => limits.string(key.len()),
+      KeyPattern::Any => Ok(()),
+    })?;
+    limits.collection(self.types.len())?;
+    self.types.iter().try_for_each(|t| t.check_limits(limits))
+  }
+}
+
+impl CheckLimits for CodeInfo {
+  fn check_limits(&self, limits: &DecodeLimits) -> Result<(), Error> {
+    for vars in [&self.requires, &self.exposes] {
+      limits.collection(vars.len())?;
+      for (name, ty) in vars {
+        limits.string(name.len())?;
+        ty.check_limits(limits)?;
+      }
+    }
+    self.inputs.check_limits(limits)?;
+    self.output.check_limits(limits)
+  }
+}
+
+impl CheckLimits for VariableTypeInfo {
+  fn check_limits(&self, limits: &DecodeLimits) -> Result<(), Error> {
+    if let Some(default) = &self.default {
+      limits.string(default.len())?;
+    }
+    self.type_.check_limits(limits)
+  }
+}
+
+impl CheckLimits for Record {
+  fn check_limits(&self, limits: &DecodeLimits) -> Result<(), Error> {
+    limits.string(self.name.len())?;
+    self.types.check_limits(limits)
+  }
+}
+
+impl CheckLimits for Trait {
+  fn check_limits(&self, limits: &DecodeLimits) -> Result<(), Error> {
+    limits.string(self.name.len())?;
+    self.records.check_limits(limits)
+  }
+}
+
+impl CheckLimits for TraitDocsBundle {
+  fn check_limits(&self, limits: &DecodeLimits) -> Result<(), Error> {
+    limits.string(self.markdown.len())?;
+    limits.collection(self.examples.len())?;
+    self
+      .examples
+      .iter()
+      .try_for_each(|example| limits.string(example.len()))
+  }
+}
+
+impl CheckLimits for Categories {
+  fn check_limits(&self, limits: &DecodeLimits) -> Result<(), Error> {
+    if let Categories::Shards(info) = self {
+      limits.collection(info.requiring.len())?;
+      limits.collection(info.implementing.len())?;
+    }
+    Ok(())
+  }
+}
+
+#[cfg(feature = "values")]
+impl CheckLimits for Value {
+  fn check_limits(&self, limits: &DecodeLimits) -> Result<(), Error> {
+    match self {
+      Value::Bytes(b) | Value::Object(b) | Value::Code(b) => limits.string(b.len()),
+      Value::String(s) => limits.string(s.len()),
+      Value::Seq(values) => values.check_limits(limits),
+      Value::Table(values) => {
+        limits.collection(values.len())?;
+        values.iter().try_for_each(|(k, v)| {
+          limits.string(k.len())?;
+          v.check_limits(limits)
+        })
+      }
+      _ => Ok(()),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn nested(depth: usize) -> VariableType {
+    (0..depth).fold(VariableType::Bool, |inner, _| {
+      VariableType::Channel(Box::new(inner))
+    })
+  }
+
+  #[test]
+  fn depth() {
+    let limits = DecodeLimits {
+      max_depth: 8,
+      ..DecodeLimits::RUNTIME_SAFE
+    };
+    assert_eq!(
+      limits.decode::<VariableType>(&nested(8).encode()),
+      Ok(nested(8))
+    );
+    assert!(limits.decode::<VariableType>(&nested(9).encode()).is_err());
+    assert!(DecodeLimits::TOOLING_RELAXED
+      .decode::<VariableType>(&nested(9).encode())
+      .is_ok());
+
+    #[cfg(feature = "values")]
+    {
+      let value = (0..9).fold(Value::None, |inner, _| Value::Seq(vec![inner]));
+      assert!(limits
+        .decode_value(&VariableType::Any, &value.encode())
+        .is_err());
+    }
+
+    let (registry, id) = crate::dynamic::registry_for::<VariableType>();
+    assert!(limits
+      .decode_dynamic(&registry, id, &nested(7).encode())
+      .is_ok());
+    assert!(limits
+      .decode_dynamic(&registry, id, &nested(8).encode())
+      .is_err());
+  }
+
+  #[test]
+  fn lengths() {
+    let limits = DecodeLimits {
+      max_collection_len: 2,
+      max_string_len: 4,
+      ..DecodeLimits::default()
+    };
+    let trait1 = Trait {
+      name: "Long".to_string(),
+      records: vec![Record::from(("name".to_string(), vec![]))],
+    };
+    assert_eq!(limits.decode::<Trait>(&trait1.encode()), Ok(trait1.clone()));
+
+    let long_name = Trait {
+      name: "Longer".to_string(),
+      ..trait1.clone()
+    };
+    assert!(limits.decode::<Trait>(&long_name.encode()).is_err());
+
+    let seq = VariableType::Seq {
+      types: vec![VariableType::Bool; 3],
+      length_limits: None,
+    };
+    assert!(limits.decode::<VariableType>(&seq.encode()).is_err());
+
+    let mut trailing = trait1.encode();
+    trailing.push(0);
+    assert!(limits.decode::<Trait>(&trailing).is_err());
+  }
+
+  #[test]
+  fn allocation() {
+    let limits = DecodeLimits {
+      max_alloc_bytes: 64,
+      ..DecodeLimits::TOOLING_RELAXED
+    };
+    assert!(limits.decode::<Trait>(&Trait::default().encode()).is_ok());
+    #[cfg(feature = "values")]
+    {
+      let bytes = Value::Bytes(vec![0; 65]);
+      assert!(limits
+        .decode_value(&VariableType::Bytes(None), &bytes.encode()[1..])
+        .is_err());
+    }
+  }
+}
@@ -0,0 +1,226 @@
+//! The wire form of the bounds of `Limits`.
+//!
+//! SCALE only has unsigned compact integers, so the signed bounds are mapped to `u64` before being
+//! compact encoded. `Limits` itself always uses the v1 mapping, [`encode_i64`]; `VersionedLimits`
+//! tags the limits with the mapping used, allowing the v2 [`zigzag_encode`] mapping which keeps
+//! small negative bounds small on the wire.
+
+use crate::traits::Limits;
+use parity_scale_codec::{Compact, Decode, Encode, Input, Output};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The v1 mapping: offset binary, flipping the sign bit.
+///
+/// Order preserving: `i64::MIN` maps to `0`, `0` to `2^63` and `i64::MAX` to `u64::MAX`. Every
+/// bound takes at least 8 bytes once compact encoded, except the most negative ones.
+pub const fn encode_i64(x: i64) -> u64 {
+  (x as u64) ^ (1 << 63)
+}
+
+/// The inverse of [`encode_i64`].
+pub const fn decode_i64(x: u64) -> i64 {
+  (x ^ (1 << 63)) as i64
+}
+
+/// The v2 mapping: zigzag, interleaving negative and positive values.
+///
+/// `0, -1, 1, -2, 2, ..` map to `0, 1, 2, 3, 4, ..`, so bounds close to zero stay small once compact
+/// encoded. `i64::MAX` maps to `u64::MAX - 1` and `i64::MIN` to `u64::MAX`.
+pub const fn zigzag_encode(x: i64) -> u64 {
+  ((x << 1) ^ (x >> 63)) as u64
+}
+
+/// The inverse of [`zigzag_encode`].
+pub const fn zigzag_decode(x: u64) -> i64 {
+  ((x >> 1) as i64) ^ -((x & 1) as i64)
+}
+
+/// The mapping used for the bounds of `VersionedLimits`, encoded as its version number.
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub enum LimitsEncoding {
+  /// [`encode_i64`], the encoding of plain `Limits`
+  #[codec(index = 1)]
+  V1,
+  /// [`zigzag_encode`]
+  #[codec(index = 2)]
+  V2,
+}
+
+impl LimitsEncoding {
+  /// Maps `x` to its wire form.
+  pub const fn to_wire(self, x: i64) -> u64 {
+    match self {
+      Self::V1 => encode_i64(x),
+      Self::V2 => zigzag_encode(x),
+    }
+  }
+
+  /// Maps the wire form `x` back to the bound.
+  pub const fn from_wire(self, x: u64) -> i64 {
+    match self {
+      Self::V1 => decode_i64(x),
+      Self::V2 => zigzag_decode(x),
+    }
+  }
+}
+
+/// `Limits` prefixed by the version of the mapping of their bounds.
+#[derive(Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct VersionedLimits {
+  pub encoding: LimitsEncoding,
+  pub limits: Limits,
+}
+
+impl VersionedLimits {
+  /// `limits` with the encoding giving the shortest wire form, v1 when both are as short.
+  pub fn compact(limits: Limits) -> Self {
+    let with = |encoding| Self {
+      encoding,
+      limits: limits.clone(),
+    };
+    let (v1, v2) = (with(LimitsEncoding::V1), with(LimitsEncoding::V2));
+    if v2.encoded_size() < v1.encoded_size() {
+      v2
+    } else {
+      v1
+    }
+  }
+}
+
+impl From<VersionedLimits> for Limits {
+  fn from(versioned: VersionedLimits) -> Self {
+    versioned.limits
+  }
+}
+
+impl Encode for VersionedLimits {
+  fn encode_to<W: Output + ?Sized>(&self, dest: &mut W) {
+    self.encoding.encode_to(dest);
+    Compact(self.encoding.to_wire(self.limits.min)).encode_to(dest);
+    Compact(self.encoding.to_wire(self.limits.max)).encode_to(dest);
+    Compact(self.limits.scale).encode_to(dest);
+  }
+}
+
+impl Decode for VersionedLimits {
+  fn decode<I: Input>(input: &mut I) -> Result<Self, parity_scale_codec::Error> {
+    let encoding = LimitsEncoding::decode(input)?;
+    Ok(Self {
+      encoding,
+      limits: Limits {
+        min: encoding.from_wire(Compact::<u64>::decode(input)?.into()),
+        max: encoding.from_wire(Compact::<u64>::decode(input)?.into()),
+        scale: Compact::<u32>::decode(input)?.into(),
+      },
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const EDGES: [i64; 9] = [
+    i64::MIN,
+    i64::MIN + 1,
+    -(1 << 32),
+    -1,
+    0,
+    1,
+    1 << 32,
+    i64::MAX - 1,
+    i64::MAX,
+  ];
+
+  #[test]
+  fn offset_binary() {
+    assert_eq!(encode_i64(i64::MIN), 0);
+    assert_eq!(encode_i64(-1), (1 << 63) - 1);
+    assert_eq!(encode_i64(0), 1 << 63);
+    assert_eq!(encode_i64(i64::MAX), u64::MAX);
+    for x in EDGES {
+      assert_eq!(decode_i64(encode_i64(x)), x);
+    }
+    // order preserving
+    assert!(EDGES
+      .windows(2)
+      .all(|w| encode_i64(w[0]) < encode_i64(w[1])));
+    assert_eq!(decode_i64(0), i64::MIN);
+    assert_eq!(decode_i64(u64::MAX), i64::MAX);
+  }
+
+  #[test]
+  fn zigzag() {
+    assert_eq!(zigzag_encode(0), 0);
+    assert_eq!(zigzag_encode(-1), 1);
+    assert_eq!(zigzag_encode(1), 2);
+    assert_eq!(zigzag_encode(-2), 3);
+    assert_eq!(zigzag_encode(i64::MAX), u64::MAX - 1);
+    assert_eq!(zigzag_encode(i64::MIN), u64::MAX);
+    for x in EDGES {
+      assert_eq!(zigzag_decode(zigzag_encode(x)), x);
+    }
+    for x in [0, 1, u64::MAX - 1, u64::MAX] {
+      assert_eq!(zigzag_encode(zigzag_decode(x)), x);
+    }
+  }
+
+  #[test]
+  fn versioned_limits() {
+    let small = Limits {
+      min: -10,
+      max: 10,
+      scale: 0,
+    };
+    // plain limits keep the v1 wire form
+    assert_eq!(
+      small.encode(),
+      (
+        Compact(encode_i64(-10)),
+        Compact(encode_i64(10)),
+        Compact(0u32)
+      )
+        .encode()
+    );
+
+    let versioned = VersionedLimits::compact(small.clone());
+    assert_eq!(versioned.encoding, LimitsEncoding::V2);
+    assert_eq!(versioned.encode(), [2, 19 << 2, 20 << 2, 0]);
+    assert!(versioned.encoded_size() < small.encoded_size());
+
+    let wide = Limits {
+      min: i64::MIN,
+      max: i64::MAX,
+      scale: 2,
+    };
+    assert_eq!(
+      VersionedLimits::compact(wide.clone()).encoding,
+      LimitsEncoding::V1
+    );
+
+    for limits in [small, wide] {
+      for encoding in [LimitsEncoding::V1, LimitsEncoding::V2] {
+        let versioned = VersionedLimits {
+          encoding,
+          limits: limits.clone(),
+        };
+        let decoded = VersionedLimits::decode(&mut &versioned.encode()[..]).unwrap();
+        assert_eq!(decoded, versioned);
+        assert_eq!(Limits::from(decoded), limits);
+      }
+    }
+    assert!(VersionedLimits::decode(&mut &[3, 0, 0, 0][..]).is_err());
+  }
+}
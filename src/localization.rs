@@ -0,0 +1,139 @@
+use crate::traits::Trait;
+use parity_scale_codec::{Decode, Encode};
+use scale_info::prelude::{collections::BTreeMap, string::String};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Localized labels and descriptions for the records of a trait.
+///
+/// Localizations are companions of a trait and are not part of its hash, so they can be added or
+/// fixed without changing the trait interface.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct TraitLocalization {
+  /// BCP 47 language tag, e.g. `en-US`
+  pub locale: String,
+  /// Display names keyed by record name
+  pub record_labels: BTreeMap<String, String>,
+  /// Descriptions keyed by record name
+  pub descriptions: BTreeMap<String, String>,
+}
+
+/// Errors returned by `TraitLocalization` validation and merging.
+#[derive(Clone, PartialEq, Debug, Eq)]
+pub enum LocalizationError {
+  /// A key does not name a record of the trait
+  UnknownRecord(String),
+  /// Localizations of different locales cannot be merged
+  LocaleMismatch,
+}
+
+impl core::fmt::Display for LocalizationError {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    match self {
+      Self::UnknownRecord(name) => write!(f, "unknown record {:?}", name),
+      Self::LocaleMismatch => write!(f, "localizations have different locales"),
+    }
+  }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for LocalizationError {}
+
+impl TraitLocalization {
+  pub fn new(locale: String) -> Self {
+    Self {
+      locale,
+      record_labels: BTreeMap::new(),
+      descriptions: BTreeMap::new(),
+    }
+  }
+
+  pub fn label(&self, record: &String) -> Option<&String> {
+    self.record_labels.get(record)
+  }
+
+  pub fn description(&self, record: &String) -> Option<&String> {
+    self.descriptions.get(record)
+  }
+
+  /// Checks that every key names a record of `trait_`.
+  pub fn validate(&self, trait_: &Trait) -> Result<(), LocalizationError> {
+    let unknown = self
+      .record_labels
+      .keys()
+      .chain(self.descriptions.keys())
+      .find(|key| !trait_.records.iter().any(|record| record.name == **key));
+    match unknown {
+      Some(key) => Err(LocalizationError::UnknownRecord(key.clone())),
+      None => Ok(()),
+    }
+  }
+
+  /// Merges `other` into this localization, entries of `other` replacing existing ones.
+  pub fn merge(&mut self, other: TraitLocalization) -> Result<(), LocalizationError> {
+    if self.locale != other.locale {
+      return Err(LocalizationError::LocaleMismatch);
+    }
+    self.record_labels.extend(other.record_labels);
+    self.descriptions.extend(other.descriptions);
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::traits::{Record, VariableType, VariableTypeInfo};
+
+  #[test]
+  fn validate_and_merge() {
+    let trait1 = Trait {
+      name: "Localized".to_string(),
+      records: vec![Record::from((
+        "speed".to_string(),
+        vec![VariableTypeInfo {
+          type_: VariableType::Float(None),
+          default: None,
+        }],
+      ))],
+    };
+
+    let mut base = TraitLocalization::new("it-IT".to_string());
+    base
+      .record_labels
+      .insert("speed".to_string(), "Velocita".to_string());
+    assert_eq!(base.validate(&trait1), Ok(()));
+
+    let mut fix = TraitLocalization::new("it-IT".to_string());
+    fix
+      .record_labels
+      .insert("speed".to_string(), "Velocità".to_string());
+    fix
+      .descriptions
+      .insert("sped".to_string(), "Unità al secondo".to_string());
+    base.merge(fix).unwrap();
+    assert_eq!(
+      base.label(&"speed".to_string()).map(String::as_str),
+      Some("Velocità")
+    );
+    assert_eq!(
+      base.validate(&trait1),
+      Err(LocalizationError::UnknownRecord("sped".to_string()))
+    );
+
+    assert_eq!(
+      base.merge(TraitLocalization::new("en".to_string())),
+      Err(LocalizationError::LocaleMismatch)
+    );
+    assert_eq!(
+      TraitLocalization::decode(&mut &base.encode()[..]).unwrap(),
+      base
+    );
+  }
+}
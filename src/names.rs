@@ -0,0 +1,238 @@
+//! Validated names of trait records and code variables.
+//!
+//! The fields of `Record` and `CodeInfo` keep plain strings for compatibility, `VariableName`
+//! converts into them and is accepted by the constructors below, so names built through it are
+//! always valid.
+
+use crate::traits::{CodeInfo, Record, VariableType, VariableTypeInfo};
+use core::{borrow::Borrow, fmt, ops::Deref, str::FromStr};
+use parity_scale_codec::{Decode, Encode, Error, Input};
+use scale_info::prelude::{string::String, vec::Vec};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The name of a record or of a code variable.
+///
+/// Names are 1 to `MAX_LEN` ASCII letters, digits, `_`, `-` or `.`, starting with a letter or `_`.
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(try_from = "String", into = "String")
+)]
+#[derive(Encode, Clone, PartialEq, Debug, Eq, PartialOrd, Ord, Hash, scale_info::TypeInfo)]
+pub struct VariableName(String);
+
+/// Errors returned when validating a `VariableName`.
+#[derive(Clone, PartialEq, Debug, Eq)]
+pub enum VariableNameError {
+  /// The name is empty
+  Empty,
+  /// The name is longer than `VariableName::MAX_LEN`, holds its length
+  TooLong(u32),
+  /// The name contains, or starts with, a character it cannot have
+  InvalidChar(char),
+}
+
+impl fmt::Display for VariableNameError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Empty => write!(f, "empty variable name"),
+      Self::TooLong(len) => write!(
+        f,
+        "variable name of {} bytes, the maximum is {}",
+        len,
+        VariableName::MAX_LEN
+      ),
+      Self::InvalidChar(c) => write!(f, "invalid character {:?} in variable name", c),
+    }
+  }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for VariableNameError {}
+
+impl VariableName {
+  /// The maximum length of a name, in bytes.
+  pub const MAX_LEN: usize = 64;
+
+  /// Checks that `name` is a valid name.
+  pub fn validate(name: &str) -> Result<(), VariableNameError> {
+    let first = name.chars().next().ok_or(VariableNameError::Empty)?;
+    if name.len() > Self::MAX_LEN {
+      return Err(VariableNameError::TooLong(name.len() as u32));
+    }
+    if !(first.is_ascii_alphabetic() || first == '_') {
+      return Err(VariableNameError::InvalidChar(first));
+    }
+    match name
+      .chars()
+      .find(|c| !(c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.')))
+    {
+      Some(c) => Err(VariableNameError::InvalidChar(c)),
+      None => Ok(()),
+    }
+  }
+
+  /// The name `name`, if valid.
+  pub fn new(name: &str) -> Result<Self, VariableNameError> {
+    Self::validate(name)?;
+    Ok(Self(name.into()))
+  }
+
+  /// The name as a string slice.
+  pub fn as_str(&self) -> &str {
+    &self.0
+  }
+}
+
+impl TryFrom<String> for VariableName {
+  type Error = VariableNameError;
+
+  fn try_from(name: String) -> Result<Self, Self::Error> {
+    Self::validate(&name)?;
+    Ok(Self(name))
+  }
+}
+
+impl TryFrom<&str> for VariableName {
+  type Error = VariableNameError;
+
+  fn try_from(name: &str) -> Result<Self, Self::Error> {
+    Self::new(name)
+  }
+}
+
+impl FromStr for VariableName {
+  type Err = VariableNameError;
+
+  fn from_str(name: &str) -> Result<Self, Self::Err> {
+    Self::new(name)
+  }
+}
+
+impl From<VariableName> for String {
+  fn from(name: VariableName) -> Self {
+    name.0
+  }
+}
+
+impl Decode for VariableName {
+  fn decode<I: Input>(input: &mut I) -> Result<Self, Error> {
+    Self::try_from(String::decode(input)?).map_err(|_| "Invalid variable name".into())
+  }
+}
+
+impl Deref for VariableName {
+  type Target = str;
+
+  fn deref(&self) -> &str {
+    &self.0
+  }
+}
+
+impl AsRef<str> for VariableName {
+  fn as_ref(&self) -> &str {
+    &self.0
+  }
+}
+
+impl Borrow<str> for VariableName {
+  fn borrow(&self) -> &str {
+    &self.0
+  }
+}
+
+impl PartialEq<str> for VariableName {
+  fn eq(&self, other: &str) -> bool {
+    self.0 == other
+  }
+}
+
+impl fmt::Display for VariableName {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(&self.0)
+  }
+}
+
+impl Record {
+  /// A record named `name` accepting `types`.
+  pub fn new(name: VariableName, types: Vec<VariableTypeInfo>) -> Self {
+    Self {
+      name: name.into(),
+      types,
+    }
+  }
+}
+
+impl CodeInfo {
+  /// The code also requiring the variable `name` of type `type_`.
+  pub fn require(mut self, name: VariableName, type_: VariableType) -> Self {
+    self.requires.push((name.into(), type_));
+    self
+  }
+
+  /// The code also exposing the variable `name` of type `type_`.
+  pub fn expose(mut self, name: VariableName, type_: VariableType) -> Self {
+    self.exposes.push((name.into(), type_));
+    self
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::collections::BTreeMap;
+
+  #[test]
+  fn validation() {
+    for valid in ["speed", "_hidden", "pos.x", "max-hp", "A1"] {
+      assert_eq!(VariableName::new(valid).unwrap().as_str(), valid);
+    }
+    assert_eq!(VariableName::new(""), Err(VariableNameError::Empty));
+    assert_eq!(
+      VariableName::new("1st"),
+      Err(VariableNameError::InvalidChar('1'))
+    );
+    assert_eq!(
+      VariableName::new("a b"),
+      Err(VariableNameError::InvalidChar(' '))
+    );
+    assert_eq!(
+      VariableName::new("é"),
+      Err(VariableNameError::InvalidChar('é'))
+    );
+    let long = "a".repeat(VariableName::MAX_LEN + 1);
+    assert_eq!(
+      long.parse::<VariableName>(),
+      Err(VariableNameError::TooLong(65))
+    );
+  }
+
+  #[test]
+  fn conversions() {
+    let name: VariableName = "speed".parse().unwrap();
+    assert_eq!(name.to_string(), "speed");
+    assert_eq!(&*name, "speed");
+
+    let mut map = BTreeMap::new();
+    map.insert(name.clone(), 1);
+    assert_eq!(map.get("speed"), Some(&1));
+
+    let encoded = name.encode();
+    assert_eq!(encoded, "speed".to_string().encode());
+    assert_eq!(VariableName::decode(&mut &encoded[..]).unwrap(), name);
+    assert!(VariableName::decode(&mut &"no way".to_string().encode()[..]).is_err());
+
+    assert_eq!(serde_json::to_string(&name).unwrap(), r#""speed""#);
+    assert!(serde_json::from_str::<VariableName>(r#""no way""#).is_err());
+
+    let record = Record::new(name.clone(), vec![]);
+    assert_eq!(record.name, "speed");
+    let code = CodeInfo::default()
+      .require(name, VariableType::Float(None))
+      .expose("done".parse().unwrap(), VariableType::Bool);
+    assert_eq!(code.requires[0].0, "speed");
+    assert_eq!(code.exposes[0].0, "done");
+  }
+}
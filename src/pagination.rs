@@ -0,0 +1,219 @@
+//! Pagination and ordering shared by every list endpoint, so all of them page the same way.
+
+use parity_scale_codec::{Decode, Encode};
+use scale_info::prelude::vec::Vec;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A slice of the items of a list endpoint.
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase", default)
+)]
+pub struct PageRequest {
+  /// The amount of items to skip
+  pub from: u64,
+  /// The maximum amount of items to return
+  pub limit: u64,
+}
+
+impl PageRequest {
+  /// The `limit` of requests not setting one.
+  pub const DEFAULT_LIMIT: u64 = 100;
+  /// The largest `limit` allowed.
+  pub const MAX_LIMIT: u64 = 1000;
+
+  /// Checks that `limit` is neither 0 nor above [`PageRequest::MAX_LIMIT`].
+  pub fn validate(&self) -> Result<(), PageError> {
+    match self.limit {
+      0 => Err(PageError::ZeroLimit),
+      limit if limit > Self::MAX_LIMIT => Err(PageError::LimitTooLarge(limit)),
+      _ => Ok(()),
+    }
+  }
+
+  /// The page of `items`, which are every item of the list, in order.
+  pub fn slice<'a, T>(&self, items: &'a [T]) -> &'a [T] {
+    let len = items.len() as u64;
+    let start = self.from.min(len);
+    let end = start.saturating_add(self.limit).min(len);
+    &items[start as usize..end as usize]
+  }
+
+  /// The request of the page after this one, when the list has `total` items.
+  ///
+  /// `None` with a 0 `limit`, which would request the same page forever.
+  pub fn next(&self, total: u64) -> Option<Self> {
+    if self.limit == 0 {
+      return None;
+    }
+    let from = self.from.saturating_add(self.limit);
+    (from < total).then_some(Self { from, ..*self })
+  }
+}
+
+impl Default for PageRequest {
+  fn default() -> Self {
+    Self {
+      from: 0,
+      limit: Self::DEFAULT_LIMIT,
+    }
+  }
+}
+
+/// Errors returned when validating a `PageRequest`.
+#[derive(Copy, Clone, PartialEq, Debug, Eq)]
+pub enum PageError {
+  /// The `limit` is 0
+  ZeroLimit,
+  /// The `limit` is above `PageRequest::MAX_LIMIT`
+  LimitTooLarge(u64),
+}
+
+impl core::fmt::Display for PageError {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    match self {
+      Self::ZeroLimit => write!(f, "page limit must not be 0"),
+      Self::LimitTooLarge(limit) => write!(
+        f,
+        "page limit {} is above {}",
+        limit,
+        PageRequest::MAX_LIMIT
+      ),
+    }
+  }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PageError {}
+
+/// The order of the items of a list endpoint.
+#[derive(
+  Encode, Decode, Copy, Clone, PartialEq, Debug, Eq, Default, Hash, scale_info::TypeInfo,
+)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub enum SortOrder {
+  /// In the order of the list
+  #[default]
+  Ascending,
+  /// In the reverse order of the list
+  Descending,
+}
+
+/// A page of the items of a list endpoint.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct Paged<T> {
+  /// The items of the page
+  pub items: Vec<T>,
+  /// The amount of items in every page
+  pub total: u64,
+  /// The request of the next page, `None` on the last page
+  pub next: Option<PageRequest>,
+}
+
+impl<T> Paged<T> {
+  /// The page of `request` of a list with `total` items.
+  pub fn new(items: Vec<T>, total: u64, request: &PageRequest) -> Self {
+    Self {
+      items,
+      total,
+      next: request.next(total),
+    }
+  }
+
+  /// The same page with every item mapped by `f`.
+  pub fn map<U>(self, f: impl FnMut(T) -> U) -> Paged<U> {
+    Paged {
+      items: self.items.into_iter().map(f).collect(),
+      total: self.total,
+      next: self.next,
+    }
+  }
+}
+
+impl<T: Clone> Paged<T> {
+  /// The page of `request` of `items`, which are every item of the list, in `order`.
+  pub fn from_slice(items: &[T], request: &PageRequest, order: SortOrder) -> Self {
+    let total = items.len() as u64;
+    let page = match order {
+      SortOrder::Ascending => request.slice(items).to_vec(),
+      SortOrder::Descending => {
+        let skip = request.from.min(total) as usize;
+        items
+          .iter()
+          .rev()
+          .skip(skip)
+          .take(request.limit.min(total) as usize)
+          .cloned()
+          .collect()
+      }
+    };
+    Self::new(page, total, request)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn page_requests() {
+    assert_eq!(PageRequest::default().validate(), Ok(()));
+    let zero = PageRequest { from: 0, limit: 0 };
+    assert_eq!(zero.validate(), Err(PageError::ZeroLimit));
+    let large = PageRequest {
+      from: 0,
+      limit: PageRequest::MAX_LIMIT + 1,
+    };
+    assert_eq!(large.validate(), Err(PageError::LimitTooLarge(1001)));
+
+    let request: PageRequest = serde_json::from_str(r#"{"from":4}"#).unwrap();
+    assert_eq!(request.limit, PageRequest::DEFAULT_LIMIT);
+
+    let items = [1, 2, 3, 4, 5];
+    let request = PageRequest { from: 3, limit: 10 };
+    assert_eq!(request.slice(&items), [4, 5]);
+    assert!(request.slice(&items[..2]).is_empty());
+    assert_eq!(request.next(5), None);
+    let request = PageRequest { from: 0, limit: 2 };
+    assert_eq!(request.next(5), Some(PageRequest { from: 2, limit: 2 }));
+    assert_eq!(zero.next(5), None);
+    let request = PageRequest {
+      from: u64::MAX,
+      limit: u64::MAX,
+    };
+    assert!(request.slice(&items).is_empty());
+    assert_eq!(request.next(5), None);
+  }
+
+  #[test]
+  fn pages() {
+    let items = [1, 2, 3, 4, 5];
+    let request = PageRequest { from: 1, limit: 2 };
+    let page = Paged::from_slice(&items, &request, SortOrder::Ascending);
+    assert_eq!(page.items, [2, 3]);
+    assert_eq!(page.total, 5);
+    assert_eq!(page.next, Some(PageRequest { from: 3, limit: 2 }));
+    let page = Paged::from_slice(&items, &request, SortOrder::Descending).map(|i| i * 10);
+    assert_eq!(page.items, [40, 30]);
+
+    let json = serde_json::to_string(&page).unwrap();
+    assert_eq!(
+      json,
+      r#"{"items":[40,30],"total":5,"next":{"from":3,"limit":2}}"#
+    );
+    assert_eq!(serde_json::from_str::<Paged<u32>>(&json).unwrap(), page);
+  }
+}
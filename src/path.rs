@@ -0,0 +1,348 @@
+//! Addressing of the types nested inside trait records.
+//!
+//! A path starts with a record name and descends into nested types, one segment at a time:
+//! `position / seq[2] / table["pos"] / code.output`.
+//! Records, table keys and code variables may have several alternative types, the first one is
+//! used unless an alternative index follows (`position[1]`, `table["pos"][1]`).
+
+use crate::traits::{Trait, VariableType};
+use core::fmt;
+use scale_info::prelude::{string::String, vec::Vec};
+
+/// One step from a type into one of its nested types.
+#[derive(Clone, PartialEq, Debug, Eq)]
+pub enum PathSegment {
+  /// `seq[index]`, the type at `index` of a sequence
+  Seq(u32),
+  /// `table["key"]`, a type of the key `key` of a table
+  Table { key: String, alternative: u32 },
+  /// `code.requires["name"]`, the type of a variable required by some code
+  CodeRequires(String),
+  /// `code.exposes["name"]`, the type of a variable exposed by some code
+  CodeExposes(String),
+  /// `code.inputs[index]`, an input type of some code
+  CodeInput(u32),
+  /// `code.output`, the output type of some code
+  CodeOutput,
+  /// `channel`, the type carried by a channel
+  Channel,
+  /// `event`, the type carried by an event
+  Event,
+}
+
+impl PathSegment {
+  /// The type `ty` leads to through this segment.
+  pub fn resolve<'a>(&self, ty: &'a VariableType) -> Option<&'a VariableType> {
+    match (self, ty) {
+      (Self::Seq(index), VariableType::Seq { types, .. }) => types.get(*index as usize),
+      (Self::Table { key, alternative }, VariableType::Table(table)) => {
+        let index = table.keys.iter().position(|k| k == key)?;
+        table.types.get(index)?.get(*alternative as usize)
+      }
+      (Self::Table { key, alternative }, VariableType::TableV2(table)) => {
+        let index = table.keys.iter().position(|k| k.to_key() == *key)?;
+        table.types.get(index)?.get(*alternative as usize)
+      }
+      (Self::CodeRequires(name), VariableType::Code(code)) => variable(&code.requires, name),
+      (Self::CodeExposes(name), VariableType::Code(code)) => variable(&code.exposes, name),
+      (Self::CodeInput(index), VariableType::Code(code)) => code.inputs.get(*index as usize),
+      (Self::CodeOutput, VariableType::Code(code)) => Some(&code.output),
+      (Self::Channel, VariableType::Channel(_) | VariableType::ChannelV2(_))
+      | (Self::Event, VariableType::Event(_) | VariableType::EventV2(_)) => ty.carried(),
+      _ => None,
+    }
+  }
+}
+
+fn variable<'a>(
+  variables: &'a [(String, VariableType)],
+  name: &String,
+) -> Option<&'a VariableType> {
+  variables.iter().find(|(n, _)| n == name).map(|(_, ty)| ty)
+}
+
+/// The location of a type inside a trait.
+#[derive(Clone, PartialEq, Debug, Eq)]
+pub struct RecordPath {
+  /// The name of the record
+  pub record: String,
+  /// The index of the type among the alternative types of the record
+  pub alternative: u32,
+  /// The steps from the record type to the addressed type
+  pub segments: Vec<PathSegment>,
+}
+
+impl RecordPath {
+  /// A path to the first type of the record `record`.
+  pub fn new(record: String) -> Self {
+    Self {
+      record,
+      alternative: 0,
+      segments: Vec::new(),
+    }
+  }
+
+  /// This path extended with `segment`.
+  pub fn join(mut self, segment: PathSegment) -> Self {
+    self.segments.push(segment);
+    self
+  }
+
+  /// The type addressed by this path in `trait_`.
+  pub fn resolve<'a>(&self, trait_: &'a Trait) -> Option<&'a VariableType> {
+    let record = trait_.records.iter().find(|r| r.name == self.record)?;
+    let ty = &record.types.get(self.alternative as usize)?.type_;
+    self
+      .segments
+      .iter()
+      .try_fold(ty, |ty, segment| segment.resolve(ty))
+  }
+}
+
+fn write_alternative(f: &mut fmt::Formatter<'_>, alternative: u32) -> fmt::Result {
+  if alternative != 0 {
+    write!(f, "[{}]", alternative)?;
+  }
+  Ok(())
+}
+
+impl fmt::Display for PathSegment {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Seq(index) => write!(f, "seq[{}]", index),
+      Self::Table { key, alternative } => {
+        write!(f, "table[\"{}\"]", key)?;
+        write_alternative(f, *alternative)
+      }
+      Self::CodeRequires(name) => write!(f, "code.requires[\"{}\"]", name),
+      Self::CodeExposes(name) => write!(f, "code.exposes[\"{}\"]", name),
+      Self::CodeInput(index) => write!(f, "code.inputs[{}]", index),
+      Self::CodeOutput => write!(f, "code.output"),
+      Self::Channel => write!(f, "channel"),
+      Self::Event => write!(f, "event"),
+    }
+  }
+}
+
+impl fmt::Display for RecordPath {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}", self.record)?;
+    write_alternative(f, self.alternative)?;
+    self
+      .segments
+      .iter()
+      .try_for_each(|segment| write!(f, " / {}", segment))
+  }
+}
+
+/// Errors returned when parsing a `RecordPath`.
+#[cfg(feature = "std")]
+#[derive(Clone, PartialEq, Debug, Eq)]
+pub enum PathParseError {
+  /// The path has no record name
+  Empty,
+  /// A segment is not valid, holds the segment
+  InvalidSegment(String),
+}
+
+#[cfg(feature = "std")]
+impl core::fmt::Display for PathParseError {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    match self {
+      Self::Empty => write!(f, "empty record path"),
+      Self::InvalidSegment(segment) => write!(f, "invalid record path segment: {}", segment),
+    }
+  }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PathParseError {}
+
+#[cfg(feature = "std")]
+mod text {
+  use super::*;
+  use core::str::FromStr;
+
+  /// Splits `[index]` off the end of `s`.
+  fn split_index(s: &str) -> Option<(&str, u32)> {
+    let (head, index) = s.strip_suffix(']')?.rsplit_once('[')?;
+    Some((head, index.parse().ok()?))
+  }
+
+  /// Splits `name` and an optional `[alternative]` suffix.
+  fn split_alternative(s: &str) -> Option<(&str, u32)> {
+    if s.ends_with(']') && !s.ends_with("\"]") {
+      split_index(s)
+    } else {
+      Some((s, 0))
+    }
+  }
+
+  /// Parses `head["key"]`, returning `head` and `key`.
+  fn split_key<'a>(s: &'a str, head: &str) -> Option<(String, &'a str)> {
+    let rest = s.strip_prefix(head)?.strip_prefix("[\"")?;
+    let (key, rest) = rest.split_once("\"]")?;
+    Some((key.to_string(), rest))
+  }
+
+  impl FromStr for PathSegment {
+    type Err = PathParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+      let invalid = || PathParseError::InvalidSegment(s.to_string());
+      let segment = match s {
+        "code.output" => Self::CodeOutput,
+        "channel" => Self::Channel,
+        "event" => Self::Event,
+        _ if s.starts_with("table[") => {
+          let (key, rest) = split_key(s, "table").ok_or_else(invalid)?;
+          let alternative = match rest {
+            "" => 0,
+            _ => {
+              split_index(rest)
+                .filter(|(h, _)| h.is_empty())
+                .ok_or_else(invalid)?
+                .1
+            }
+          };
+          Self::Table { key, alternative }
+        }
+        _ if s.starts_with("code.requires[") => {
+          match split_key(s, "code.requires").ok_or_else(invalid)? {
+            (name, "") => Self::CodeRequires(name),
+            _ => return Err(invalid()),
+          }
+        }
+        _ if s.starts_with("code.exposes[") => {
+          match split_key(s, "code.exposes").ok_or_else(invalid)? {
+            (name, "") => Self::CodeExposes(name),
+            _ => return Err(invalid()),
+          }
+        }
+        _ => match split_index(s).ok_or_else(invalid)? {
+          ("seq", index) => Self::Seq(index),
+          ("code.inputs", index) => Self::CodeInput(index),
+          _ => return Err(invalid()),
+        },
+      };
+      Ok(segment)
+    }
+  }
+
+  impl FromStr for RecordPath {
+    type Err = PathParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+      let mut quoted = false;
+      let mut parts = s
+        .split(|c| {
+          quoted ^= c == '"';
+          c == '/' && !quoted
+        })
+        .map(str::trim);
+      let first = parts
+        .next()
+        .filter(|p| !p.is_empty())
+        .ok_or(PathParseError::Empty)?;
+      let (record, alternative) = split_alternative(first)
+        .filter(|(record, _)| !record.is_empty())
+        .ok_or_else(|| PathParseError::InvalidSegment(first.to_string()))?;
+      Ok(Self {
+        record: record.to_string(),
+        alternative,
+        segments: parts.map(str::parse).collect::<Result<_, _>>()?,
+      })
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::traits::{CodeInfo, Record, TableInfo, VariableTypeInfo};
+
+  fn sample() -> Trait {
+    let table = VariableType::Table(TableInfo {
+      keys: vec!["pos".to_string()],
+      types: vec![vec![
+        VariableType::Float3(Default::default()),
+        VariableType::Code(Box::new(CodeInfo {
+          output: VariableType::Bool,
+          ..Default::default()
+        })),
+      ]],
+    });
+    Trait {
+      name: "Sample".to_string(),
+      records: vec![Record::from((
+        "items".to_string(),
+        vec![
+          VariableTypeInfo {
+            type_: VariableType::Int(None),
+            default: None,
+          },
+          VariableTypeInfo {
+            type_: VariableType::Seq {
+              types: vec![VariableType::Any, table],
+              length_limits: None,
+            },
+            default: None,
+          },
+        ],
+      ))],
+    }
+  }
+
+  #[test]
+  fn resolve() {
+    let trait1 = sample();
+    let path = RecordPath::new("items".to_string());
+    assert_eq!(trait1.resolve_path(&path), Some(&VariableType::Int(None)));
+
+    let path = RecordPath {
+      alternative: 1,
+      ..path
+    }
+    .join(PathSegment::Seq(1))
+    .join(PathSegment::Table {
+      key: "pos".to_string(),
+      alternative: 1,
+    })
+    .join(PathSegment::CodeOutput);
+    assert_eq!(trait1.resolve_path(&path), Some(&VariableType::Bool));
+
+    let wrong = RecordPath::new("items".to_string()).join(PathSegment::Seq(0));
+    assert_eq!(trait1.resolve_path(&wrong), None);
+    assert_eq!(
+      trait1.resolve_path(&RecordPath::new("missing".to_string())),
+      None
+    );
+  }
+
+  #[test]
+  fn text() {
+    let text = r#"items[1] / seq[1] / table["pos"][1] / code.output"#;
+    let path: RecordPath = text.parse().unwrap();
+    assert_eq!(path.to_string(), text);
+    assert_eq!(sample().resolve_path(&path), Some(&VariableType::Bool));
+
+    let text = r#"items / table["a/b"] / code.requires["x"] / code.exposes["y"] / code.inputs[2] / channel / event"#;
+    let path: RecordPath =
+      r#"items/table["a/b"]/code.requires["x"]/ code.exposes["y"] /code.inputs[2]/channel/event"#
+        .parse()
+        .unwrap();
+    assert_eq!(path.to_string(), text);
+    assert_eq!(path.segments.len(), 6);
+
+    assert_eq!("".parse::<RecordPath>(), Err(PathParseError::Empty));
+    for invalid in [
+      "items / seq",
+      "items / seq[x]",
+      "[1]",
+      "items / table[\"a\"]x",
+      "items / code",
+    ] {
+      assert!(invalid.parse::<RecordPath>().is_err(), "{}", invalid);
+    }
+  }
+}
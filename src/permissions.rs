@@ -1,6 +1,10 @@
+use crate::categories::loose_eq;
 use bitflags::bitflags;
 use parity_scale_codec::{Decode, Encode, MaxEncodedLen};
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 bitflags! {
   /// Permissions for fragments and fragment's bundles.
   #[derive(Encode, Decode, MaxEncodedLen, scale_info::TypeInfo)]
@@ -13,6 +17,157 @@ bitflags! {
   }
 }
 
+impl FragmentPerms {
+  /// Every individual flag along with its name, in bit order.
+  pub const FLAGS: [(FragmentPerms, &'static str); 3] = [
+    (Self::EDIT, "edit"),
+    (Self::COPY, "copy"),
+    (Self::TRANSFER, "transfer"),
+  ];
+
+  /// Iterates over the individual flags set in `self`.
+  pub fn iter_set(self) -> impl Iterator<Item = FragmentPerms> {
+    Self::FLAGS
+      .into_iter()
+      .map(|(flag, _)| flag)
+      .filter(move |flag| self.contains(*flag))
+  }
+
+  /// The name of `self` if it is a single flag.
+  pub fn flag_name(self) -> Option<&'static str> {
+    Self::FLAGS
+      .iter()
+      .find(|(flag, _)| *flag == self)
+      .map(|(_, name)| *name)
+  }
+
+  /// Iterates over the names of the flags set in `self`.
+  pub fn names(self) -> impl Iterator<Item = &'static str> {
+    self.iter_set().filter_map(Self::flag_name)
+  }
+
+  /// Builds permissions from flag names, ignoring case.
+  ///
+  /// Returns the first name that is not a known flag as the error.
+  pub fn from_names<'a>(names: &[&'a str]) -> Result<Self, &'a str> {
+    names.iter().try_fold(Self::NONE, |perms, name| {
+      Self::FLAGS
+        .iter()
+        .find(|(_, flag_name)| flag_name.eq_ignore_ascii_case(name))
+        .map(|(flag, _)| perms | *flag)
+        .ok_or(*name)
+    })
+  }
+
+  /// The license shorthand of the permissions, for product UIs.
+  pub fn to_license_summary(self) -> LicenseSummary {
+    LicenseSummary::PRESETS
+      .into_iter()
+      .find(|preset| preset.perms() == self)
+      .unwrap_or(LicenseSummary::Custom(self))
+  }
+}
+
+/// A human readable name of a set of permissions, see [`FragmentPerms::to_license_summary`].
+#[derive(Copy, Clone, PartialEq, Debug, Eq)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub enum LicenseSummary {
+  /// No permissions, the holder can only view the fragment
+  ViewOnly,
+  /// The holder can give the fragment away but not change or copy it
+  Transferable,
+  /// The holder can edit and copy the fragment, but not give it away
+  Remixable,
+  /// Every permission
+  FullyTransferable,
+  /// A combination of permissions without shorthand
+  Custom(FragmentPerms),
+}
+
+impl LicenseSummary {
+  /// Every summary with a shorthand.
+  pub const PRESETS: [LicenseSummary; 4] = [
+    Self::ViewOnly,
+    Self::Transferable,
+    Self::Remixable,
+    Self::FullyTransferable,
+  ];
+
+  /// The permissions of the summary.
+  pub fn perms(self) -> FragmentPerms {
+    match self {
+      Self::ViewOnly => FragmentPerms::NONE,
+      Self::Transferable => FragmentPerms::TRANSFER,
+      Self::Remixable => FragmentPerms::EDIT | FragmentPerms::COPY,
+      Self::FullyTransferable => FragmentPerms::ALL,
+      Self::Custom(perms) => perms,
+    }
+  }
+
+  /// The shorthand of the summary, `None` for `Custom`.
+  pub fn shorthand(self) -> Option<&'static str> {
+    match self {
+      Self::ViewOnly => Some("view-only"),
+      Self::Transferable => Some("transferable"),
+      Self::Remixable => Some("remixable"),
+      Self::FullyTransferable => Some("fully transferable"),
+      Self::Custom(_) => None,
+    }
+  }
+
+  /// Parses a shorthand, ignoring ASCII case, `_`, `-` and spaces (`"View only"`, `"viewOnly"`).
+  pub fn from_shorthand(shorthand: &str) -> Option<Self> {
+    Self::PRESETS.into_iter().find(|preset| {
+      preset
+        .shorthand()
+        .is_some_and(|name| loose_eq(shorthand.trim(), name))
+    })
+  }
+}
+
+impl core::fmt::Display for LicenseSummary {
+  /// The shorthand, or the names of the permissions joined by `+` (`"edit+transfer"`).
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    match self.shorthand() {
+      Some(shorthand) => f.write_str(shorthand),
+      None => {
+        for (i, name) in self.perms().names().enumerate() {
+          if i > 0 {
+            f.write_str("+")?;
+          }
+          f.write_str(name)?;
+        }
+        Ok(())
+      }
+    }
+  }
+}
+
+// Permissions are (de)serialized as their raw bits, the same way they are SCALE encoded.
+#[cfg(feature = "serde")]
+impl serde::Serialize for FragmentPerms {
+  fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u8(self.bits)
+  }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for FragmentPerms {
+  fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    let bits = <u8 as serde::Deserialize>::deserialize(deserializer)?;
+    Self::from_bits(bits).ok_or_else(|| {
+      serde::de::Error::custom(scale_info::prelude::format!(
+        "invalid permission bits: {}",
+        bits
+      ))
+    })
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -71,4 +226,70 @@ mod tests {
 
     assert_eq!(test_struct.permissions.bits, 7);
   }
+
+  #[test]
+  fn json_bits() {
+    let perms = FragmentPerms::EDIT | FragmentPerms::TRANSFER;
+    assert_eq!(serde_json::to_string(&perms).unwrap(), "5");
+    assert_eq!(serde_json::from_str::<FragmentPerms>("5").unwrap(), perms);
+    assert!(serde_json::from_str::<FragmentPerms>("8").is_err());
+  }
+
+  #[test]
+  fn flag_names() {
+    let perms = FragmentPerms::EDIT | FragmentPerms::TRANSFER;
+    assert_eq!(
+      perms.iter_set().collect::<Vec<_>>(),
+      vec![FragmentPerms::EDIT, FragmentPerms::TRANSFER]
+    );
+    assert_eq!(perms.names().collect::<Vec<_>>(), vec!["edit", "transfer"]);
+    assert_eq!(FragmentPerms::NONE.iter_set().count(), 0);
+    assert_eq!(FragmentPerms::ALL.iter_set().count(), 3);
+
+    assert_eq!(FragmentPerms::COPY.flag_name(), Some("copy"));
+    assert_eq!(FragmentPerms::ALL.flag_name(), None);
+
+    assert_eq!(FragmentPerms::from_names(&["Edit", "transfer"]), Ok(perms));
+    assert_eq!(FragmentPerms::from_names(&[]), Ok(FragmentPerms::NONE));
+    assert_eq!(FragmentPerms::from_names(&["edit", "burn"]), Err("burn"));
+  }
+
+  #[test]
+  fn license_summaries() {
+    for bits in 0..8 {
+      let perms = FragmentPerms::from_bits(bits).unwrap();
+      assert_eq!(perms.to_license_summary().perms(), perms);
+    }
+    for preset in LicenseSummary::PRESETS {
+      let shorthand = preset.shorthand().unwrap();
+      assert_eq!(LicenseSummary::from_shorthand(shorthand), Some(preset));
+      assert_eq!(preset.perms().to_license_summary(), preset);
+      assert_eq!(preset.to_string(), shorthand);
+    }
+    assert_eq!(
+      FragmentPerms::ALL.to_license_summary(),
+      LicenseSummary::FullyTransferable
+    );
+    assert_eq!(
+      LicenseSummary::from_shorthand(" View Only "),
+      Some(LicenseSummary::ViewOnly)
+    );
+    assert_eq!(
+      LicenseSummary::from_shorthand("fully_transferable"),
+      Some(LicenseSummary::FullyTransferable)
+    );
+    assert_eq!(LicenseSummary::from_shorthand("public domain"), None);
+
+    let custom = (FragmentPerms::EDIT | FragmentPerms::TRANSFER).to_license_summary();
+    assert_eq!(
+      custom,
+      LicenseSummary::Custom(FragmentPerms::from_bits(5).unwrap())
+    );
+    assert_eq!(custom.to_string(), "edit+transfer");
+    assert_eq!(serde_json::to_string(&custom).unwrap(), r#"{"custom":5}"#);
+    assert_eq!(
+      serde_json::to_string(&LicenseSummary::ViewOnly).unwrap(),
+      r#""viewOnly""#
+    );
+  }
 }
@@ -0,0 +1,226 @@
+//! Moderation policies deciding which categories of protos a platform accepts, expressed as data
+//! so every service of the platform applies the same rules.
+
+use crate::categories::{loose_eq, Categories};
+use parity_scale_codec::{Decode, Encode};
+use scale_info::prelude::vec::Vec;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A set of categories.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub enum CategoryFilter {
+  /// Every category
+  Any,
+  /// Every sub-category of the category variant with this SCALE index
+  Variant(u8),
+  /// The category and sub-category of this category, ignoring payloads (trait hashes and script
+  /// infos)
+  Exact(Categories),
+}
+
+impl CategoryFilter {
+  /// Parses `"*"`, a category name (`"texture"`, `"texture/*"`) or a category with its
+  /// sub-category (`"texture/png_file"`), see [`Categories::from_str_loose`].
+  pub fn from_str_loose(filter: &str) -> Option<Self> {
+    let filter = filter.trim();
+    if filter == "*" {
+      return Some(Self::Any);
+    }
+    let variant = filter
+      .strip_suffix('*')
+      .and_then(|name| name.strip_suffix(['/', '.', ':']))
+      .unwrap_or(filter);
+    match Categories::VARIANT_NAMES
+      .iter()
+      .position(|name| loose_eq(variant.trim(), name))
+    {
+      Some(index) => Some(Self::Variant(index as u8)),
+      None if variant.len() == filter.len() => Categories::from_str_loose(filter).map(Self::Exact),
+      None => None,
+    }
+  }
+
+  /// Whether `category` is in the set.
+  pub fn matches(&self, category: &Categories) -> bool {
+    match self {
+      Self::Any => true,
+      Self::Variant(index) => category.variant_index() == *index,
+      Self::Exact(exact) => {
+        exact.variant_index() == category.variant_index()
+          && exact.sub_index() == category.sub_index()
+      }
+    }
+  }
+
+  /// How narrow the set is, narrower filters take precedence.
+  fn specificity(&self) -> u8 {
+    match self {
+      Self::Any => 0,
+      Self::Variant(_) => 1,
+      Self::Exact(_) => 2,
+    }
+  }
+}
+
+/// What a platform does with protos of some category.
+#[derive(
+  Encode, Decode, Copy, Clone, PartialEq, Debug, Eq, PartialOrd, Ord, scale_info::TypeInfo,
+)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub enum PolicyDecision {
+  /// Protos are accepted
+  Allow,
+  /// Protos are accepted after a manual review
+  Review,
+  /// Protos are rejected
+  Block,
+}
+
+/// A decision applying to a set of categories.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct PolicyRule {
+  pub filter: CategoryFilter,
+  pub decision: PolicyDecision,
+}
+
+/// The categories a platform allows, blocks or reviews.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct CategoryPolicy {
+  /// The rules of the policy, in any order
+  pub rules: Vec<PolicyRule>,
+  /// The decision for categories matched by no rule
+  pub default: PolicyDecision,
+}
+
+impl CategoryPolicy {
+  /// A policy deciding `default` for every category.
+  pub fn new(default: PolicyDecision) -> Self {
+    Self {
+      rules: Vec::new(),
+      default,
+    }
+  }
+
+  /// The policy with the rule deciding `decision` for the categories of `filter` added.
+  pub fn with(mut self, filter: CategoryFilter, decision: PolicyDecision) -> Self {
+    self.rules.push(PolicyRule { filter, decision });
+    self
+  }
+
+  /// The decision for protos of `category`.
+  ///
+  /// The narrowest matching rule applies, an exact category before a category variant before
+  /// `Any`. When equally narrow rules disagree the most restrictive decision applies.
+  pub fn evaluate(&self, category: &Categories) -> PolicyDecision {
+    self
+      .rules
+      .iter()
+      .filter(|rule| rule.filter.matches(category))
+      .max_by_key(|rule| (rule.filter.specificity(), rule.decision))
+      .map_or(self.default, |rule| rule.decision)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::categories::{
+    ShardsScriptInfo, TextCategories, TextureCategories, TraitRef, VideoCategories,
+  };
+
+  #[test]
+  fn filters() {
+    assert_eq!(
+      CategoryFilter::from_str_loose("*"),
+      Some(CategoryFilter::Any)
+    );
+    assert_eq!(
+      CategoryFilter::from_str_loose("Texture/*"),
+      Some(CategoryFilter::Variant(4))
+    );
+    assert_eq!(
+      CategoryFilter::from_str_loose("video"),
+      Some(CategoryFilter::Variant(6))
+    );
+    assert_eq!(
+      CategoryFilter::from_str_loose("shards"),
+      Some(CategoryFilter::Variant(2))
+    );
+    assert_eq!(
+      CategoryFilter::from_str_loose("text:json"),
+      Some(CategoryFilter::Exact(Categories::Text(
+        TextCategories::Json
+      )))
+    );
+    assert_eq!(
+      CategoryFilter::from_str_loose("trait"),
+      Some(CategoryFilter::Variant(1))
+    );
+    assert_eq!(CategoryFilter::from_str_loose("nope/*"), None);
+
+    let trait_filter = CategoryFilter::from_str_loose("trait").unwrap();
+    assert!(trait_filter.matches(&Categories::Trait(TraitRef::Hash([1; 8]))));
+    assert!(CategoryFilter::Variant(2).matches(&Categories::Shards(ShardsScriptInfo::default())));
+  }
+
+  #[test]
+  fn evaluate() {
+    let policy = CategoryPolicy::new(PolicyDecision::Allow)
+      .with(CategoryFilter::Variant(6), PolicyDecision::Block)
+      .with(
+        CategoryFilter::Exact(Categories::Video(VideoCategories::Mp4File)),
+        PolicyDecision::Review,
+      )
+      .with(
+        CategoryFilter::Exact(Categories::Texture(TextureCategories::PngFile)),
+        PolicyDecision::Allow,
+      )
+      .with(
+        CategoryFilter::Exact(Categories::Texture(TextureCategories::PngFile)),
+        PolicyDecision::Review,
+      );
+
+    assert_eq!(
+      policy.evaluate(&Categories::Video(VideoCategories::MkvFile)),
+      PolicyDecision::Block
+    );
+    assert_eq!(
+      policy.evaluate(&Categories::Video(VideoCategories::Mp4File)),
+      PolicyDecision::Review
+    );
+    assert_eq!(
+      policy.evaluate(&Categories::Texture(TextureCategories::PngFile)),
+      PolicyDecision::Review
+    );
+    assert_eq!(policy.evaluate(&Categories::Bundle), PolicyDecision::Allow);
+
+    let decoded = CategoryPolicy::decode(&mut &policy.encode()[..]).unwrap();
+    assert_eq!(decoded, policy);
+    let json = serde_json::to_string(&policy).unwrap();
+    assert_eq!(
+      serde_json::from_str::<CategoryPolicy>(&json).unwrap(),
+      policy
+    );
+  }
+}
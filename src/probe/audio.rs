@@ -0,0 +1,263 @@
+use super::{ProbeError, Reader};
+use crate::categories::AudioCategories;
+use parity_scale_codec::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+
+/// Metadata of an audio payload.
+#[derive(
+  Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo, Serialize, Deserialize,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioMeta {
+  pub format: AudioCategories,
+  pub sample_rate: u32,
+  pub channels: u8,
+  /// Duration in milliseconds, `None` when it cannot be known from the given bytes
+  pub duration_ms: Option<u64>,
+}
+
+/// Reads the sample rate, channels and duration of an OGG (Vorbis or Opus) or MP3 payload.
+pub fn audio(bytes: &[u8], category: AudioCategories) -> Result<AudioMeta, ProbeError> {
+  match category {
+    AudioCategories::OggFile => ogg(bytes),
+    AudioCategories::Mp3File => mp3(bytes),
+  }
+}
+
+pub(super) const OGG_CAPTURE: &[u8] = b"OggS";
+
+/// An OGG page header and its payload.
+struct OggPage<'a> {
+  granule_position: u64,
+  serial: u32,
+  payload: &'a [u8],
+}
+
+fn ogg_page(data: &[u8]) -> Result<OggPage<'_>, ProbeError> {
+  let mut reader = Reader::new(data);
+  if reader.bytes(4)? != OGG_CAPTURE || reader.u8()? != 0 {
+    return Err(ProbeError::UnknownFormat);
+  }
+  reader.skip(1)?; // header type
+  let granule_position = u64::from_le_bytes(reader.array()?);
+  let serial = u32::from_le_bytes(reader.array()?);
+  reader.skip(8)?; // sequence number and checksum
+  let segments = reader.u8()? as usize;
+  let payload_len = reader.bytes(segments)?.iter().map(|&s| s as usize).sum();
+  Ok(OggPage {
+    granule_position,
+    serial,
+    payload: reader.bytes(payload_len)?,
+  })
+}
+
+fn ogg(bytes: &[u8]) -> Result<AudioMeta, ProbeError> {
+  let first = ogg_page(bytes)?;
+  let mut reader = Reader::new(first.payload);
+  // samples per second of the granule position, and samples to skip at the start
+  let (sample_rate, channels, granule_rate, pre_skip) = match reader.bytes(7)? {
+    b"\x01vorbis" => {
+      reader.skip(4)?; // version
+      let channels = reader.u8()?;
+      let sample_rate = u32::from_le_bytes(reader.array()?);
+      (sample_rate, channels, sample_rate, 0)
+    }
+    b"OpusHea" if reader.u8()? == b'd' => {
+      reader.skip(1)?; // version
+      let channels = reader.u8()?;
+      let pre_skip = u16::from_le_bytes(reader.array()?) as u64;
+      let sample_rate = u32::from_le_bytes(reader.array()?);
+      // opus always runs at 48kHz, the header carries the original sample rate
+      (sample_rate, channels, 48_000, pre_skip)
+    }
+    _ => return Err(ProbeError::Malformed("unsupported OGG codec")),
+  };
+  if granule_rate == 0 {
+    return Err(ProbeError::Malformed("zero sample rate"));
+  }
+
+  // the granule position of the last page of the stream is its length in samples
+  let last_granule = (0..bytes.len())
+    .rev()
+    .filter(|&at| bytes[at..].starts_with(OGG_CAPTURE))
+    .filter_map(|at| ogg_page(&bytes[at..]).ok())
+    .find(|page| page.serial == first.serial)
+    .map(|page| page.granule_position)
+    // -1 marks pages where no packet ends
+    .filter(|&granule| granule != 0 && granule != u64::MAX);
+
+  Ok(AudioMeta {
+    format: AudioCategories::OggFile,
+    sample_rate,
+    channels,
+    // in 128 bits, granule positions go up to 2^64 - 2
+    duration_ms: last_granule.and_then(|g| {
+      let samples = g.saturating_sub(pre_skip) as u128;
+      u64::try_from(samples * 1000 / granule_rate as u128).ok()
+    }),
+  })
+}
+
+const MP3_BITRATES_V1: [u32; 15] = [
+  0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320,
+];
+const MP3_BITRATES_V2: [u32; 15] = [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160];
+const MP3_SAMPLE_RATES: [u32; 3] = [44_100, 48_000, 32_000];
+
+fn mp3(bytes: &[u8]) -> Result<AudioMeta, ProbeError> {
+  let mut start = 0;
+  // skip the ID3v2 tag, its size is a 28 bits syncsafe integer
+  if bytes.starts_with(b"ID3") {
+    let mut reader = Reader::new(bytes);
+    reader.skip(5)?;
+    let flags = reader.u8()?;
+    let size = reader
+      .array::<4>()?
+      .iter()
+      .fold(0usize, |size, &b| size << 7 | (b & 0x7f) as usize);
+    start = 10 + size + if flags & 0x10 != 0 { 10 } else { 0 };
+  }
+  let mut end = bytes.len();
+  if end >= start + 128 && bytes[end - 128..].starts_with(b"TAG") {
+    end -= 128;
+  }
+
+  let frame = bytes.get(start..end).ok_or(ProbeError::Truncated)?;
+  let mut reader = Reader::new(frame);
+  let header = reader.u32_be()?;
+  if header >> 21 != 0x7ff {
+    return Err(ProbeError::UnknownFormat);
+  }
+  let version = (header >> 19) & 3;
+  if version == 1 {
+    return Err(ProbeError::Malformed("reserved MPEG version"));
+  }
+  if (header >> 17) & 3 != 1 {
+    return Err(ProbeError::Malformed("not an MPEG layer III stream"));
+  }
+  let mpeg1 = version == 3;
+  let bitrate_kbps = match (header >> 12) & 0xf {
+    0 | 15 => return Err(ProbeError::Malformed("unsupported MP3 bitrate")),
+    index if mpeg1 => MP3_BITRATES_V1[index as usize],
+    index => MP3_BITRATES_V2[index as usize],
+  };
+  let sample_rate = match (header >> 10) & 3 {
+    3 => return Err(ProbeError::Malformed("reserved MP3 sample rate")),
+    // MPEG 2 halves the MPEG 1 sample rates and MPEG 2.5 quarters them
+    index => MP3_SAMPLE_RATES[index as usize] >> (3 - version).min(2),
+  };
+  let mono = (header >> 6) & 3 == 3;
+  let samples_per_frame: u64 = if mpeg1 { 1152 } else { 576 };
+
+  // a Xing/Info header after the side information carries the frame count of VBR files
+  let side_info = match (mpeg1, mono) {
+    (true, false) => 32,
+    (true, true) | (false, false) => 17,
+    (false, true) => 9,
+  };
+  let frames = frame.get(4 + side_info..).and_then(|xing| {
+    let mut reader = Reader::new(xing);
+    let tag = reader.array::<4>().ok()?;
+    if &tag != b"Xing" && &tag != b"Info" {
+      return None;
+    }
+    let flags = reader.u32_be().ok()?;
+    (flags & 1 != 0).then(|| reader.u32_be().ok()).flatten()
+  });
+  let duration_ms = match frames {
+    Some(frames) => frames as u64 * samples_per_frame * 1000 / sample_rate as u64,
+    // constant bitrate, estimate from the stream size
+    None => frame.len() as u64 * 8 / bitrate_kbps as u64,
+  };
+
+  Ok(AudioMeta {
+    format: AudioCategories::Mp3File,
+    sample_rate,
+    channels: if mono { 1 } else { 2 },
+    duration_ms: Some(duration_ms),
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn page(granule: u64, payload: &[u8]) -> Vec<u8> {
+    let mut bytes = OGG_CAPTURE.to_vec();
+    bytes.extend_from_slice(&[0, 0]);
+    bytes.extend_from_slice(&granule.to_le_bytes());
+    bytes.extend_from_slice(&7u32.to_le_bytes());
+    bytes.extend_from_slice(&[0; 8]);
+    bytes.extend_from_slice(&[1, payload.len() as u8]);
+    bytes.extend_from_slice(payload);
+    bytes
+  }
+
+  #[test]
+  fn ogg_vorbis() {
+    let mut ident = b"\x01vorbis".to_vec();
+    ident.extend_from_slice(&0u32.to_le_bytes());
+    ident.push(2);
+    ident.extend_from_slice(&44_100u32.to_le_bytes());
+    let mut bytes = page(0, &ident);
+
+    let meta = audio(&bytes, AudioCategories::OggFile).unwrap();
+    assert_eq!((meta.sample_rate, meta.channels), (44_100, 2));
+    assert_eq!(meta.duration_ms, None);
+
+    bytes.extend(page(88_200, &[0; 4]));
+    let meta = audio(&bytes, AudioCategories::OggFile).unwrap();
+    assert_eq!(meta.duration_ms, Some(2000));
+    // the largest granule position, -1 excluded
+    let mut bytes = page(0, &ident);
+    bytes.extend(page(u64::MAX - 1, &[0; 4]));
+    let meta = audio(&bytes, AudioCategories::OggFile).unwrap();
+    assert_eq!(
+      meta.duration_ms,
+      Some(((u64::MAX - 1) as u128 * 1000 / 44_100) as u64)
+    );
+  }
+
+  #[test]
+  fn ogg_opus() {
+    let mut head = b"OpusHead".to_vec();
+    head.extend_from_slice(&[1, 1]);
+    head.extend_from_slice(&312u16.to_le_bytes());
+    head.extend_from_slice(&16_000u32.to_le_bytes());
+    let mut bytes = page(0, &head);
+    bytes.extend(page(48_312, &[0; 4]));
+
+    let meta = audio(&bytes, AudioCategories::OggFile).unwrap();
+    assert_eq!((meta.sample_rate, meta.channels), (16_000, 1));
+    assert_eq!(meta.duration_ms, Some(1000));
+    assert_eq!(
+      audio(b"RIFF", AudioCategories::OggFile),
+      Err(ProbeError::UnknownFormat)
+    );
+  }
+
+  #[test]
+  fn mp3_frames() {
+    // MPEG 1 layer III, 128 kbps, 44.1kHz, joint stereo
+    let header = [0xff, 0xfb, 0x90, 0x40];
+    let mut cbr = vec![b'I', b'D', b'3', 4, 0, 0, 0, 0, 0, 2, 0, 0];
+    cbr.extend_from_slice(&header);
+    cbr.resize(12 + 16_000, 0);
+    let meta = audio(&cbr, AudioCategories::Mp3File).unwrap();
+    assert_eq!((meta.sample_rate, meta.channels), (44_100, 2));
+    assert_eq!(meta.duration_ms, Some(1000));
+
+    let mut vbr = header.to_vec();
+    vbr.resize(4 + 32, 0);
+    vbr.extend_from_slice(b"Xing");
+    vbr.extend_from_slice(&1u32.to_be_bytes());
+    vbr.extend_from_slice(&383u32.to_be_bytes());
+    let meta = audio(&vbr, AudioCategories::Mp3File).unwrap();
+    assert_eq!(meta.duration_ms, Some(383 * 1152 * 1000 / 44_100));
+
+    assert_eq!(
+      audio(&[0xff, 0xfd, 0x90, 0x40], AudioCategories::Mp3File),
+      Err(ProbeError::Malformed("not an MPEG layer III stream"))
+    );
+  }
+}
@@ -0,0 +1,214 @@
+use super::{ProbeError, Reader};
+use parity_scale_codec::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+
+pub(super) const GLB_MAGIC: &[u8; 4] = b"glTF";
+const CHUNK_JSON: u32 = 0x4e4f534a;
+const CHUNK_BIN: u32 = 0x004e4942;
+
+/// Metadata of a binary GLTF (GLB) model.
+#[derive(
+  Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo, Serialize, Deserialize,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelMeta {
+  pub meshes: u32,
+  /// Primitives across all meshes, each one being a draw call
+  pub primitives: u32,
+  /// Whether the container carries the embedded binary buffer
+  pub has_binary_chunk: bool,
+  /// Extensions a loader must support to display the model
+  pub extensions_required: Vec<String>,
+  pub extensions_used: Vec<String>,
+}
+
+/// Errors returned when validating a GLB container.
+#[derive(Clone, PartialEq, Debug, Eq)]
+pub enum GltfError {
+  /// The payload does not start with the GLB magic
+  NotGlb,
+  /// Only GLB version 2 is supported
+  UnsupportedVersion(u32),
+  /// The header length does not match the payload length
+  LengthMismatch { header: u32, actual: usize },
+  /// A chunk is truncated, misaligned or of the wrong type
+  InvalidChunk(&'static str),
+  /// The JSON chunk is not a valid glTF 2.0 document
+  InvalidJson(String),
+}
+
+impl core::fmt::Display for GltfError {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    match self {
+      Self::NotGlb => write!(f, "not a GLB container"),
+      Self::UnsupportedVersion(version) => write!(f, "unsupported GLB version {}", version),
+      Self::LengthMismatch { header, actual } => write!(
+        f,
+        "GLB header declares {} bytes but the payload has {}",
+        header, actual
+      ),
+      Self::InvalidChunk(what) => write!(f, "invalid GLB chunk: {}", what),
+      Self::InvalidJson(what) => write!(f, "invalid glTF JSON: {}", what),
+    }
+  }
+}
+
+impl std::error::Error for GltfError {}
+
+impl From<ProbeError> for GltfError {
+  fn from(_: ProbeError) -> Self {
+    Self::InvalidChunk("truncated")
+  }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Document {
+  asset: Asset,
+  #[serde(default)]
+  meshes: Vec<Mesh>,
+  #[serde(default)]
+  extensions_required: Vec<String>,
+  #[serde(default)]
+  extensions_used: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct Asset {
+  version: String,
+}
+
+#[derive(Deserialize)]
+struct Mesh {
+  primitives: Vec<serde_json::Value>,
+}
+
+fn read_u32(reader: &mut Reader) -> Result<u32, ProbeError> {
+  Ok(u32::from_le_bytes(reader.array()?))
+}
+
+/// Validates a GLB container and summarizes the glTF document it carries.
+pub fn model_gltf(bytes: &[u8]) -> Result<ModelMeta, GltfError> {
+  let mut reader = Reader::new(bytes);
+  if reader.array::<4>().ok().as_ref() != Some(GLB_MAGIC) {
+    return Err(GltfError::NotGlb);
+  }
+  let version = read_u32(&mut reader)?;
+  if version != 2 {
+    return Err(GltfError::UnsupportedVersion(version));
+  }
+  let length = read_u32(&mut reader)?;
+  if length as usize != bytes.len() {
+    return Err(GltfError::LengthMismatch {
+      header: length,
+      actual: bytes.len(),
+    });
+  }
+
+  let mut json = None;
+  let mut has_binary_chunk = false;
+  while reader.pos < bytes.len() {
+    let chunk_length = read_u32(&mut reader)? as usize;
+    let chunk_type = read_u32(&mut reader)?;
+    let data = reader.bytes(chunk_length)?;
+    if !chunk_length.is_multiple_of(4) {
+      return Err(GltfError::InvalidChunk("chunks must be 4-byte aligned"));
+    }
+    match (chunk_type, json.is_some(), has_binary_chunk) {
+      (CHUNK_JSON, false, _) => json = Some(data),
+      (CHUNK_BIN, true, false) => has_binary_chunk = true,
+      (CHUNK_JSON, true, _) => return Err(GltfError::InvalidChunk("duplicate JSON chunk")),
+      (CHUNK_BIN, false, _) => {
+        return Err(GltfError::InvalidChunk("the JSON chunk must come first"))
+      }
+      (CHUNK_BIN, true, true) => return Err(GltfError::InvalidChunk("duplicate BIN chunk")),
+      // unknown chunks must be ignored by loaders
+      _ => {}
+    }
+  }
+
+  let json = json.ok_or(GltfError::InvalidChunk("missing JSON chunk"))?;
+  let document: Document =
+    serde_json::from_slice(json).map_err(|e| GltfError::InvalidJson(e.to_string()))?;
+  if !document.asset.version.starts_with("2.") {
+    return Err(GltfError::InvalidJson(format!(
+      "unsupported asset version {}",
+      document.asset.version
+    )));
+  }
+
+  Ok(ModelMeta {
+    meshes: document.meshes.len() as u32,
+    primitives: document
+      .meshes
+      .iter()
+      .map(|m| m.primitives.len() as u32)
+      .sum(),
+    has_binary_chunk,
+    extensions_required: document.extensions_required,
+    extensions_used: document.extensions_used,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn glb(chunks: &[(u32, &[u8])]) -> Vec<u8> {
+    let mut body = Vec::new();
+    for (chunk_type, data) in chunks {
+      body.extend_from_slice(&(data.len() as u32).to_le_bytes());
+      body.extend_from_slice(&chunk_type.to_le_bytes());
+      body.extend_from_slice(data);
+    }
+    let mut bytes = GLB_MAGIC.to_vec();
+    bytes.extend_from_slice(&2u32.to_le_bytes());
+    bytes.extend_from_slice(&(12 + body.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&body);
+    bytes
+  }
+
+  fn padded(json: &str) -> Vec<u8> {
+    let mut json = json.as_bytes().to_vec();
+    while !json.len().is_multiple_of(4) {
+      json.push(b' ');
+    }
+    json
+  }
+
+  #[test]
+  fn summary() {
+    let json = padded(
+      r#"{"asset":{"version":"2.0"},"meshes":[{"primitives":[{},{}]},{"primitives":[{}]}],
+      "extensionsUsed":["KHR_draco_mesh_compression"],"extensionsRequired":["KHR_draco_mesh_compression"]}"#,
+    );
+    let meta = model_gltf(&glb(&[(CHUNK_JSON, &json), (CHUNK_BIN, &[0; 8])])).unwrap();
+    assert_eq!(meta.meshes, 2);
+    assert_eq!(meta.primitives, 3);
+    assert!(meta.has_binary_chunk);
+    assert_eq!(meta.extensions_required, vec!["KHR_draco_mesh_compression"]);
+  }
+
+  #[test]
+  fn invalid_containers() {
+    let json = padded(r#"{"asset":{"version":"2.0"}}"#);
+    let mut bytes = glb(&[(CHUNK_JSON, &json)]);
+    assert_eq!(model_gltf(&bytes).unwrap().meshes, 0);
+
+    bytes.push(0);
+    assert!(matches!(
+      model_gltf(&bytes),
+      Err(GltfError::LengthMismatch { .. })
+    ));
+    assert_eq!(model_gltf(b"PK\x03\x04"), Err(GltfError::NotGlb));
+    assert_eq!(
+      model_gltf(&glb(&[(CHUNK_BIN, &[0; 4])])),
+      Err(GltfError::InvalidChunk("the JSON chunk must come first"))
+    );
+    let old = padded(r#"{"asset":{"version":"1.0"}}"#);
+    assert!(matches!(
+      model_gltf(&glb(&[(CHUNK_JSON, &old)])),
+      Err(GltfError::InvalidJson(_))
+    ));
+  }
+}
@@ -0,0 +1,144 @@
+use super::{ProbeError, Reader};
+use crate::categories::TextureCategories;
+use parity_scale_codec::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+
+pub(super) const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+pub(super) const JPEG_SOI: &[u8] = &[0xff, 0xd8];
+
+/// Metadata of a texture payload.
+#[derive(
+  Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo, Serialize, Deserialize,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct TextureMeta {
+  /// The sub-category matching the payload format
+  pub format: TextureCategories,
+  pub width: u32,
+  pub height: u32,
+  /// Bits per channel
+  pub bit_depth: u8,
+  /// Channels per pixel, palette images count as one
+  pub channels: u8,
+}
+
+/// Reads the dimensions and depth of a PNG or JPEG texture from its headers.
+pub fn texture(bytes: &[u8]) -> Result<TextureMeta, ProbeError> {
+  if bytes.starts_with(PNG_SIGNATURE) {
+    png(&bytes[PNG_SIGNATURE.len()..])
+  } else if bytes.starts_with(JPEG_SOI) {
+    jpeg(&bytes[JPEG_SOI.len()..])
+  } else {
+    Err(ProbeError::UnknownFormat)
+  }
+}
+
+fn png(data: &[u8]) -> Result<TextureMeta, ProbeError> {
+  let mut reader = Reader::new(data);
+  let length = reader.u32_be()?;
+  if reader.array::<4>()? != *b"IHDR" || length != 13 {
+    return Err(ProbeError::Malformed("the first PNG chunk must be IHDR"));
+  }
+  let width = reader.u32_be()?;
+  let height = reader.u32_be()?;
+  let bit_depth = reader.u8()?;
+  let channels = match reader.u8()? {
+    0 | 3 => 1,
+    4 => 2,
+    2 => 3,
+    6 => 4,
+    _ => return Err(ProbeError::Malformed("unknown PNG color type")),
+  };
+  Ok(TextureMeta {
+    format: TextureCategories::PngFile,
+    width,
+    height,
+    bit_depth,
+    channels,
+  })
+}
+
+fn jpeg(data: &[u8]) -> Result<TextureMeta, ProbeError> {
+  let mut reader = Reader::new(data);
+  loop {
+    if reader.u8()? != 0xff {
+      return Err(ProbeError::Malformed("expected a JPEG marker"));
+    }
+    let mut marker = reader.u8()?;
+    // markers can be preceded by any number of fill bytes
+    while marker == 0xff {
+      marker = reader.u8()?;
+    }
+    match marker {
+      // standalone markers without a payload
+      0x01 | 0xd0..=0xd7 => continue,
+      0xd9 | 0xda => return Err(ProbeError::Malformed("no JPEG frame header")),
+      _ => {}
+    }
+    let length = reader.u16_be()? as usize;
+    if length < 2 {
+      return Err(ProbeError::Malformed("invalid JPEG segment length"));
+    }
+    // every start of frame marker except DHT, JPG and DAC
+    if matches!(marker, 0xc0..=0xcf) && !matches!(marker, 0xc4 | 0xc8 | 0xcc) {
+      let bit_depth = reader.u8()?;
+      let height = reader.u16_be()? as u32;
+      let width = reader.u16_be()? as u32;
+      let channels = reader.u8()?;
+      return Ok(TextureMeta {
+        format: TextureCategories::JpgFile,
+        width,
+        height,
+        bit_depth,
+        channels,
+      });
+    }
+    reader.skip(length - 2)?;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn png_header() {
+    let mut bytes = PNG_SIGNATURE.to_vec();
+    bytes.extend_from_slice(&13u32.to_be_bytes());
+    bytes.extend_from_slice(b"IHDR");
+    bytes.extend_from_slice(&640u32.to_be_bytes());
+    bytes.extend_from_slice(&480u32.to_be_bytes());
+    bytes.extend_from_slice(&[16, 6, 0, 0, 0]);
+    assert_eq!(
+      texture(&bytes),
+      Ok(TextureMeta {
+        format: TextureCategories::PngFile,
+        width: 640,
+        height: 480,
+        bit_depth: 16,
+        channels: 4,
+      })
+    );
+    assert_eq!(texture(&bytes[..20]), Err(ProbeError::Truncated));
+  }
+
+  #[test]
+  fn jpeg_header() {
+    let bytes = [
+      0xff, 0xd8, // SOI
+      0xff, 0xe0, 0x00, 0x04, 0x4a, 0x46, // APP0, truncated JFIF
+      0xff, 0xff, 0xc2, 0x00, 0x0b, 0x08, 0x01, 0x00, 0x02, 0x00, 0x03, // SOF2
+    ];
+    assert_eq!(
+      texture(&bytes),
+      Ok(TextureMeta {
+        format: TextureCategories::JpgFile,
+        width: 512,
+        height: 256,
+        bit_depth: 8,
+        channels: 3,
+      })
+    );
+    assert_eq!(texture(b"GIF89a"), Err(ProbeError::UnknownFormat));
+  }
+}
@@ -0,0 +1,361 @@
+use super::{ProbeError, Reader};
+use crate::categories::BinaryCategories;
+use parity_scale_codec::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+
+/// A named tensor of a model.
+#[derive(
+  Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo, Serialize, Deserialize,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct TensorInfo {
+  pub name: String,
+  /// Element type in safetensors notation (`F32`, `I64`, `BF16`...)
+  pub dtype: String,
+  /// Dimensions, `None` for dynamic (symbolic) dimensions
+  pub shape: Vec<Option<u64>>,
+}
+
+/// The interface of an ML model payload.
+#[derive(
+  Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo, Serialize, Deserialize,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct MlModelMeta {
+  pub format: BinaryCategories,
+  /// Graph inputs, ONNX only
+  pub inputs: Vec<TensorInfo>,
+  /// Graph outputs, ONNX only
+  pub outputs: Vec<TensorInfo>,
+  /// Stored tensors, safetensors only
+  pub tensors: Vec<TensorInfo>,
+  /// Number of operators in the graph, ONNX only
+  pub node_count: u32,
+  /// Producer name and version for ONNX, `__metadata__` entries for safetensors
+  pub metadata: Vec<(String, String)>,
+}
+
+impl MlModelMeta {
+  fn new(format: BinaryCategories) -> Self {
+    Self {
+      format,
+      inputs: Vec::new(),
+      outputs: Vec::new(),
+      tensors: Vec::new(),
+      node_count: 0,
+      metadata: Vec::new(),
+    }
+  }
+}
+
+/// Reads the tensors of a safetensors file or the graph interface of an ONNX model.
+///
+/// Only the header of safetensors files is needed, ONNX models are walked without decoding the
+/// weights.
+pub fn ml_model(bytes: &[u8], category: BinaryCategories) -> Result<MlModelMeta, ProbeError> {
+  match category {
+    BinaryCategories::SafeTensors => safetensors(bytes),
+    BinaryCategories::OnnxModel => onnx(bytes),
+    _ => Err(ProbeError::UnknownFormat),
+  }
+}
+
+#[derive(Deserialize)]
+struct SafeTensor {
+  dtype: String,
+  shape: Vec<u64>,
+  data_offsets: [u64; 2],
+}
+
+/// The safetensors header entries, in file order.
+struct SafeTensorsHeader(Vec<(String, serde_json::Value)>);
+
+impl<'de> Deserialize<'de> for SafeTensorsHeader {
+  fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    struct Visitor;
+
+    impl<'de> serde::de::Visitor<'de> for Visitor {
+      type Value = SafeTensorsHeader;
+
+      fn expecting(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "a safetensors header object")
+      }
+
+      fn visit_map<A: serde::de::MapAccess<'de>>(
+        self,
+        mut map: A,
+      ) -> Result<Self::Value, A::Error> {
+        let mut entries = Vec::new();
+        while let Some(entry) = map.next_entry()? {
+          entries.push(entry);
+        }
+        Ok(SafeTensorsHeader(entries))
+      }
+    }
+
+    deserializer.deserialize_map(Visitor)
+  }
+}
+
+fn safetensors(bytes: &[u8]) -> Result<MlModelMeta, ProbeError> {
+  let mut reader = Reader::new(bytes);
+  let header_len = u64::from_le_bytes(reader.array()?);
+  let header_len = usize::try_from(header_len).map_err(|_| ProbeError::Truncated)?;
+  let SafeTensorsHeader(header) = serde_json::from_slice(reader.bytes(header_len)?)
+    .map_err(|_| ProbeError::Malformed("invalid safetensors header"))?;
+
+  let mut meta = MlModelMeta::new(BinaryCategories::SafeTensors);
+  for (name, value) in header {
+    if name == "__metadata__" {
+      let metadata: std::collections::BTreeMap<String, String> = serde_json::from_value(value)
+        .map_err(|_| ProbeError::Malformed("invalid safetensors metadata"))?;
+      meta.metadata = metadata.into_iter().collect();
+      continue;
+    }
+    let tensor: SafeTensor = serde_json::from_value(value)
+      .map_err(|_| ProbeError::Malformed("invalid safetensors tensor"))?;
+    if tensor.data_offsets[0] > tensor.data_offsets[1] {
+      return Err(ProbeError::Malformed("invalid safetensors data offsets"));
+    }
+    meta.tensors.push(TensorInfo {
+      name,
+      dtype: tensor.dtype,
+      shape: tensor.shape.into_iter().map(Some).collect(),
+    });
+  }
+  Ok(meta)
+}
+
+/// A protobuf field value, groups are not supported as ONNX does not use them.
+enum Field<'a> {
+  Varint(u64),
+  Bytes(&'a [u8]),
+  Fixed,
+}
+
+fn varint(reader: &mut Reader) -> Result<u64, ProbeError> {
+  let mut value = 0u64;
+  for shift in (0..64).step_by(7) {
+    let byte = reader.u8()?;
+    value |= ((byte & 0x7f) as u64) << shift;
+    if byte & 0x80 == 0 {
+      return Ok(value);
+    }
+  }
+  Err(ProbeError::Malformed("varint too long"))
+}
+
+/// Walks the fields of a protobuf message.
+fn fields<'a>(
+  data: &'a [u8],
+  mut visit: impl FnMut(u64, Field<'a>) -> Result<(), ProbeError>,
+) -> Result<(), ProbeError> {
+  let mut reader = Reader::new(data);
+  while reader.pos < data.len() {
+    let key = varint(&mut reader)?;
+    let field = match key & 7 {
+      0 => Field::Varint(varint(&mut reader)?),
+      1 => {
+        reader.skip(8)?;
+        Field::Fixed
+      }
+      2 => {
+        let len = varint(&mut reader)?;
+        Field::Bytes(reader.bytes(usize::try_from(len).map_err(|_| ProbeError::Truncated)?)?)
+      }
+      5 => {
+        reader.skip(4)?;
+        Field::Fixed
+      }
+      _ => return Err(ProbeError::Malformed("unsupported protobuf wire type")),
+    };
+    visit(key >> 3, field)?;
+  }
+  Ok(())
+}
+
+fn string(bytes: &[u8]) -> Result<String, ProbeError> {
+  core::str::from_utf8(bytes)
+    .map(|s| s.to_string())
+    .map_err(|_| ProbeError::Malformed("invalid UTF-8 string"))
+}
+
+fn onnx_dtype(elem_type: u64) -> String {
+  match elem_type {
+    1 => "F32",
+    2 => "U8",
+    3 => "I8",
+    4 => "U16",
+    5 => "I16",
+    6 => "I32",
+    7 => "I64",
+    8 => "STRING",
+    9 => "BOOL",
+    10 => "F16",
+    11 => "F64",
+    12 => "U32",
+    13 => "U64",
+    14 => "C64",
+    15 => "C128",
+    16 => "BF16",
+    _ => "UNKNOWN",
+  }
+  .to_string()
+}
+
+/// Parses a `ValueInfoProto`, non-tensor values get an empty dtype and shape.
+fn onnx_value_info(data: &[u8]) -> Result<TensorInfo, ProbeError> {
+  let mut info = TensorInfo {
+    name: String::new(),
+    dtype: String::new(),
+    shape: Vec::new(),
+  };
+  fields(data, |number, field| {
+    match (number, field) {
+      (1, Field::Bytes(name)) => info.name = string(name)?,
+      // TypeProto
+      (2, Field::Bytes(type_proto)) => fields(type_proto, |number, field| {
+        if let (1, Field::Bytes(tensor)) = (number, field) {
+          fields(tensor, |number, field| {
+            match (number, field) {
+              (1, Field::Varint(elem_type)) => info.dtype = onnx_dtype(elem_type),
+              // TensorShapeProto
+              (2, Field::Bytes(shape)) => fields(shape, |number, field| {
+                if let (1, Field::Bytes(dim)) = (number, field) {
+                  let mut value = None;
+                  fields(dim, |number, field| {
+                    if let (1, Field::Varint(v)) = (number, field) {
+                      value = Some(v);
+                    }
+                    Ok(())
+                  })?;
+                  info.shape.push(value);
+                }
+                Ok(())
+              })?,
+              _ => {}
+            }
+            Ok(())
+          })?;
+        }
+        Ok(())
+      })?,
+      _ => {}
+    }
+    Ok(())
+  })?;
+  Ok(info)
+}
+
+fn onnx(bytes: &[u8]) -> Result<MlModelMeta, ProbeError> {
+  let mut meta = MlModelMeta::new(BinaryCategories::OnnxModel);
+  let mut has_graph = false;
+  fields(bytes, |number, field| {
+    match (number, field) {
+      (2, Field::Bytes(name)) => meta.metadata.push(("producer".to_string(), string(name)?)),
+      (3, Field::Bytes(version)) => meta
+        .metadata
+        .push(("producerVersion".to_string(), string(version)?)),
+      // GraphProto
+      (7, Field::Bytes(graph)) => {
+        has_graph = true;
+        fields(graph, |number, field| {
+          match (number, field) {
+            (1, Field::Bytes(_)) => meta.node_count += 1,
+            (11, Field::Bytes(input)) => meta.inputs.push(onnx_value_info(input)?),
+            (12, Field::Bytes(output)) => meta.outputs.push(onnx_value_info(output)?),
+            _ => {}
+          }
+          Ok(())
+        })?
+      }
+      _ => {}
+    }
+    Ok(())
+  })?;
+  if !has_graph {
+    return Err(ProbeError::Malformed("ONNX model without a graph"));
+  }
+  Ok(meta)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn safetensors_header() {
+    let header = br#"{"weight":{"dtype":"F16","shape":[2,3],"data_offsets":[0,12]},"__metadata__":{"format":"pt"},"bias":{"dtype":"F32","shape":[3],"data_offsets":[12,24]}}"#;
+    let mut bytes = (header.len() as u64).to_le_bytes().to_vec();
+    bytes.extend_from_slice(header);
+
+    let meta = ml_model(&bytes, BinaryCategories::SafeTensors).unwrap();
+    assert_eq!(
+      meta.metadata,
+      vec![("format".to_string(), "pt".to_string())]
+    );
+    assert_eq!(
+      meta.tensors,
+      vec![
+        TensorInfo {
+          name: "weight".to_string(),
+          dtype: "F16".to_string(),
+          shape: vec![Some(2), Some(3)],
+        },
+        TensorInfo {
+          name: "bias".to_string(),
+          dtype: "F32".to_string(),
+          shape: vec![Some(3)],
+        },
+      ]
+    );
+    assert_eq!(
+      ml_model(&bytes[..20], BinaryCategories::SafeTensors),
+      Err(ProbeError::Truncated)
+    );
+    assert_eq!(
+      ml_model(&bytes, BinaryCategories::BlendFile),
+      Err(ProbeError::UnknownFormat)
+    );
+  }
+
+  fn message(number: u8, data: &[u8]) -> Vec<u8> {
+    let mut bytes = vec![number << 3 | 2, data.len() as u8];
+    bytes.extend_from_slice(data);
+    bytes
+  }
+
+  #[test]
+  fn onnx_graph() {
+    // input "x": float tensor of shape [N, 4]
+    let dims = [message(1, &message(2, b"N")), message(1, &[0x08, 4])].concat();
+    let tensor = [vec![0x08, 1], message(2, &dims)].concat();
+    let input = [message(1, b"x"), message(2, &message(1, &tensor))].concat();
+    let output = message(1, b"y");
+    let graph = [
+      message(1, b"relu"),
+      message(1, b"add"),
+      message(11, &input),
+      message(12, &output),
+    ]
+    .concat();
+    let model = [vec![0x08, 8], message(2, b"pytorch"), message(7, &graph)].concat();
+
+    let meta = ml_model(&model, BinaryCategories::OnnxModel).unwrap();
+    assert_eq!(meta.node_count, 2);
+    assert_eq!(
+      meta.metadata,
+      vec![("producer".to_string(), "pytorch".to_string())]
+    );
+    assert_eq!(
+      meta.inputs,
+      vec![TensorInfo {
+        name: "x".to_string(),
+        dtype: "F32".to_string(),
+        shape: vec![None, Some(4)],
+      }]
+    );
+    assert_eq!(meta.outputs[0].name, "y");
+    assert!(ml_model(&[0x08, 8], BinaryCategories::OnnxModel).is_err());
+  }
+}
@@ -0,0 +1,95 @@
+//! Header-only probing of proto payloads, extracting the metadata listings and players need
+//! without decoding the whole file or pulling in format-specific crates.
+
+mod audio;
+mod gltf;
+mod image;
+mod ml;
+
+pub use self::audio::{audio, AudioMeta};
+pub use self::gltf::{model_gltf, GltfError, ModelMeta};
+pub use self::image::{texture, TextureMeta};
+pub use self::ml::{ml_model, MlModelMeta, TensorInfo};
+
+use crate::categories::{AudioCategories, Categories, ModelCategories, TextureCategories};
+
+/// Errors returned when probing a payload.
+#[derive(Copy, Clone, PartialEq, Debug, Eq)]
+pub enum ProbeError {
+  /// The payload is not in any of the formats of the category
+  UnknownFormat,
+  /// The payload ends before the metadata
+  Truncated,
+  /// The headers are inconsistent
+  Malformed(&'static str),
+}
+
+impl core::fmt::Display for ProbeError {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    match self {
+      Self::UnknownFormat => write!(f, "unknown format"),
+      Self::Truncated => write!(f, "truncated payload"),
+      Self::Malformed(what) => write!(f, "malformed payload: {}", what),
+    }
+  }
+}
+
+impl std::error::Error for ProbeError {}
+
+/// The category of `bytes` guessed from their magic bytes, for the formats with one.
+pub fn sniff(bytes: &[u8]) -> Option<Categories> {
+  if bytes.starts_with(image::PNG_SIGNATURE) {
+    Some(Categories::Texture(TextureCategories::PngFile))
+  } else if bytes.starts_with(image::JPEG_SOI) {
+    Some(Categories::Texture(TextureCategories::JpgFile))
+  } else if bytes.starts_with(gltf::GLB_MAGIC) {
+    Some(Categories::Model(ModelCategories::GltfFile))
+  } else if bytes.starts_with(audio::OGG_CAPTURE) {
+    Some(Categories::Audio(AudioCategories::OggFile))
+  } else if bytes.starts_with(b"ID3") || matches!(bytes, [0xff, b, ..] if b & 0xe0 == 0xe0) {
+    Some(Categories::Audio(AudioCategories::Mp3File))
+  } else {
+    None
+  }
+}
+
+/// A cursor over a byte slice failing with `ProbeError::Truncated` when reading past the end.
+struct Reader<'a> {
+  data: &'a [u8],
+  pos: usize,
+}
+
+impl<'a> Reader<'a> {
+  fn new(data: &'a [u8]) -> Self {
+    Self { data, pos: 0 }
+  }
+
+  fn bytes(&mut self, len: usize) -> Result<&'a [u8], ProbeError> {
+    let end = self.pos.checked_add(len).ok_or(ProbeError::Truncated)?;
+    let bytes = self.data.get(self.pos..end).ok_or(ProbeError::Truncated)?;
+    self.pos = end;
+    Ok(bytes)
+  }
+
+  fn array<const N: usize>(&mut self) -> Result<[u8; N], ProbeError> {
+    let mut array = [0; N];
+    array.copy_from_slice(self.bytes(N)?);
+    Ok(array)
+  }
+
+  fn skip(&mut self, len: usize) -> Result<(), ProbeError> {
+    self.bytes(len).map(|_| ())
+  }
+
+  fn u8(&mut self) -> Result<u8, ProbeError> {
+    Ok(self.array::<1>()?[0])
+  }
+
+  fn u16_be(&mut self) -> Result<u16, ProbeError> {
+    Ok(u16::from_be_bytes(self.array()?))
+  }
+
+  fn u32_be(&mut self) -> Result<u32, ProbeError> {
+    Ok(u32::from_be_bytes(self.array()?))
+  }
+}
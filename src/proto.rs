@@ -0,0 +1,386 @@
+use crate::{categories::Categories, fragments::Hash256};
+use parity_scale_codec::{Compact, Decode, Encode, Error, Input, MaxEncodedLen};
+use scale_info::prelude::{string::String, vec::Vec};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Struct representing a Proto-Fragment as stored on chain, without its data.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct Proto<TAccountId, TBlockNumber> {
+  /// The block number at which the proto was uploaded
+  pub block: TBlockNumber,
+  /// The account that uploaded the proto
+  pub creator: TAccountId,
+  /// The current owner of the proto
+  pub owner: TAccountId,
+  /// The hashes of the protos this proto references
+  pub references: Vec<Hash256>,
+  /// The category of the proto
+  pub category: Categories,
+  /// The tags attached to the proto
+  pub tags: Vec<String>,
+}
+
+impl<TAccountId, TBlockNumber> Proto<TAccountId, TBlockNumber> {
+  /// Canonicalizes the category, see [`Categories::canonicalize`], before hashing or signing the
+  /// proto.
+  pub fn canonicalize(&mut self) {
+    self.category.canonicalize();
+  }
+}
+
+/// The information about a proto needed to reason about it without its data.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct ProtoInfo<THash, TAccountId> {
+  /// The hash of the proto
+  pub hash: THash,
+  /// The current owner of the proto
+  pub owner: TAccountId,
+  /// The category of the proto
+  pub category: Categories,
+  /// The tags attached to the proto
+  pub tags: Vec<String>,
+  /// The protos referenced by this proto, for bundles these are the protos bundled
+  pub references: Vec<THash>,
+}
+
+/// A sorted set of at most `TagSet::MAX_TAGS` tags, each at most `TagSet::MAX_TAG_LEN` bytes long.
+#[derive(Encode, Clone, PartialEq, Debug, Eq, Default, scale_info::TypeInfo)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(transparent))]
+pub struct TagSet(Vec<String>);
+
+/// Errors returned when building a `TagSet`.
+#[derive(Clone, PartialEq, Debug, Eq)]
+pub enum TagSetError {
+  /// There are more than `TagSet::MAX_TAGS` distinct tags
+  TooManyTags(usize),
+  /// The tag is longer than `TagSet::MAX_TAG_LEN` bytes
+  TagTooLong(String),
+}
+
+impl core::fmt::Display for TagSetError {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    match self {
+      Self::TooManyTags(len) => write!(f, "{} tags, at most {} are allowed", len, TagSet::MAX_TAGS),
+      Self::TagTooLong(tag) => write!(f, "tag {:?} is too long", tag),
+    }
+  }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TagSetError {}
+
+impl TagSet {
+  /// The largest amount of tags of a set.
+  pub const MAX_TAGS: usize = 16;
+  /// The longest tag, in bytes.
+  pub const MAX_TAG_LEN: usize = 32;
+
+  /// The set of `tags`, skipping tags that are too long and keeping the first `MAX_TAGS` of the
+  /// others, in sorted order.
+  pub fn from_lossy<'a>(tags: impl IntoIterator<Item = &'a String>) -> Self {
+    let mut tags: Vec<String> = tags
+      .into_iter()
+      .filter(|tag| tag.len() <= Self::MAX_TAG_LEN)
+      .cloned()
+      .collect();
+    tags.sort();
+    tags.dedup();
+    tags.truncate(Self::MAX_TAGS);
+    Self(tags)
+  }
+
+  /// Whether `tag` is in the set.
+  pub fn contains(&self, tag: &str) -> bool {
+    self.0.binary_search_by(|t| t.as_str().cmp(tag)).is_ok()
+  }
+
+  /// The tags, sorted.
+  pub fn as_slice(&self) -> &[String] {
+    &self.0
+  }
+
+  pub fn len(&self) -> usize {
+    self.0.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.0.is_empty()
+  }
+}
+
+impl TryFrom<Vec<String>> for TagSet {
+  type Error = TagSetError;
+
+  /// Sorts and deduplicates `tags`, failing if any is too long or if there are too many.
+  fn try_from(mut tags: Vec<String>) -> Result<Self, TagSetError> {
+    if let Some(tag) = tags.iter().find(|tag| tag.len() > Self::MAX_TAG_LEN) {
+      return Err(TagSetError::TagTooLong(tag.clone()));
+    }
+    tags.sort();
+    tags.dedup();
+    if tags.len() > Self::MAX_TAGS {
+      return Err(TagSetError::TooManyTags(tags.len()));
+    }
+    Ok(Self(tags))
+  }
+}
+
+impl Decode for TagSet {
+  fn decode<I: Input>(input: &mut I) -> Result<Self, Error> {
+    let len = Compact::<u32>::decode(input)?.0 as usize;
+    if len > Self::MAX_TAGS {
+      return Err("Too many tags".into());
+    }
+    let mut tags = Vec::with_capacity(len);
+    for _ in 0..len {
+      let tag = String::decode(input)?;
+      if tag.len() > Self::MAX_TAG_LEN {
+        return Err("Tag too long".into());
+      }
+      if tags.last().is_some_and(|last| *last >= tag) {
+        return Err("Tags not sorted".into());
+      }
+      tags.push(tag);
+    }
+    Ok(Self(tags))
+  }
+}
+
+impl MaxEncodedLen for TagSet {
+  fn max_encoded_len() -> usize {
+    Compact::<u32>::max_encoded_len()
+      + Self::MAX_TAGS * (Compact::<u32>::max_encoded_len() + Self::MAX_TAG_LEN)
+  }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for TagSet {
+  fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    let tags = <Vec<String> as Deserialize>::deserialize(deserializer)?;
+    Self::try_from(tags).map_err(serde::de::Error::custom)
+  }
+}
+
+/// The metadata of a proto shown in listings.
+#[derive(Clone, PartialEq, Debug, Eq, Default)]
+pub struct ListingMetadata<THash> {
+  /// The title of the proto
+  pub title: Option<String>,
+  /// The proto to use as thumbnail
+  pub thumbnail: Option<THash>,
+}
+
+/// A small record of a proto for list RPCs and caches, bounded by its `MaxEncodedLen`.
+#[derive(Encode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "camelCase"))]
+pub struct ProtoSummary<THash, TAccountId> {
+  /// The hash of the proto
+  pub hash: THash,
+  /// The category of the proto without its payload, see [`Categories::stable_id`]
+  pub category_kind: u16,
+  /// The tags of the proto
+  pub tags: TagSet,
+  /// The current owner of the proto
+  pub owner: TAccountId,
+  /// The title of the proto, at most `ProtoSummary::MAX_TITLE_LEN` bytes long
+  pub title: Option<String>,
+  /// The proto to use as thumbnail
+  pub thumbnail: Option<THash>,
+}
+
+impl<THash, TAccountId> ProtoSummary<THash, TAccountId> {
+  /// The longest title, in bytes.
+  pub const MAX_TITLE_LEN: usize = 128;
+
+  fn check_title(self) -> Result<Self, &'static str> {
+    match &self.title {
+      Some(title) if title.len() > Self::MAX_TITLE_LEN => Err("Proto title too long"),
+      _ => Ok(self),
+    }
+  }
+}
+
+/// Truncates `title` to `ProtoSummary::MAX_TITLE_LEN` bytes, on a character boundary.
+fn truncate_title(mut title: String) -> String {
+  let max = ProtoSummary::<(), ()>::MAX_TITLE_LEN;
+  if title.len() > max {
+    let end = (0..=max)
+      .rev()
+      .find(|i| title.is_char_boundary(*i))
+      .unwrap_or(0);
+    title.truncate(end);
+  }
+  title
+}
+
+impl<THash, TAccountId: Clone, TBlockNumber>
+  From<(
+    THash,
+    &Proto<TAccountId, TBlockNumber>,
+    ListingMetadata<THash>,
+  )> for ProtoSummary<THash, TAccountId>
+{
+  /// The listing of the proto `hash`, tags and title beyond the bounds of the listing are dropped
+  /// or truncated.
+  fn from(
+    (hash, proto, metadata): (
+      THash,
+      &Proto<TAccountId, TBlockNumber>,
+      ListingMetadata<THash>,
+    ),
+  ) -> Self {
+    Self {
+      hash,
+      category_kind: proto.category.stable_id(),
+      tags: TagSet::from_lossy(&proto.tags),
+      owner: proto.owner.clone(),
+      title: metadata.title.map(truncate_title),
+      thumbnail: metadata.thumbnail,
+    }
+  }
+}
+
+impl<THash: Decode, TAccountId: Decode> Decode for ProtoSummary<THash, TAccountId> {
+  fn decode<I: Input>(input: &mut I) -> Result<Self, Error> {
+    Self {
+      hash: Decode::decode(input)?,
+      category_kind: Decode::decode(input)?,
+      tags: Decode::decode(input)?,
+      owner: Decode::decode(input)?,
+      title: Decode::decode(input)?,
+      thumbnail: Decode::decode(input)?,
+    }
+    .check_title()
+    .map_err(Into::into)
+  }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, THash: Deserialize<'de>, TAccountId: Deserialize<'de>> Deserialize<'de>
+  for ProtoSummary<THash, TAccountId>
+{
+  fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Listing<THash, TAccountId> {
+      hash: THash,
+      category_kind: u16,
+      tags: TagSet,
+      owner: TAccountId,
+      title: Option<String>,
+      thumbnail: Option<THash>,
+    }
+
+    let listing = Listing::deserialize(deserializer)?;
+    ProtoSummary {
+      hash: listing.hash,
+      category_kind: listing.category_kind,
+      tags: listing.tags,
+      owner: listing.owner,
+      title: listing.title,
+      thumbnail: listing.thumbnail,
+    }
+    .check_title()
+    .map_err(serde::de::Error::custom)
+  }
+}
+
+impl<THash: MaxEncodedLen, TAccountId: MaxEncodedLen> MaxEncodedLen
+  for ProtoSummary<THash, TAccountId>
+{
+  fn max_encoded_len() -> usize {
+    THash::max_encoded_len()
+      + u16::max_encoded_len()
+      + TagSet::max_encoded_len()
+      + TAccountId::max_encoded_len()
+      + 1
+      + Compact::<u32>::max_encoded_len()
+      + Self::MAX_TITLE_LEN
+      + Option::<THash>::max_encoded_len()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::categories::TextureCategories;
+
+  fn proto(tags: Vec<String>) -> Proto<[u8; 32], u32> {
+    Proto {
+      block: 1,
+      creator: [1; 32],
+      owner: [2; 32],
+      references: vec![],
+      category: Categories::Texture(TextureCategories::PngFile),
+      tags,
+    }
+  }
+
+  #[test]
+  fn tag_sets() {
+    let tags = TagSet::try_from(vec!["meme".to_string(), "cat".into(), "meme".into()]).unwrap();
+    assert_eq!(tags.as_slice(), ["cat", "meme"]);
+    assert!(tags.contains("cat") && !tags.contains("dog"));
+    assert_eq!(
+      TagSet::try_from(vec!["a".repeat(33)]),
+      Err(TagSetError::TagTooLong("a".repeat(33)))
+    );
+    let many: Vec<String> = (0..17).map(|i| i.to_string()).collect();
+    assert_eq!(
+      TagSet::try_from(many.clone()),
+      Err(TagSetError::TooManyTags(17))
+    );
+    assert_eq!(TagSet::from_lossy(&many).len(), TagSet::MAX_TAGS);
+
+    let encoded = tags.encode();
+    assert_eq!(TagSet::decode(&mut &encoded[..]), Ok(tags.clone()));
+    let unsorted = vec!["meme".to_string(), "cat".into()].encode();
+    assert!(TagSet::decode(&mut &unsorted[..]).is_err());
+    assert_eq!(serde_json::to_string(&tags).unwrap(), r#"["cat","meme"]"#);
+    assert!(serde_json::from_str::<TagSet>(&format!(r#"["{}"]"#, "a".repeat(33))).is_err());
+  }
+
+  #[test]
+  fn listings() {
+    let mut tags: Vec<String> = (0..20).map(|i| format!("tag{:02}", i)).collect();
+    tags.push("a".repeat(40));
+    let metadata = ListingMetadata {
+      title: Some("é".repeat(100)),
+      thumbnail: Some([3; 32]),
+    };
+    let listing = ProtoSummary::from(([9; 32], &proto(tags), metadata));
+    assert_eq!(listing.category_kind, 0x0400);
+    assert_eq!(listing.owner, [2; 32]);
+    assert_eq!(listing.tags.len(), TagSet::MAX_TAGS);
+    assert_eq!(listing.tags.as_slice()[0], "tag00");
+    assert_eq!(listing.title.as_ref().unwrap().len(), 128);
+
+    let encoded = listing.encode();
+    assert!(encoded.len() <= ProtoSummary::<[u8; 32], [u8; 32]>::max_encoded_len());
+    assert_eq!(ProtoSummary::decode(&mut &encoded[..]), Ok(listing.clone()));
+    let mut long = listing.clone();
+    long.title = Some("a".repeat(129));
+    assert!(ProtoSummary::<[u8; 32], [u8; 32]>::decode(&mut &long.encode()[..]).is_err());
+
+    let json = serde_json::to_string(&listing).unwrap();
+    assert!(json.contains(r#""categoryKind":1024"#));
+    assert_eq!(
+      serde_json::from_str::<ProtoSummary<[u8; 32], [u8; 32]>>(&json).unwrap(),
+      listing
+    );
+    let json = serde_json::to_string(&long).unwrap();
+    assert!(serde_json::from_str::<ProtoSummary<[u8; 32], [u8; 32]>>(&json).is_err());
+  }
+}
@@ -0,0 +1,382 @@
+use core::fmt;
+use parity_scale_codec::{Decode, Encode};
+use scale_info::prelude::{string::String, vec::Vec};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A single upstream work a derivative proto is based on.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct Attribution<THash> {
+  /// The upstream proto
+  pub upstream: THash,
+  /// The share of the derivative attributed to the upstream proto, in percent
+  pub weight: u8,
+  /// The proto holding the license the upstream proto was used under, if any
+  pub license: Option<THash>,
+}
+
+/// The upstream protos a derivative proto is based on.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct AttributionChain<THash> {
+  pub hops: Vec<Attribution<THash>>,
+}
+
+/// Errors returned when an attribution chain is inconsistent.
+#[derive(Clone, PartialEq, Debug, Eq)]
+pub enum AttributionError<THash> {
+  /// The weights add up to more than 100%
+  WeightOverflow { total: u32 },
+  /// The same upstream proto is attributed twice
+  DuplicateUpstream(THash),
+  /// Following upstream attributions leads back to a proto already visited, the chain is returned
+  Cycle(Vec<THash>),
+}
+
+impl<THash: fmt::Debug> fmt::Display for AttributionError<THash> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::WeightOverflow { total } => write!(f, "attribution weights add up to {}%", total),
+      Self::DuplicateUpstream(hash) => write!(f, "upstream {:?} is attributed twice", hash),
+      Self::Cycle(chain) => write!(f, "circular attribution {:?}", chain),
+    }
+  }
+}
+
+#[cfg(feature = "std")]
+impl<THash: fmt::Debug> std::error::Error for AttributionError<THash> {}
+
+impl<THash: Clone + PartialEq> AttributionChain<THash> {
+  /// The sum of the weights of every hop, in percent.
+  pub fn total_weight(&self) -> u32 {
+    self.hops.iter().map(|hop| hop.weight as u32).sum()
+  }
+
+  /// Checks the chain of the proto `subject`.
+  ///
+  /// `resolve` returns the attribution chain of upstream protos, it is followed to detect cycles.
+  pub fn validate(
+    &self,
+    subject: &THash,
+    resolve: impl Fn(&THash) -> Option<AttributionChain<THash>>,
+  ) -> Result<(), AttributionError<THash>> {
+    let total = self.total_weight();
+    if total > 100 {
+      return Err(AttributionError::WeightOverflow { total });
+    }
+    for (i, hop) in self.hops.iter().enumerate() {
+      if self.hops[..i]
+        .iter()
+        .any(|other| other.upstream == hop.upstream)
+      {
+        return Err(AttributionError::DuplicateUpstream(hop.upstream.clone()));
+      }
+    }
+
+    let mut path = Vec::new();
+    path.push(subject.clone());
+    self.find_cycle(&resolve, &mut path)
+  }
+
+  fn find_cycle(
+    &self,
+    resolve: &impl Fn(&THash) -> Option<AttributionChain<THash>>,
+    path: &mut Vec<THash>,
+  ) -> Result<(), AttributionError<THash>> {
+    for hop in &self.hops {
+      if path.contains(&hop.upstream) {
+        let mut cycle = path.clone();
+        cycle.push(hop.upstream.clone());
+        return Err(AttributionError::Cycle(cycle));
+      }
+      if let Some(upstream) = resolve(&hop.upstream) {
+        path.push(hop.upstream.clone());
+        upstream.find_cycle(resolve, path)?;
+        path.pop();
+      }
+    }
+    Ok(())
+  }
+}
+
+/// Who created a proto, when, and with what.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct Provenance<TAccountId, TBlockNumber> {
+  /// The account of the author
+  pub author: TAccountId,
+  /// The block at which the proto was created
+  pub created_at_block: TBlockNumber,
+  /// The tool used to create the proto (e.g. "blender 3.4"), if disclosed
+  pub tool: Option<String>,
+  /// The signature of the author over the provenance `message`
+  pub signature: Option<Vec<u8>>,
+}
+
+impl<TAccountId: Encode, TBlockNumber: Encode> Provenance<TAccountId, TBlockNumber> {
+  /// The bytes signed by the author: every field except the signature.
+  pub fn message(&self) -> Vec<u8> {
+    (&self.author, &self.created_at_block, &self.tool).encode()
+  }
+
+  pub fn is_signed(&self) -> bool {
+    self.signature.is_some()
+  }
+}
+
+/// A single patch applied to the data of a proto.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct PatchEntry<THash, TBlockNumber> {
+  /// The block at which the patch was applied
+  pub at_block: TBlockNumber,
+  /// The hash of the data after the patch
+  pub data_hash: THash,
+  /// The hash of the release notes of the patch, if any
+  pub note_hash: Option<THash>,
+}
+
+/// The ordered list of patches applied to a proto, oldest first.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct PatchHistory<THash, TBlockNumber> {
+  pub entries: Vec<PatchEntry<THash, TBlockNumber>>,
+}
+
+impl<THash, TBlockNumber> Default for PatchHistory<THash, TBlockNumber> {
+  fn default() -> Self {
+    Self {
+      entries: Vec::new(),
+    }
+  }
+}
+
+/// Errors returned when a patch history is not a valid timeline.
+#[derive(Copy, Clone, PartialEq, Debug, Eq)]
+pub enum PatchError {
+  /// The patch at this index was applied before the previous one
+  OutOfOrder(usize),
+  /// The patch at this index does not change the data of the previous one
+  UnchangedData(usize),
+}
+
+impl fmt::Display for PatchError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::OutOfOrder(index) => write!(f, "patch {} precedes the previous patch", index),
+      Self::UnchangedData(index) => write!(f, "patch {} does not change the data", index),
+    }
+  }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PatchError {}
+
+impl<THash: PartialEq, TBlockNumber: PartialOrd> PatchHistory<THash, TBlockNumber> {
+  /// The most recent patch, if any.
+  pub fn latest(&self) -> Option<&PatchEntry<THash, TBlockNumber>> {
+    self.entries.last()
+  }
+
+  /// Appends a patch, checking it follows the latest one.
+  pub fn append(&mut self, entry: PatchEntry<THash, TBlockNumber>) -> Result<(), PatchError> {
+    if let Some(latest) = self.latest() {
+      Self::check_follows(latest, &entry, self.entries.len())?;
+    }
+    self.entries.push(entry);
+    Ok(())
+  }
+
+  /// Checks that patches are ordered by block and every patch changes the data.
+  pub fn validate(&self) -> Result<(), PatchError> {
+    self
+      .entries
+      .windows(2)
+      .enumerate()
+      .try_for_each(|(i, pair)| Self::check_follows(&pair[0], &pair[1], i + 1))
+  }
+
+  fn check_follows(
+    previous: &PatchEntry<THash, TBlockNumber>,
+    next: &PatchEntry<THash, TBlockNumber>,
+    index: usize,
+  ) -> Result<(), PatchError> {
+    if next.at_block < previous.at_block {
+      return Err(PatchError::OutOfOrder(index));
+    }
+    if next.data_hash == previous.data_hash {
+      return Err(PatchError::UnchangedData(index));
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn hop(upstream: u32, weight: u8) -> Attribution<u32> {
+    Attribution {
+      upstream,
+      weight,
+      license: None,
+    }
+  }
+
+  fn resolve(proto: &u32) -> Option<AttributionChain<u32>> {
+    match proto {
+      2 => Some(AttributionChain {
+        hops: vec![hop(3, 50)],
+      }),
+      3 => Some(AttributionChain { hops: vec![] }),
+      // 4 -> 5 -> 4
+      4 => Some(AttributionChain {
+        hops: vec![hop(5, 10)],
+      }),
+      5 => Some(AttributionChain {
+        hops: vec![hop(4, 10)],
+      }),
+      _ => None,
+    }
+  }
+
+  #[test]
+  fn valid_chain() {
+    let chain = AttributionChain {
+      hops: vec![
+        hop(2, 60),
+        Attribution {
+          upstream: 3,
+          weight: 40,
+          license: Some(9),
+        },
+      ],
+    };
+    assert_eq!(chain.total_weight(), 100);
+    assert_eq!(chain.validate(&1, resolve), Ok(()));
+
+    let encoded = chain.encode();
+    assert_eq!(
+      AttributionChain::decode(&mut encoded.as_slice()).unwrap(),
+      chain
+    );
+    let json = serde_json::to_string(&chain).unwrap();
+    assert_eq!(
+      serde_json::from_str::<AttributionChain<u32>>(&json).unwrap(),
+      chain
+    );
+  }
+
+  #[test]
+  fn invalid_chains() {
+    let chain = AttributionChain {
+      hops: vec![hop(2, 60), hop(3, 41)],
+    };
+    assert_eq!(
+      chain.validate(&1, resolve),
+      Err(AttributionError::WeightOverflow { total: 101 })
+    );
+
+    let chain = AttributionChain {
+      hops: vec![hop(2, 10), hop(2, 10)],
+    };
+    assert_eq!(
+      chain.validate(&1, resolve),
+      Err(AttributionError::DuplicateUpstream(2))
+    );
+
+    let chain = AttributionChain {
+      hops: vec![hop(3, 10)],
+    };
+    assert_eq!(
+      chain.validate(&3, resolve),
+      Err(AttributionError::Cycle(vec![3, 3]))
+    );
+
+    let chain = AttributionChain {
+      hops: vec![hop(4, 10)],
+    };
+    assert_eq!(
+      chain.validate(&1, resolve),
+      Err(AttributionError::Cycle(vec![1, 4, 5, 4]))
+    );
+  }
+
+  #[test]
+  fn provenance_message() {
+    let provenance = Provenance {
+      author: [1u8; 32],
+      created_at_block: 42u32,
+      tool: Some("blender 3.4".to_string()),
+      signature: None,
+    };
+    assert!(!provenance.is_signed());
+
+    let signed = Provenance {
+      signature: Some(vec![9; 64]),
+      ..provenance.clone()
+    };
+    assert!(signed.is_signed());
+    assert_eq!(signed.message(), provenance.message());
+
+    let encoded = signed.encode();
+    assert_eq!(Provenance::decode(&mut encoded.as_slice()).unwrap(), signed);
+    let json = serde_json::to_string(&signed).unwrap();
+    assert!(json.contains("\"createdAtBlock\":42"));
+    assert_eq!(
+      serde_json::from_str::<Provenance<[u8; 32], u32>>(&json).unwrap(),
+      signed
+    );
+  }
+
+  #[test]
+  fn patch_history() {
+    let patch = |at_block, data_hash| PatchEntry {
+      at_block,
+      data_hash,
+      note_hash: None,
+    };
+
+    let mut history = PatchHistory::<u32, u32>::default();
+    assert_eq!(history.append(patch(10, 1)), Ok(()));
+    assert_eq!(history.append(patch(10, 2)), Ok(()));
+    assert_eq!(history.append(patch(9, 3)), Err(PatchError::OutOfOrder(2)));
+    assert_eq!(
+      history.append(patch(11, 2)),
+      Err(PatchError::UnchangedData(2))
+    );
+    assert_eq!(history.latest().unwrap().data_hash, 2);
+    assert_eq!(history.validate(), Ok(()));
+
+    history.entries.push(patch(5, 4));
+    assert_eq!(history.validate(), Err(PatchError::OutOfOrder(2)));
+
+    let encoded = history.encode();
+    assert_eq!(
+      PatchHistory::decode(&mut encoded.as_slice()).unwrap(),
+      history
+    );
+  }
+}
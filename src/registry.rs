@@ -0,0 +1,301 @@
+use crate::{categories::ShardsTrait, fragments::Hash256, traits::Trait};
+use parity_scale_codec::{Compact, Decode, Encode, Error};
+use scale_info::prelude::{collections::BTreeMap, vec::Vec};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Whether a trait should still be implemented.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, Default, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase", rename_all_fields = "camelCase")
+)]
+pub enum TraitStatus {
+  /// The trait can be implemented
+  #[default]
+  Active,
+  /// The trait should no longer be implemented
+  Deprecated {
+    /// The trait replacing this one, if any
+    superseded_by: Option<ShardsTrait>,
+    /// The hash of a proto explaining the deprecation, if any
+    note_hash: Option<Hash256>,
+  },
+}
+
+impl TraitStatus {
+  pub fn is_deprecated(&self) -> bool {
+    matches!(self, Self::Deprecated { .. })
+  }
+
+  /// The trait to implement instead, if the trait is deprecated in favor of another one.
+  pub fn superseded_by(&self) -> Option<&ShardsTrait> {
+    match self {
+      Self::Active => None,
+      Self::Deprecated { superseded_by, .. } => superseded_by.as_ref(),
+    }
+  }
+}
+
+/// A collection of known traits indexed by their `ShardsTrait` hash.
+#[derive(Clone, PartialEq, Debug, Eq, Default)]
+pub struct TraitRegistry {
+  traits: BTreeMap<ShardsTrait, Trait>,
+  statuses: BTreeMap<ShardsTrait, TraitStatus>,
+}
+
+impl TraitRegistry {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Registers `trait_` under `hash`, returning the trait previously registered under the same hash if any.
+  pub fn insert(&mut self, hash: ShardsTrait, trait_: Trait) -> Option<Trait> {
+    self.traits.insert(hash, trait_)
+  }
+
+  pub fn get(&self, hash: &ShardsTrait) -> Option<&Trait> {
+    self.traits.get(hash)
+  }
+
+  pub fn contains(&self, hash: &ShardsTrait) -> bool {
+    self.traits.contains_key(hash)
+  }
+
+  pub fn remove(&mut self, hash: &ShardsTrait) -> Option<Trait> {
+    self.statuses.remove(hash);
+    self.traits.remove(hash)
+  }
+
+  pub fn len(&self) -> usize {
+    self.traits.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.traits.is_empty()
+  }
+
+  /// Iterates over the registered traits, ordered by hash.
+  pub fn iter(&self) -> impl Iterator<Item = (&ShardsTrait, &Trait)> {
+    self.traits.iter()
+  }
+
+  /// Sets the status of the trait registered under `hash`, returns `false` if there is none.
+  pub fn set_status(&mut self, hash: &ShardsTrait, status: TraitStatus) -> bool {
+    if !self.contains(hash) {
+      return false;
+    }
+    match status {
+      TraitStatus::Active => self.statuses.remove(hash),
+      status => self.statuses.insert(*hash, status),
+    };
+    true
+  }
+
+  /// The status of the trait registered under `hash`, traits are active unless deprecated.
+  pub fn status(&self, hash: &ShardsTrait) -> TraitStatus {
+    self.statuses.get(hash).cloned().unwrap_or_default()
+  }
+}
+
+/// A change to apply to a `TraitRegistry`, e.g. from a chain event.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub enum IndexUpdate {
+  Insert(ShardsTrait, Trait),
+  Remove(ShardsTrait),
+  SetStatus(ShardsTrait, TraitStatus),
+}
+
+impl TraitRegistry {
+  /// Applies `updates` in order.
+  pub fn apply(&mut self, updates: impl IntoIterator<Item = IndexUpdate>) {
+    for update in updates {
+      match update {
+        IndexUpdate::Insert(hash, trait_) => {
+          self.insert(hash, trait_);
+        }
+        IndexUpdate::Remove(hash) => {
+          self.remove(&hash);
+        }
+        IndexUpdate::SetStatus(hash, status) => {
+          self.set_status(&hash, status);
+        }
+      }
+    }
+  }
+
+  /// A snapshot of the registry, up to date with the chain position `cursor` (e.g. a block number).
+  pub fn snapshot(&self, cursor: u64) -> TraitIndexSnapshot {
+    TraitIndexSnapshot {
+      version: TraitIndexSnapshot::VERSION,
+      cursor,
+      traits: self.traits.iter().map(|(h, t)| (*h, t.clone())).collect(),
+      statuses: self.statuses.iter().map(|(h, s)| (*h, s.clone())).collect(),
+    }
+  }
+
+  /// The registry saved in `snapshot`, along with the chain position it is up to date with.
+  pub fn from_snapshot(snapshot: TraitIndexSnapshot) -> (Self, u64) {
+    let registry = Self {
+      traits: snapshot.traits.into_iter().collect(),
+      statuses: snapshot.statuses.into_iter().collect(),
+    };
+    (registry, snapshot.cursor)
+  }
+}
+
+/// The saved content of a `TraitRegistry`, so indexers can restart without decoding every trait
+/// from the chain state again.
+///
+/// The binary form starts with `TraitIndexSnapshot::MAGIC` followed by the SCALE encoding of the
+/// snapshot, whose first field is the format version.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+pub struct TraitIndexSnapshot {
+  /// The format version of the snapshot
+  #[codec(compact)]
+  pub version: u32,
+  /// The chain position the snapshot is up to date with
+  #[codec(compact)]
+  pub cursor: u64,
+  /// The registered traits, ordered by hash
+  pub traits: Vec<(ShardsTrait, Trait)>,
+  /// The statuses of the traits that are not active, ordered by hash
+  pub statuses: Vec<(ShardsTrait, TraitStatus)>,
+}
+
+impl TraitIndexSnapshot {
+  /// The bytes starting every binary snapshot.
+  pub const MAGIC: [u8; 4] = *b"PTIX";
+  /// The format version written by this version of the crate.
+  pub const VERSION: u32 = 1;
+
+  /// Applies `updates` made up to the chain position `cursor`, so a snapshot can be kept up to date
+  /// without rebuilding it from a registry.
+  pub fn update(&mut self, cursor: u64, updates: impl IntoIterator<Item = IndexUpdate>) {
+    let mut registry = TraitRegistry {
+      traits: core::mem::take(&mut self.traits).into_iter().collect(),
+      statuses: core::mem::take(&mut self.statuses).into_iter().collect(),
+    };
+    registry.apply(updates);
+    *self = registry.snapshot(cursor);
+  }
+
+  /// The binary form of the snapshot.
+  pub fn to_bytes(&self) -> Vec<u8> {
+    let mut bytes = Vec::from(Self::MAGIC);
+    self.encode_to(&mut bytes);
+    bytes
+  }
+
+  /// Parses the binary form of a snapshot, rejecting snapshots written by newer versions.
+  pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+    let mut input = bytes
+      .strip_prefix(&Self::MAGIC[..])
+      .ok_or("Not a trait index snapshot")?;
+    let version = <Compact<u32>>::decode(&mut &input[..])?.0;
+    if version > Self::VERSION {
+      return Err("Unsupported trait index snapshot version".into());
+    }
+    let snapshot = Self::decode(&mut input)?;
+    if !input.is_empty() {
+      return Err("Trailing bytes after the trait index snapshot".into());
+    }
+    Ok(snapshot)
+  }
+
+  /// Writes the binary form of the snapshot to the file at `path`.
+  #[cfg(feature = "std")]
+  pub fn save(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+    std::fs::write(path, self.to_bytes())
+  }
+
+  /// Reads a snapshot from the file at `path`.
+  #[cfg(feature = "std")]
+  pub fn load(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+    let bytes = std::fs::read(path)?;
+    Self::from_bytes(&bytes)
+      .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string()))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn deprecation() {
+    let mut registry = TraitRegistry::new();
+    let (old, new) = ([1; 8], [2; 8]);
+    registry.insert(old, Trait::default());
+    registry.insert(new, Trait::default());
+
+    let deprecated = TraitStatus::Deprecated {
+      superseded_by: Some(new),
+      note_hash: None,
+    };
+    assert!(registry.set_status(&old, deprecated.clone()));
+    assert!(!registry.set_status(&[3; 8], deprecated.clone()));
+    assert_eq!(registry.status(&old).superseded_by(), Some(&new));
+    assert!(!registry.status(&new).is_deprecated());
+
+    assert_eq!(
+      TraitStatus::decode(&mut &deprecated.encode()[..]).unwrap(),
+      deprecated
+    );
+    let json = serde_json::to_string(&deprecated).unwrap();
+    assert!(json.contains(r#""supersededBy":"#) && json.contains(r#""noteHash":null"#));
+    registry.remove(&old);
+    assert_eq!(registry.status(&old), TraitStatus::Active);
+  }
+
+  #[test]
+  fn snapshot() {
+    let mut registry = TraitRegistry::new();
+    let flag = Trait {
+      name: "Flag".to_string(),
+      records: vec![],
+    };
+    registry.apply([
+      IndexUpdate::Insert([1; 8], flag.clone()),
+      IndexUpdate::Insert([2; 8], Trait::default()),
+      IndexUpdate::SetStatus(
+        [1; 8],
+        TraitStatus::Deprecated {
+          superseded_by: Some([2; 8]),
+          note_hash: None,
+        },
+      ),
+    ]);
+
+    let mut snapshot = registry.snapshot(42);
+    let bytes = snapshot.to_bytes();
+    assert_eq!(bytes[..4], TraitIndexSnapshot::MAGIC);
+    assert_eq!(TraitIndexSnapshot::from_bytes(&bytes).unwrap(), snapshot);
+    let (restored, cursor) = TraitRegistry::from_snapshot(snapshot.clone());
+    assert_eq!(restored, registry);
+    assert_eq!(cursor, 42);
+
+    let updates = [IndexUpdate::Remove([1; 8])];
+    snapshot.update(50, updates.clone());
+    registry.apply(updates);
+    assert_eq!(snapshot, registry.snapshot(50));
+    assert!(snapshot.statuses.is_empty());
+
+    let path = std::env::temp_dir().join(format!("protos-index-{}.bin", std::process::id()));
+    snapshot.save(&path).unwrap();
+    assert_eq!(TraitIndexSnapshot::load(&path).unwrap(), snapshot);
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(TraitIndexSnapshot::from_bytes(&bytes[1..]).is_err());
+    let mut newer = bytes.clone();
+    newer[4] = (TraitIndexSnapshot::VERSION as u8 + 1) << 2;
+    assert!(TraitIndexSnapshot::from_bytes(&newer).is_err());
+  }
+}
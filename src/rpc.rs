@@ -0,0 +1,255 @@
+//! Parameters and results of the proto query RPCs, shared by the node and the client SDKs.
+
+use crate::{
+  categories::Categories,
+  fragments::Hash256,
+  pagination::{PageError, PageRequest, Paged, SortOrder},
+  proto::Proto,
+};
+use parity_scale_codec::{Decode, Encode};
+use scale_info::prelude::{string::String, vec::Vec};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The fields of a proto a query can return, besides its hash.
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub enum ProtoField {
+  /// The block the proto was uploaded in
+  Block,
+  /// The account that uploaded the proto
+  Creator,
+  /// The current owner of the proto
+  Owner,
+  /// The protos the proto references
+  References,
+  /// The category of the proto
+  Category,
+  /// The tags of the proto
+  Tags,
+}
+
+/// The parameters of `protos_getProtos`.
+///
+/// Laid out like the `GetProtosParams` type of `index.js`, field for field and with the same
+/// snake_case names, so the SCALE and JSON forms match what the node and the SDKs exchange.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(default))]
+pub struct GetProtosParams<TAccountId> {
+  /// Whether the most recently uploaded protos come first
+  pub desc: bool,
+  /// The amount of protos to skip
+  pub from: u32,
+  /// The maximum amount of protos to return
+  pub limit: u32,
+  /// The metadata entries to return with each proto
+  pub metadata_keys: Vec<String>,
+  /// Only protos owned by this account
+  pub owner: Option<TAccountId>,
+  /// Whether to return the owner of each proto
+  pub return_owners: bool,
+  /// Only protos in one of these categories, any category when empty
+  pub categories: Vec<Categories>,
+  /// Only protos having every one of these tags
+  pub tags: Vec<String>,
+  /// Only protos having none of these tags
+  pub exclude_tags: Vec<String>,
+  /// Only protos that can (`true`) or cannot (`false`) be used by anyone, any proto when `None`.
+  /// Availability is tracked by the node, [`GetProtosParams::matches`] does not check it
+  pub available: Option<bool>,
+}
+
+impl<TAccountId> Default for GetProtosParams<TAccountId> {
+  fn default() -> Self {
+    Self {
+      desc: false,
+      from: 0,
+      limit: PageRequest::DEFAULT_LIMIT as u32,
+      metadata_keys: Vec::new(),
+      owner: None,
+      return_owners: false,
+      categories: Vec::new(),
+      tags: Vec::new(),
+      exclude_tags: Vec::new(),
+      available: None,
+    }
+  }
+}
+
+impl<TAccountId> GetProtosParams<TAccountId> {
+  /// The requested page.
+  pub fn page(&self) -> PageRequest {
+    PageRequest {
+      from: self.from.into(),
+      limit: self.limit.into(),
+    }
+  }
+
+  /// The order of the protos, by upload block.
+  pub fn order(&self) -> SortOrder {
+    match self.desc {
+      true => SortOrder::Descending,
+      false => SortOrder::Ascending,
+    }
+  }
+
+  /// Checks that the requested page is within bounds.
+  pub fn validate(&self) -> Result<(), PageError> {
+    self.page().validate()
+  }
+}
+
+impl<TAccountId: PartialEq> GetProtosParams<TAccountId> {
+  /// Whether `proto` matches the filters of the query, availability aside.
+  pub fn matches<TBlockNumber>(&self, proto: &Proto<TAccountId, TBlockNumber>) -> bool {
+    (self.categories.is_empty() || self.categories.contains(&proto.category))
+      && self.tags.iter().all(|tag| proto.tags.contains(tag))
+      && !self.exclude_tags.iter().any(|tag| proto.tags.contains(tag))
+      && self
+        .owner
+        .as_ref()
+        .is_none_or(|owner| *owner == proto.owner)
+  }
+}
+
+/// A proto returned by a query, with the requested fields only.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct ProtoResult<TAccountId, TBlockNumber> {
+  pub hash: Hash256,
+  #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+  pub block: Option<TBlockNumber>,
+  #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+  pub creator: Option<TAccountId>,
+  #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+  pub owner: Option<TAccountId>,
+  #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+  pub references: Option<Vec<Hash256>>,
+  #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+  pub category: Option<Categories>,
+  #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+  pub tags: Option<Vec<String>>,
+}
+
+impl<TAccountId: Clone, TBlockNumber: Clone> ProtoResult<TAccountId, TBlockNumber> {
+  /// The `fields` of the proto `hash`.
+  pub fn new(
+    hash: Hash256,
+    proto: &Proto<TAccountId, TBlockNumber>,
+    fields: &[ProtoField],
+  ) -> Self {
+    let field = |field| fields.contains(&field);
+    Self {
+      hash,
+      block: field(ProtoField::Block).then(|| proto.block.clone()),
+      creator: field(ProtoField::Creator).then(|| proto.creator.clone()),
+      owner: field(ProtoField::Owner).then(|| proto.owner.clone()),
+      references: field(ProtoField::References).then(|| proto.references.clone()),
+      category: field(ProtoField::Category).then(|| proto.category.clone()),
+      tags: field(ProtoField::Tags).then(|| proto.tags.clone()),
+    }
+  }
+}
+
+/// The result of `protos_getProtos`, the matching protos of the requested page.
+pub type GetProtosResult<TAccountId, TBlockNumber> = Paged<ProtoResult<TAccountId, TBlockNumber>>;
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::categories::TextureCategories;
+
+  fn proto() -> Proto<u64, u32> {
+    Proto {
+      block: 5,
+      creator: 1,
+      owner: 2,
+      references: vec![],
+      category: Categories::Texture(TextureCategories::PngFile),
+      tags: vec!["cat".to_string(), "meme".to_string()],
+    }
+  }
+
+  #[test]
+  fn params() {
+    let params: GetProtosParams<u64> = serde_json::from_str(
+      r#"{"categories":[{"texture":"pngFile"}],"tags":["cat"],"owner":2,"return_owners":true}"#,
+    )
+    .unwrap();
+    assert_eq!(params.page(), PageRequest::default());
+    assert_eq!(params.order(), SortOrder::Ascending);
+    assert!(params.return_owners);
+    assert_eq!(params.validate(), Ok(()));
+    assert!(params.matches(&proto()));
+    assert!(!GetProtosParams {
+      owner: Some(1),
+      ..params.clone()
+    }
+    .matches(&proto()));
+    assert!(!GetProtosParams {
+      tags: vec!["dog".to_string()],
+      ..params.clone()
+    }
+    .matches(&proto()));
+    assert!(!GetProtosParams {
+      exclude_tags: vec!["meme".to_string()],
+      ..params.clone()
+    }
+    .matches(&proto()));
+    assert!(!GetProtosParams {
+      categories: vec![Categories::Texture(TextureCategories::JpgFile)],
+      ..params
+    }
+    .matches(&proto()));
+    assert!(GetProtosParams::default().matches(&proto()));
+    assert_eq!(
+      GetProtosParams::<u64> {
+        limit: 0,
+        ..Default::default()
+      }
+      .validate(),
+      Err(PageError::ZeroLimit)
+    );
+
+    // the field order of index.js
+    let json = serde_json::to_value(GetProtosParams::<u64>::default()).unwrap();
+    let keys: Vec<_> = json.as_object().unwrap().keys().cloned().collect();
+    let mut expected = [
+      "desc",
+      "from",
+      "limit",
+      "metadata_keys",
+      "owner",
+      "return_owners",
+      "categories",
+      "tags",
+      "exclude_tags",
+      "available",
+    ];
+    expected.sort();
+    assert_eq!(keys, expected);
+  }
+
+  #[test]
+  fn results() {
+    let result = ProtoResult::new([0; 32], &proto(), &[ProtoField::Owner, ProtoField::Tags]);
+    assert_eq!(result.owner, Some(2));
+    assert_eq!(result.block, None);
+    let json = serde_json::to_string(&result).unwrap();
+    assert!(json.contains(r#""owner":2,"tags":["cat","meme"]"#));
+    assert!(!json.contains("block"));
+    assert_eq!(
+      serde_json::from_str::<ProtoResult<u64, u32>>(&json).unwrap(),
+      result
+    );
+  }
+}
@@ -0,0 +1,287 @@
+use core::fmt;
+use parity_scale_codec::{Decode, Encode, HasCompact};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Balance types that support overflow checked arithmetic.
+pub trait Balance: Copy + Ord {
+  fn checked_add(self, other: Self) -> Option<Self>;
+  fn checked_sub(self, other: Self) -> Option<Self>;
+  fn checked_mul_u32(self, factor: u32) -> Option<Self>;
+}
+
+macro_rules! impl_balance {
+  ($($t:ty),*) => {
+    $(
+      impl Balance for $t {
+        fn checked_add(self, other: Self) -> Option<Self> {
+          <$t>::checked_add(self, other)
+        }
+        fn checked_sub(self, other: Self) -> Option<Self> {
+          <$t>::checked_sub(self, other)
+        }
+        fn checked_mul_u32(self, factor: u32) -> Option<Self> {
+          <$t>::checked_mul(self, factor.try_into().ok()?)
+        }
+      }
+    )*
+  };
+}
+
+impl_balance!(u32, u64, u128);
+
+/// An amount of a given asset.
+///
+/// The amount is SCALE encoded as compact and serialized as a string,
+/// as JavaScript numbers cannot represent every `u128`.
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(
+    rename_all = "camelCase",
+    bound(
+      serialize = "TAssetId: Serialize, TBalance: fmt::Display",
+      deserialize = "TAssetId: Deserialize<'de>, TBalance: core::str::FromStr"
+    )
+  )
+)]
+pub struct AssetAmount<TAssetId, TBalance: HasCompact> {
+  pub asset: TAssetId,
+  #[codec(compact)]
+  #[cfg_attr(feature = "serde", serde(with = "string_amount"))]
+  pub amount: TBalance,
+}
+
+#[cfg(feature = "serde")]
+mod string_amount {
+  use core::{fmt::Display, str::FromStr};
+  use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+  pub fn serialize<S: Serializer, T: Display>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+  }
+
+  pub fn deserialize<'de, D: Deserializer<'de>, T: FromStr>(
+    deserializer: D,
+  ) -> Result<T, D::Error> {
+    let s = <scale_info::prelude::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+    s.parse()
+      .map_err(|_| D::Error::custom(scale_info::prelude::format!("invalid amount {:?}", s)))
+  }
+}
+
+/// Errors returned by the asset amount arithmetic.
+#[derive(Copy, Clone, PartialEq, Debug, Eq)]
+pub enum AmountError {
+  /// The amounts are of different assets
+  AssetMismatch,
+  /// The result does not fit the balance type
+  Overflow,
+}
+
+impl fmt::Display for AmountError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::AssetMismatch => write!(f, "amounts are of different assets"),
+      Self::Overflow => write!(f, "amount overflow"),
+    }
+  }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AmountError {}
+
+impl<TAssetId: PartialEq + Clone, TBalance: HasCompact + Balance> AssetAmount<TAssetId, TBalance> {
+  pub fn new(asset: TAssetId, amount: TBalance) -> Self {
+    Self { asset, amount }
+  }
+
+  pub fn checked_add(&self, other: &Self) -> Result<Self, AmountError> {
+    self.combine(other, TBalance::checked_add)
+  }
+
+  pub fn checked_sub(&self, other: &Self) -> Result<Self, AmountError> {
+    self.combine(other, TBalance::checked_sub)
+  }
+
+  /// The amount multiplied by `factor`, e.g. the price of `factor` editions.
+  pub fn checked_mul(&self, factor: u32) -> Result<Self, AmountError> {
+    Ok(Self::new(
+      self.asset.clone(),
+      self
+        .amount
+        .checked_mul_u32(factor)
+        .ok_or(AmountError::Overflow)?,
+    ))
+  }
+
+  fn combine(
+    &self,
+    other: &Self,
+    op: impl Fn(TBalance, TBalance) -> Option<TBalance>,
+  ) -> Result<Self, AmountError> {
+    if self.asset != other.asset {
+      return Err(AmountError::AssetMismatch);
+    }
+    Ok(Self::new(
+      self.asset.clone(),
+      op(self.amount, other.amount).ok_or(AmountError::Overflow)?,
+    ))
+  }
+}
+
+/// The terms under which a proto or fragment is listed for sale.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase",
+    bound(
+      serialize = "TAssetId: Serialize, TBalance: fmt::Display",
+      deserialize = "TAssetId: Deserialize<'de>, TBalance: core::str::FromStr"
+    )
+  )
+)]
+pub enum SaleTerms<TAssetId, TBalance: HasCompact> {
+  /// Sold at a fixed price
+  FixedPrice {
+    price: AssetAmount<TAssetId, TBalance>,
+  },
+  /// Sold to the highest bidder once the auction ends
+  Auction {
+    /// The minimum amount of the first bid
+    min_bid: AssetAmount<TAssetId, TBalance>,
+    /// The duration of the auction in blocks
+    #[codec(compact)]
+    duration: u32,
+  },
+  /// Sold at a fixed price to anyone, creating a new edition for every sale
+  OpenEdition {
+    price: AssetAmount<TAssetId, TBalance>,
+    /// The maximum amount of editions a single account can buy, unlimited if `None`
+    per_account_limit: Option<u32>,
+  },
+}
+
+/// Errors returned when sale terms cannot be honored.
+#[derive(Copy, Clone, PartialEq, Debug, Eq)]
+pub enum SaleTermsError {
+  /// An auction must last at least one block
+  ZeroDuration,
+  /// An open edition limit must allow at least one edition per account
+  ZeroAccountLimit,
+}
+
+impl fmt::Display for SaleTermsError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::ZeroDuration => write!(f, "auction duration cannot be zero"),
+      Self::ZeroAccountLimit => write!(f, "per account limit cannot be zero"),
+    }
+  }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SaleTermsError {}
+
+impl<TAssetId, TBalance: HasCompact> SaleTerms<TAssetId, TBalance> {
+  /// The asset the sale is paid in.
+  pub fn asset(&self) -> &TAssetId {
+    match self {
+      Self::FixedPrice { price }
+      | Self::Auction { min_bid: price, .. }
+      | Self::OpenEdition { price, .. } => &price.asset,
+    }
+  }
+
+  pub fn validate(&self) -> Result<(), SaleTermsError> {
+    match self {
+      Self::Auction { duration: 0, .. } => Err(SaleTermsError::ZeroDuration),
+      Self::OpenEdition {
+        per_account_limit: Some(0),
+        ..
+      } => Err(SaleTermsError::ZeroAccountLimit),
+      _ => Ok(()),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn encode_decode_terms() {
+    let terms: SaleTerms<u32, u128> = SaleTerms::Auction {
+      min_bid: AssetAmount::new(1, 1_000),
+      duration: 600,
+    };
+    assert_eq!(terms.validate(), Ok(()));
+    assert_eq!(*terms.asset(), 1);
+
+    let encoded = terms.encode();
+    assert_eq!(SaleTerms::decode(&mut encoded.as_slice()).unwrap(), terms);
+    let json = serde_json::to_string(&terms).unwrap();
+    assert!(json.starts_with(r#"{"auction":{"minBid":"#));
+    assert_eq!(
+      serde_json::from_str::<SaleTerms<u32, u128>>(&json).unwrap(),
+      terms
+    );
+  }
+
+  #[test]
+  fn asset_amounts() {
+    let amount = AssetAmount::new(1u32, u128::MAX);
+    let json = serde_json::to_string(&amount).unwrap();
+    assert_eq!(
+      json,
+      format!("{{\"asset\":1,\"amount\":\"{}\"}}", u128::MAX)
+    );
+    assert_eq!(
+      serde_json::from_str::<AssetAmount<u32, u128>>(&json).unwrap(),
+      amount
+    );
+    assert!(
+      serde_json::from_str::<AssetAmount<u32, u128>>(r#"{"asset":1,"amount":"-1"}"#).is_err()
+    );
+
+    // compact encoding
+    assert_eq!(AssetAmount::new(1u32, 1u128).encode(), vec![1, 0, 0, 0, 4]);
+
+    let one = AssetAmount::new(1u32, 1u128);
+    assert_eq!(one.checked_add(&one), Ok(AssetAmount::new(1, 2)));
+    assert_eq!(amount.checked_add(&one), Err(AmountError::Overflow));
+    assert_eq!(
+      one.checked_sub(&AssetAmount::new(1, 2)),
+      Err(AmountError::Overflow)
+    );
+    assert_eq!(
+      one.checked_add(&AssetAmount::new(2, 1)),
+      Err(AmountError::AssetMismatch)
+    );
+    assert_eq!(one.checked_mul(10), Ok(AssetAmount::new(1, 10)));
+    assert_eq!(
+      AssetAmount::new(1u32, u32::MAX).checked_mul(2),
+      Err(AmountError::Overflow)
+    );
+  }
+
+  #[test]
+  fn invalid_terms() {
+    let terms: SaleTerms<u32, u128> = SaleTerms::Auction {
+      min_bid: AssetAmount::new(1, 1_000),
+      duration: 0,
+    };
+    assert_eq!(terms.validate(), Err(SaleTermsError::ZeroDuration));
+
+    let terms: SaleTerms<u32, u128> = SaleTerms::OpenEdition {
+      price: AssetAmount::new(1, 10),
+      per_account_limit: Some(0),
+    };
+    assert_eq!(terms.validate(), Err(SaleTermsError::ZeroAccountLimit));
+  }
+}
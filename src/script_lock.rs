@@ -0,0 +1,218 @@
+//! Lockfiles of Shards scripts: the traits a script was resolved against when it was published,
+//! so later builds use the same trait revisions and runtime version.
+//!
+//! Locks are stored as JSON on disk and can be anchored on chain by their `hash`.
+
+use crate::{
+  categories::{ShardsScriptInfo, ShardsTrait, TraitRefFull},
+  hashing::blake2_256,
+  hex::HexBytes,
+};
+use parity_scale_codec::{Decode, Encode};
+use scale_info::prelude::vec::Vec;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The resolved dependencies of a Shards script.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct ScriptLock {
+  /// The `shards_version` the script was published with
+  #[codec(compact)]
+  pub shards_version: u32,
+  /// The traits required by the script, sorted by hash
+  pub requiring: Vec<TraitRefFull>,
+  /// The traits implemented by the script, sorted by hash
+  pub implementing: Vec<TraitRefFull>,
+}
+
+/// A difference between a script and its lock.
+#[derive(Copy, Clone, PartialEq, Debug, Eq)]
+pub enum LockMismatch {
+  /// The script declares another `shards_version` than the locked one
+  ShardsVersion { locked: u32, actual: u32 },
+  /// The script uses a trait missing from the lock
+  UnlockedTrait(ShardsTrait),
+  /// The lock has a trait the script no longer uses
+  StaleTrait(ShardsTrait),
+}
+
+impl core::fmt::Display for LockMismatch {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    match self {
+      Self::ShardsVersion { locked, actual } => write!(
+        f,
+        "script uses shards version {} but the lock has {}",
+        actual, locked
+      ),
+      Self::UnlockedTrait(hash) => {
+        write!(f, "trait {} is not locked", HexBytes(hash))
+      }
+      Self::StaleTrait(hash) => write!(f, "locked trait {} is no longer used", HexBytes(hash)),
+    }
+  }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for LockMismatch {}
+
+fn lock_traits(
+  traits: &[ShardsTrait],
+  revision: &mut impl FnMut(&ShardsTrait) -> Option<u32>,
+) -> Vec<TraitRefFull> {
+  let mut locked: Vec<TraitRefFull> = traits
+    .iter()
+    .map(|hash| TraitRefFull {
+      hash: *hash,
+      revision: revision(hash),
+    })
+    .collect();
+  locked.sort_by_key(|t| t.hash);
+  locked.dedup_by_key(|t| t.hash);
+  locked
+}
+
+fn compare_traits(locked: &[TraitRefFull], actual: &[ShardsTrait], errors: &mut Vec<LockMismatch>) {
+  for hash in actual {
+    if !locked.iter().any(|t| t.hash == *hash) {
+      errors.push(LockMismatch::UnlockedTrait(*hash));
+    }
+  }
+  for t in locked {
+    if !actual.contains(&t.hash) {
+      errors.push(LockMismatch::StaleTrait(t.hash));
+    }
+  }
+}
+
+impl ScriptLock {
+  /// Locks `script`, `revision` resolves the current revision of each trait it uses.
+  pub fn new(
+    script: &ShardsScriptInfo,
+    mut revision: impl FnMut(&ShardsTrait) -> Option<u32>,
+  ) -> Self {
+    Self {
+      shards_version: script.shards_version,
+      requiring: lock_traits(&script.requiring, &mut revision),
+      implementing: lock_traits(&script.implementing, &mut revision),
+    }
+  }
+
+  /// Checks that `script` still uses exactly the locked traits and `shards_version`.
+  pub fn verify(&self, script: &ShardsScriptInfo) -> Result<(), Vec<LockMismatch>> {
+    let mut errors = Vec::new();
+    if script.shards_version != self.shards_version {
+      errors.push(LockMismatch::ShardsVersion {
+        locked: self.shards_version,
+        actual: script.shards_version,
+      });
+    }
+    compare_traits(&self.requiring, &script.requiring, &mut errors);
+    compare_traits(&self.implementing, &script.implementing, &mut errors);
+
+    if errors.is_empty() {
+      Ok(())
+    } else {
+      Err(errors)
+    }
+  }
+
+  /// The locked revision of the trait `hash`, if the trait is locked with a revision.
+  pub fn revision(&self, hash: &ShardsTrait) -> Option<u32> {
+    self
+      .requiring
+      .iter()
+      .chain(&self.implementing)
+      .find(|t| t.hash == *hash)
+      .and_then(|t| t.revision)
+  }
+
+  /// The Blake2b 256 hash of the SCALE encoding of the lock, to anchor it on chain.
+  pub fn hash(&self) -> [u8; 32] {
+    blake2_256(&self.encode())
+  }
+
+  /// The lock as a pretty printed JSON lockfile.
+  #[cfg(feature = "std")]
+  pub fn to_lockfile(&self) -> String {
+    serde_json::to_string_pretty(self).expect("locks always serialize")
+  }
+
+  /// Parses a JSON lockfile.
+  #[cfg(feature = "std")]
+  pub fn from_lockfile(json: &str) -> serde_json::Result<Self> {
+    serde_json::from_str(json)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::categories::ShardsFormat;
+
+  fn script() -> ShardsScriptInfo {
+    ShardsScriptInfo {
+      format: ShardsFormat::Edn,
+      shards_version: 2,
+      requiring: vec![[2; 8], [1; 8], [2; 8]],
+      implementing: vec![[3; 8]],
+    }
+  }
+
+  #[test]
+  fn lock_and_verify() {
+    let script = script();
+    let lock = ScriptLock::new(&script, |hash| {
+      (hash[0] != 3).then_some(hash[0] as u32 * 10)
+    });
+    assert_eq!(
+      lock.requiring,
+      [
+        TraitRefFull {
+          hash: [1; 8],
+          revision: Some(10)
+        },
+        TraitRefFull {
+          hash: [2; 8],
+          revision: Some(20)
+        },
+      ]
+    );
+    assert_eq!(lock.revision(&[2; 8]), Some(20));
+    assert_eq!(lock.revision(&[3; 8]), None);
+    assert_eq!(lock.verify(&script), Ok(()));
+
+    let changed = ShardsScriptInfo {
+      shards_version: 3,
+      requiring: vec![[1; 8], [4; 8]],
+      ..script
+    };
+    assert_eq!(
+      lock.verify(&changed),
+      Err(vec![
+        LockMismatch::ShardsVersion {
+          locked: 2,
+          actual: 3
+        },
+        LockMismatch::UnlockedTrait([4; 8]),
+        LockMismatch::StaleTrait([2; 8]),
+      ])
+    );
+  }
+
+  #[test]
+  fn lockfile() {
+    let lock = ScriptLock::new(&script(), |_| Some(1));
+    let json = lock.to_lockfile();
+    assert!(json.contains("\"shardsVersion\": 2"));
+    assert_eq!(ScriptLock::from_lockfile(&json).unwrap(), lock);
+
+    assert_eq!(ScriptLock::decode(&mut &lock.encode()[..]).unwrap(), lock);
+    assert_ne!(lock.hash(), ScriptLock::new(&script(), |_| Some(2)).hash());
+  }
+}
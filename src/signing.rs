@@ -0,0 +1,219 @@
+use crate::{bundles::BundleManifest, proto::Proto, traits::Trait, transfer::TransferIntent};
+use parity_scale_codec::{Decode, Encode};
+use scale_info::prelude::vec::Vec;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Domain separator prefixed to every signing payload, so signatures cannot be replayed in other contexts.
+pub const SIGNING_DOMAIN: &[u8] = b"fragnova-protos";
+
+/// The current version of the signing payload layout.
+pub const SIGNING_VERSION: u8 = 1;
+
+/// The kind of value a signing payload was built from.
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub enum PayloadKind {
+  Trait,
+  Proto,
+  Bundle,
+  Transfer,
+}
+
+/// Types that can be signed off-chain.
+pub trait Signable: Encode + Clone {
+  const KIND: PayloadKind;
+
+  /// Brings the value into the canonical form it is signed in, so equivalent values have the same
+  /// payload. Values without a canonical form are left as is.
+  fn canonicalize(&mut self) {}
+}
+
+impl Signable for Trait {
+  const KIND: PayloadKind = PayloadKind::Trait;
+
+  fn canonicalize(&mut self) {
+    Trait::canonicalize(self)
+  }
+}
+
+impl<TAccountId: Encode + Clone, TBlockNumber: Encode + Clone> Signable
+  for Proto<TAccountId, TBlockNumber>
+{
+  const KIND: PayloadKind = PayloadKind::Proto;
+
+  fn canonicalize(&mut self) {
+    Proto::canonicalize(self)
+  }
+}
+
+impl<THash: Encode + Clone> Signable for BundleManifest<THash> {
+  const KIND: PayloadKind = PayloadKind::Bundle;
+
+  fn canonicalize(&mut self) {
+    BundleManifest::canonicalize(self)
+  }
+}
+
+impl<TAccountId: Encode + Clone, TBlockNumber: Encode + Clone> Signable
+  for TransferIntent<TAccountId, TBlockNumber>
+{
+  const KIND: PayloadKind = PayloadKind::Transfer;
+}
+
+/// A versioned, domain separated payload to be signed off-chain.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct SigningPayload {
+  pub version: u8,
+  pub kind: PayloadKind,
+  /// The SCALE encoding of the value signed
+  pub data: Vec<u8>,
+}
+
+/// Builds the signing payload of the canonical form of `value`, see [`Signable::canonicalize`].
+pub fn typed_payload<T: Signable>(value: &T) -> SigningPayload {
+  let mut canonical = value.clone();
+  canonical.canonicalize();
+  SigningPayload {
+    version: SIGNING_VERSION,
+    kind: T::KIND,
+    data: canonical.encode(),
+  }
+}
+
+impl SigningPayload {
+  /// The bytes to sign: the domain separator followed by the SCALE encoded payload.
+  pub fn to_bytes(&self) -> Vec<u8> {
+    let mut bytes = SIGNING_DOMAIN.to_vec();
+    self.encode_to(&mut bytes);
+    bytes
+  }
+
+  /// Renders the payload as EIP-712 typed data, to be signed with `eth_signTypedData_v4`.
+  #[cfg(feature = "std")]
+  pub fn to_eip712_json(&self) -> serde_json::Value {
+    let data: String = self.data.iter().map(|b| format!("{:02x}", b)).collect();
+    serde_json::json!({
+      "types": {
+        "EIP712Domain": [
+          { "name": "name", "type": "string" },
+          { "name": "version", "type": "string" }
+        ],
+        "FragnovaPayload": [
+          { "name": "kind", "type": "string" },
+          { "name": "data", "type": "bytes" }
+        ]
+      },
+      "primaryType": "FragnovaPayload",
+      "domain": {
+        "name": String::from_utf8_lossy(SIGNING_DOMAIN),
+        "version": self.version.to_string()
+      },
+      "message": {
+        "kind": serde_json::to_value(self.kind).unwrap_or_default(),
+        "data": format!("0x{}", data)
+      }
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::categories::{Categories, ShardsScriptInfo, TextCategories};
+
+  #[test]
+  fn payload_is_domain_separated() {
+    let trait1 = Trait {
+      name: "Trait1".to_string(),
+      records: vec![],
+    };
+    let payload = typed_payload(&trait1);
+    assert_eq!(payload.kind, PayloadKind::Trait);
+    assert_eq!(payload.data, trait1.encode());
+
+    let bytes = payload.to_bytes();
+    assert!(bytes.starts_with(SIGNING_DOMAIN));
+    assert_eq!(bytes[SIGNING_DOMAIN.len()], SIGNING_VERSION);
+    assert_eq!(
+      SigningPayload::decode(&mut &bytes[SIGNING_DOMAIN.len()..]).unwrap(),
+      payload
+    );
+  }
+
+  #[test]
+  fn payload_kinds_differ() {
+    let proto = Proto {
+      block: 1u32,
+      creator: [0u8; 32],
+      owner: [0u8; 32],
+      references: vec![],
+      category: Categories::Text(TextCategories::Plain),
+      tags: vec![],
+    };
+    let bundle = BundleManifest::<[u8; 32]> { entries: vec![] };
+    assert_eq!(typed_payload(&proto).kind, PayloadKind::Proto);
+    assert_eq!(typed_payload(&bundle).kind, PayloadKind::Bundle);
+    assert_ne!(
+      typed_payload(&bundle).to_bytes(),
+      SigningPayload {
+        kind: PayloadKind::Trait,
+        ..typed_payload(&bundle)
+      }
+      .to_bytes()
+    );
+  }
+
+  #[test]
+  fn payload_is_canonical() {
+    use crate::traits::Record;
+
+    let record = |name: &str| Record::from((name.to_string(), vec![Default::default()]));
+    let a = Trait {
+      name: "Trait1".to_string(),
+      records: vec![record("a"), record("b")],
+    };
+    let b = Trait {
+      records: vec![record("B"), record("a")],
+      ..a.clone()
+    };
+    assert_ne!(a.encode(), b.encode());
+    assert_eq!(typed_payload(&a), typed_payload(&b));
+
+    let proto = |requiring| Proto {
+      block: 1u32,
+      creator: [0u8; 32],
+      owner: [0u8; 32],
+      references: vec![],
+      category: Categories::Shards(ShardsScriptInfo {
+        requiring,
+        ..Default::default()
+      }),
+      tags: vec![],
+    };
+    assert_eq!(
+      typed_payload(&proto(vec![[2; 8], [1; 8]])),
+      typed_payload(&proto(vec![[1; 8], [2; 8], [1; 8]]))
+    );
+  }
+
+  #[test]
+  fn eip712_rendering() {
+    let bundle = BundleManifest::<[u8; 32]> { entries: vec![] };
+    let json = typed_payload(&bundle).to_eip712_json();
+    assert_eq!(json["primaryType"], "FragnovaPayload");
+    assert_eq!(json["domain"]["version"], "1");
+    assert_eq!(json["message"]["kind"], "bundle");
+    assert_eq!(json["message"]["data"], "0x00");
+  }
+}
@@ -0,0 +1,418 @@
+//! Standard traits, the interfaces protos of a category are expected to implement so every
+//! platform can use them the same way.
+
+use crate::{
+  categories::{Categories, ModelCategories, ShardsTrait, VectorCategories},
+  hashing::{twox_64, HashScheme},
+  traits::{Record, Trait, VariableType, VariableTypeInfo},
+};
+use scale_info::prelude::vec::Vec;
+
+/// A record of a standard trait.
+#[derive(Copy, Clone, PartialEq, Debug, Eq)]
+pub struct RecordSignature {
+  /// The name of the record
+  pub name: &'static str,
+  /// The alternative types of the record
+  pub types: &'static [VariableType],
+}
+
+/// A standard trait, usable in constants.
+#[derive(Copy, Clone, PartialEq, Debug, Eq)]
+pub struct TraitSignature {
+  /// The name of the trait
+  pub name: &'static str,
+  /// The records of the trait
+  pub records: &'static [RecordSignature],
+  /// The scheme of [`TraitSignature::hash`]
+  pub scheme: HashScheme,
+}
+
+impl TraitSignature {
+  /// The trait, without defaults.
+  pub fn to_trait(&self) -> Trait {
+    Trait {
+      name: self.name.into(),
+      records: self
+        .records
+        .iter()
+        .map(|record| Record {
+          name: record.name.into(),
+          types: record
+            .types
+            .iter()
+            .map(|type_| VariableTypeInfo {
+              type_: type_.clone(),
+              default: None,
+            })
+            .collect(),
+        })
+        .collect::<Vec<_>>(),
+    }
+  }
+
+  /// The hash of the trait with its scheme, to list in `ShardsScriptInfo::implementing`.
+  pub fn hash(&self) -> ShardsTrait {
+    self.to_trait().hash_with(&self.scheme)
+  }
+}
+
+/// The longest canonical encoding [`TraitSignature::const_shards_hash`] supports, in bytes.
+pub const MAX_CONST_ENCODED_LEN: usize = 1024;
+
+/// A SCALE encoder usable in constants, see [`TraitSignature::const_shards_hash`].
+struct ConstEncoder {
+  bytes: [u8; MAX_CONST_ENCODED_LEN],
+  len: usize,
+}
+
+impl ConstEncoder {
+  const fn push(mut self, byte: u8) -> Self {
+    assert!(
+      self.len < MAX_CONST_ENCODED_LEN,
+      "the trait is too large to be encoded in a constant"
+    );
+    self.bytes[self.len] = byte;
+    self.len += 1;
+    self
+  }
+
+  const fn compact(mut self, x: u32) -> Self {
+    let (value, len) = match x {
+      0..=0x3f => ((x as u64) << 2, 1),
+      0x40..=0x3fff => (((x as u64) << 2) | 1, 2),
+      0x4000..=0x3fff_ffff => (((x as u64) << 2) | 2, 4),
+      _ => (0b11 | ((x as u64) << 8), 5),
+    };
+    let mut i = 0;
+    while i < len {
+      self = self.push((value >> (8 * i)) as u8);
+      i += 1;
+    }
+    self
+  }
+
+  const fn str(mut self, s: &str) -> Self {
+    let bytes = s.as_bytes();
+    self = self.compact(bytes.len() as u32);
+    let mut i = 0;
+    while i < bytes.len() {
+      self = self.push(bytes[i]);
+      i += 1;
+    }
+    self
+  }
+
+  /// Encodes the types without data or with `None` limits or categories, and enums and objects.
+  const fn type_(self, ty: &VariableType) -> Self {
+    let encoder = self.push(ty.variant_index());
+    match ty {
+      VariableType::None
+      | VariableType::Any
+      | VariableType::Bool
+      | VariableType::Color
+      | VariableType::Image
+      | VariableType::Audio
+      | VariableType::Mesh => encoder,
+      VariableType::Bytes(None)
+      | VariableType::String(None)
+      | VariableType::Int(None)
+      | VariableType::Float(None) => encoder.push(0),
+      VariableType::Enum { vendor_id, type_id } | VariableType::Object { vendor_id, type_id } => {
+        encoder.compact(*vendor_id).compact(*type_id)
+      }
+      _ => panic!("the type cannot be encoded in a constant"),
+    }
+  }
+}
+
+/// Whether `a` and `b` are the same type, for the types [`ConstEncoder::type_`] encodes.
+const fn same_type(a: &VariableType, b: &VariableType) -> bool {
+  match (a, b) {
+    (
+      VariableType::Enum {
+        vendor_id: v1,
+        type_id: t1,
+      },
+      VariableType::Enum {
+        vendor_id: v2,
+        type_id: t2,
+      },
+    )
+    | (
+      VariableType::Object {
+        vendor_id: v1,
+        type_id: t1,
+      },
+      VariableType::Object {
+        vendor_id: v2,
+        type_id: t2,
+      },
+    ) => *v1 == *v2 && *t1 == *t2,
+    _ => a.variant_index() == b.variant_index(),
+  }
+}
+
+/// Whether the record name `a` sorts strictly before `b`, as canonical traits are sorted.
+const fn sorts_before(a: &str, b: &str) -> bool {
+  let (a, b) = (a.as_bytes(), b.as_bytes());
+  let mut i = 0;
+  while i < a.len() && i < b.len() {
+    if a[i] != b[i] {
+      return a[i] < b[i];
+    }
+    i += 1;
+  }
+  a.len() < b.len()
+}
+
+impl TraitSignature {
+  /// The hash of the trait with its scheme, usable in constants, see `static_assert_trait_hash!`.
+  ///
+  /// The signature must already be canonical (see [`Trait::canonicalize`]): record names
+  /// lowercase, sorted and unique, the types of a record unique. Only types without data, with
+  /// `None` limits or categories, enums and objects are supported. Other signatures panic, which
+  /// fails the compilation of the constant.
+  pub const fn const_shards_hash(&self) -> ShardsTrait {
+    let mut encoder = ConstEncoder {
+      bytes: [0; MAX_CONST_ENCODED_LEN],
+      len: 0,
+    }
+    .str(self.name)
+    .compact(self.records.len() as u32);
+    let mut r = 0;
+    while r < self.records.len() {
+      let record = &self.records[r];
+      let mut i = 0;
+      while i < record.name.len() {
+        assert!(
+          !record.name.as_bytes()[i].is_ascii_uppercase(),
+          "record names must be lowercase"
+        );
+        i += 1;
+      }
+      assert!(
+        r == 0 || sorts_before(self.records[r - 1].name, record.name),
+        "records must be sorted by name and unique"
+      );
+      encoder = encoder.str(record.name).compact(record.types.len() as u32);
+      let mut t = 0;
+      while t < record.types.len() {
+        let mut previous = 0;
+        while previous < t {
+          assert!(
+            !same_type(&record.types[previous], &record.types[t]),
+            "the types of a record must be unique"
+          );
+          previous += 1;
+        }
+        // no default value
+        encoder = encoder.type_(&record.types[t]).push(0);
+        t += 1;
+      }
+      r += 1;
+    }
+    match self.scheme {
+      HashScheme::Xx64 => twox_64(encoder.bytes.split_at(encoder.len).0),
+    }
+  }
+}
+
+/// Fails to compile unless the hash of the [`TraitSignature`] constant `$signature` is `$hash`.
+///
+/// `$hash` is an `u64` literal written in the same byte order as the hex string of the hash, so
+/// projects embedding well-known traits notice when a dependency bump changes their hash. See
+/// [`TraitSignature::const_shards_hash`] for the signatures supported. Requires the `hashing`
+/// feature.
+///
+/// ```
+/// use protos::{standard::{RecordSignature, TraitSignature}, traits::VariableType};
+///
+/// const FLAG: TraitSignature = TraitSignature {
+///   name: "Flag",
+///   records: &[RecordSignature {
+///     name: "on",
+///     types: &[VariableType::Bool],
+///   }],
+///   scheme: protos::hashing::HashScheme::Xx64,
+/// };
+/// protos::static_assert_trait_hash!(FLAG, 0x27ed_dd95_9344_d676);
+/// ```
+#[cfg(feature = "hashing")]
+#[macro_export]
+macro_rules! static_assert_trait_hash {
+  ($signature:expr, $hash:expr) => {
+    const _: () = assert!(
+      u64::from_be_bytes($crate::standard::TraitSignature::const_shards_hash(
+        &$signature
+      )) == $hash,
+      "the trait hash does not match the expected one"
+    );
+  };
+}
+
+/// A two dimensional image.
+pub const IMAGE_2D: TraitSignature = TraitSignature {
+  name: "Image2D",
+  records: &[RecordSignature {
+    name: "image",
+    types: &[VariableType::Image],
+  }],
+  scheme: HashScheme::Xx64,
+};
+
+/// A sound.
+pub const AUDIO_CLIP: TraitSignature = TraitSignature {
+  name: "AudioClip",
+  records: &[RecordSignature {
+    name: "audio",
+    types: &[VariableType::Audio],
+  }],
+  scheme: HashScheme::Xx64,
+};
+
+/// A three dimensional mesh.
+pub const MESH_3D: TraitSignature = TraitSignature {
+  name: "Mesh3D",
+  records: &[RecordSignature {
+    name: "mesh",
+    types: &[VariableType::Mesh],
+  }],
+  scheme: HashScheme::Xx64,
+};
+
+/// A text.
+pub const TEXT: TraitSignature = TraitSignature {
+  name: "Text",
+  records: &[RecordSignature {
+    name: "text",
+    types: &[VariableType::String(None)],
+  }],
+  scheme: HashScheme::Xx64,
+};
+
+/// Every standard trait.
+pub const ALL: &[TraitSignature] = &[IMAGE_2D, AUDIO_CLIP, MESH_3D, TEXT];
+
+/// The standard traits protos of `category` should implement, so upload tools can suggest them.
+pub fn suggested_traits_for(category: &Categories) -> &'static [TraitSignature] {
+  match category {
+    Categories::Texture(_) | Categories::Vector(VectorCategories::SvgFile) => &[IMAGE_2D],
+    Categories::Audio(_) => &[AUDIO_CLIP],
+    Categories::Model(ModelCategories::GltfFile) => &[MESH_3D],
+    Categories::Text(_) => &[TEXT],
+    _ => &[],
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::categories::{AudioCategories, TextureCategories, VideoCategories};
+
+  #[test]
+  fn standard_traits() {
+    for signature in ALL {
+      let trait1 = signature.to_trait();
+      assert_eq!(trait1.validate(), Ok(()));
+      assert_eq!(signature.hash(), trait1.shards_hash());
+    }
+    // protos list these hashes in `implementing`, they must never change
+    for (signature, hash) in [
+      (IMAGE_2D, 0x2b50_1c33_a69b_92dbu64),
+      (AUDIO_CLIP, 0xa9ce_1c96_d4eb_be42),
+      (MESH_3D, 0x855d_da52_e141_d72d),
+      (TEXT, 0xe54c_30af_6978_02fd),
+    ] {
+      assert_eq!(signature.hash(), hash.to_be_bytes(), "{}", signature.name);
+    }
+    let hashes: Vec<_> = ALL.iter().map(TraitSignature::hash).collect();
+    assert!(hashes
+      .iter()
+      .enumerate()
+      .all(|(i, h)| !hashes[..i].contains(h)));
+  }
+
+  #[test]
+  fn const_hashes() {
+    for signature in ALL {
+      assert_eq!(signature.const_shards_hash(), signature.hash());
+    }
+    let typed = TraitSignature {
+      name: "Typed",
+      records: &[
+        RecordSignature {
+          name: "a",
+          types: &[
+            VariableType::Enum {
+              vendor_id: 300,
+              type_id: 1,
+            },
+            VariableType::Enum {
+              vendor_id: 300,
+              type_id: 2,
+            },
+          ],
+        },
+        RecordSignature {
+          name: "b",
+          types: &[VariableType::Int(None), VariableType::Any],
+        },
+      ],
+      scheme: HashScheme::Xx64,
+    };
+    assert_eq!(typed.const_shards_hash(), typed.hash());
+  }
+
+  #[test]
+  #[should_panic(expected = "records must be sorted by name and unique")]
+  fn const_hash_requires_canonical() {
+    TraitSignature {
+      records: &[
+        RecordSignature {
+          name: "image",
+          types: &[VariableType::Image],
+        },
+        RecordSignature {
+          name: "image",
+          types: &[VariableType::Image],
+        },
+      ],
+      ..IMAGE_2D
+    }
+    .const_shards_hash();
+  }
+
+  #[cfg(feature = "hashing")]
+  #[test]
+  fn static_trait_hash() {
+    const FLAG: TraitSignature = TraitSignature {
+      name: "Flag",
+      records: &[RecordSignature {
+        name: "on",
+        types: &[VariableType::Bool],
+      }],
+      scheme: HashScheme::Xx64,
+    };
+    static_assert_trait_hash!(FLAG, 0x27ed_dd95_9344_d676);
+    assert_eq!(FLAG.hash(), 0x27ed_dd95_9344_d676u64.to_be_bytes());
+  }
+
+  #[test]
+  fn suggestions() {
+    assert_eq!(
+      suggested_traits_for(&Categories::Texture(TextureCategories::PngFile)),
+      [IMAGE_2D]
+    );
+    assert_eq!(
+      suggested_traits_for(&Categories::Audio(AudioCategories::OggFile)),
+      [AUDIO_CLIP]
+    );
+    assert_eq!(
+      suggested_traits_for(&Categories::Model(ModelCategories::GltfFile)),
+      [MESH_3D]
+    );
+    assert!(suggested_traits_for(&Categories::Video(VideoCategories::Mp4File)).is_empty());
+    assert!(suggested_traits_for(&Categories::Vector(VectorCategories::TtfFile)).is_empty());
+  }
+}
@@ -0,0 +1,162 @@
+//! The static mapping tables of the crate as plain data, so services in other languages can load
+//! them as a single JSON document instead of binding to the crate.
+
+use crate::{
+  categories::{
+    AudioCategories, BinaryCategories, Categories, ModelCategories, ShardsScriptInfo,
+    TextCategories, TextureCategories, TraitRef, VectorCategories, VideoCategories,
+  },
+  compat::MIN_SHARDS_VERSIONS,
+  docs::{variant_name, variant_summary},
+  traits::VariableType,
+};
+use scale_info::prelude::vec::Vec;
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+/// A category and sub-category, see [`Categories::stable_id`].
+#[derive(Clone, PartialEq, Debug, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "camelCase"))]
+pub struct CategoryRow {
+  pub stable_id: u16,
+  /// The name of the category variant, e.g. `Texture`
+  pub category: &'static str,
+  /// The name of the sub-category variant, e.g. `PngFile`
+  pub sub_category: Option<&'static str>,
+  /// See [`Categories::mime_type`]
+  pub mime_type: Option<&'static str>,
+  /// See [`Categories::extensions`]
+  pub extensions: &'static [&'static str],
+}
+
+/// A variant of [`VariableType`].
+#[derive(Clone, PartialEq, Debug, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "camelCase"))]
+pub struct VariableTypeRow {
+  /// The SCALE index of the variant
+  pub index: u8,
+  pub name: &'static str,
+  /// The first doc comment line of the variant, empty without the `docs` feature
+  pub description: &'static str,
+  /// The first `shards_version` understanding the variant
+  pub min_shards_version: u32,
+}
+
+/// Every table returned by [`metadata_tables`].
+#[derive(Clone, PartialEq, Debug, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "camelCase"))]
+pub struct MetadataTables {
+  /// Every category and sub-category, ordered by stable id
+  pub categories: Vec<CategoryRow>,
+  /// Every variable type, ordered by SCALE index
+  pub variable_types: Vec<VariableTypeRow>,
+}
+
+fn category_row(category: Categories) -> CategoryRow {
+  let sub_name = |index| match &category {
+    Categories::Text(_) => variant_name::<TextCategories>(index),
+    Categories::Audio(_) => variant_name::<AudioCategories>(index),
+    Categories::Texture(_) => variant_name::<TextureCategories>(index),
+    Categories::Vector(_) => variant_name::<VectorCategories>(index),
+    Categories::Video(_) => variant_name::<VideoCategories>(index),
+    Categories::Model(_) => variant_name::<ModelCategories>(index),
+    Categories::Binary(_) => variant_name::<BinaryCategories>(index),
+    Categories::Trait(_) | Categories::Shards(_) | Categories::Bundle => "",
+  };
+  CategoryRow {
+    stable_id: category.stable_id(),
+    category: Categories::VARIANT_NAMES[category.variant_index() as usize],
+    sub_category: category.sub_index().map(sub_name),
+    mime_type: category.mime_type(),
+    extensions: category.extensions(),
+  }
+}
+
+/// The category and variable type tables, serialized to JSON with the `serde` feature.
+///
+/// ```
+/// let tables = protos::tables::metadata_tables();
+/// let png = tables.categories.iter().find(|row| row.stable_id == 0x0400).unwrap();
+/// assert_eq!(png.mime_type, Some("image/png"));
+/// ```
+pub fn metadata_tables() -> MetadataTables {
+  let mut categories: Vec<Categories> = [
+    Categories::Trait(TraitRef::Any),
+    Categories::Shards(ShardsScriptInfo::default()),
+    Categories::Bundle,
+  ]
+  .into_iter()
+  .chain(TextCategories::ALL.iter().copied().map(Categories::Text))
+  .chain(AudioCategories::ALL.iter().copied().map(Categories::Audio))
+  .chain(
+    TextureCategories::ALL
+      .iter()
+      .copied()
+      .map(Categories::Texture),
+  )
+  .chain(
+    VectorCategories::ALL
+      .iter()
+      .copied()
+      .map(Categories::Vector),
+  )
+  .chain(VideoCategories::ALL.iter().copied().map(Categories::Video))
+  .chain(ModelCategories::ALL.iter().copied().map(Categories::Model))
+  .chain(
+    BinaryCategories::ALL
+      .iter()
+      .copied()
+      .map(Categories::Binary),
+  )
+  .collect();
+  categories.sort_by_key(Categories::stable_id);
+
+  MetadataTables {
+    categories: categories.into_iter().map(category_row).collect(),
+    variable_types: (0..VariableType::VARIANT_COUNT)
+      .map(|index| VariableTypeRow {
+        index,
+        name: variant_name::<VariableType>(index),
+        description: variant_summary::<VariableType>(index),
+        min_shards_version: MIN_SHARDS_VERSIONS[index as usize],
+      })
+      .collect(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn tables() {
+    let tables = metadata_tables();
+    let ids: Vec<_> = tables.categories.iter().map(|row| row.stable_id).collect();
+    assert!(ids.windows(2).all(|w| w[0] < w[1]));
+    assert_eq!(ids.len(), 25);
+    assert_eq!(
+      tables.categories[0],
+      CategoryRow {
+        stable_id: 0,
+        category: "Text",
+        sub_category: Some("Plain"),
+        mime_type: Some("text/plain"),
+        extensions: &["txt"],
+      }
+    );
+    let bundle = tables.categories.last().unwrap();
+    assert_eq!((bundle.category, bundle.sub_category), ("Bundle", None));
+
+    assert_eq!(
+      tables.variable_types.len(),
+      VariableType::VARIANT_COUNT as usize
+    );
+    assert_eq!(tables.variable_types[2].name, "Bool");
+    assert_eq!(tables.variable_types[27].name, "EventV2");
+
+    let json = serde_json::to_value(&tables).unwrap();
+    assert_eq!(json["categories"][0]["subCategory"], "Plain");
+    assert_eq!(json["variableTypes"][2]["minShardsVersion"], 1);
+  }
+}
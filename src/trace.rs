@@ -0,0 +1,78 @@
+//! Optional `tracing` instrumentation, compiled out unless the `tracing` feature is enabled.
+//!
+//! Decoding, validation, canonicalization and hashing run inside `debug` spans named after the
+//! operation and carrying the sizes of their inputs, subscribers measure their durations. Payloads
+//! failing to decode are reported as `warn` events inside their span.
+
+/// Enters a `debug` span until the end of the enclosing block.
+macro_rules! span {
+  ($name:literal $(, $($fields:tt)*)?) => {
+    #[cfg(feature = "tracing")]
+    let _span = tracing::debug_span!($name $(, $($fields)*)?).entered();
+  };
+}
+
+pub(crate) use span;
+
+/// Reports `result` as a malformed payload when it is an error.
+pub(crate) fn malformed<T, E: core::fmt::Display>(result: Result<T, E>) -> Result<T, E> {
+  #[cfg(feature = "tracing")]
+  if let Err(error) = &result {
+    tracing::warn!(%error, "malformed payload");
+  }
+  result
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+  use crate::{limits::DecodeLimits, traits::Trait};
+  use std::sync::{Arc, Mutex};
+  use tracing::{span, Event, Metadata, Subscriber};
+
+  /// Records the names of the spans and the levels of the events.
+  #[derive(Default, Clone)]
+  struct Recorder(Arc<Mutex<Vec<String>>>);
+
+  impl Subscriber for Recorder {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+      true
+    }
+
+    fn new_span(&self, span: &span::Attributes<'_>) -> span::Id {
+      let mut seen = self.0.lock().unwrap();
+      seen.push(span.metadata().name().to_string());
+      span::Id::from_u64(seen.len() as u64)
+    }
+
+    fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+    fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+      let level = event.metadata().level().to_string();
+      self.0.lock().unwrap().push(level);
+    }
+
+    fn enter(&self, _: &span::Id) {}
+
+    fn exit(&self, _: &span::Id) {}
+  }
+
+  #[test]
+  fn spans_and_events() {
+    let recorder = Recorder::default();
+    tracing::subscriber::with_default(recorder.clone(), || {
+      let trait1 = Trait {
+        name: "Empty".to_string(),
+        records: vec![],
+      };
+      let _ = trait1.validate();
+      let _ = trait1.shards_hash();
+      assert!(DecodeLimits::default().decode::<Trait>(&[1]).is_err());
+    });
+    assert_eq!(
+      *recorder.0.lock().unwrap(),
+      ["validate", "shards_hash", "canonicalize", "decode", "WARN"]
+    );
+  }
+}
@@ -0,0 +1,123 @@
+use crate::{
+  categories::ShardsTrait,
+  hex::{HexBytes, HexList},
+  traits::Trait,
+};
+use parity_scale_codec::{Decode, Encode};
+use scale_info::prelude::vec::Vec;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Documentation of a trait, published as a proto referencing the trait it documents.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct TraitDocsBundle {
+  /// The hash of the documented trait
+  pub trait_hash: ShardsTrait,
+  /// Revision of the documentation, increased every time it is republished
+  #[codec(compact)]
+  pub revision: u32,
+  /// UTF-8 markdown documentation
+  pub markdown: Vec<u8>,
+  /// Example scripts using the trait
+  pub examples: Vec<Vec<u8>>,
+}
+
+impl core::fmt::Debug for TraitDocsBundle {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("TraitDocsBundle")
+      .field("trait_hash", &HexBytes(&self.trait_hash))
+      .field("revision", &self.revision)
+      .field("markdown", &HexBytes(&self.markdown))
+      .field("examples", &HexList(&self.examples))
+      .finish()
+  }
+}
+
+/// Errors returned by `TraitDocsBundle::verify`.
+#[derive(Copy, Clone, PartialEq, Debug, Eq)]
+pub enum TraitDocsError {
+  /// The bundle documents another trait
+  HashMismatch {
+    expected: ShardsTrait,
+    actual: ShardsTrait,
+  },
+  /// The markdown is not valid UTF-8
+  InvalidMarkdown,
+}
+
+impl core::fmt::Display for TraitDocsError {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    match self {
+      Self::HashMismatch { expected, actual } => write!(
+        f,
+        "documentation is bound to trait {:?} but the trait hashes to {:?}",
+        expected, actual
+      ),
+      Self::InvalidMarkdown => write!(f, "documentation markdown is not valid UTF-8"),
+    }
+  }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TraitDocsError {}
+
+impl TraitDocsBundle {
+  /// Checks that the bundle documents `trait_` (hashed in its canonical form) and that its
+  /// markdown is valid UTF-8.
+  pub fn verify(&self, trait_: &Trait) -> Result<(), TraitDocsError> {
+    let actual = trait_.shards_hash();
+    if actual != self.trait_hash {
+      return Err(TraitDocsError::HashMismatch {
+        expected: self.trait_hash,
+        actual,
+      });
+    }
+    core::str::from_utf8(&self.markdown).map_err(|_| TraitDocsError::InvalidMarkdown)?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::traits::{Record, VariableType, VariableTypeInfo};
+
+  #[test]
+  fn verify_against_trait() {
+    let mut trait1 = Trait {
+      name: "Documented".to_string(),
+      records: vec![Record::from((
+        "Position".to_string(),
+        vec![VariableTypeInfo {
+          type_: VariableType::Float3(Default::default()),
+          default: None,
+        }],
+      ))],
+    };
+    let mut canonical = trait1.clone();
+    canonical.canonicalize();
+
+    let mut docs = TraitDocsBundle {
+      trait_hash: sp_core::twox_64(&canonical.encode()),
+      revision: 1,
+      markdown: b"# Documented\n`position` is in meters.".to_vec(),
+      examples: vec![b"(Get .position)".to_vec()],
+    };
+    assert_eq!(docs.verify(&trait1), Ok(()));
+
+    docs.markdown = vec![0xff];
+    assert_eq!(docs.verify(&trait1), Err(TraitDocsError::InvalidMarkdown));
+
+    trait1.records[0].types[0].type_ = VariableType::Float2(Default::default());
+    assert!(matches!(
+      docs.verify(&trait1),
+      Err(TraitDocsError::HashMismatch { .. })
+    ));
+  }
+}
@@ -1,20 +1,117 @@
-use crate::categories::{BinaryCategories, TextCategories};
+use crate::{
+  categories::{BinaryCategories, Categories, ShardsTrait, TextCategories},
+  hashing::{TraitHasher, Xx64, XxHash64},
+  hex::HexBytes,
+  limits_wire::{decode_i64, encode_i64},
+  path::{PathSegment, RecordPath},
+  trace,
+};
 use parity_scale_codec::{Compact, Decode, Encode, Input, Output};
-use scale_info::prelude::{boxed::Box, vec::Vec};
+use scale_info::{
+  build::{Fields, Variants},
+  meta_type,
+  prelude::{
+    boxed::Box,
+    string::{FromUtf8Error, String},
+    vec::Vec,
+  },
+  Path, Type, TypeDefArray, TypeInfo,
+};
 
-#[cfg(feature = "std")]
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 // For more info refer to:
 // https://github.com/fragcolor-xyz/shards/blob/devel/include/shards.h
 
-#[cfg(not(feature = "std"))]
-type String = Vec<u8>;
+/// The interface common to all `traits`: the records they all have (by canonical name), each
+/// with the types accepted by every trait (see [`VariableType::intersect`]).
+///
+/// Records whose types do not intersect are left out. The result is canonical and named after
+/// the intersected traits, `A&B`.
+pub fn intersect(traits: &[&Trait]) -> Trait {
+  let Some((first, rest)) = traits.split_first() else {
+    return Trait::default();
+  };
+  let mut result = (*first).clone();
+  result.canonicalize();
+  let mut name = first.name.clone();
+
+  for other in rest {
+    let mut other = (*other).clone();
+    other.canonicalize();
+    name.push('&');
+    name.push_str(&other.name);
+
+    result.records.retain_mut(|record| {
+      let Some(theirs) = other.records.iter().find(|r| r.name == record.name) else {
+        return false;
+      };
+      let mut types: Vec<VariableTypeInfo> = Vec::new();
+      for ours in &record.types {
+        for info in &theirs.types {
+          let Some(type_) = ours.type_.intersect(&info.type_) else {
+            continue;
+          };
+          // defaults only survive when both sides agree
+          let default = (ours.default == info.default)
+            .then(|| ours.default.clone())
+            .flatten();
+          let info = VariableTypeInfo { type_, default };
+          if !types.contains(&info) {
+            types.push(info);
+          }
+        }
+      }
+      record.types = types;
+      !record.types.is_empty()
+    });
+  }
+
+  result.name = name;
+  result.canonicalize();
+  result
+}
+
+/// Canonicalizes `types` and drops the repeated ones, returning whether anything changed.
+fn canonicalize_types(types: &mut Vec<VariableType>) -> bool {
+  let mut changed = false;
+  for t in types.iter_mut() {
+    changed |= t.canonicalize();
+  }
+  let mut i = 0;
+  while i < types.len() {
+    if types[..i].contains(&types[i]) {
+      types.remove(i);
+      changed = true;
+    } else {
+      i += 1;
+    }
+  }
+  changed
+}
+
+/// Canonicalizes the types of a table and sorts its entries by key.
+fn canonicalize_table<K: Ord>(keys: &mut Vec<K>, types: &mut Vec<Vec<VariableType>>) -> bool {
+  let mut changed = false;
+  for types in types.iter_mut() {
+    changed |= canonicalize_types(types);
+  }
+  let sorted = keys.windows(2).all(|w| w[0] <= w[1]);
+  if !sorted && keys.len() == types.len() {
+    let mut entries: Vec<_> = keys.drain(..).zip(types.drain(..)).collect();
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    (*keys, *types) = entries.into_iter().unzip();
+    changed = true;
+  }
+  changed
+}
 
 /// Struct representing limits on numbers (such has min and max values)
-/// Sadly SCALE supports only unsigned integers, so we need to wrap the limits to u64 and unwrap them when decoding.
-#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
-#[derive(Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+/// Sadly SCALE supports only unsigned integers, so we need to wrap the limits to u64 and unwrap them when decoding
+/// (see [`crate::limits_wire`]).
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, PartialEq, Debug, Eq)]
 pub struct Limits {
   /// The minimum value
   pub min: i64,
@@ -27,18 +124,283 @@ pub struct Limits {
   pub scale: u32,
 }
 
-fn wrap_to_u64(x: i64) -> u64 {
-  (x as u64).wrapping_add(u64::MAX / 2 + 1)
+/// The fixed point value `x` brought up `by` more decimal places, in 128 bits.
+///
+/// A value that overflows even there is larger in magnitude than any `i64` and saturates, so
+/// comparisons with rescaled `i64` values still hold.
+fn rescale(x: i64, by: u32) -> i128 {
+  10i128
+    .checked_pow(by)
+    .and_then(|factor| i128::from(x).checked_mul(factor))
+    .unwrap_or(match x.signum() {
+      1 => i128::MAX,
+      -1 => i128::MIN,
+      _ => 0,
+    })
+}
+
+/// Compares the fixed point values `a` at `a_scale` and `b` at `b_scale`, bringing both to the
+/// larger scale.
+fn cmp_scaled(a: i64, a_scale: u32, b: i64, b_scale: u32) -> core::cmp::Ordering {
+  let scale = a_scale.max(b_scale);
+  rescale(a, scale - a_scale).cmp(&rescale(b, scale - b_scale))
+}
+
+impl Limits {
+  /// Whether these limits are at least as wide as `other`, comparing the bounds at the larger of
+  /// both scales.
+  pub fn contains(&self, other: &Limits) -> bool {
+    cmp_scaled(self.min, self.scale, other.min, other.scale).is_le()
+      && cmp_scaled(self.max, self.scale, other.max, other.scale).is_ge()
+  }
+
+  /// The values within both limits, if any, at the larger of both scales.
+  pub fn intersect(&self, other: &Limits) -> Option<Limits> {
+    let scale = self.scale.max(other.scale);
+    let (a, b) = (scale - self.scale, scale - other.scale);
+    let min = rescale(self.min, a).max(rescale(other.min, b));
+    let max = rescale(self.max, a).min(rescale(other.max, b));
+    // the limits at `scale` bound both ends within `i64` when they are not empty
+    (min <= max).then_some(Limits {
+      min: min as i64,
+      max: max as i64,
+      scale,
+    })
+  }
+}
+
+/// The limits of the lanes of a vector type.
+///
+/// Encoded exactly like `[Option<Limits>; N]` when per lane. Shared limits are encoded as a `2`
+/// tag followed by the limits, which per lane limits never start with, and need a
+/// `shards_version` of at least 3 (see [`crate::compat`]).
+#[derive(Clone, PartialEq, Debug, Eq)]
+pub enum VectorLimits<const N: usize> {
+  /// Every lane has the same limits
+  Shared(Limits),
+  /// Each lane has its own limits, if any
+  PerLane([Option<Limits>; N]),
+}
+
+impl<const N: usize> VectorLimits<N> {
+  const SHARED_TAG: u8 = 2;
+
+  /// Shared limits if every lane has the same limits, per lane limits otherwise.
+  pub fn from_lanes(lanes: [Option<Limits>; N]) -> Self {
+    match &lanes[..] {
+      [Some(first), rest @ ..] if rest.iter().all(|l| l.as_ref() == Some(first)) => {
+        Self::Shared(first.clone())
+      }
+      _ => Self::PerLane(lanes),
+    }
+  }
+
+  /// The limits of the lane `index`.
+  pub fn lane(&self, index: usize) -> Option<&Limits> {
+    match self {
+      Self::Shared(limits) => Some(limits),
+      Self::PerLane(lanes) => lanes.get(index)?.as_ref(),
+    }
+  }
+
+  /// The limits of every lane.
+  pub fn to_lanes(&self) -> [Option<Limits>; N] {
+    match self {
+      Self::Shared(limits) => core::array::from_fn(|_| Some(limits.clone())),
+      Self::PerLane(lanes) => lanes.clone(),
+    }
+  }
+
+  /// Whether the lanes use the shared encoding.
+  pub fn is_shared(&self) -> bool {
+    matches!(self, Self::Shared(_))
+  }
+
+  /// Folds per lane limits that are the same on every lane into shared limits, returns whether
+  /// they changed.
+  ///
+  /// Opt-in only: the shared encoding changes the trait hash and needs a newer `shards_version`,
+  /// so [`VariableType::canonicalize`] keeps per lane limits as they are.
+  pub fn fold_shared(&mut self) -> bool {
+    match self {
+      Self::PerLane(lanes) => {
+        let folded = Self::from_lanes(lanes.clone());
+        let changed = folded.is_shared();
+        *self = folded;
+        changed
+      }
+      Self::Shared(_) => false,
+    }
+  }
+}
+
+impl<const N: usize> Default for VectorLimits<N> {
+  fn default() -> Self {
+    Self::PerLane(core::array::from_fn(|_| None))
+  }
+}
+
+impl<const N: usize> From<[Option<Limits>; N]> for VectorLimits<N> {
+  fn from(lanes: [Option<Limits>; N]) -> Self {
+    Self::PerLane(lanes)
+  }
+}
+
+impl<const N: usize> Encode for VectorLimits<N> {
+  fn encode_to<W: Output + ?Sized>(&self, dest: &mut W) {
+    match self {
+      Self::Shared(limits) => {
+        dest.push_byte(Self::SHARED_TAG);
+        limits.encode_to(dest);
+      }
+      Self::PerLane(lanes) => lanes.encode_to(dest),
+    }
+  }
+}
+
+impl<const N: usize> Decode for VectorLimits<N> {
+  fn decode<I: Input>(input: &mut I) -> Result<Self, parity_scale_codec::Error> {
+    // the tag is the `Option` tag of the first lane when per lane
+    let first = match input.read_byte()? {
+      Self::SHARED_TAG => return Ok(Self::Shared(Limits::decode(input)?)),
+      0 => None,
+      1 => Some(Limits::decode(input)?),
+      _ => return Err("Invalid vector limits tag".into()),
+    };
+    let mut lanes = Vec::with_capacity(N);
+    lanes.push(first);
+    for _ in 1..N {
+      lanes.push(Option::<Limits>::decode(input)?);
+    }
+    lanes
+      .try_into()
+      .map(Self::PerLane)
+      .map_err(|_| "Vector limits without lanes".into())
+  }
+}
+
+/// The lanes after the first of per lane limits, `[Option<Limits>; N - 1]` in the metadata.
+struct OtherLanes<const N: usize>;
+
+impl<const N: usize> TypeInfo for OtherLanes<N> {
+  type Identity = Self;
+
+  fn type_info() -> Type {
+    Type::new(
+      Path::default(),
+      Vec::new(),
+      TypeDefArray::new(N.saturating_sub(1) as u32, meta_type::<Option<Limits>>()),
+      Vec::new(),
+    )
+  }
+}
+
+// The metadata follows the wire format: per lane limits are split on the `Option` tag of their
+// first lane, which shares the byte of the shared tag.
+impl<const N: usize> TypeInfo for VectorLimits<N> {
+  type Identity = Self;
+
+  fn type_info() -> Type {
+    Type::builder()
+      .path(Path::new("VectorLimits", module_path!()))
+      .docs(&["The limits of the lanes of a vector type."])
+      .variant(
+        Variants::new()
+          .variant("PerLaneFirstUnlimited", |v| {
+            v.index(0)
+              .fields(Fields::named().field(|f| f.ty::<OtherLanes<N>>().name("lanes")))
+          })
+          .variant("PerLaneFirstLimited", |v| {
+            v.index(1).fields(
+              Fields::named()
+                .field(|f| f.ty::<Limits>().name("first"))
+                .field(|f| f.ty::<OtherLanes<N>>().name("lanes")),
+            )
+          })
+          .variant("Shared", |v| {
+            v.index(Self::SHARED_TAG)
+              .fields(Fields::unnamed().field(|f| f.ty::<Limits>()))
+          }),
+      )
+  }
+}
+
+// Per lane limits are (de)serialized as the array of lanes, shared limits as the limits.
+#[cfg(feature = "serde")]
+impl<const N: usize> Serialize for VectorLimits<N> {
+  fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    match self {
+      Self::Shared(limits) => limits.serialize(serializer),
+      Self::PerLane(lanes) => lanes[..].serialize(serializer),
+    }
+  }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, const N: usize> Deserialize<'de> for VectorLimits<N> {
+  fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Repr {
+      PerLane(Vec<Option<Limits>>),
+      Shared(Limits),
+    }
+    match Repr::deserialize(deserializer)? {
+      Repr::Shared(limits) => Ok(Self::Shared(limits)),
+      Repr::PerLane(lanes) => {
+        let len = lanes.len();
+        lanes.try_into().map(Self::PerLane).map_err(|_| {
+          serde::de::Error::invalid_length(
+            len,
+            &scale_info::prelude::format!("{} lanes", N).as_str(),
+          )
+        })
+      }
+    }
+  }
+}
+
+/// Intersects optional limits, `None` being unlimited. The outer `None` means no intersection.
+fn intersect_limits(a: &Option<Limits>, b: &Option<Limits>) -> Option<Option<Limits>> {
+  match (a, b) {
+    (None, other) | (other, None) => Some(other.clone()),
+    (Some(a), Some(b)) => a.intersect(b).map(Some),
+  }
+}
+
+fn intersect_lanes<const N: usize>(
+  a: &VectorLimits<N>,
+  b: &VectorLimits<N>,
+) -> Option<VectorLimits<N>> {
+  if let (VectorLimits::Shared(a), VectorLimits::Shared(b)) = (a, b) {
+    return a.intersect(b).map(VectorLimits::Shared);
+  }
+  let mut lanes = a.to_lanes();
+  for (lane, theirs) in lanes.iter_mut().zip(b.to_lanes()) {
+    *lane = intersect_limits(lane, &theirs)?;
+  }
+  Some(VectorLimits::PerLane(lanes))
 }
 
-fn to_i64(x: u64) -> i64 {
-  ((x as i64) ^ (1 << 63)) & (1 << 63) | (x & (u64::MAX >> 1)) as i64
+/// The pairwise intersections of two lists of alternative types, without repetitions.
+fn intersect_alternatives(a: &[VariableType], b: &[VariableType]) -> Vec<VariableType> {
+  let mut types = Vec::new();
+  for a in a {
+    for b in b {
+      if let Some(t) = a.intersect(b) {
+        if !types.contains(&t) {
+          types.push(t);
+        }
+      }
+    }
+  }
+  types
 }
 
 impl Encode for Limits {
   fn encode_to<W: Output + ?Sized>(&self, dest: &mut W) {
-    Compact(wrap_to_u64(self.min)).encode_to(dest);
-    Compact(wrap_to_u64(self.max)).encode_to(dest);
+    Compact(encode_i64(self.min)).encode_to(dest);
+    Compact(encode_i64(self.max)).encode_to(dest);
     Compact(self.scale).encode_to(dest);
   }
 }
@@ -46,36 +408,71 @@ impl Encode for Limits {
 impl Decode for Limits {
   fn decode<I: Input>(input: &mut I) -> Result<Self, parity_scale_codec::Error> {
     Ok(Self {
-      min: to_i64(Compact::<u64>::decode(input)?.into()),
-      max: to_i64(Compact::<u64>::decode(input)?.into()),
+      min: decode_i64(Compact::<u64>::decode(input)?.into()),
+      max: decode_i64(Compact::<u64>::decode(input)?.into()),
       scale: Compact::<u32>::decode(input)?.into(),
     })
   }
 }
 
+impl TypeInfo for Limits {
+  type Identity = Self;
+
+  fn type_info() -> Type {
+    Type::builder()
+      .path(Path::new("Limits", module_path!()))
+      .docs(&["Struct representing limits on numbers (such has min and max values)"])
+      .composite(
+        Fields::named()
+          .field(|f| f.compact::<u64>().name("min").type_name("u64"))
+          .field(|f| f.compact::<u64>().name("max").type_name("u64"))
+          .field(|f| f.compact::<u32>().name("scale").type_name("u32")),
+      )
+  }
+}
+
 /// Enum that represents the type of Code.
 ///
 /// There are only two possible types of code:
 /// 1. Shard
-/// 2. Wire
-#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
-#[derive(Encode, Decode, Copy, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+/// 2. Wire, optionally with scheduling metadata (`WireV2`)
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Default, Copy, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
 pub enum CodeType {
   /// A collection of shards that can be injected into more complex blocks of code or wires.
+  #[default]
   Shards,
   /// A single wire that can be executed.
   Wire {
     looped: Option<bool>,
     pure: Option<bool>,
   },
+  /// A single wire that can be executed, with the metadata hosts need to schedule it.
+  WireV2 {
+    looped: Option<bool>,
+    pure: Option<bool>,
+    /// The scheduling priority of the wire, higher runs first
+    priority: Option<i32>,
+    /// Whether the wire runs detached from the wire scheduling it
+    detached: Option<bool>,
+    /// The maximum amount of ticks per second of the wire
+    rate_limit_hz: Option<u32>,
+  },
+}
+
+impl CodeType {
+  /// Whether the code is a wire, with or without scheduling metadata.
+  pub fn is_wire(&self) -> bool {
+    matches!(self, Self::Wire { .. } | Self::WireV2 { .. })
+  }
 }
 
 /// Struct that represents information about a Code.
 ///
 /// Note: There are only two possible types of code: Shard and Wire.
 /// See the `CodeType` enum for more information.
-#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
-#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Default, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
 pub struct CodeInfo {
   /// The type of code, either Shard or Wire.
   pub kind: CodeType,
@@ -91,8 +488,124 @@ pub struct CodeInfo {
   pub output: VariableType,
 }
 
-#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
-#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+impl CodeInfo {
+  /// The XXHash64 of the SCALE encoding of the canonical interface of the code: its requires,
+  /// exposes, inputs and output, ignoring its kind.
+  ///
+  /// Variable names are lowercased and sorted, keeping the first of each name, and nested
+  /// types are canonicalized, so the hash only changes when the contract of the code does.
+  pub fn interface_hash(&self) -> [u8; 8] {
+    let mut canonical = VariableType::Code(Box::new(self.clone())).map(&mut |t| match t {
+      VariableType::Code(mut code) => {
+        for (name, _) in code.requires.iter_mut().chain(code.exposes.iter_mut()) {
+          *name = name.to_lowercase();
+        }
+        VariableType::Code(code)
+      }
+      t => t,
+    });
+    canonical.canonicalize();
+    let VariableType::Code(code) = canonical else {
+      unreachable!("mapping keeps the variant");
+    };
+    crate::hashing::twox_64(&(&code.requires, &code.exposes, &code.inputs, &code.output).encode())
+  }
+
+  /// Checks that the host exposes every variable required by the code, with a type the code
+  /// accepts (see [`VariableType::accepts`]).
+  pub fn check_requirements(
+    &self,
+    host_exposes: &[(String, VariableType)],
+  ) -> Result<(), Vec<MissingRequirement>> {
+    let missing: Vec<MissingRequirement> = self
+      .requires
+      .iter()
+      .filter_map(|(name, required)| {
+        let exposed = host_exposes.iter().find(|(n, _)| n == name).map(|(_, t)| t);
+        match exposed {
+          Some(exposed) if required.accepts(exposed) => None,
+          exposed => Some(MissingRequirement {
+            name: name.clone(),
+            required: required.clone(),
+            exposed: exposed.cloned(),
+          }),
+        }
+      })
+      .collect();
+
+    if missing.is_empty() {
+      Ok(())
+    } else {
+      Err(missing)
+    }
+  }
+}
+
+/// A variable required by some code that the host does not expose with an accepted type.
+#[derive(Clone, PartialEq, Debug, Eq)]
+pub struct MissingRequirement {
+  /// The name of the variable
+  pub name: String,
+  /// The type required by the code
+  pub required: VariableType,
+  /// The type exposed by the host under this name, if any
+  pub exposed: Option<VariableType>,
+}
+
+impl core::fmt::Display for MissingRequirement {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    match self.exposed {
+      None => write!(f, "required variable {} is not exposed", self.name),
+      Some(_) => write!(
+        f,
+        "required variable {} is exposed with an incompatible type",
+        self.name
+      ),
+    }
+  }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MissingRequirement {}
+
+/// A channel type along with the constraints a scheduler must respect when wiring it.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Default, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+pub struct ChannelInfo {
+  /// The type of the values carried by the channel
+  #[cfg_attr(feature = "serde", serde(rename = "type", alias = "type_"))]
+  pub type_: VariableType,
+  /// The amount of values the channel buffers (optional), unbounded if unspecified
+  pub capacity: Option<Limits>,
+}
+
+/// How the values of an event reach their listeners.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+pub enum EventDelivery {
+  /// Only the latest value is kept, older values not yet received are dropped
+  Latest,
+  /// Each value is received at most once, values may be dropped
+  AtMostOnce,
+  /// Each value is received at least once, values may be received again
+  AtLeastOnce,
+  /// Each value is received exactly once
+  ExactlyOnce,
+}
+
+/// An event type along with the constraints a scheduler must respect when wiring it.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Default, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+pub struct EventInfo {
+  /// The type of the values carried by the event
+  #[cfg_attr(feature = "serde", serde(rename = "type", alias = "type_"))]
+  pub type_: VariableType,
+  /// The delivery semantics of the event (optional), any delivery if unspecified
+  pub delivery: Option<EventDelivery>,
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Default, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
 pub struct TableInfo {
   /// The names of the keys. An empty key represents any name and allows multiple instances of the corresponding index type.
   pub keys: Vec<String>,
@@ -100,30 +613,163 @@ pub struct TableInfo {
   pub types: Vec<Vec<VariableType>>,
 }
 
+impl TableInfo {
+  /// The index of the key `key`, or of the empty key if the table does not declare `key`.
+  pub fn key_index(&self, key: &str) -> Option<usize> {
+    self
+      .keys
+      .iter()
+      .position(|k| k == key)
+      .or_else(|| self.keys.iter().position(String::is_empty))
+  }
+
+  /// The types a table value under `key` can have, `None` if the table does not declare the key.
+  pub fn key_types(&self, key: &str) -> Option<&[VariableType]> {
+    let index = self.key_index(key)?;
+    Some(self.types.get(index).map_or(&[][..], |t| &t[..]))
+  }
+}
+
+/// How a key of a `TableInfoV2` matches the keys of table values.
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+#[derive(
+  Encode, Decode, Clone, PartialEq, Debug, Eq, PartialOrd, Ord, Hash, scale_info::TypeInfo,
+)]
+pub enum KeyPattern {
+  /// Only this key
+  Exact(String),
+  /// Any key starting with this prefix
+  Prefix(String),
+  /// Any key
+  Any,
+}
+
+impl KeyPattern {
+  /// The character ending prefix keys in the textual form of patterns.
+  pub const WILDCARD: char = '*';
+
+  /// The pattern written as `key`: the empty key is `Any`, a key ending with `*` is a `Prefix` and
+  /// any other key is `Exact`.
+  pub fn parse(key: &str) -> Self {
+    match key.strip_suffix(Self::WILDCARD) {
+      _ if key.is_empty() => Self::Any,
+      Some(prefix) => Self::Prefix(prefix.into()),
+      None => Self::Exact(key.into()),
+    }
+  }
+
+  /// The textual form of the pattern, see [`KeyPattern::parse`].
+  pub fn to_key(&self) -> String {
+    match self {
+      Self::Exact(key) => key.clone(),
+      Self::Prefix(prefix) => {
+        let mut key = prefix.clone();
+        key.push(Self::WILDCARD);
+        key
+      }
+      Self::Any => String::new(),
+    }
+  }
+
+  /// Whether the pattern matches the table value key `key`.
+  pub fn matches(&self, key: &str) -> bool {
+    match self {
+      Self::Exact(exact) => exact == key,
+      Self::Prefix(prefix) => key.starts_with(prefix.as_str()),
+      Self::Any => true,
+    }
+  }
+
+  /// Exact keys first, then the longest prefixes.
+  fn specificity(&self) -> (u8, usize) {
+    match self {
+      Self::Exact(_) => (2, 0),
+      Self::Prefix(prefix) => (1, prefix.len()),
+      Self::Any => (0, 0),
+    }
+  }
+}
+
+/// A table type whose keys are patterns, needs a `shards_version` of at least 4 (see
+/// [`crate::compat`]).
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Default, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+pub struct TableInfoV2 {
+  /// The patterns of the keys, a table value key uses the types of the most specific pattern
+  /// matching it (see [`TableInfoV2::key_index`]).
+  pub keys: Vec<KeyPattern>,
+  /// The types expected for each key, following the keys array (should be the same length).
+  pub types: Vec<Vec<VariableType>>,
+}
+
+impl TableInfoV2 {
+  /// The index of the most specific pattern matching the table value key `key`, if any: exact
+  /// keys first, then the longest prefixes, then `Any`. Equally specific patterns resolve to the
+  /// first one.
+  pub fn key_index(&self, key: &str) -> Option<usize> {
+    self
+      .keys
+      .iter()
+      .enumerate()
+      .filter(|(_, pattern)| pattern.matches(key))
+      .max_by(|(i, a), (j, b)| a.specificity().cmp(&b.specificity()).then(j.cmp(i)))
+      .map(|(index, _)| index)
+  }
+
+  /// The types a table value under `key` can have, `None` if no pattern matches.
+  pub fn key_types(&self, key: &str) -> Option<&[VariableType]> {
+    let index = self.key_index(key)?;
+    Some(self.types.get(index).map_or(&[][..], |t| &t[..]))
+  }
+}
+
+/// Exact keys, the empty key becoming `KeyPattern::Any`.
+impl From<TableInfo> for TableInfoV2 {
+  fn from(table: TableInfo) -> Self {
+    Self {
+      keys: table
+        .keys
+        .into_iter()
+        .map(|key| match key.is_empty() {
+          true => KeyPattern::Any,
+          false => KeyPattern::Exact(key),
+        })
+        .collect(),
+      types: table.types,
+    }
+  }
+}
+
 /// Enum represents all the possible types that a variable can be
-#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
-#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Default, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[allow(clippy::large_enum_variant)] // boxing the vector limits would change the public API
 pub enum VariableType {
-  // No type
+  /// No type
+  #[default]
   None,
-  // Any type
+  /// Any type
   Any,
-  // Boolean type
+  /// Boolean type
   Bool,
-  // Color type (vector of 4 8-bit unsigned integers)
+  /// Color type (vector of 4 8-bit unsigned integers)
   Color,
-  // Binary data type
+  /// Binary data type
   Bytes(Option<BinaryCategories>),
-  // String type
+  /// String type
   String(Option<TextCategories>),
-  // Image type
+  /// Image type
   Image,
-  // Audio type
+  /// Audio type
   Audio,
-  // Shards Mesh type
+  /// Shards Mesh type
   Mesh,
 
-  // Enum type with vendor ID and type ID
+  /// Enum type with vendor ID and type ID
   Enum {
     #[codec(compact)]
     vendor_id: u32,
@@ -131,38 +777,38 @@ pub enum VariableType {
     type_id: u32,
   },
 
-  // Integer type with optional limits
+  /// Integer type with optional limits
   Int(Option<Limits>),
-  // Vector of 2 integers with optional limits
-  Int2([Option<Limits>; 2]),
-  // Vector of 3 integers with optional limits
-  Int3([Option<Limits>; 3]),
-  // Vector of 4 integers with optional limits
-  Int4([Option<Limits>; 4]),
-  // Vector of 8 integers with optional limits
-  Int8([Option<Limits>; 8]),
-  // Vector of 16 integers with optional limits
-  Int16([Option<Limits>; 16]),
-
-  // Float type with optional limits
+  /// Vector of 2 integers with optional limits
+  Int2(VectorLimits<2>),
+  /// Vector of 3 integers with optional limits
+  Int3(VectorLimits<3>),
+  /// Vector of 4 integers with optional limits
+  Int4(VectorLimits<4>),
+  /// Vector of 8 integers with optional limits
+  Int8(VectorLimits<8>),
+  /// Vector of 16 integers with optional limits
+  Int16(VectorLimits<16>),
+
+  /// Float type with optional limits
   Float(Option<Limits>),
-  // Vector of 2 floats with optional limits
-  Float2([Option<Limits>; 2]),
-  // Vector of 3 floats with optional limits
-  Float3([Option<Limits>; 3]),
-  // Vector of 4 floats with optional limits
-  Float4([Option<Limits>; 4]),
-
-  // Sequence of variable types with optional length limits
+  /// Vector of 2 floats with optional limits
+  Float2(VectorLimits<2>),
+  /// Vector of 3 floats with optional limits
+  Float3(VectorLimits<3>),
+  /// Vector of 4 floats with optional limits
+  Float4(VectorLimits<4>),
+
+  /// Sequence of variable types with optional length limits
   Seq {
     types: Vec<VariableType>,
     length_limits: Option<Limits>,
   },
 
-  // Table type
+  /// Table type
   Table(TableInfo),
 
-  // Object type with vendor ID and type ID
+  /// Object type with vendor ID and type ID
   Object {
     #[codec(compact)]
     vendor_id: u32,
@@ -170,26 +816,477 @@ pub enum VariableType {
     type_id: u32,
   },
 
-  // Code type with information
+  /// Code type with information
   Code(Box<CodeInfo>),
-  // Channel type with variable type
+  /// Channel type with variable type
   Channel(Box<VariableType>),
-  // Event type with variable type
+  /// Event type with variable type
   Event(Box<VariableType>),
+  /// Channel type with variable type and buffer constraints
+  ChannelV2(Box<ChannelInfo>),
+  /// Event type with variable type and delivery semantics
+  EventV2(Box<EventInfo>),
+  /// Table type with key patterns
+  TableV2(TableInfoV2),
+}
+
+impl VariableType {
+  /// The amount of variants, SCALE indexes range from 0 to `VARIANT_COUNT - 1`.
+  pub const VARIANT_COUNT: u8 = 29;
+
+  /// The description of the variant, taken from its doc comment.
+  ///
+  /// Empty unless the `docs` feature is enabled.
+  pub fn describe(&self) -> &'static str {
+    crate::docs::variant_summary::<Self>(self.variant_index())
+  }
+
+  /// The SCALE index of the variant.
+  pub const fn variant_index(&self) -> u8 {
+    match self {
+      Self::None => 0,
+      Self::Any => 1,
+      Self::Bool => 2,
+      Self::Color => 3,
+      Self::Bytes(_) => 4,
+      Self::String(_) => 5,
+      Self::Image => 6,
+      Self::Audio => 7,
+      Self::Mesh => 8,
+      Self::Enum { .. } => 9,
+      Self::Int(_) => 10,
+      Self::Int2(_) => 11,
+      Self::Int3(_) => 12,
+      Self::Int4(_) => 13,
+      Self::Int8(_) => 14,
+      Self::Int16(_) => 15,
+      Self::Float(_) => 16,
+      Self::Float2(_) => 17,
+      Self::Float3(_) => 18,
+      Self::Float4(_) => 19,
+      Self::Seq { .. } => 20,
+      Self::Table(_) => 21,
+      Self::Object { .. } => 22,
+      Self::Code(_) => 23,
+      Self::Channel(_) => 24,
+      Self::Event(_) => 25,
+      Self::ChannelV2(_) => 26,
+      Self::EventV2(_) => 27,
+      Self::TableV2(_) => 28,
+    }
+  }
+
+  /// The types a value of a table type can have under `key`, `None` for other types and keys the
+  /// table does not declare (see [`TableInfo::key_types`] and [`TableInfoV2::key_types`]).
+  pub fn table_key_types(&self, key: &str) -> Option<&[VariableType]> {
+    match self {
+      Self::Table(table) => table.key_types(key),
+      Self::TableV2(table) => table.key_types(key),
+      _ => None,
+    }
+  }
+
+  /// The type carried by a channel or an event.
+  pub fn carried(&self) -> Option<&VariableType> {
+    match self {
+      Self::Channel(inner) | Self::Event(inner) => Some(inner),
+      Self::ChannelV2(info) => Some(&info.type_),
+      Self::EventV2(info) => Some(&info.type_),
+      _ => None,
+    }
+  }
+
+  fn carried_mut(&mut self) -> Option<&mut VariableType> {
+    match self {
+      Self::Channel(inner) | Self::Event(inner) => Some(inner),
+      Self::ChannelV2(info) => Some(&mut info.type_),
+      Self::EventV2(info) => Some(&mut info.type_),
+      _ => None,
+    }
+  }
+
+  /// The carried type and the capacity limits of a channel, plain channels have no limits.
+  fn as_channel(&self) -> Option<(&VariableType, &Option<Limits>)> {
+    match self {
+      Self::Channel(inner) => Some((inner, &None)),
+      Self::ChannelV2(info) => Some((&info.type_, &info.capacity)),
+      _ => None,
+    }
+  }
+
+  /// The carried type and the delivery semantics of an event, plain events have none.
+  fn as_event(&self) -> Option<(&VariableType, Option<EventDelivery>)> {
+    match self {
+      Self::Event(inner) => Some((inner, None)),
+      Self::EventV2(info) => Some((&info.type_, info.delivery)),
+      _ => None,
+    }
+  }
+}
+
+fn limits_accept(ours: &Option<Limits>, theirs: &Option<Limits>) -> bool {
+  match (ours, theirs) {
+    (None, _) => true,
+    (Some(_), None) => false,
+    (Some(ours), Some(theirs)) => ours.contains(theirs),
+  }
+}
+
+fn lanes_accept<const N: usize>(ours: &VectorLimits<N>, theirs: &VectorLimits<N>) -> bool {
+  ours
+    .to_lanes()
+    .iter()
+    .zip(&theirs.to_lanes())
+    .all(|(o, t)| limits_accept(o, t))
+}
+
+impl VariableType {
+  /// Whether a value of type `other` can be used where a value of this type is expected.
+  ///
+  /// `Any` accepts everything, categories and limits left unspecified accept any category or value,
+  /// sequences accept sequences whose every type is accepted by one of theirs.
+  pub fn accepts(&self, other: &VariableType) -> bool {
+    use VariableType::*;
+    match (self, other) {
+      (Any, _) => true,
+      (Bytes(Option::None), Bytes(_)) | (String(Option::None), String(_)) => true,
+      (Int(o), Int(t)) | (Float(o), Float(t)) => limits_accept(o, t),
+      (Int2(o), Int2(t)) | (Float2(o), Float2(t)) => lanes_accept(o, t),
+      (Int3(o), Int3(t)) | (Float3(o), Float3(t)) => lanes_accept(o, t),
+      (Int4(o), Int4(t)) | (Float4(o), Float4(t)) => lanes_accept(o, t),
+      (Int8(o), Int8(t)) => lanes_accept(o, t),
+      (Int16(o), Int16(t)) => lanes_accept(o, t),
+      (
+        Seq {
+          types: ours,
+          length_limits: our_limits,
+        },
+        Seq {
+          types: theirs,
+          length_limits: their_limits,
+        },
+      ) => {
+        limits_accept(our_limits, their_limits)
+          && (ours.is_empty() || theirs.iter().all(|t| ours.iter().any(|o| o.accepts(t))))
+      }
+      (Channel(_) | ChannelV2(_), Channel(_) | ChannelV2(_)) => {
+        match (self.as_channel(), other.as_channel()) {
+          (Some((o, o_capacity)), Some((t, t_capacity))) => {
+            limits_accept(o_capacity, t_capacity) && o.accepts(t)
+          }
+          _ => false,
+        }
+      }
+      (Event(_) | EventV2(_), Event(_) | EventV2(_)) => match (self.as_event(), other.as_event()) {
+        (Some((o, o_delivery)), Some((t, t_delivery))) => {
+          o_delivery.is_none_or(|d| t_delivery == Some(d)) && o.accepts(t)
+        }
+        _ => false,
+      },
+      _ => self == other,
+    }
+  }
+
+  /// Whether a proto of `category` can be stored in a variable of this type.
+  pub fn accepts_category(&self, category: &Categories) -> bool {
+    match (self, category) {
+      (VariableType::Any, _) => true,
+      (VariableType::String(None), Categories::Text(_)) => true,
+      (VariableType::String(Some(ours)), Categories::Text(theirs)) => ours == theirs,
+      (VariableType::Bytes(None), Categories::Binary(_)) => true,
+      (VariableType::Bytes(Some(ours)), Categories::Binary(theirs)) => ours == theirs,
+      (VariableType::Image, Categories::Texture(_)) => true,
+      (VariableType::Audio, Categories::Audio(_)) => true,
+      (VariableType::Mesh, Categories::Model(_)) => true,
+      (VariableType::Code(_), Categories::Shards(_)) => true,
+      _ => false,
+    }
+  }
+
+  /// Rebuilds the type bottom-up, nested types are transformed before the type containing them.
+  pub fn try_map<E>(
+    self,
+    f: &mut impl FnMut(VariableType) -> Result<VariableType, E>,
+  ) -> Result<VariableType, E> {
+    let mapped = match self {
+      VariableType::Seq {
+        types,
+        length_limits,
+      } => VariableType::Seq {
+        types: types
+          .into_iter()
+          .map(|t| t.try_map(f))
+          .collect::<Result<_, _>>()?,
+        length_limits,
+      },
+      VariableType::Table(TableInfo { keys, types }) => VariableType::Table(TableInfo {
+        keys,
+        types: types
+          .into_iter()
+          .map(|types| types.into_iter().map(|t| t.try_map(f)).collect())
+          .collect::<Result<_, _>>()?,
+      }),
+      VariableType::TableV2(TableInfoV2 { keys, types }) => VariableType::TableV2(TableInfoV2 {
+        keys,
+        types: types
+          .into_iter()
+          .map(|types| types.into_iter().map(|t| t.try_map(f)).collect())
+          .collect::<Result<_, _>>()?,
+      }),
+      VariableType::Code(code) => {
+        let CodeInfo {
+          kind,
+          requires,
+          exposes,
+          inputs,
+          output,
+        } = *code;
+        let mut map_named = |vars: Vec<(String, VariableType)>| {
+          vars
+            .into_iter()
+            .map(|(name, t)| Ok((name, t.try_map(f)?)))
+            .collect::<Result<Vec<_>, E>>()
+        };
+        let requires = map_named(requires)?;
+        let exposes = map_named(exposes)?;
+        VariableType::Code(Box::new(CodeInfo {
+          kind,
+          requires,
+          exposes,
+          inputs: inputs
+            .into_iter()
+            .map(|t| t.try_map(f))
+            .collect::<Result<_, _>>()?,
+          output: output.try_map(f)?,
+        }))
+      }
+      VariableType::Channel(inner) => VariableType::Channel(Box::new(inner.try_map(f)?)),
+      VariableType::Event(inner) => VariableType::Event(Box::new(inner.try_map(f)?)),
+      VariableType::ChannelV2(info) => {
+        let ChannelInfo { type_, capacity } = *info;
+        VariableType::ChannelV2(Box::new(ChannelInfo {
+          type_: type_.try_map(f)?,
+          capacity,
+        }))
+      }
+      VariableType::EventV2(info) => {
+        let EventInfo { type_, delivery } = *info;
+        VariableType::EventV2(Box::new(EventInfo {
+          type_: type_.try_map(f)?,
+          delivery,
+        }))
+      }
+      leaf => leaf,
+    };
+    f(mapped)
+  }
+
+  /// Infallible version of [`VariableType::try_map`].
+  pub fn map(self, f: &mut impl FnMut(VariableType) -> VariableType) -> VariableType {
+    match self.try_map::<core::convert::Infallible>(&mut |t| Ok(f(t))) {
+      Ok(t) => t,
+      Err(never) => match never {},
+    }
+  }
+
+  /// Brings the nested types into canonical form, returning whether anything changed.
+  ///
+  /// Alternative types listed several times keep their first occurrence, table entries are
+  /// sorted by key and code variables are sorted by name, keeping the first of each name.
+  /// Vector limits keep their encoding, folding them is left to [`VectorLimits::fold_shared`].
+  /// Duplicate table keys are left for [`Trait::validate`] to report.
+  pub fn canonicalize(&mut self) -> bool {
+    match self {
+      VariableType::Seq { types, .. } => canonicalize_types(types),
+      VariableType::Table(table) => canonicalize_table(&mut table.keys, &mut table.types),
+      VariableType::TableV2(table) => canonicalize_table(&mut table.keys, &mut table.types),
+      VariableType::Code(code) => {
+        let mut changed = false;
+        for vars in [&mut code.requires, &mut code.exposes] {
+          for (_, t) in vars.iter_mut() {
+            changed |= t.canonicalize();
+          }
+          if !vars.windows(2).all(|w| w[0].0 < w[1].0) {
+            vars.sort_by(|a, b| a.0.cmp(&b.0));
+            vars.dedup_by(|next, kept| next.0 == kept.0);
+            changed = true;
+          }
+        }
+        changed |= canonicalize_types(&mut code.inputs);
+        changed | code.output.canonicalize()
+      }
+      other => other.carried_mut().is_some_and(VariableType::canonicalize),
+    }
+  }
+
+  /// The narrowest type accepted by both `self` and `other`, if any.
+  ///
+  /// `Any` narrows to the other type, categories narrow to the categorized type, numeric limits
+  /// to their overlap and sequences to the intersections of their element types. Tables and code
+  /// only intersect with equal types.
+  pub fn intersect(&self, other: &VariableType) -> Option<VariableType> {
+    use VariableType::*;
+    if self == other {
+      return Some(self.clone());
+    }
+    Some(match (self, other) {
+      (Any, t) | (t, Any) => t.clone(),
+      (Bytes(Option::None), t @ Bytes(_)) | (t @ Bytes(_), Bytes(Option::None)) => t.clone(),
+      (String(Option::None), t @ String(_)) | (t @ String(_), String(Option::None)) => t.clone(),
+      (Int(a), Int(b)) => Int(intersect_limits(a, b)?),
+      (Int2(a), Int2(b)) => Int2(intersect_lanes(a, b)?),
+      (Int3(a), Int3(b)) => Int3(intersect_lanes(a, b)?),
+      (Int4(a), Int4(b)) => Int4(intersect_lanes(a, b)?),
+      (Int8(a), Int8(b)) => Int8(intersect_lanes(a, b)?),
+      (Int16(a), Int16(b)) => Int16(intersect_lanes(a, b)?),
+      (Float(a), Float(b)) => Float(intersect_limits(a, b)?),
+      (Float2(a), Float2(b)) => Float2(intersect_lanes(a, b)?),
+      (Float3(a), Float3(b)) => Float3(intersect_lanes(a, b)?),
+      (Float4(a), Float4(b)) => Float4(intersect_lanes(a, b)?),
+      (
+        Seq {
+          types: a,
+          length_limits: a_limits,
+        },
+        Seq {
+          types: b,
+          length_limits: b_limits,
+        },
+      ) => {
+        // no element types accepts any element
+        let types = match (a.is_empty(), b.is_empty()) {
+          (true, _) => b.clone(),
+          (_, true) => a.clone(),
+          _ => match intersect_alternatives(a, b) {
+            types if types.is_empty() => return Option::None,
+            types => types,
+          },
+        };
+        Seq {
+          types,
+          length_limits: intersect_limits(a_limits, b_limits)?,
+        }
+      }
+      (Channel(a), Channel(b)) => Channel(Box::new(a.intersect(b)?)),
+      (Event(a), Event(b)) => Event(Box::new(a.intersect(b)?)),
+      (Channel(_) | ChannelV2(_), Channel(_) | ChannelV2(_)) => {
+        let ((a, a_capacity), (b, b_capacity)) = (self.as_channel()?, other.as_channel()?);
+        ChannelV2(Box::new(ChannelInfo {
+          type_: a.intersect(b)?,
+          capacity: intersect_limits(a_capacity, b_capacity)?,
+        }))
+      }
+      (Event(_) | EventV2(_), Event(_) | EventV2(_)) => {
+        let ((a, a_delivery), (b, b_delivery)) = (self.as_event()?, other.as_event()?);
+        let delivery = match (a_delivery, b_delivery) {
+          (Some(a), Some(b)) if a != b => return Option::None,
+          (a, b) => a.or(b),
+        };
+        EventV2(Box::new(EventInfo {
+          type_: a.intersect(b)?,
+          delivery,
+        }))
+      }
+      _ => return Option::None,
+    })
+  }
+
+  /// Whether this type, or any type nested in it, matches `predicate`.
+  pub fn any_nested(&self, predicate: &mut impl FnMut(&VariableType) -> bool) -> bool {
+    if predicate(self) {
+      return true;
+    }
+    match self {
+      VariableType::Seq { types, .. } => types.iter().any(|t| t.any_nested(predicate)),
+      VariableType::Table(TableInfo { types, .. })
+      | VariableType::TableV2(TableInfoV2 { types, .. }) => {
+        types.iter().flatten().any(|t| t.any_nested(predicate))
+      }
+      VariableType::Code(code) => code
+        .requires
+        .iter()
+        .chain(code.exposes.iter())
+        .map(|(_, t)| t)
+        .chain(code.inputs.iter())
+        .chain([&code.output])
+        .any(|t| t.any_nested(predicate)),
+      other => other
+        .carried()
+        .is_some_and(|inner| inner.any_nested(predicate)),
+    }
+  }
 }
 
 /// Struct contains information about a variable type
-#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
-#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
 pub struct VariableTypeInfo {
   /// The variable type
-  #[cfg_attr(feature = "std", serde(alias = "type"))]
+  #[cfg_attr(feature = "serde", serde(rename = "type", alias = "type_"))]
   pub type_: VariableType,
   /// Raw-bytes representation of the default value of the variable type (optional)
   pub default: Option<Vec<u8>>,
 }
 
-#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+impl VariableTypeInfo {
+  /// The variable type, serialized as `"type"`.
+  pub fn kind(&self) -> &VariableType {
+    &self.type_
+  }
+
+  /// Replaces the variable type.
+  pub fn set_kind(&mut self, kind: VariableType) {
+    self.type_ = kind;
+  }
+}
+
+/// The metadata names the variable type `type`, as JSON does, rather than after the Rust field.
+impl TypeInfo for VariableTypeInfo {
+  type Identity = Self;
+
+  fn type_info() -> Type {
+    Type::builder()
+      .path(Path::new("VariableTypeInfo", module_path!()))
+      .docs(&["Struct contains information about a variable type"])
+      .composite(
+        Fields::named()
+          .field(|f| {
+            f.ty::<VariableType>()
+              .name("type")
+              .type_name("VariableType")
+              .docs(&["The variable type"])
+          })
+          .field(|f| {
+            f.ty::<Option<Vec<u8>>>()
+              .name("default")
+              .type_name("Option<Vec<u8>>")
+              .docs(&[
+                "Raw-bytes representation of the default value of the variable type (optional)",
+              ])
+          }),
+      )
+  }
+}
+
+impl core::fmt::Debug for VariableTypeInfo {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("VariableTypeInfo")
+      .field("type_", &self.type_)
+      .field("default", &self.default.as_deref().map(HexBytes))
+      .finish()
+  }
+}
+
+impl Default for VariableTypeInfo {
+  fn default() -> Self {
+    Self {
+      type_: VariableType::Any,
+      default: None,
+    }
+  }
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
 pub struct Record {
   pub name: String,
@@ -203,8 +1300,8 @@ impl From<(String, Vec<VariableTypeInfo>)> for Record {
 }
 
 /// Struct represents a Trait
-#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
-#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Default, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
 pub struct Trait {
   /// Name of the Trait
   pub name: String,
@@ -212,46 +1309,1027 @@ pub struct Trait {
   pub records: Vec<Record>,
 }
 
-#[cfg(test)]
-mod tests {
-  use super::*;
-  use crate::categories::TextCategories;
-
-  #[test]
-  fn encode_decode_simple_1() {
-    let mut trait1: Vec<Record> = vec![(
-      "int1".to_string(),
-      vec![VariableTypeInfo {
-        type_: VariableType::Int(None),
-        default: None,
-      }],
-    )
-      .into()];
+/// A record of a [`LegacyTrait`].
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+pub struct LegacyRecord {
+  /// The name as raw bytes, not necessarily UTF-8
+  pub name: Vec<u8>,
+  pub types: Vec<VariableTypeInfo>,
+}
 
-    // THIS IS the way we reprocess the trait declaration before sorting it on chain and hashing it
-    trait1 = trait1
-      .into_iter()
-      .map(|r| (r.name.to_lowercase(), r.types).into())
-      .collect();
-    trait1.dedup_by(|a, b| a.name == b.name);
-    // Note: "Strings are ordered lexicographically by their byte values ... This is not necessarily the same as “alphabetical” order, which varies by language and locale". Source: https://doc.rust-lang.org/std/primitive.str.html#impl-Ord-for-str
-    trait1.sort_by(|a, b| a.name.cmp(&b.name));
+/// A trait with the names as raw bytes, as no_std builds used to encode them.
+///
+/// The wire format is the one of [`Trait`], which refuses names that are not valid UTF-8. Decode
+/// traits stored by older no_std builds as a `LegacyTrait` to keep their exact names, then
+/// convert them with `Trait::try_from`.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, scale_info::TypeInfo)]
+pub struct LegacyTrait {
+  /// The name as raw bytes, not necessarily UTF-8
+  pub name: Vec<u8>,
+  pub records: Vec<LegacyRecord>,
+}
 
-    let trait1 = Trait {
-      name: "Trait1".to_string(),
-      records: trait1,
-    };
+impl From<Trait> for LegacyTrait {
+  fn from(trait_: Trait) -> Self {
+    Self {
+      name: trait_.name.into_bytes(),
+      records: trait_
+        .records
+        .into_iter()
+        .map(|record| LegacyRecord {
+          name: record.name.into_bytes(),
+          types: record.types,
+        })
+        .collect(),
+    }
+  }
+}
 
-    let e_trait1 = trait1.encode();
+/// Fails on the first name that is not valid UTF-8, its bytes are kept in the error.
+impl TryFrom<LegacyTrait> for Trait {
+  type Error = FromUtf8Error;
+
+  fn try_from(legacy: LegacyTrait) -> Result<Self, Self::Error> {
+    Ok(Self {
+      name: String::from_utf8(legacy.name)?,
+      records: legacy
+        .records
+        .into_iter()
+        .map(|record| {
+          Ok(Record {
+            name: String::from_utf8(record.name)?,
+            types: record.types,
+          })
+        })
+        .collect::<Result<_, FromUtf8Error>>()?,
+    })
+  }
+}
+
+/// A problem found by [`Trait::validate`].
+#[derive(Clone, PartialEq, Debug, Eq)]
+pub enum TraitError {
+  /// The trait has no records
+  NoRecords,
+  /// A record has an empty name
+  EmptyRecordName,
+  /// Several records share this canonical name
+  DuplicateRecord(String),
+  /// The record has no types
+  NoTypes(String),
+  /// The type at `path` is invalid
+  InvalidType { path: RecordPath, issue: TypeIssue },
+}
+
+/// Why a type nested in a trait is invalid.
+#[derive(Clone, PartialEq, Debug, Eq)]
+pub enum TypeIssue {
+  /// The table has this key several times
+  DuplicateTableKey(String),
+  /// The table does not have as many type lists as keys
+  TableLengthMismatch { keys: u32, types: u32 },
+  /// Limits with a minimum above their maximum
+  InvalidLimits { min: i64, max: i64 },
+  /// The code requires or exposes this variable several times
+  DuplicateVariable(String),
+}
+
+impl core::fmt::Display for TypeIssue {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    match self {
+      Self::DuplicateTableKey(key) => write!(f, "duplicate table key {}", key),
+      Self::TableLengthMismatch { keys, types } => {
+        write!(f, "table has {} keys but {} type lists", keys, types)
+      }
+      Self::InvalidLimits { min, max } => write!(f, "limits minimum {} above maximum {}", min, max),
+      Self::DuplicateVariable(name) => write!(f, "duplicate code variable {}", name),
+    }
+  }
+}
+
+impl core::fmt::Display for TraitError {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    match self {
+      Self::NoRecords => write!(f, "trait has no records"),
+      Self::EmptyRecordName => write!(f, "record with an empty name"),
+      Self::DuplicateRecord(name) => write!(f, "duplicate record {}", name),
+      Self::NoTypes(name) => write!(f, "record {} has no types", name),
+      Self::InvalidType { path, issue } => write!(f, "{} at {}", issue, path),
+    }
+  }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TraitError {}
+
+fn invalid(errors: &mut Vec<TraitError>, path: &RecordPath, issue: TypeIssue) {
+  errors.push(TraitError::InvalidType {
+    path: path.clone(),
+    issue,
+  });
+}
+
+fn validate_type(ty: &VariableType, path: &mut RecordPath, errors: &mut Vec<TraitError>) {
+  let limits: Vec<Option<Limits>> = match ty {
+    VariableType::Int(l) | VariableType::Float(l) => Vec::from([l.clone()]),
+    VariableType::Seq { length_limits, .. } => Vec::from([length_limits.clone()]),
+    VariableType::ChannelV2(info) => Vec::from([info.capacity.clone()]),
+    VariableType::Int2(l) | VariableType::Float2(l) => l.to_lanes().into(),
+    VariableType::Int3(l) | VariableType::Float3(l) => l.to_lanes().into(),
+    VariableType::Int4(l) | VariableType::Float4(l) => l.to_lanes().into(),
+    VariableType::Int8(l) => l.to_lanes().into(),
+    VariableType::Int16(l) => l.to_lanes().into(),
+    _ => Vec::new(),
+  };
+  for limits in limits.iter().flatten() {
+    if limits.min > limits.max {
+      invalid(
+        errors,
+        path,
+        TypeIssue::InvalidLimits {
+          min: limits.min,
+          max: limits.max,
+        },
+      );
+    }
+  }
+
+  match ty {
+    VariableType::Seq { types, .. } => {
+      for (i, t) in types.iter().enumerate() {
+        path.segments.push(PathSegment::Seq(i as u32));
+        validate_type(t, path, errors);
+        path.segments.pop();
+      }
+    }
+    VariableType::Table(table) => validate_table(&table.keys, &table.types, path, errors),
+    VariableType::TableV2(table) => {
+      let keys: Vec<_> = table.keys.iter().map(KeyPattern::to_key).collect();
+      validate_table(&keys, &table.types, path, errors)
+    }
+    VariableType::Code(code) => {
+      let variables = [
+        (
+          &code.requires,
+          PathSegment::CodeRequires as fn(String) -> PathSegment,
+        ),
+        (&code.exposes, PathSegment::CodeExposes),
+      ];
+      for (vars, segment) in variables {
+        for (i, (name, t)) in vars.iter().enumerate() {
+          if vars[..i].iter().filter(|(n, _)| n == name).count() == 1 {
+            invalid(errors, path, TypeIssue::DuplicateVariable(name.clone()));
+          }
+          path.segments.push(segment(name.clone()));
+          validate_type(t, path, errors);
+          path.segments.pop();
+        }
+      }
+      for (i, t) in code.inputs.iter().enumerate() {
+        path.segments.push(PathSegment::CodeInput(i as u32));
+        validate_type(t, path, errors);
+        path.segments.pop();
+      }
+      path.segments.push(PathSegment::CodeOutput);
+      validate_type(&code.output, path, errors);
+      path.segments.pop();
+    }
+    VariableType::Channel(inner) => validate_carried(inner, PathSegment::Channel, path, errors),
+    VariableType::Event(inner) => validate_carried(inner, PathSegment::Event, path, errors),
+    VariableType::ChannelV2(info) => {
+      validate_carried(&info.type_, PathSegment::Channel, path, errors)
+    }
+    VariableType::EventV2(info) => validate_carried(&info.type_, PathSegment::Event, path, errors),
+    _ => {}
+  }
+}
+
+/// Validates the keys and types of a table, keys in their textual form.
+fn validate_table(
+  keys: &[String],
+  types: &[Vec<VariableType>],
+  path: &mut RecordPath,
+  errors: &mut Vec<TraitError>,
+) {
+  for (i, key) in keys.iter().enumerate() {
+    if keys[..i].iter().filter(|k| *k == key).count() == 1 {
+      invalid(errors, path, TypeIssue::DuplicateTableKey(key.clone()));
+    }
+  }
+  if keys.len() != types.len() {
+    invalid(
+      errors,
+      path,
+      TypeIssue::TableLengthMismatch {
+        keys: keys.len() as u32,
+        types: types.len() as u32,
+      },
+    );
+  }
+  for (key, types) in keys.iter().zip(types) {
+    for (alternative, t) in types.iter().enumerate() {
+      path.segments.push(PathSegment::Table {
+        key: key.clone(),
+        alternative: alternative as u32,
+      });
+      validate_type(t, path, errors);
+      path.segments.pop();
+    }
+  }
+}
+
+fn validate_carried(
+  ty: &VariableType,
+  segment: PathSegment,
+  path: &mut RecordPath,
+  errors: &mut Vec<TraitError>,
+) {
+  path.segments.push(segment);
+  validate_type(ty, path, errors);
+  path.segments.pop();
+}
+
+/// The changes applied by [`Trait::canonicalize_with_report`], so authors can be warned about
+/// what the chain will change before submitting.
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+#[derive(Clone, PartialEq, Debug, Eq, Default)]
+pub struct NormalizationReport {
+  /// Record names that were lowercased, as `(original, canonical)`
+  pub renamed: Vec<(String, String)>,
+  /// Canonical names of the records dropped because an earlier record had the same name
+  pub dropped_duplicates: Vec<String>,
+  /// Whether the records had to be reordered
+  pub reordered: bool,
+  /// Whether types nested in the records had to be canonicalized
+  pub types_normalized: bool,
+}
+
+impl NormalizationReport {
+  /// Whether canonicalization left the trait untouched.
+  pub fn is_empty(&self) -> bool {
+    self.renamed.is_empty()
+      && self.dropped_duplicates.is_empty()
+      && !self.reordered
+      && !self.types_normalized
+  }
+}
+
+impl Trait {
+  /// Brings the trait into the canonical form it is hashed and stored in on chain: record names
+  /// are lowercased, consecutive records sharing a name keep only their first occurrence and
+  /// records are sorted by name, in this order, like the chain does. Nested types are
+  /// canonicalized as well (see [`VariableType::canonicalize`]).
+  pub fn canonicalize(&mut self) {
+    self.canonicalize_with_report();
+  }
+
+  /// The `ShardsTrait` hash of the trait: the XXHash64 of the SCALE encoding of its canonical
+  /// form, as used by `Categories::Trait` and `ShardsScriptInfo`.
+  pub fn shards_hash(&self) -> ShardsTrait {
+    self.hash_with(&Xx64)
+  }
+
+  /// The hash of the canonical form of the trait with `hasher`, see [`Trait::shards_hash`].
+  pub fn hash_with<H: TraitHasher>(&self, hasher: &H) -> H::Output {
+    trace::span!("shards_hash", records = self.records.len());
+    let mut canonical = self.clone();
+    canonical.canonicalize();
+    hasher.hash_trait(&canonical.encode())
+  }
+
+  /// The canonical sub-trait made of the records of `self` named in `record_names`, ignoring case.
+  ///
+  /// Names matching no record are ignored. The sub-trait is named after the trait and the records
+  /// it kept, `Name(a,b)`, so its hash never collides with the one of the full trait or of another
+  /// projection.
+  pub fn project(&self, record_names: &[&str]) -> Trait {
+    let mut projection = self.clone();
+    projection.canonicalize();
+    projection.records.retain(|record| {
+      record_names
+        .iter()
+        .any(|wanted| wanted.to_lowercase() == record.name)
+    });
+
+    let mut name = self.name.clone();
+    name.push('(');
+    for (i, record) in projection.records.iter().enumerate() {
+      if i > 0 {
+        name.push(',');
+      }
+      name.push_str(&record.name);
+    }
+    name.push(')');
+    projection.name = name;
+    projection
+  }
+
+  /// Same as [`Trait::canonicalize`], describing the applied changes.
+  pub fn canonicalize_with_report(&mut self) -> NormalizationReport {
+    trace::span!("canonicalize", records = self.records.len());
+    let mut report = NormalizationReport::default();
+    for record in self.records.iter_mut() {
+      let canonical = record.name.to_lowercase();
+      if canonical != record.name {
+        report.renamed.push((
+          core::mem::replace(&mut record.name, canonical.clone()),
+          canonical,
+        ));
+      }
+    }
+
+    // dedup then sort, as the chain does, so only consecutive duplicates are dropped
+    let mut dropped = Vec::new();
+    self.records.dedup_by(|next, kept| {
+      let duplicate = next.name == kept.name;
+      if duplicate {
+        dropped.push(next.name.clone());
+      }
+      duplicate
+    });
+    report.dropped_duplicates = dropped;
+    report.reordered = self.records.windows(2).any(|w| w[0].name > w[1].name);
+    self.records.sort_by(|a, b| a.name.cmp(&b.name));
+
+    for info in self.records.iter_mut().flat_map(|r| r.types.iter_mut()) {
+      report.types_normalized |= info.type_.canonicalize();
+    }
+    report
+  }
+
+  /// Checks the trait for mistakes canonicalization cannot fix, returning every problem found.
+  pub fn validate(&self) -> Result<(), Vec<TraitError>> {
+    trace::span!("validate", records = self.records.len());
+    let mut errors = Vec::new();
+    if self.records.is_empty() {
+      errors.push(TraitError::NoRecords);
+    }
+    for (i, record) in self.records.iter().enumerate() {
+      if record.name.is_empty() {
+        errors.push(TraitError::EmptyRecordName);
+      }
+      let canonical = record.name.to_lowercase();
+      let earlier = self.records[..i]
+        .iter()
+        .filter(|r| r.name.to_lowercase() == canonical)
+        .count();
+      // reported once, on the first duplicate
+      if earlier == 1 {
+        errors.push(TraitError::DuplicateRecord(canonical));
+      }
+      if record.types.is_empty() {
+        errors.push(TraitError::NoTypes(record.name.clone()));
+      }
+      for (alternative, info) in record.types.iter().enumerate() {
+        let mut path = RecordPath {
+          record: record.name.clone(),
+          alternative: alternative as u32,
+          segments: Vec::new(),
+        };
+        validate_type(&info.type_, &mut path, &mut errors);
+      }
+    }
+    if errors.is_empty() {
+      Ok(())
+    } else {
+      Err(errors)
+    }
+  }
+
+  /// Whether any record, at any depth, can hold a proto of `category`.
+  pub fn accepts_category(&self, category: &Categories) -> bool {
+    self.uses_type(|t| t.accepts_category(category))
+  }
+
+  /// The type addressed by `path`, if it exists in this trait.
+  pub fn resolve_path(&self, path: &crate::path::RecordPath) -> Option<&VariableType> {
+    path.resolve(self)
+  }
+
+  /// The records with at least one type accepting a value of type `type_`.
+  pub fn records_accepting(&self, type_: &VariableType) -> Vec<&Record> {
+    self
+      .records
+      .iter()
+      .filter(|record| record.types.iter().any(|info| info.type_.accepts(type_)))
+      .collect()
+  }
+
+  /// Rebuilds the trait with every type, at any depth, transformed by `f` (see
+  /// [`VariableType::try_map`]), stopping at the first error.
+  pub fn try_map_types<E>(
+    self,
+    mut f: impl FnMut(VariableType) -> Result<VariableType, E>,
+  ) -> Result<Trait, E> {
+    let records = self
+      .records
+      .into_iter()
+      .map(|record| {
+        let types = record
+          .types
+          .into_iter()
+          .map(|info| {
+            Ok(VariableTypeInfo {
+              type_: info.type_.try_map(&mut f)?,
+              default: info.default,
+            })
+          })
+          .collect::<Result<_, E>>()?;
+        Ok(Record {
+          name: record.name,
+          types,
+        })
+      })
+      .collect::<Result<_, E>>()?;
+    Ok(Trait {
+      name: self.name,
+      records,
+    })
+  }
+
+  /// Rebuilds the trait with every type, at any depth, transformed by `f`.
+  pub fn map_types(self, mut f: impl FnMut(VariableType) -> VariableType) -> Trait {
+    match self.try_map_types::<core::convert::Infallible>(|t| Ok(f(t))) {
+      Ok(t) => t,
+      Err(never) => match never {},
+    }
+  }
+
+  /// The record types matching `predicate`, paired with the name of their record.
+  ///
+  /// With `recursive`, a type also matches when any type nested in it does.
+  pub fn find_records(
+    &self,
+    mut predicate: impl FnMut(&VariableType) -> bool,
+    recursive: bool,
+  ) -> Vec<(&String, &VariableTypeInfo)> {
+    self
+      .records
+      .iter()
+      .flat_map(|record| record.types.iter().map(move |info| (&record.name, info)))
+      .filter(|(_, info)| {
+        if recursive {
+          info.type_.any_nested(&mut predicate)
+        } else {
+          predicate(&info.type_)
+        }
+      })
+      .collect()
+  }
+
+  /// Whether any type of the trait, at any depth, matches `predicate`.
+  pub fn uses_type(&self, mut predicate: impl FnMut(&VariableType) -> bool) -> bool {
+    self
+      .records
+      .iter()
+      .flat_map(|record| record.types.iter())
+      .any(|info| info.type_.any_nested(&mut predicate))
+  }
+}
+
+/// Errors returned when streaming records into a `TraitHashStream`.
+#[derive(Clone, PartialEq, Debug, Eq)]
+pub enum TraitStreamError {
+  /// The record, named in lowercase, does not sort after the previous one
+  RecordOutOfOrder(String),
+  /// More or fewer records were pushed than announced, the amount pushed including the extra one
+  RecordCountMismatch(u32),
+}
+
+impl core::fmt::Display for TraitStreamError {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    match self {
+      Self::RecordOutOfOrder(name) => write!(f, "record {} is out of order", name),
+      Self::RecordCountMismatch(pushed) => {
+        write!(f, "{} records pushed, not the amount announced", pushed)
+      }
+    }
+  }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TraitStreamError {}
+
+/// Computes [`Trait::shards_hash`] while the records of a trait arrive, without holding them.
+///
+/// The SCALE encoding of a trait starts with its name and its amount of records, so both are given
+/// upfront. Records must arrive in canonical order, sorted by lowercase name without duplicates,
+/// their names and types are canonicalized as they are pushed.
+#[derive(Clone, Debug)]
+pub struct TraitHashStream {
+  hasher: XxHash64,
+  announced: u32,
+  pushed: u32,
+  last: Option<String>,
+}
+
+impl TraitHashStream {
+  /// A stream of the trait `name` with `records` records.
+  pub fn new(name: &str, records: u32) -> Self {
+    let mut hasher = XxHash64::new(0);
+    name.encode_to(&mut hasher);
+    Compact(records).encode_to(&mut hasher);
+    Self {
+      hasher,
+      announced: records,
+      pushed: 0,
+      last: None,
+    }
+  }
+
+  /// Hashes the next record.
+  pub fn push_record(&mut self, record: &Record) -> Result<(), TraitStreamError> {
+    if self.pushed == self.announced {
+      return Err(TraitStreamError::RecordCountMismatch(self.pushed + 1));
+    }
+    let mut record = record.clone();
+    record.name = record.name.to_lowercase();
+    if self.last.as_ref().is_some_and(|last| *last >= record.name) {
+      return Err(TraitStreamError::RecordOutOfOrder(record.name));
+    }
+    for info in record.types.iter_mut() {
+      info.type_.canonicalize();
+    }
+    record.encode_to(&mut self.hasher);
+    self.pushed += 1;
+    self.last = Some(record.name);
+    Ok(())
+  }
+
+  /// The hash of the trait, once every announced record was pushed.
+  pub fn finalize(self) -> Result<ShardsTrait, TraitStreamError> {
+    if self.pushed != self.announced {
+      return Err(TraitStreamError::RecordCountMismatch(self.pushed));
+    }
+    Ok(self.hasher.finish().to_le_bytes())
+  }
+}
+
+/// Types that can be deserialized from JSON with unknown fields rejected, see [`from_json_strict`].
+#[cfg(feature = "std")]
+pub trait StrictJson: Sized {
+  #[doc(hidden)]
+  type Strict: serde::de::DeserializeOwned + Into<Self>;
+}
+
+/// Deserializes a `Trait`, `Record` or `VariableTypeInfo` from JSON, failing on unknown fields
+/// (e.g. a misspelled `"defualt"`) instead of silently dropping them.
+#[cfg(feature = "std")]
+pub fn from_json_strict<T: StrictJson>(json: &str) -> serde_json::Result<T> {
+  serde_json::from_str::<T::Strict>(json).map(Into::into)
+}
+
+/// Mirrors of the JSON shapes with `deny_unknown_fields`, down to the types nested in variable
+/// types, converted into the public types.
+#[cfg(feature = "std")]
+mod strict {
+  use super::{BinaryCategories, EventDelivery, KeyPattern, StrictJson, TextCategories};
+  use serde::Deserialize;
+
+  #[derive(Deserialize)]
+  #[serde(deny_unknown_fields)]
+  pub struct Limits {
+    min: i64,
+    max: i64,
+    scale: u32,
+  }
+
+  impl From<Limits> for super::Limits {
+    fn from(limits: Limits) -> Self {
+      Self {
+        min: limits.min,
+        max: limits.max,
+        scale: limits.scale,
+      }
+    }
+  }
+
+  fn limits(limits: Option<Limits>) -> Option<super::Limits> {
+    limits.map(Into::into)
+  }
+
+  pub struct VectorLimits<const N: usize>(super::VectorLimits<N>);
+
+  impl<'de, const N: usize> Deserialize<'de> for VectorLimits<N> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+      #[derive(Deserialize)]
+      #[serde(untagged)]
+      enum Repr {
+        PerLane(Vec<Option<Limits>>),
+        Shared(Limits),
+      }
+      match Repr::deserialize(deserializer)? {
+        Repr::Shared(shared) => Ok(Self(super::VectorLimits::Shared(shared.into()))),
+        Repr::PerLane(lanes) => {
+          let len = lanes.len();
+          let lanes: Vec<_> = lanes.into_iter().map(limits).collect();
+          lanes
+            .try_into()
+            .map(|lanes| Self(super::VectorLimits::PerLane(lanes)))
+            .map_err(|_| serde::de::Error::invalid_length(len, &format!("{} lanes", N).as_str()))
+        }
+      }
+    }
+  }
+
+  #[derive(Deserialize)]
+  #[serde(deny_unknown_fields)]
+  pub enum CodeType {
+    Shards,
+    Wire {
+      looped: Option<bool>,
+      pure: Option<bool>,
+    },
+    WireV2 {
+      looped: Option<bool>,
+      pure: Option<bool>,
+      priority: Option<i32>,
+      detached: Option<bool>,
+      rate_limit_hz: Option<u32>,
+    },
+  }
+
+  #[derive(Deserialize)]
+  #[serde(deny_unknown_fields)]
+  pub struct CodeInfo {
+    kind: CodeType,
+    requires: Vec<(String, VariableType)>,
+    exposes: Vec<(String, VariableType)>,
+    inputs: Vec<VariableType>,
+    output: VariableType,
+  }
+
+  #[derive(Deserialize)]
+  #[serde(deny_unknown_fields)]
+  pub struct ChannelInfo {
+    #[serde(rename = "type", alias = "type_")]
+    type_: VariableType,
+    capacity: Option<Limits>,
+  }
+
+  #[derive(Deserialize)]
+  #[serde(deny_unknown_fields)]
+  pub struct EventInfo {
+    #[serde(rename = "type", alias = "type_")]
+    type_: VariableType,
+    delivery: Option<EventDelivery>,
+  }
+
+  #[derive(Deserialize)]
+  #[serde(deny_unknown_fields)]
+  pub struct TableInfo {
+    keys: Vec<String>,
+    types: Vec<Vec<VariableType>>,
+  }
+
+  #[derive(Deserialize)]
+  #[serde(deny_unknown_fields)]
+  pub struct TableInfoV2 {
+    keys: Vec<KeyPattern>,
+    types: Vec<Vec<VariableType>>,
+  }
+
+  #[derive(Deserialize)]
+  #[serde(deny_unknown_fields)]
+  #[allow(clippy::large_enum_variant)] // same layout as the public type
+  pub enum VariableType {
+    None,
+    Any,
+    Bool,
+    Color,
+    Bytes(Option<BinaryCategories>),
+    String(Option<TextCategories>),
+    Image,
+    Audio,
+    Mesh,
+    Enum {
+      vendor_id: u32,
+      type_id: u32,
+    },
+    Int(Option<Limits>),
+    Int2(VectorLimits<2>),
+    Int3(VectorLimits<3>),
+    Int4(VectorLimits<4>),
+    Int8(VectorLimits<8>),
+    Int16(VectorLimits<16>),
+    Float(Option<Limits>),
+    Float2(VectorLimits<2>),
+    Float3(VectorLimits<3>),
+    Float4(VectorLimits<4>),
+    Seq {
+      types: Vec<VariableType>,
+      length_limits: Option<Limits>,
+    },
+    Table(TableInfo),
+    Object {
+      vendor_id: u32,
+      type_id: u32,
+    },
+    Code(Box<CodeInfo>),
+    Channel(Box<VariableType>),
+    Event(Box<VariableType>),
+    ChannelV2(Box<ChannelInfo>),
+    EventV2(Box<EventInfo>),
+    TableV2(TableInfoV2),
+  }
+
+  fn types(types: Vec<VariableType>) -> Vec<super::VariableType> {
+    types.into_iter().map(Into::into).collect()
+  }
+
+  fn variables(variables: Vec<(String, VariableType)>) -> Vec<(String, super::VariableType)> {
+    variables
+      .into_iter()
+      .map(|(name, type_)| (name, type_.into()))
+      .collect()
+  }
+
+  impl From<CodeType> for super::CodeType {
+    fn from(kind: CodeType) -> Self {
+      match kind {
+        CodeType::Shards => Self::Shards,
+        CodeType::Wire { looped, pure } => Self::Wire { looped, pure },
+        CodeType::WireV2 {
+          looped,
+          pure,
+          priority,
+          detached,
+          rate_limit_hz,
+        } => Self::WireV2 {
+          looped,
+          pure,
+          priority,
+          detached,
+          rate_limit_hz,
+        },
+      }
+    }
+  }
+
+  impl From<VariableType> for super::VariableType {
+    fn from(type_: VariableType) -> Self {
+      use VariableType as Strict;
+      match type_ {
+        Strict::None => Self::None,
+        Strict::Any => Self::Any,
+        Strict::Bool => Self::Bool,
+        Strict::Color => Self::Color,
+        Strict::Bytes(category) => Self::Bytes(category),
+        Strict::String(category) => Self::String(category),
+        Strict::Image => Self::Image,
+        Strict::Audio => Self::Audio,
+        Strict::Mesh => Self::Mesh,
+        Strict::Enum { vendor_id, type_id } => Self::Enum { vendor_id, type_id },
+        Strict::Int(l) => Self::Int(limits(l)),
+        Strict::Int2(l) => Self::Int2(l.0),
+        Strict::Int3(l) => Self::Int3(l.0),
+        Strict::Int4(l) => Self::Int4(l.0),
+        Strict::Int8(l) => Self::Int8(l.0),
+        Strict::Int16(l) => Self::Int16(l.0),
+        Strict::Float(l) => Self::Float(limits(l)),
+        Strict::Float2(l) => Self::Float2(l.0),
+        Strict::Float3(l) => Self::Float3(l.0),
+        Strict::Float4(l) => Self::Float4(l.0),
+        Strict::Seq {
+          types: seq,
+          length_limits,
+        } => Self::Seq {
+          types: types(seq),
+          length_limits: limits(length_limits),
+        },
+        Strict::Table(table) => Self::Table(super::TableInfo {
+          keys: table.keys,
+          types: table.types.into_iter().map(types).collect(),
+        }),
+        Strict::Object { vendor_id, type_id } => Self::Object { vendor_id, type_id },
+        Strict::Code(code) => Self::Code(Box::new(super::CodeInfo {
+          kind: code.kind.into(),
+          requires: variables(code.requires),
+          exposes: variables(code.exposes),
+          inputs: types(code.inputs),
+          output: code.output.into(),
+        })),
+        Strict::Channel(inner) => Self::Channel(Box::new((*inner).into())),
+        Strict::Event(inner) => Self::Event(Box::new((*inner).into())),
+        Strict::ChannelV2(channel) => Self::ChannelV2(Box::new(super::ChannelInfo {
+          type_: channel.type_.into(),
+          capacity: limits(channel.capacity),
+        })),
+        Strict::EventV2(event) => Self::EventV2(Box::new(super::EventInfo {
+          type_: event.type_.into(),
+          delivery: event.delivery,
+        })),
+        Strict::TableV2(table) => Self::TableV2(super::TableInfoV2 {
+          keys: table.keys,
+          types: table.types.into_iter().map(types).collect(),
+        }),
+      }
+    }
+  }
+
+  #[derive(Deserialize)]
+  #[serde(deny_unknown_fields)]
+  pub struct VariableTypeInfo {
+    #[serde(rename = "type", alias = "type_")]
+    type_: VariableType,
+    default: Option<Vec<u8>>,
+  }
+
+  #[derive(Deserialize)]
+  #[serde(deny_unknown_fields)]
+  pub struct Record {
+    name: String,
+    types: Vec<VariableTypeInfo>,
+  }
+
+  #[derive(Deserialize)]
+  #[serde(deny_unknown_fields)]
+  pub struct Trait {
+    name: String,
+    records: Vec<Record>,
+  }
+
+  impl From<VariableTypeInfo> for super::VariableTypeInfo {
+    fn from(info: VariableTypeInfo) -> Self {
+      Self {
+        type_: info.type_.into(),
+        default: info.default,
+      }
+    }
+  }
+
+  impl From<Record> for super::Record {
+    fn from(record: Record) -> Self {
+      Self {
+        name: record.name,
+        types: record.types.into_iter().map(Into::into).collect(),
+      }
+    }
+  }
+
+  impl From<Trait> for super::Trait {
+    fn from(trait_: Trait) -> Self {
+      Self {
+        name: trait_.name,
+        records: trait_.records.into_iter().map(Into::into).collect(),
+      }
+    }
+  }
+
+  impl StrictJson for super::VariableTypeInfo {
+    type Strict = VariableTypeInfo;
+  }
+
+  impl StrictJson for super::Record {
+    type Strict = Record;
+  }
+
+  impl StrictJson for super::Trait {
+    type Strict = Trait;
+  }
+}
+
+/// The JSON dialects accepted by [`Trait::from_json_lenient`].
+#[cfg(feature = "std")]
+mod lenient {
+  use super::*;
+  use std::collections::BTreeMap;
+
+  #[derive(Deserialize)]
+  #[serde(untagged)]
+  enum Info {
+    Full(VariableTypeInfo),
+    Bare(VariableType),
+  }
+
+  #[derive(Deserialize)]
+  #[serde(untagged)]
+  enum Types {
+    Many(Vec<Info>),
+    One(Box<Info>),
+  }
+
+  #[derive(Deserialize)]
+  #[serde(untagged)]
+  enum Records {
+    List(Vec<Record>),
+    Map(BTreeMap<String, Types>),
+  }
+
+  #[derive(Deserialize)]
+  pub struct Trait {
+    name: String,
+    records: Records,
+  }
+
+  impl From<Info> for VariableTypeInfo {
+    fn from(info: Info) -> Self {
+      match info {
+        Info::Full(info) => info,
+        Info::Bare(type_) => Self {
+          type_,
+          default: None,
+        },
+      }
+    }
+  }
+
+  impl From<Trait> for super::Trait {
+    fn from(trait_: Trait) -> Self {
+      let records = match trait_.records {
+        Records::List(records) => records,
+        Records::Map(records) => records
+          .into_iter()
+          .map(|(name, types)| Record {
+            name,
+            types: match types {
+              Types::Many(types) => types.into_iter().map(Into::into).collect(),
+              Types::One(info) => Vec::from([(*info).into()]),
+            },
+          })
+          .collect(),
+      };
+      let mut trait_ = Self {
+        name: trait_.name,
+        records,
+      };
+      trait_.canonicalize();
+      trait_
+    }
+  }
+}
+
+#[cfg(feature = "std")]
+impl Trait {
+  /// Deserializes a trait from JSON written by older or alternate tooling, in canonical form.
+  ///
+  /// The records can be a list of `{"name", "types"}` objects or a map from record names to their
+  /// types, `{"banner": [...]}`. The types can be a list or a single type, each either a
+  /// `{"type", "default"}` object (`"type_"` is accepted too) or a bare type such as `"Bool"`.
+  pub fn from_json_lenient(json: &str) -> serde_json::Result<Self> {
+    serde_json::from_str::<lenient::Trait>(json).map(Into::into)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::categories::TextCategories;
+
+  #[test]
+  fn encode_decode_simple_1() {
+    let records: Vec<Record> = vec![(
+      "int1".to_string(),
+      vec![VariableTypeInfo {
+        type_: VariableType::Int(None),
+        default: None,
+      }],
+    )
+      .into()];
+
+    let mut trait1 = Trait {
+      name: "Trait1".to_string(),
+      records,
+    };
+    trait1.canonicalize();
+
+    let e_trait1 = trait1.encode();
 
     let d_trait1 = Trait::decode(&mut e_trait1.as_slice()).unwrap();
 
     assert!(trait1 == d_trait1);
   }
 
+  #[test]
+  fn string_wire_format() {
+    // names encode as their UTF-8 bytes, the same as the raw bytes older no_std builds used
+    let trait1 = Trait {
+      name: "Trait1".to_string(),
+      records: vec![],
+    };
+    let bytes = (b"Trait1".to_vec(), Vec::<Record>::new()).encode();
+    assert_eq!(trait1.encode(), bytes);
+    assert_eq!(Trait::decode(&mut &bytes[..]).unwrap(), trait1);
+
+    let invalid = (Vec::from([0xffu8]), Vec::<Record>::new()).encode();
+    assert!(Trait::decode(&mut &invalid[..]).is_err());
+
+    // the legacy decode path keeps such names byte for byte
+    let legacy = LegacyTrait::decode(&mut &invalid[..]).unwrap();
+    assert_eq!(legacy.encode(), invalid);
+    assert_eq!(Trait::try_from(legacy).unwrap_err().into_bytes(), [0xff]);
+    let legacy = LegacyTrait::decode(&mut &bytes[..]).unwrap();
+    assert_eq!(legacy, LegacyTrait::from(trait1.clone()));
+    assert_eq!(Trait::try_from(legacy), Ok(trait1));
+  }
+
   #[test]
   fn encode_decode_boxed_1() {
-    let mut trait1: Vec<Record> = vec![
+    let records: Vec<Record> = vec![
       (
         "int1".to_string(),
         vec![VariableTypeInfo {
@@ -279,36 +2357,29 @@ mod tests {
         .into(),
     ];
 
-    // THIS IS the way we reprocess the trait declaration before sorting it on chain and hashing it
-    trait1 = trait1
-      .into_iter()
-      .map(|r| (r.name.to_lowercase(), r.types).into())
-      .collect();
-    trait1.dedup_by(|a, b| a.name == b.name);
-    trait1.sort_by(|a, b| a.name.cmp(&b.name));
-
-    let trait1 = Trait {
+    let mut trait1 = Trait {
       name: "Trait1".to_string(),
-      records: trait1,
+      records,
     };
+    trait1.canonicalize();
 
     let e_trait1 = trait1.encode();
 
     let d_trait1 = Trait::decode(&mut e_trait1.as_slice()).unwrap();
 
     assert!(trait1 == d_trait1);
-    assert!(d_trait1.records[0].name == "boxed1".to_string());
+    assert!(d_trait1.records[0].name == "boxed1");
     let type_ = &d_trait1.records[0].types[0].type_;
     let requires = match type_ {
       VariableType::Code(code) => &code.requires,
       _ => panic!("Should be a code"),
     };
-    assert!(requires[0].0 == "int1".to_string());
+    assert!(requires[0].0 == "int1");
   }
 
   #[test]
   fn test_json_simple_1() {
-    let mut trait1: Vec<Record> = vec![(
+    let records: Vec<Record> = vec![(
       "int1".to_string(),
       vec![VariableTypeInfo {
         type_: VariableType::Int(None),
@@ -317,18 +2388,11 @@ mod tests {
     )
       .into()];
 
-    // THIS IS the way we reprocess the trait declaration before sorting it on chain and hashing it
-    trait1 = trait1
-      .into_iter()
-      .map(|r| (r.name.to_lowercase(), r.types).into())
-      .collect();
-    trait1.dedup_by(|a, b| a.name == b.name);
-    trait1.sort_by(|a, b| a.name.cmp(&b.name));
-
-    let trait1 = Trait {
+    let mut trait1 = Trait {
       name: "Trait1".to_string(),
-      records: trait1,
+      records,
     };
+    trait1.canonicalize();
 
     let e_trait1 = serde_json::to_string(&trait1).unwrap();
 
@@ -339,7 +2403,7 @@ mod tests {
 
   #[test]
   fn test_json_boxed_1() {
-    let mut trait1: Vec<Record> = vec![
+    let records: Vec<Record> = vec![
       (
         "int1".to_string(),
         vec![VariableTypeInfo {
@@ -367,31 +2431,24 @@ mod tests {
         .into(),
     ];
 
-    // THIS IS the way we reprocess the trait declaration before sorting it on chain and hashing it
-    trait1 = trait1
-      .into_iter()
-      .map(|r| (r.name.to_lowercase(), r.types).into())
-      .collect();
-    trait1.dedup_by(|a, b| a.name == b.name);
-    trait1.sort_by(|a, b| a.name.cmp(&b.name));
-
-    let trait1 = Trait {
+    let mut trait1 = Trait {
       name: "Trait1".to_string(),
-      records: trait1,
+      records,
     };
+    trait1.canonicalize();
 
     let e_trait1 = serde_json::to_string(&trait1).unwrap();
 
     let d_trait1: Trait = serde_json::from_str(&e_trait1).unwrap();
 
     assert!(trait1 == d_trait1);
-    assert!(d_trait1.records[0].name == "boxed1".to_string());
+    assert!(d_trait1.records[0].name == "boxed1");
     let type_ = &d_trait1.records[0].types[0].type_;
     let requires = match type_ {
       VariableType::Code(code) => &code.requires,
       _ => panic!("Should be a code"),
     };
-    assert!(requires[0].0 == "int1".to_string());
+    assert!(requires[0].0 == "int1");
   }
 
   #[test]
@@ -423,7 +2480,7 @@ mod tests {
       ]
     }"#;
 
-    let d_trait1 = serde_json::from_str(&json_trait1).unwrap();
+    let d_trait1 = serde_json::from_str(json_trait1).unwrap();
 
     assert!(trait1 == d_trait1);
   }
@@ -448,7 +2505,7 @@ mod tests {
       ]
     }"#;
 
-    let d_trait1 = serde_json::from_str(&json_trait1).unwrap();
+    let d_trait1 = serde_json::from_str(json_trait1).unwrap();
 
     let trait1 = Trait {
       name: "AmbalLoreFragment".to_string(),
@@ -458,8 +2515,7 @@ mod tests {
           vec![VariableTypeInfo {
             type_: VariableType::Image,
             default: None,
-          }
-          .into()],
+          }],
         )
           .into(),
         (
@@ -467,8 +2523,7 @@ mod tests {
           vec![VariableTypeInfo {
             type_: VariableType::String(Some(TextCategories::Markdown)),
             default: None,
-          }
-          .into()],
+          }],
         )
           .into(),
       ],
@@ -500,4 +2555,1157 @@ mod tests {
     let decoded = Limits::decode(&mut encoded.as_slice()).unwrap();
     assert!(limits == decoded);
   }
+
+  #[test]
+  fn capability_queries() {
+    use crate::categories::TextureCategories;
+
+    let info = |type_| VariableTypeInfo {
+      type_,
+      default: None,
+    };
+    let trait1 = Trait {
+      name: "Capabilities".to_string(),
+      records: vec![
+        Record::from((
+          "gallery".to_string(),
+          vec![info(VariableType::Seq {
+            types: vec![VariableType::Image],
+            length_limits: None,
+          })],
+        )),
+        Record::from((
+          "level".to_string(),
+          vec![
+            info(VariableType::None),
+            info(VariableType::Int(Some(Limits {
+              min: 0,
+              max: 10,
+              scale: 0,
+            }))),
+          ],
+        )),
+      ],
+    };
+
+    assert!(trait1.accepts_category(&Categories::Texture(TextureCategories::PngFile)));
+    assert!(!trait1.accepts_category(&Categories::Text(TextCategories::Plain)));
+    assert!(trait1.uses_type(|t| *t == VariableType::Image));
+    assert!(!trait1.uses_type(|t| matches!(t, VariableType::Float(_))));
+
+    let small = VariableType::Int(Some(Limits {
+      min: 1,
+      max: 5,
+      scale: 0,
+    }));
+    let accepting = trait1.records_accepting(&small);
+    assert_eq!(accepting.len(), 1);
+    assert_eq!(accepting[0].name, "level");
+    assert!(trait1
+      .records_accepting(&VariableType::Int(None))
+      .is_empty());
+  }
+
+  #[test]
+  fn defaults() {
+    assert_eq!(VariableTypeInfo::default().type_, VariableType::Any);
+    assert_eq!(VariableTypeInfo::default().default, None);
+
+    let code = CodeInfo::default();
+    assert_eq!(code.kind, CodeType::Shards);
+    assert_eq!(code.output, VariableType::None);
+    assert!(code.requires.is_empty() && code.inputs.is_empty());
+
+    let trait1 = Trait {
+      name: "Empty".to_string(),
+      ..Default::default()
+    };
+    assert!(trait1.records.is_empty());
+    assert_eq!(TableInfo::default().keys.len(), 0);
+  }
+
+  #[cfg(feature = "std")]
+  #[test]
+  fn strict_json() {
+    let json = r#"{"name":"T","records":[{"name":"a","types":[{"type":"Bool","default":null}]}]}"#;
+    let trait1: Trait = from_json_strict(json).unwrap();
+    assert_eq!(trait1, serde_json::from_str(json).unwrap());
+
+    let typo = r#"{"name":"T","records":[{"name":"a","types":[{"type":"Bool","defualt":[1]}]}]}"#;
+    assert!(serde_json::from_str::<Trait>(typo).is_ok());
+    let err = from_json_strict::<Trait>(typo).unwrap_err();
+    assert!(err.to_string().contains("defualt"));
+    assert!(from_json_strict::<Record>(r#"{"name":"a","types":[],"extra":1}"#).is_err());
+    let legacy = r#"{"name":"a","types":[{"type_":"Bool","default":null}]}"#;
+    assert!(from_json_strict::<Record>(legacy).is_ok());
+
+    // nested types are strict too
+    let limits = Some(Limits {
+      min: -1,
+      max: 1,
+      scale: 0,
+    });
+    let nested = [
+      VariableType::Seq {
+        types: vec![VariableType::Float3(VectorLimits::PerLane([
+          limits.clone(),
+          None,
+          None,
+        ]))],
+        length_limits: limits.clone(),
+      },
+      VariableType::Table(TableInfo {
+        keys: vec!["a".into()],
+        types: vec![vec![VariableType::Int2(VectorLimits::Shared(
+          limits.clone().unwrap(),
+        ))]],
+      }),
+      VariableType::TableV2(TableInfoV2 {
+        keys: vec![KeyPattern::Prefix("a".into())],
+        types: vec![vec![VariableType::Enum {
+          vendor_id: 1,
+          type_id: 2,
+        }]],
+      }),
+      VariableType::Code(Box::new(CodeInfo {
+        kind: CodeType::Wire {
+          looped: Some(true),
+          pure: None,
+        },
+        requires: vec![("x".into(), VariableType::Int(limits.clone()))],
+        exposes: vec![],
+        inputs: vec![VariableType::String(Some(TextCategories::Json))],
+        output: VariableType::Channel(Box::new(VariableType::Bool)),
+      })),
+      VariableType::ChannelV2(Box::new(ChannelInfo {
+        type_: VariableType::Image,
+        capacity: limits,
+      })),
+      VariableType::EventV2(Box::new(EventInfo {
+        type_: VariableType::Bytes(None),
+        delivery: Some(EventDelivery::Latest),
+      })),
+    ];
+    for type_ in nested {
+      let info = VariableTypeInfo {
+        type_,
+        default: None,
+      };
+      let json = serde_json::to_string(&info).unwrap();
+      assert_eq!(from_json_strict::<VariableTypeInfo>(&json).unwrap(), info);
+    }
+    for typo in [
+      r#"{"type":{"Seq":{"types":[],"length_limits":null,"lenght":1}},"default":null}"#,
+      r#"{"type":{"Int":{"min":0,"max":1,"scale":0,"sclae":1}},"default":null}"#,
+      r#"{"type":{"Int2":{"min":0,"max":1,"scale":0,"sclae":1}},"default":null}"#,
+      r#"{"type":{"Table":{"keys":[],"types":[],"kyes":[]}},"default":null}"#,
+      r#"{"type":{"ChannelV2":{"type":"Bool","capacity":null,"capcity":1}},"default":null}"#,
+      r#"{"type":{"Code":{"kind":{"Wire":{"looped":null,"pure":null,"loped":true}},"requires":[],"exposes":[],"inputs":[],"output":"None"}},"default":null}"#,
+    ] {
+      assert!(
+        serde_json::from_str::<VariableTypeInfo>(typo).is_ok(),
+        "{}",
+        typo
+      );
+      assert!(
+        from_json_strict::<VariableTypeInfo>(typo).is_err(),
+        "{}",
+        typo
+      );
+    }
+  }
+
+  #[test]
+  fn type_field_name() {
+    let mut info = VariableTypeInfo {
+      type_: VariableType::Bool,
+      default: None,
+    };
+    assert_eq!(
+      serde_json::to_string(&info).unwrap(),
+      r#"{"type":"Bool","default":null}"#
+    );
+    let legacy: VariableTypeInfo =
+      serde_json::from_str(r#"{"type_":"Bool","default":null}"#).unwrap();
+    assert_eq!(legacy, info);
+
+    info.set_kind(VariableType::Image);
+    assert_eq!(info.kind(), &VariableType::Image);
+    let channel = ChannelInfo {
+      type_: VariableType::Any,
+      capacity: None,
+    };
+    assert_eq!(
+      serde_json::to_string(&channel).unwrap(),
+      r#"{"type":"Any","capacity":null}"#
+    );
+  }
+
+  #[test]
+  fn lenient_json() {
+    let list = r#"{"name":"Banner","records":[
+      {"name":"visible","types":[{"type":"Bool"}]},
+      {"name":"banner","types":[{"type_":"Image","default":null}]}
+    ]}"#;
+    let map = r#"{"name":"Banner","records":{"banner":["Image"],"visible":{"type":"Bool"}}}"#;
+    let mut expected: Trait = serde_json::from_str(list).unwrap();
+    expected.canonicalize();
+    assert_eq!(Trait::from_json_lenient(list).unwrap(), expected);
+    assert_eq!(Trait::from_json_lenient(map).unwrap(), expected);
+    assert!(Trait::from_json_lenient(r#"{"name":"Banner","records":{"banner":1}}"#).is_err());
+  }
+
+  #[test]
+  fn table_key_patterns() {
+    for key in ["", "anim_*", "name", "*"] {
+      assert_eq!(KeyPattern::parse(key).to_key(), key);
+    }
+    assert_eq!(KeyPattern::parse("*"), KeyPattern::Prefix(String::new()));
+    assert!(KeyPattern::parse("anim_*").matches("anim_walk"));
+    assert!(!KeyPattern::parse("anim_*").matches("mesh"));
+
+    let types = vec![
+      vec![VariableType::Bool],
+      vec![VariableType::Seq {
+        types: vec![VariableType::Float(None)],
+        length_limits: None,
+      }],
+      vec![VariableType::Int(None)],
+      vec![VariableType::String(None)],
+    ];
+    let keys = ["", "anim_*", "anim_idle", "anim_w*"];
+
+    // plain tables only match keys exactly, `*` included
+    let exact = TableInfo {
+      keys: keys.iter().map(|key| key.to_string()).collect(),
+      types: types.clone(),
+    };
+    assert_eq!(exact.key_index("anim_*"), Some(1));
+    assert_eq!(exact.key_index("anim_walk"), Some(0));
+    let exact = TableInfo {
+      keys: vec!["a".to_string()],
+      types: vec![vec![VariableType::Bool]],
+    };
+    assert_eq!(exact.key_index("b"), None);
+
+    let table = TableInfoV2 {
+      keys: keys.iter().map(|key| KeyPattern::parse(key)).collect(),
+      types,
+    };
+    assert_eq!(table.key_index("anim_idle"), Some(2));
+    assert_eq!(table.key_index("anim_walk"), Some(3));
+    assert_eq!(table.key_index("anim_run"), Some(1));
+    assert_eq!(table.key_index("visible"), Some(0));
+    assert_eq!(table.key_types("visible"), Some(&[VariableType::Bool][..]));
+
+    let type_ = VariableType::TableV2(table);
+    assert_eq!(type_.encode()[0], 28);
+    assert_eq!(
+      VariableType::decode(&mut &type_.encode()[..]).unwrap(),
+      type_
+    );
+    let json = serde_json::to_string(&type_).unwrap();
+    assert!(
+      json.starts_with(r#"{"TableV2":{"keys":["any",{"prefix":"anim_"},{"exact":"anim_idle"}"#)
+    );
+    assert_eq!(serde_json::from_str::<VariableType>(&json).unwrap(), type_);
+
+    assert_eq!(
+      TableInfoV2::from(TableInfo {
+        keys: vec!["".to_string(), "a*".to_string()],
+        types: vec![],
+      })
+      .keys,
+      [KeyPattern::Any, KeyPattern::Exact("a*".to_string())]
+    );
+  }
+
+  #[test]
+  fn streamed_hash() {
+    let info = |type_| VariableTypeInfo {
+      type_,
+      default: None,
+    };
+    let seq = VariableType::Seq {
+      types: vec![
+        VariableType::Bool,
+        VariableType::Float(None),
+        VariableType::Bool,
+      ],
+      length_limits: None,
+    };
+    // in canonical order once lowercased, with a type that is not canonical
+    let records: Vec<Record> = vec![
+      ("armor".to_string(), vec![info(VariableType::Float(None))]).into(),
+      ("Name".to_string(), vec![info(VariableType::String(None))]).into(),
+      (
+        "Speed".to_string(),
+        vec![info(seq), info(VariableType::Int(None))],
+      )
+        .into(),
+    ];
+    let trait1 = Trait {
+      name: "Vehicle with a name longer than a stripe".to_string(),
+      records: records.clone(),
+    };
+
+    let mut stream = TraitHashStream::new(&trait1.name, 3);
+    for record in &records {
+      stream.push_record(record).unwrap();
+    }
+    assert_eq!(stream.clone().finalize(), Ok(trait1.shards_hash()));
+    assert_eq!(
+      stream.push_record(&records[2]),
+      Err(TraitStreamError::RecordCountMismatch(4))
+    );
+
+    let mut stream = TraitHashStream::new(&trait1.name, 3);
+    stream.push_record(&records[1]).unwrap();
+    assert_eq!(
+      stream.push_record(&records[0]),
+      Err(TraitStreamError::RecordOutOfOrder("armor".into()))
+    );
+    assert_eq!(
+      stream.push_record(&records[1]),
+      Err(TraitStreamError::RecordOutOfOrder("name".into()))
+    );
+    assert_eq!(
+      stream.finalize(),
+      Err(TraitStreamError::RecordCountMismatch(1))
+    );
+
+    let empty = Trait {
+      name: "Empty".to_string(),
+      records: vec![],
+    };
+    assert_eq!(
+      TraitHashStream::new("Empty", 0).finalize(),
+      Ok(empty.shards_hash())
+    );
+  }
+
+  #[test]
+  fn map_types() {
+    let info = |type_| VariableTypeInfo {
+      type_,
+      default: None,
+    };
+    let trait1 = Trait {
+      name: "Mapped".to_string(),
+      records: vec![Record::from((
+        "values".to_string(),
+        vec![
+          info(VariableType::Any),
+          info(VariableType::Seq {
+            types: vec![VariableType::Any, VariableType::Bool],
+            length_limits: None,
+          }),
+          info(VariableType::Channel(Box::new(VariableType::Any))),
+        ],
+      ))],
+    };
+
+    let mapped = trait1.clone().map_types(|t| match t {
+      VariableType::Any => VariableType::String(None),
+      t => t,
+    });
+    assert!(!mapped.uses_type(|t| *t == VariableType::Any));
+    assert_eq!(
+      mapped.records[0].types[1].type_,
+      VariableType::Seq {
+        types: vec![VariableType::String(None), VariableType::Bool],
+        length_limits: None,
+      }
+    );
+
+    let mut visited = 0;
+    let result = trait1.try_map_types(|t| {
+      visited += 1;
+      match t {
+        VariableType::Bool => Err("bool"),
+        t => Ok(t),
+      }
+    });
+    assert_eq!(result, Err("bool"));
+    // Any, then the nested Any and Bool of the sequence
+    assert_eq!(visited, 3);
+  }
+
+  #[test]
+  fn find_records() {
+    let info = |type_| VariableTypeInfo {
+      type_,
+      default: None,
+    };
+    let object = VariableType::Object {
+      vendor_id: 7,
+      type_id: 1,
+    };
+    let trait1 = Trait {
+      name: "Search".to_string(),
+      records: vec![
+        Record::from(("direct".to_string(), vec![info(object.clone())])),
+        Record::from((
+          "nested".to_string(),
+          vec![
+            info(VariableType::Bool),
+            info(VariableType::Seq {
+              types: vec![object],
+              length_limits: None,
+            }),
+          ],
+        )),
+      ],
+    };
+
+    let vendor = |t: &VariableType| matches!(t, VariableType::Object { vendor_id: 7, .. });
+    let names = |found: Vec<(&String, &VariableTypeInfo)>| {
+      found
+        .into_iter()
+        .map(|(name, _)| name.clone())
+        .collect::<Vec<_>>()
+    };
+    assert_eq!(names(trait1.find_records(vendor, false)), vec!["direct"]);
+    assert_eq!(
+      names(trait1.find_records(vendor, true)),
+      vec!["direct", "nested"]
+    );
+    assert!(matches!(
+      trait1.find_records(vendor, true)[1].1.type_,
+      VariableType::Seq { .. }
+    ));
+  }
+
+  #[test]
+  fn canonicalize_report() {
+    let record = |name: &str, type_| {
+      Record::from((
+        name.to_string(),
+        vec![VariableTypeInfo {
+          type_,
+          default: None,
+        }],
+      ))
+    };
+    let mut trait1 = Trait {
+      name: "Report".to_string(),
+      records: vec![
+        record("Speed", VariableType::Float(None)),
+        record("speed", VariableType::Int(None)),
+        record("alpha", VariableType::Bool),
+      ],
+    };
+
+    let report = trait1.canonicalize_with_report();
+    assert_eq!(
+      report.renamed,
+      vec![("Speed".to_string(), "speed".to_string())]
+    );
+    assert_eq!(report.dropped_duplicates, vec!["speed".to_string()]);
+    assert!(report.reordered);
+    assert_eq!(
+      trait1.records,
+      vec![
+        record("alpha", VariableType::Bool),
+        record("speed", VariableType::Float(None)),
+      ]
+    );
+
+    assert!(trait1.canonicalize_with_report().is_empty());
+  }
+
+  #[test]
+  fn canonical_like_the_chain() {
+    let record = |name: &str, type_| {
+      Record::from((
+        name.to_string(),
+        vec![VariableTypeInfo {
+          type_,
+          default: None,
+        }],
+      ))
+    };
+    let trait1 = Trait {
+      name: "Chain".to_string(),
+      records: vec![
+        record("Ärger", VariableType::Bool),
+        record("Zeta", VariableType::Int(None)),
+        record("ZETA", VariableType::Float(None)),
+        record("beta", VariableType::Int(None)),
+        record("zeta", VariableType::Bool),
+        record("ÄRGER", VariableType::Int(None)),
+      ],
+    };
+
+    // the pipeline the chain runs, as written in the original make_trait example
+    let mut chain = trait1.clone();
+    chain.records = chain
+      .records
+      .into_iter()
+      .map(|r| (r.name.to_lowercase(), r.types).into())
+      .collect();
+    chain.records.dedup_by(|a, b| a.name == b.name);
+    chain.records.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut canonical = trait1.clone();
+    canonical.canonicalize();
+    assert_eq!(canonical, chain);
+    assert_eq!(canonical.records[0].name, "beta");
+    assert_eq!(canonical.records[3].name, "ärger");
+    assert_eq!(trait1.shards_hash(), sp_core::twox_64(&chain.encode()));
+  }
+
+  #[test]
+  fn project() {
+    let record = |name: &str, type_| {
+      Record::from((
+        name.to_string(),
+        vec![VariableTypeInfo {
+          type_,
+          default: None,
+        }],
+      ))
+    };
+    let trait1 = Trait {
+      name: "Body".to_string(),
+      records: vec![
+        record("velocity", VariableType::Float3(Default::default())),
+        record("Position", VariableType::Float3(Default::default())),
+        record("mass", VariableType::Float(None)),
+      ],
+    };
+
+    let projection = trait1.project(&["velocity", "position", "unknown"]);
+    assert_eq!(projection.name, "Body(position,velocity)");
+    assert_eq!(
+      projection.records,
+      vec![
+        record("position", VariableType::Float3(Default::default())),
+        record("velocity", VariableType::Float3(Default::default())),
+      ]
+    );
+    assert_eq!(trait1.project(&["VELOCITY", "Position"]), projection);
+
+    let everything = trait1.project(&["mass", "position", "velocity"]);
+    assert_eq!(everything.records.len(), 3);
+    assert_ne!(everything.shards_hash(), trait1.shards_hash());
+    assert_eq!(trait1.project(&[]).name, "Body()");
+  }
+
+  fn nested_trait() -> Trait {
+    let table = VariableType::Table(TableInfo {
+      keys: vec!["b".to_string(), "a".to_string()],
+      types: vec![
+        vec![VariableType::Bool, VariableType::Bool],
+        vec![VariableType::Int(None)],
+      ],
+    });
+    let code = VariableType::Code(Box::new(CodeInfo {
+      requires: vec![
+        ("y".to_string(), VariableType::Any),
+        ("x".to_string(), VariableType::Bool),
+        ("y".to_string(), VariableType::Bool),
+      ],
+      inputs: vec![VariableType::Any, VariableType::Any],
+      output: VariableType::Seq {
+        types: vec![table.clone(), table],
+        length_limits: None,
+      },
+      ..Default::default()
+    }));
+    Trait {
+      name: "Nested".to_string(),
+      records: vec![
+        Record::from((
+          "Logic".to_string(),
+          vec![VariableTypeInfo {
+            type_: code,
+            default: None,
+          }],
+        )),
+        Record::from((
+          "count".to_string(),
+          vec![VariableTypeInfo {
+            type_: VariableType::Int(None),
+            default: None,
+          }],
+        )),
+      ],
+    }
+  }
+
+  #[test]
+  fn canonicalize_nested() {
+    let mut trait1 = nested_trait();
+    let report = trait1.canonicalize_with_report();
+    assert!(report.types_normalized);
+
+    let VariableType::Code(code) = &trait1.records[1].types[0].type_ else {
+      panic!("logic is code");
+    };
+    assert_eq!(
+      code.requires,
+      vec![
+        ("x".to_string(), VariableType::Bool),
+        ("y".to_string(), VariableType::Any)
+      ]
+    );
+    assert_eq!(code.inputs, vec![VariableType::Any]);
+    assert_eq!(
+      code.output,
+      VariableType::Seq {
+        types: vec![VariableType::Table(TableInfo {
+          keys: vec!["a".to_string(), "b".to_string()],
+          types: vec![vec![VariableType::Int(None)], vec![VariableType::Bool]],
+        })],
+        length_limits: None,
+      }
+    );
+
+    // idempotent
+    let canonical = trait1.clone();
+    assert!(trait1.canonicalize_with_report().is_empty());
+    assert_eq!(trait1, canonical);
+    assert_eq!(trait1.shards_hash(), nested_trait().shards_hash());
+  }
+
+  #[test]
+  fn shards_hash_stable() {
+    let mut trait1 = Trait {
+      name: "Flag".to_string(),
+      records: vec![Record::from((
+        "ON".to_string(),
+        vec![VariableTypeInfo {
+          type_: VariableType::Bool,
+          default: None,
+        }],
+      ))],
+    };
+    // the SCALE encoding of names is the same with and without std, known answer from the
+    // canonical encoding of the trait
+    let hash = trait1.shards_hash();
+    assert_eq!(hash, 0x27ed_dd95_9344_d676u64.to_be_bytes());
+    trait1.canonicalize();
+    assert_eq!(
+      trait1.encode(),
+      [16, 70, 108, 97, 103, 4, 8, 111, 110, 4, 2, 0]
+    );
+    assert_eq!(trait1.shards_hash(), hash);
+    assert_eq!(hash, sp_core::twox_64(&trait1.encode()));
+  }
+
+  #[test]
+  fn validate() {
+    let duplicate_variable = TraitError::InvalidType {
+      path: "Logic".parse().unwrap(),
+      issue: TypeIssue::DuplicateVariable("y".to_string()),
+    };
+    assert_eq!(
+      duplicate_variable.to_string(),
+      "duplicate code variable y at Logic"
+    );
+    assert_eq!(nested_trait().validate(), Err(vec![duplicate_variable]));
+    assert_eq!(
+      Trait::default().validate(),
+      Err(vec![TraitError::NoRecords])
+    );
+
+    let mut trait1 = nested_trait();
+    trait1.canonicalize();
+    assert!(trait1.validate().is_ok());
+    trait1
+      .records
+      .push(Record::from(("Logic".to_string(), vec![])));
+    trait1.records.push(Record::from((
+      "limited".to_string(),
+      vec![VariableTypeInfo {
+        type_: VariableType::Seq {
+          types: vec![VariableType::Table(TableInfo {
+            keys: vec!["k".to_string(), "k".to_string()],
+            types: vec![vec![VariableType::Float2(
+              [
+                None,
+                Some(Limits {
+                  min: 2,
+                  max: 1,
+                  scale: 0,
+                }),
+              ]
+              .into(),
+            )]],
+          })],
+          length_limits: None,
+        },
+        default: None,
+      }],
+    )));
+
+    let path: RecordPath = "limited / seq[0]".parse().unwrap();
+    assert_eq!(
+      trait1.validate(),
+      Err(vec![
+        TraitError::DuplicateRecord("logic".to_string()),
+        TraitError::NoTypes("Logic".to_string()),
+        TraitError::InvalidType {
+          path: path.clone(),
+          issue: TypeIssue::DuplicateTableKey("k".to_string()),
+        },
+        TraitError::InvalidType {
+          path: path.clone(),
+          issue: TypeIssue::TableLengthMismatch { keys: 2, types: 1 },
+        },
+        TraitError::InvalidType {
+          path: path.join(PathSegment::Table {
+            key: "k".to_string(),
+            alternative: 0,
+          }),
+          issue: TypeIssue::InvalidLimits { min: 2, max: 1 },
+        },
+      ])
+    );
+  }
+
+  #[test]
+  fn intersect_traits() {
+    let record = |name: &str, types: Vec<VariableType>| {
+      Record::from((
+        name.to_string(),
+        types
+          .into_iter()
+          .map(|type_| VariableTypeInfo {
+            type_,
+            default: None,
+          })
+          .collect(),
+      ))
+    };
+    let limits = |min, max| Some(Limits { min, max, scale: 0 });
+    let a = Trait {
+      name: "A".to_string(),
+      records: vec![
+        record("Count", vec![VariableType::Int(limits(0, 10))]),
+        record(
+          "label",
+          vec![VariableType::String(None), VariableType::Bool],
+        ),
+        record("only_a", vec![VariableType::Any]),
+      ],
+    };
+    let b = Trait {
+      name: "B".to_string(),
+      records: vec![
+        record("count", vec![VariableType::Int(limits(5, 20))]),
+        record(
+          "label",
+          vec![VariableType::String(Some(TextCategories::Plain))],
+        ),
+        record("only_a", vec![VariableType::Int(limits(0, 1))]),
+      ],
+    };
+    let c = Trait {
+      name: "C".to_string(),
+      records: vec![
+        record("count", vec![VariableType::Any]),
+        record("label", vec![VariableType::Any]),
+      ],
+    };
+
+    let common = intersect(&[&a, &b, &c]);
+    assert_eq!(common.name, "A&B&C");
+    assert_eq!(
+      common.records,
+      vec![
+        record("count", vec![VariableType::Int(limits(5, 10))]),
+        record(
+          "label",
+          vec![VariableType::String(Some(TextCategories::Plain))]
+        ),
+      ]
+    );
+
+    let disjoint = Trait {
+      name: "D".to_string(),
+      records: vec![record("count", vec![VariableType::Int(limits(11, 12))])],
+    };
+    assert!(intersect(&[&a, &disjoint]).records.is_empty());
+    assert_eq!(intersect(&[&a]).records.len(), 3);
+    assert_eq!(intersect(&[]), Trait::default());
+  }
+
+  #[test]
+  fn limits_contains_across_scales() {
+    let limits = |min, max, scale| Limits { min, max, scale };
+    // [-1, 1] contains [-0.5, 0.5] and [-1.00, 1.00], not [-1.5, 0]
+    assert!(limits(-1, 1, 0).contains(&limits(-5, 5, 1)));
+    assert!(limits(-1, 1, 0).contains(&limits(-100, 100, 2)));
+    assert!(!limits(-1, 1, 0).contains(&limits(-15, 0, 1)));
+    assert!(limits(-15, 15, 1).contains(&limits(-1, 1, 0)));
+    assert!(!limits(-5, 5, 1).contains(&limits(-1, 1, 0)));
+    // rescaling overflows 128 bits, the sign decides
+    assert!(limits(i64::MIN, i64::MAX, 0).contains(&limits(i64::MIN, i64::MAX, 40)));
+    assert!(!limits(i64::MIN, i64::MAX, 40).contains(&limits(-1, 1, 0)));
+    assert!(limits(0, 0, 0).contains(&limits(0, 0, u32::MAX)));
+
+    // [-1, 1] and [0.5, 1.5] intersect in [0.5, 1.0]
+    assert_eq!(
+      limits(-1, 1, 0).intersect(&limits(5, 15, 1)),
+      Some(limits(5, 10, 1))
+    );
+    assert_eq!(
+      limits(5, 15, 1).intersect(&limits(-1, 1, 0)),
+      Some(limits(5, 10, 1))
+    );
+    assert_eq!(limits(-1, 0, 0).intersect(&limits(5, 15, 1)), None);
+    let wide = limits(i64::MIN, i64::MAX, 0);
+    let narrow = limits(-1, 1, 40);
+    assert!(wide.contains(&narrow));
+    assert_eq!(wide.intersect(&narrow), Some(narrow.clone()));
+    assert_eq!(narrow.intersect(&wide), Some(narrow));
+    assert_eq!(limits(1, 2, 0).intersect(&limits(i64::MIN, 0, 40)), None);
+  }
+
+  #[test]
+  fn vector_limits() {
+    let limits = Limits {
+      min: -1,
+      max: 1,
+      scale: 0,
+    };
+    let lanes = [Some(limits.clone()), None, Some(limits.clone())];
+
+    // per lane limits keep the array encoding
+    let per_lane = VariableType::Int3(lanes.clone().into());
+    let mut legacy = vec![12];
+    legacy.extend(lanes.encode());
+    assert_eq!(per_lane.encode(), legacy);
+    assert_eq!(VariableType::decode(&mut &legacy[..]).unwrap(), per_lane);
+
+    let shared = VariableType::Int3(VectorLimits::from_lanes([
+      Some(limits.clone()),
+      Some(limits.clone()),
+      Some(limits.clone()),
+    ]));
+    assert_eq!(
+      shared,
+      VariableType::Int3(VectorLimits::Shared(limits.clone()))
+    );
+    assert!(shared.encode().len() < legacy.len());
+    assert_eq!(
+      VariableType::decode(&mut &shared.encode()[..]).unwrap(),
+      shared
+    );
+    assert!(VectorLimits::<3>::decode(&mut &[3u8][..]).is_err());
+
+    assert!(!shared.accepts(&per_lane));
+    assert!(VariableType::Int3(Default::default()).accepts(&shared));
+    assert_eq!(
+      VectorLimits::<2>::from_lanes([Some(limits.clone()), None]).lane(1),
+      None
+    );
+
+    let json = serde_json::to_string(&shared).unwrap();
+    assert_eq!(json, r#"{"Int3":{"min":-1,"max":1,"scale":0}}"#);
+    assert_eq!(serde_json::from_str::<VariableType>(&json).unwrap(), shared);
+    let json = serde_json::to_string(&per_lane).unwrap();
+    assert_eq!(
+      serde_json::from_str::<VariableType>(&json).unwrap(),
+      per_lane
+    );
+    assert!(serde_json::from_str::<VariableType>(r#"{"Int3":[null]}"#).is_err());
+  }
+
+  #[test]
+  fn vector_limits_metadata() {
+    use crate::dynamic::{decode_as, DynValue};
+
+    let limits = Limits {
+      min: -1,
+      max: 1,
+      scale: 2,
+    };
+    fn variants<const N: usize>(
+      limits: &Limits,
+      lanes: fn(VectorLimits<N>) -> VariableType,
+    ) -> [VariableType; 3] {
+      let mut first_limited = VectorLimits::<N>::default().to_lanes();
+      first_limited[0] = Some(limits.clone());
+      [
+        lanes(VectorLimits::Shared(limits.clone())),
+        lanes(VectorLimits::default()),
+        lanes(VectorLimits::PerLane(first_limited)),
+      ]
+    }
+    let types = [
+      variants(&limits, VariableType::Int2),
+      variants(&limits, VariableType::Int3),
+      variants(&limits, VariableType::Int4),
+      variants(&limits, VariableType::Int8),
+      variants(&limits, VariableType::Int16),
+      variants(&limits, VariableType::Float2),
+      variants(&limits, VariableType::Float3),
+      variants(&limits, VariableType::Float4),
+    ];
+    for [shared, unlimited, first_limited] in types {
+      for (type_, name) in [
+        (shared, "Shared"),
+        (unlimited, "PerLaneFirstUnlimited"),
+        (first_limited, "PerLaneFirstLimited"),
+      ] {
+        let DynValue::Variant { fields, .. } = decode_as::<VariableType>(&type_.encode()).unwrap()
+        else {
+          panic!("{:?} is not a variant", type_);
+        };
+        let DynValue::Variant {
+          name: lanes_name,
+          fields: lanes_fields,
+          ..
+        } = &fields[0].1
+        else {
+          panic!("{:?} has no vector limits", type_);
+        };
+        assert_eq!(lanes_name, name);
+        if name != "PerLaneFirstUnlimited" {
+          assert_eq!(
+            lanes_fields[0].1,
+            DynValue::Composite(vec![
+              (Some("min".into()), DynValue::UInt(encode_i64(-1).into())),
+              (Some("max".into()), DynValue::UInt(encode_i64(1).into())),
+              (Some("scale".into()), DynValue::UInt(2)),
+            ])
+          );
+        }
+      }
+    }
+
+    let mut lanes = VectorLimits::from([
+      Some(limits.clone()),
+      Some(limits.clone()),
+      Some(limits.clone()),
+    ]);
+    assert!(lanes.fold_shared());
+    assert_eq!(lanes, VectorLimits::Shared(limits.clone()));
+    assert!(!lanes.fold_shared());
+    let mut lanes = VectorLimits::from([Some(limits.clone()), None]);
+    assert!(!lanes.fold_shared());
+  }
+
+  #[test]
+  fn canonicalize_keeps_vector_limits() {
+    let limits = Some(Limits {
+      min: -1,
+      max: 1,
+      scale: 0,
+    });
+    let types = [
+      VariableType::Int2([limits.clone(), limits.clone()].into()),
+      VariableType::Int3(core::array::from_fn(|_| limits.clone()).into()),
+      VariableType::Int4(core::array::from_fn(|_| limits.clone()).into()),
+      VariableType::Int8(core::array::from_fn(|_| limits.clone()).into()),
+      VariableType::Int16(core::array::from_fn(|_| limits.clone()).into()),
+      VariableType::Float2([limits.clone(), limits.clone()].into()),
+      VariableType::Float3([limits.clone(), None, limits.clone()].into()),
+      VariableType::Float4(core::array::from_fn(|_| limits.clone()).into()),
+      VariableType::Float4(Default::default()),
+    ];
+    for type_ in types {
+      assert_eq!(crate::compat::min_shards_version(&type_), 1);
+      let mut canonical = type_.clone();
+      assert!(!canonical.canonicalize());
+      assert_eq!(canonical.encode(), type_.encode());
+      assert_eq!(crate::compat::min_shards_version(&canonical), 1);
+    }
+  }
+
+  #[test]
+  fn channel_event_info() {
+    let capacity = |min, max| Some(Limits { min, max, scale: 0 });
+    let plain = VariableType::Channel(Box::new(VariableType::Int(None)));
+    let bounded = VariableType::ChannelV2(Box::new(ChannelInfo {
+      type_: VariableType::Int(None),
+      capacity: capacity(1, 16),
+    }));
+    let small = VariableType::ChannelV2(Box::new(ChannelInfo {
+      type_: VariableType::Int(None),
+      capacity: capacity(1, 4),
+    }));
+    assert!(plain.accepts(&bounded));
+    assert!(!bounded.accepts(&plain));
+    assert!(bounded.accepts(&small));
+    assert!(!small.accepts(&bounded));
+    assert_eq!(plain.intersect(&small), Some(small.clone()));
+    assert_eq!(bounded.intersect(&small), Some(small.clone()));
+    assert_eq!(small.carried(), Some(&VariableType::Int(None)));
+
+    let event = |delivery| {
+      VariableType::EventV2(Box::new(EventInfo {
+        type_: VariableType::Any,
+        delivery,
+      }))
+    };
+    let latest = event(Some(EventDelivery::Latest));
+    let once = event(Some(EventDelivery::ExactlyOnce));
+    assert!(event(None).accepts(&once));
+    assert!(VariableType::Event(Box::new(VariableType::Any)).accepts(&once));
+    assert!(!latest.accepts(&once));
+    assert!(!latest.accepts(&small));
+    assert_eq!(latest.intersect(&once), None);
+    assert_eq!(event(None).intersect(&latest), Some(latest.clone()));
+
+    let encoded = small.encode();
+    assert_eq!(encoded[0], 26);
+    assert_eq!(VariableType::decode(&mut &encoded[..]).unwrap(), small);
+    let json = serde_json::to_string(&latest).unwrap();
+    assert_eq!(json, r#"{"EventV2":{"type":"Any","delivery":"Latest"}}"#);
+    assert_eq!(serde_json::from_str::<VariableType>(&json).unwrap(), latest);
+
+    let trait1 = Trait {
+      name: "Wiring".to_string(),
+      records: vec![Record::from((
+        "out".to_string(),
+        vec![VariableTypeInfo {
+          type_: VariableType::ChannelV2(Box::new(ChannelInfo {
+            type_: VariableType::Int(capacity(2, 1)),
+            capacity: capacity(8, 1),
+          })),
+          default: None,
+        }],
+      ))],
+    };
+    let errors = trait1.validate().unwrap_err();
+    let paths: Vec<String> = errors
+      .iter()
+      .map(|e| match e {
+        TraitError::InvalidType { path, .. } => path.to_string(),
+        e => panic!("unexpected error {}", e),
+      })
+      .collect();
+    assert_eq!(paths, ["out", "out / channel"]);
+  }
+
+  #[test]
+  fn wire_scheduling() {
+    let wire = CodeType::WireV2 {
+      looped: Some(true),
+      pure: Some(false),
+      priority: Some(-5),
+      detached: Some(true),
+      rate_limit_hz: Some(30),
+    };
+    assert!(wire.is_wire());
+    assert!(!CodeType::Shards.is_wire());
+
+    let legacy = CodeType::Wire {
+      looped: Some(true),
+      pure: None,
+    };
+    assert_eq!(legacy.encode(), [1, 1, 1, 0]);
+    let encoded = wire.encode();
+    assert_eq!(encoded[0], 2);
+    assert_eq!(CodeType::decode(&mut &encoded[..]).unwrap(), wire);
+
+    let json = serde_json::to_string(&wire).unwrap();
+    assert_eq!(
+      json,
+      r#"{"WireV2":{"looped":true,"pure":false,"priority":-5,"detached":true,"rate_limit_hz":30}}"#
+    );
+    assert_eq!(serde_json::from_str::<CodeType>(&json).unwrap(), wire);
+  }
+
+  #[test]
+  fn interface_hash() {
+    let code = CodeInfo {
+      kind: CodeType::Shards,
+      requires: vec![
+        ("Speed".to_string(), VariableType::Float(None)),
+        ("dir".to_string(), VariableType::Float3(Default::default())),
+      ],
+      exposes: vec![("out".to_string(), VariableType::Bool)],
+      inputs: vec![VariableType::Any],
+      output: VariableType::None,
+    };
+    let hash = code.interface_hash();
+
+    let reordered = CodeInfo {
+      kind: CodeType::Wire {
+        looped: Some(true),
+        pure: None,
+      },
+      requires: vec![
+        ("dir".to_string(), VariableType::Float3(Default::default())),
+        ("speed".to_string(), VariableType::Float(None)),
+      ],
+      ..code.clone()
+    };
+    assert_eq!(reordered.interface_hash(), hash);
+
+    let changed = CodeInfo {
+      output: VariableType::Bool,
+      ..code.clone()
+    };
+    assert_ne!(changed.interface_hash(), hash);
+    let renamed = CodeInfo {
+      exposes: vec![("result".to_string(), VariableType::Bool)],
+      ..code
+    };
+    assert_ne!(renamed.interface_hash(), hash);
+  }
+
+  #[test]
+  fn check_requirements() {
+    let code = CodeInfo {
+      requires: vec![
+        ("speed".to_string(), VariableType::Float(None)),
+        ("events".to_string(), VariableType::Any),
+        (
+          "dir".to_string(),
+          VariableType::Float3(VectorLimits::Shared(Limits {
+            min: -1,
+            max: 1,
+            scale: 0,
+          })),
+        ),
+      ],
+      ..Default::default()
+    };
+    let host = vec![
+      ("speed".to_string(), VariableType::Float(None)),
+      ("events".to_string(), VariableType::Bool),
+      ("dir".to_string(), VariableType::Float3(Default::default())),
+    ];
+    assert_eq!(
+      code.check_requirements(&host),
+      Err(vec![MissingRequirement {
+        name: "dir".to_string(),
+        required: code.requires[2].1.clone(),
+        exposed: Some(VariableType::Float3(Default::default())),
+      }])
+    );
+
+    let errors = code.check_requirements(&host[..1]).unwrap_err();
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].exposed, None);
+    assert_eq!(
+      errors[0].to_string(),
+      "required variable events is not exposed"
+    );
+
+    let host = vec![
+      ("dir".to_string(), code.requires[2].1.clone()),
+      ("events".to_string(), VariableType::None),
+      ("speed".to_string(), VariableType::Float(None)),
+    ];
+    assert_eq!(code.check_requirements(&host), Ok(()));
+  }
+
+  #[test]
+  fn debug_hex() {
+    let info = VariableTypeInfo {
+      type_: VariableType::Bool,
+      default: Some(vec![1, 0xff]),
+    };
+    assert_eq!(
+      format!("{:?}", info),
+      "VariableTypeInfo { type_: Bool, default: Some(0x01ff) }"
+    );
+  }
 }
@@ -0,0 +1,258 @@
+//! Intents to transfer the ownership of a proto or fragment, signed by the current owner so
+//! escrows and two-step transfers can execute them later.
+
+use crate::{
+  fragments::{Hash128, Hash256, InstanceUnit},
+  permissions::FragmentPerms,
+};
+use parity_scale_codec::{Decode, Encode, MaxEncodedLen};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// What a transfer intent moves.
+#[derive(
+  Encode, Decode, Copy, Clone, PartialEq, Debug, Eq, MaxEncodedLen, scale_info::TypeInfo,
+)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub enum TransferTarget {
+  /// The proto with this hash
+  Proto(Hash256),
+  /// The fragment definition with this hash
+  Definition(Hash128),
+  /// A single instance of a fragment definition
+  Instance {
+    definition: Hash128,
+    edition: InstanceUnit,
+    copy: InstanceUnit,
+  },
+}
+
+/// An intent of the owner `from` of a proto or fragment to transfer it to `to`.
+///
+/// The intent is signed with [`crate::signing::typed_payload`] and can be executed by anyone
+/// holding the signature until `expires_at`, once: it is bound to the chain with `genesis_hash`
+/// and to the owner with `nonce`, see [`TransferIntent::check`].
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Eq, MaxEncodedLen, scale_info::TypeInfo)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct TransferIntent<TAccountId, TBlockNumber> {
+  /// The genesis hash of the chain the intent can be executed on
+  pub genesis_hash: Hash256,
+  /// The proto or fragment transferred
+  pub proto_or_fragment: TransferTarget,
+  /// The current owner, signing the intent
+  pub from: TAccountId,
+  /// The new owner
+  pub to: TAccountId,
+  /// The permissions of the new owner, only meaningful for fragments
+  pub perms_after: FragmentPerms,
+  /// The block from which the intent can no longer be executed
+  pub expires_at: TBlockNumber,
+  /// The transfer nonce of `from` when signing, an intent can only be executed while it is the
+  /// nonce of `from`
+  pub nonce: u64,
+}
+
+/// The chain state a `TransferIntent` is checked against.
+#[derive(Clone, PartialEq, Debug, Eq)]
+pub struct TransferContext<TAccountId, TBlockNumber> {
+  /// The genesis hash of the chain executing the intent
+  pub genesis_hash: Hash256,
+  /// The current block
+  pub now: TBlockNumber,
+  /// The current owner of the proto or fragment
+  pub owner: TAccountId,
+  /// The permissions of the current owner, ignored for protos
+  pub perms: FragmentPerms,
+  /// The transfer nonce of the current owner, incremented by the chain on every executed or
+  /// cancelled intent
+  pub nonce: u64,
+}
+
+/// Errors returned by `TransferIntent::check`.
+#[derive(Copy, Clone, PartialEq, Debug, Eq)]
+pub enum TransferError {
+  /// The intent was signed for another chain
+  WrongChain,
+  /// The intent was not signed by the current owner
+  NotOwner,
+  /// The intent was already executed or cancelled
+  NonceUsed,
+  /// The intent expired
+  Expired,
+  /// The current owner cannot transfer the fragment
+  NotTransferable,
+  /// The new owner would get permissions the current owner does not have
+  PermissionEscalation(FragmentPerms),
+}
+
+impl core::fmt::Display for TransferError {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    match self {
+      Self::WrongChain => write!(f, "transfer intent signed for another chain"),
+      Self::NotOwner => write!(f, "transfer intent not signed by the owner"),
+      Self::NonceUsed => write!(f, "transfer intent already used"),
+      Self::Expired => write!(f, "transfer intent expired"),
+      Self::NotTransferable => write!(f, "fragment cannot be transferred"),
+      Self::PermissionEscalation(perms) => {
+        write!(f, "transfer would grant permissions {:?}", perms)
+      }
+    }
+  }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TransferError {}
+
+impl<TAccountId: PartialEq, TBlockNumber: PartialOrd> TransferIntent<TAccountId, TBlockNumber> {
+  /// Whether the intent is expired at block `now`.
+  pub fn is_expired(&self, now: &TBlockNumber) -> bool {
+    *now >= self.expires_at
+  }
+
+  /// Checks that the intent can be executed in `context`: signed for this chain by the current
+  /// owner with its current nonce, not expired and not granting more than the owner has.
+  ///
+  /// Executing the intent must increment the nonce of the owner, so the intent cannot be replayed.
+  /// Protos have no permissions, `perms` is only checked for fragments.
+  pub fn check(
+    &self,
+    context: &TransferContext<TAccountId, TBlockNumber>,
+  ) -> Result<(), TransferError> {
+    if self.genesis_hash != context.genesis_hash {
+      return Err(TransferError::WrongChain);
+    }
+    if self.from != context.owner {
+      return Err(TransferError::NotOwner);
+    }
+    if self.nonce != context.nonce {
+      return Err(TransferError::NonceUsed);
+    }
+    if self.is_expired(&context.now) {
+      return Err(TransferError::Expired);
+    }
+    if let TransferTarget::Proto(_) = self.proto_or_fragment {
+      return Ok(());
+    }
+    let perms = context.perms;
+    if !perms.contains(FragmentPerms::TRANSFER) {
+      return Err(TransferError::NotTransferable);
+    }
+    let escalated = self.perms_after - perms;
+    if !escalated.is_empty() {
+      return Err(TransferError::PermissionEscalation(escalated));
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::signing::{typed_payload, PayloadKind};
+
+  fn intent(target: TransferTarget, perms_after: FragmentPerms) -> TransferIntent<u64, u32> {
+    TransferIntent {
+      genesis_hash: [7; 32],
+      proto_or_fragment: target,
+      from: 1,
+      to: 2,
+      perms_after,
+      expires_at: 100,
+      nonce: 5,
+    }
+  }
+
+  fn context(now: u32, perms: FragmentPerms) -> TransferContext<u64, u32> {
+    TransferContext {
+      genesis_hash: [7; 32],
+      now,
+      owner: 1,
+      perms,
+      nonce: 5,
+    }
+  }
+
+  #[test]
+  fn checks() {
+    let instance = TransferTarget::Instance {
+      definition: [1; 16],
+      edition: 3,
+      copy: 1,
+    };
+    let transfer = intent(instance, FragmentPerms::EDIT | FragmentPerms::TRANSFER);
+    assert_eq!(transfer.check(&context(99, FragmentPerms::ALL)), Ok(()));
+    assert_eq!(
+      transfer.check(&context(100, FragmentPerms::ALL)),
+      Err(TransferError::Expired)
+    );
+    assert_eq!(
+      transfer.check(&context(1, FragmentPerms::EDIT)),
+      Err(TransferError::NotTransferable)
+    );
+    assert_eq!(
+      transfer.check(&context(1, FragmentPerms::COPY | FragmentPerms::TRANSFER)),
+      Err(TransferError::PermissionEscalation(FragmentPerms::EDIT))
+    );
+
+    let proto = intent(TransferTarget::Proto([0; 32]), FragmentPerms::ALL);
+    assert_eq!(proto.check(&context(1, FragmentPerms::NONE)), Ok(()));
+  }
+
+  #[test]
+  fn replay() {
+    let transfer = intent(TransferTarget::Proto([0; 32]), FragmentPerms::NONE);
+    // executed, the chain moved to the next nonce
+    let executed = TransferContext {
+      nonce: 6,
+      ..context(1, FragmentPerms::NONE)
+    };
+    assert_eq!(transfer.check(&executed), Err(TransferError::NonceUsed));
+    let other_chain = TransferContext {
+      genesis_hash: [8; 32],
+      ..context(1, FragmentPerms::NONE)
+    };
+    assert_eq!(transfer.check(&other_chain), Err(TransferError::WrongChain));
+    // the proto changed hands since the intent was signed
+    let new_owner = TransferContext {
+      owner: 3,
+      ..context(1, FragmentPerms::NONE)
+    };
+    assert_eq!(transfer.check(&new_owner), Err(TransferError::NotOwner));
+  }
+
+  #[test]
+  fn signing() {
+    let intent = intent(TransferTarget::Definition([1; 16]), FragmentPerms::TRANSFER);
+    let payload = typed_payload(&intent);
+    assert_eq!(payload.kind, PayloadKind::Transfer);
+    assert_eq!(
+      TransferIntent::<u64, u32>::decode(&mut &payload.data[..]),
+      Ok(intent.clone())
+    );
+    assert!(intent.encode().len() <= TransferIntent::<u64, u32>::max_encoded_len());
+
+    let json = serde_json::to_string(&intent).unwrap();
+    assert_eq!(
+      json,
+      format!(
+        r#"{{"genesisHash":{:?},"protoOrFragment":{{"definition":{:?}}},"from":1,"to":2,"permsAfter":4,"expiresAt":100,"nonce":5}}"#,
+        [7; 32],
+        [1; 16]
+      )
+      .replace(' ', "")
+    );
+    assert_eq!(
+      serde_json::from_str::<TransferIntent<u64, u32>>(&json).unwrap(),
+      intent
+    );
+  }
+}
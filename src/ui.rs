@@ -0,0 +1,338 @@
+//! The widget to enter a value of each variable type, so form generators render trait records
+//! consistently without their own mapping of every type.
+
+use crate::{
+  categories::{
+    AudioCategories, BinaryCategories, Categories, ModelCategories, TextCategories,
+    TextureCategories,
+  },
+  traits::{CodeType, KeyPattern, Limits, TableInfoV2, VariableType, VectorLimits},
+};
+use scale_info::prelude::{string::String, vec::Vec};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A field of a [`UiHint::Form`], for an exact key of a table.
+#[derive(Clone, PartialEq, Debug, Eq)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct FormField {
+  /// The key of the table
+  pub key: String,
+  /// The widgets of the alternative types of the key
+  pub hints: Vec<UiHint>,
+}
+
+/// An editor of any number of entries of a table whose keys match a pattern, see [`UiHint::Map`].
+#[derive(Clone, PartialEq, Debug, Eq)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct MapField {
+  /// The pattern the keys entered must match
+  pub key_pattern: KeyPattern,
+  /// The widgets of the alternative types of the values
+  pub hints: Vec<UiHint>,
+}
+
+/// The widget suited to enter a value of a variable type, see [`VariableType::ui_hint`].
+#[derive(Clone, PartialEq, Debug, Eq)]
+#[cfg_attr(
+  feature = "serde",
+  derive(Serialize, Deserialize),
+  serde(rename_all = "camelCase", rename_all_fields = "camelCase")
+)]
+pub enum UiHint {
+  /// Nothing to enter: `None`, channels and events
+  Hidden,
+  /// Free-form JSON, the value can be of any type
+  Json,
+  /// A checkbox or switch
+  Toggle,
+  /// A color picker with alpha
+  ColorPicker,
+  /// A single or multi-line text input, `format` is the expected text category if any
+  Text { format: Option<TextCategories> },
+  /// A picker of the values of a vendor enum
+  Dropdown { vendor_id: u32, type_id: u32 },
+  /// A number input without bounds
+  Number { integer: bool },
+  /// A slider within `limits`, floats step by `10^-limits.scale`
+  Slider { integer: bool, limits: Limits },
+  /// One widget per lane of a vector
+  Vector { lanes: Vec<UiHint> },
+  /// A picker of protos, or of files to upload, of the accepted categories
+  FilePicker { categories: Vec<Categories> },
+  /// A list of items entered with any of `items`, the length within `length_limits` if any
+  List {
+    items: Vec<UiHint>,
+    length_limits: Option<Limits>,
+  },
+  /// A group of fields, one per exact key of a table, followed by the editors of the entries
+  /// under the other keys of the table
+  Form {
+    fields: Vec<FormField>,
+    maps: Vec<MapField>,
+  },
+  /// An editor of key and value entries, for tables whose only key is a pattern
+  Map(MapField),
+  /// Vendor-defined data, edited by a vendor widget
+  Opaque { vendor_id: u32, type_id: u32 },
+  /// A code editor for shards or wires
+  CodeEditor { kind: CodeType },
+}
+
+fn number(limits: &Option<Limits>, integer: bool) -> UiHint {
+  match limits {
+    Some(limits) => UiHint::Slider {
+      integer,
+      limits: limits.clone(),
+    },
+    None => UiHint::Number { integer },
+  }
+}
+
+fn vector<const N: usize>(limits: &VectorLimits<N>, integer: bool) -> UiHint {
+  UiHint::Vector {
+    lanes: limits
+      .to_lanes()
+      .iter()
+      .map(|lane| number(lane, integer))
+      .collect(),
+  }
+}
+
+/// The widgets of alternative types, free-form JSON when any type is accepted.
+fn alternatives(types: &[VariableType]) -> Vec<UiHint> {
+  if types.is_empty() {
+    return Vec::from([UiHint::Json]);
+  }
+  types.iter().map(VariableType::ui_hint).collect()
+}
+
+/// The form of a table, a map when its only key is a pattern.
+fn form(keys: &[KeyPattern], types: &[Vec<VariableType>]) -> UiHint {
+  let (mut fields, mut maps) = (Vec::new(), Vec::new());
+  for (index, key) in keys.iter().enumerate() {
+    let hints = alternatives(types.get(index).map_or(&[][..], |t| &t[..]));
+    match key {
+      KeyPattern::Exact(key) => fields.push(FormField {
+        key: key.clone(),
+        hints,
+      }),
+      pattern => maps.push(MapField {
+        key_pattern: pattern.clone(),
+        hints,
+      }),
+    }
+  }
+  match (fields.is_empty(), maps.len()) {
+    (true, 1) => UiHint::Map(maps.remove(0)),
+    _ => UiHint::Form { fields, maps },
+  }
+}
+
+fn file_picker<T: Copy>(all: &[T], category: impl Fn(T) -> Categories) -> UiHint {
+  UiHint::FilePicker {
+    categories: all.iter().map(|sub| category(*sub)).collect(),
+  }
+}
+
+impl VariableType {
+  /// The widget suited to enter a value of the type.
+  ///
+  /// Limited numbers are sliders, colors use a color picker, images, audio, meshes and bytes pick
+  /// protos of the categories the type accepts (see [`VariableType::accepts_category`]) and code
+  /// uses a code editor. Sequences and tables nest the widgets of their types, the keys of tables
+  /// that are patterns (the empty key of `Table`) are edited as maps.
+  pub fn ui_hint(&self) -> UiHint {
+    match self {
+      Self::None | Self::Channel(_) | Self::Event(_) | Self::ChannelV2(_) | Self::EventV2(_) => {
+        UiHint::Hidden
+      }
+      Self::Any => UiHint::Json,
+      Self::Bool => UiHint::Toggle,
+      Self::Color => UiHint::ColorPicker,
+      Self::String(format) => UiHint::Text { format: *format },
+      Self::Bytes(Some(category)) => UiHint::FilePicker {
+        categories: Vec::from([Categories::Binary(*category)]),
+      },
+      Self::Bytes(None) => file_picker(BinaryCategories::ALL, Categories::Binary),
+      Self::Image => file_picker(TextureCategories::ALL, Categories::Texture),
+      Self::Audio => file_picker(AudioCategories::ALL, Categories::Audio),
+      Self::Mesh => file_picker(ModelCategories::ALL, Categories::Model),
+      Self::Enum { vendor_id, type_id } => UiHint::Dropdown {
+        vendor_id: *vendor_id,
+        type_id: *type_id,
+      },
+      Self::Int(limits) => number(limits, true),
+      Self::Int2(limits) => vector(limits, true),
+      Self::Int3(limits) => vector(limits, true),
+      Self::Int4(limits) => vector(limits, true),
+      Self::Int8(limits) => vector(limits, true),
+      Self::Int16(limits) => vector(limits, true),
+      Self::Float(limits) => number(limits, false),
+      Self::Float2(limits) => vector(limits, false),
+      Self::Float3(limits) => vector(limits, false),
+      Self::Float4(limits) => vector(limits, false),
+      Self::Seq {
+        types,
+        length_limits,
+      } => UiHint::List {
+        items: alternatives(types),
+        length_limits: length_limits.clone(),
+      },
+      Self::Table(table) => {
+        let table = TableInfoV2::from(table.clone());
+        form(&table.keys, &table.types)
+      }
+      Self::TableV2(table) => form(&table.keys, &table.types),
+      Self::Object { vendor_id, type_id } => UiHint::Opaque {
+        vendor_id: *vendor_id,
+        type_id: *type_id,
+      },
+      Self::Code(info) => UiHint::CodeEditor { kind: info.kind },
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::traits::{CodeInfo, TableInfo};
+
+  fn limits(min: i64, max: i64) -> Limits {
+    Limits { min, max, scale: 0 }
+  }
+
+  #[test]
+  fn hints() {
+    assert_eq!(VariableType::Bool.ui_hint(), UiHint::Toggle);
+    assert_eq!(VariableType::Color.ui_hint(), UiHint::ColorPicker);
+    assert_eq!(
+      VariableType::Int(Some(limits(0, 100))).ui_hint(),
+      UiHint::Slider {
+        integer: true,
+        limits: limits(0, 100),
+      }
+    );
+    assert_eq!(
+      VariableType::Float2(VectorLimits::PerLane([Some(limits(-1, 1)), None])).ui_hint(),
+      UiHint::Vector {
+        lanes: vec![
+          UiHint::Slider {
+            integer: false,
+            limits: limits(-1, 1),
+          },
+          UiHint::Number { integer: false },
+        ],
+      }
+    );
+    assert_eq!(
+      VariableType::Code(Box::default()).ui_hint(),
+      UiHint::CodeEditor {
+        kind: CodeInfo::default().kind,
+      }
+    );
+    assert_eq!(
+      VariableType::Channel(Box::new(VariableType::Bool)).ui_hint(),
+      UiHint::Hidden
+    );
+
+    let table = VariableType::Table(TableInfo {
+      keys: vec!["name".into(), "tags".into()],
+      types: vec![vec![VariableType::String(None)]],
+    });
+    assert_eq!(
+      table.ui_hint(),
+      UiHint::Form {
+        fields: vec![
+          FormField {
+            key: "name".into(),
+            hints: vec![UiHint::Text { format: None }],
+          },
+          FormField {
+            key: "tags".into(),
+            hints: vec![UiHint::Json],
+          },
+        ],
+        maps: vec![],
+      }
+    );
+  }
+
+  #[test]
+  fn maps() {
+    let any = VariableType::Table(TableInfo {
+      keys: vec!["".into()],
+      types: vec![vec![VariableType::Bool]],
+    });
+    assert_eq!(
+      any.ui_hint(),
+      UiHint::Map(MapField {
+        key_pattern: KeyPattern::Any,
+        hints: vec![UiHint::Toggle],
+      })
+    );
+
+    let mixed = VariableType::TableV2(TableInfoV2 {
+      keys: vec![
+        KeyPattern::Exact("name".into()),
+        KeyPattern::Prefix("tag_".into()),
+      ],
+      types: vec![vec![VariableType::String(None)], vec![VariableType::Bool]],
+    });
+    assert_eq!(
+      mixed.ui_hint(),
+      UiHint::Form {
+        fields: vec![FormField {
+          key: "name".into(),
+          hints: vec![UiHint::Text { format: None }],
+        }],
+        maps: vec![MapField {
+          key_pattern: KeyPattern::Prefix("tag_".into()),
+          hints: vec![UiHint::Toggle],
+        }],
+      }
+    );
+    assert_eq!(
+      serde_json::to_string(&any.ui_hint()).unwrap(),
+      r#"{"map":{"keyPattern":"any","hints":["toggle"]}}"#
+    );
+  }
+
+  #[test]
+  fn file_pickers() {
+    for type_ in [
+      VariableType::Image,
+      VariableType::Audio,
+      VariableType::Mesh,
+      VariableType::Bytes(None),
+      VariableType::Bytes(Some(BinaryCategories::OnnxModel)),
+    ] {
+      let UiHint::FilePicker { categories } = type_.ui_hint() else {
+        panic!("{:?} has no file picker", type_);
+      };
+      assert!(!categories.is_empty());
+      assert!(categories.iter().all(|c| type_.accepts_category(c)));
+    }
+
+    let json = serde_json::to_string(&VariableType::Image.ui_hint()).unwrap();
+    assert_eq!(
+      json,
+      r#"{"filePicker":{"categories":[{"texture":"pngFile"},{"texture":"jpgFile"}]}}"#
+    );
+    let json = serde_json::to_string(&UiHint::List {
+      items: vec![],
+      length_limits: None,
+    })
+    .unwrap();
+    assert_eq!(json, r#"{"list":{"items":[],"lengthLimits":null}}"#);
+  }
+}
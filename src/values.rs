@@ -0,0 +1,981 @@
+//! Values of the variables described by `VariableType`.
+
+use crate::traits::{Limits, VariableType};
+use parity_scale_codec::{Compact, Decode, Encode, Error, Input, Output};
+use scale_info::prelude::{collections::BTreeMap, string::String, vec::Vec};
+
+/// Nesting depth of sequences and tables allowed when decoding a `Value`.
+pub const MAX_VALUE_DEPTH: u32 = 32;
+
+/// A value of a variable, vector lanes use the same widths as the Shards runtime.
+///
+/// Images, audio and meshes are carried as `Bytes`, channels and events have no value (`None`).
+#[derive(Clone, PartialEq, Debug)]
+pub enum Value {
+  None,
+  Bool(bool),
+  Color([u8; 4]),
+  Bytes(Vec<u8>),
+  String(String),
+  Enum(u32),
+  Int(i64),
+  Int2([i64; 2]),
+  Int3([i32; 3]),
+  Int4([i32; 4]),
+  Int8([i16; 8]),
+  Int16([i8; 16]),
+  Float(f64),
+  Float2([f64; 2]),
+  Float3([f32; 3]),
+  Float4([f32; 4]),
+  Seq(Vec<Value>),
+  Table(BTreeMap<String, Value>),
+  /// Vendor-defined data of an `Object`
+  Object(Vec<u8>),
+  /// Serialized code
+  Code(Vec<u8>),
+}
+
+/// Errors returned when a value does not match a `VariableType`.
+#[derive(Clone, PartialEq, Debug, Eq)]
+pub enum ValueError {
+  /// The value is not of the expected type
+  TypeMismatch,
+  /// A number, or a lane of a vector, is out of the type limits
+  OutOfLimits,
+  /// A sequence length is out of the type length limits
+  LengthOutOfLimits,
+  /// A table has a key its type does not declare
+  UnknownKey(String),
+}
+
+impl core::fmt::Display for ValueError {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    match self {
+      Self::TypeMismatch => write!(f, "value does not match the type"),
+      Self::OutOfLimits => write!(f, "value out of the type limits"),
+      Self::LengthOutOfLimits => write!(f, "sequence length out of the type limits"),
+      Self::UnknownKey(key) => write!(f, "unknown table key {:?}", key),
+    }
+  }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ValueError {}
+
+fn int_within(limits: &Option<Limits>, value: i64) -> bool {
+  limits
+    .as_ref()
+    .is_none_or(|l| l.min <= value && value <= l.max)
+}
+
+pub(crate) fn float_within(limits: &Option<Limits>, value: f64) -> bool {
+  limits.as_ref().is_none_or(|l| {
+    // limits are fixed point values with `scale` decimals
+    let divisor = (0..l.scale).fold(1f64, |d, _| d * 10.0);
+    l.min as f64 / divisor <= value && value <= l.max as f64 / divisor
+  })
+}
+
+fn lanes<T: Copy>(
+  limits: &[Option<Limits>],
+  values: &[T],
+  within: impl Fn(&Option<Limits>, T) -> bool,
+) -> Result<(), ValueError> {
+  if limits.iter().zip(values).all(|(l, v)| within(l, *v)) {
+    Ok(())
+  } else {
+    Err(ValueError::OutOfLimits)
+  }
+}
+
+/// A value matches a list of alternative types if it matches any, an empty list accepts anything.
+pub(crate) fn check_any_of(value: &Value, types: &[VariableType]) -> Result<(), ValueError> {
+  if types.is_empty() {
+    return Ok(());
+  }
+  let mut error = ValueError::TypeMismatch;
+  for ty in types {
+    match value.check(ty) {
+      Ok(()) => return Ok(()),
+      // report the most specific error
+      Err(ValueError::TypeMismatch) => {}
+      Err(e) => error = e,
+    }
+  }
+  Err(error)
+}
+
+impl Value {
+  /// Checks that the value is of type `ty` and within its limits.
+  pub fn check(&self, ty: &VariableType) -> Result<(), ValueError> {
+    match (ty, self) {
+      (VariableType::Any, _) => Ok(()),
+      (VariableType::None, Value::None)
+      | (VariableType::Channel(_), Value::None)
+      | (VariableType::Event(_), Value::None)
+      | (VariableType::ChannelV2(_), Value::None)
+      | (VariableType::EventV2(_), Value::None)
+      | (VariableType::Bool, Value::Bool(_))
+      | (VariableType::Color, Value::Color(_))
+      | (VariableType::Bytes(_), Value::Bytes(_))
+      | (VariableType::Image, Value::Bytes(_))
+      | (VariableType::Audio, Value::Bytes(_))
+      | (VariableType::Mesh, Value::Bytes(_))
+      | (VariableType::String(_), Value::String(_))
+      | (VariableType::Enum { .. }, Value::Enum(_))
+      | (VariableType::Object { .. }, Value::Object(_))
+      | (VariableType::Code(_), Value::Code(_)) => Ok(()),
+      (VariableType::Int(limits), Value::Int(v)) => {
+        lanes(core::slice::from_ref(limits), &[*v], int_within)
+      }
+      (VariableType::Int2(limits), Value::Int2(v)) => lanes(&limits.to_lanes(), v, int_within),
+      (VariableType::Int3(limits), Value::Int3(v)) => {
+        lanes(&limits.to_lanes(), v, |l, v| int_within(l, v.into()))
+      }
+      (VariableType::Int4(limits), Value::Int4(v)) => {
+        lanes(&limits.to_lanes(), v, |l, v| int_within(l, v.into()))
+      }
+      (VariableType::Int8(limits), Value::Int8(v)) => {
+        lanes(&limits.to_lanes(), v, |l, v| int_within(l, v.into()))
+      }
+      (VariableType::Int16(limits), Value::Int16(v)) => {
+        lanes(&limits.to_lanes(), v, |l, v| int_within(l, v.into()))
+      }
+      (VariableType::Float(limits), Value::Float(v)) => {
+        lanes(core::slice::from_ref(limits), &[*v], float_within)
+      }
+      (VariableType::Float2(limits), Value::Float2(v)) => {
+        lanes(&limits.to_lanes(), v, float_within)
+      }
+      (VariableType::Float3(limits), Value::Float3(v)) => {
+        lanes(&limits.to_lanes(), v, |l, v| float_within(l, v.into()))
+      }
+      (VariableType::Float4(limits), Value::Float4(v)) => {
+        lanes(&limits.to_lanes(), v, |l, v| float_within(l, v.into()))
+      }
+      (
+        VariableType::Seq {
+          types,
+          length_limits,
+        },
+        Value::Seq(values),
+      ) => {
+        if !int_within(length_limits, values.len() as i64) {
+          return Err(ValueError::LengthOutOfLimits);
+        }
+        values.iter().try_for_each(|v| check_any_of(v, types))
+      }
+      (VariableType::Table(_) | VariableType::TableV2(_), Value::Table(values)) => {
+        values.iter().try_for_each(|(k, v)| {
+          let types = ty
+            .table_key_types(k)
+            .ok_or_else(|| ValueError::UnknownKey(k.clone()))?;
+          check_any_of(v, types)
+        })
+      }
+      _ => Err(ValueError::TypeMismatch),
+    }
+  }
+
+  /// The SCALE index of the variant.
+  fn variant_index(&self) -> u8 {
+    match self {
+      Value::None => 0,
+      Value::Bool(_) => 1,
+      Value::Color(_) => 2,
+      Value::Bytes(_) => 3,
+      Value::String(_) => 4,
+      Value::Enum(_) => 5,
+      Value::Int(_) => 6,
+      Value::Int2(_) => 7,
+      Value::Int3(_) => 8,
+      Value::Int4(_) => 9,
+      Value::Int8(_) => 10,
+      Value::Int16(_) => 11,
+      Value::Float(_) => 12,
+      Value::Float2(_) => 13,
+      Value::Float3(_) => 14,
+      Value::Float4(_) => 15,
+      Value::Seq(_) => 16,
+      Value::Table(_) => 17,
+      Value::Object(_) => 18,
+      Value::Code(_) => 19,
+    }
+  }
+}
+
+// SCALE has no floats, they are encoded as their IEEE 754 bits.
+
+fn f64_bits<const N: usize>(v: &[f64; N]) -> [u64; N] {
+  v.map(f64::to_bits)
+}
+
+fn f32_bits<const N: usize>(v: &[f32; N]) -> [u32; N] {
+  v.map(f32::to_bits)
+}
+
+/// Self-describing encoding: the variant index followed by the value.
+impl Encode for Value {
+  fn encode_to<W: Output + ?Sized>(&self, dest: &mut W) {
+    dest.push_byte(self.variant_index());
+    match self {
+      Value::None => {}
+      Value::Bool(v) => v.encode_to(dest),
+      Value::Color(v) => v.encode_to(dest),
+      Value::Bytes(v) | Value::Object(v) | Value::Code(v) => v.encode_to(dest),
+      Value::String(v) => v.encode_to(dest),
+      Value::Enum(v) => Compact(*v).encode_to(dest),
+      Value::Int(v) => v.encode_to(dest),
+      Value::Int2(v) => v.encode_to(dest),
+      Value::Int3(v) => v.encode_to(dest),
+      Value::Int4(v) => v.encode_to(dest),
+      Value::Int8(v) => v.encode_to(dest),
+      Value::Int16(v) => v.encode_to(dest),
+      Value::Float(v) => v.to_bits().encode_to(dest),
+      Value::Float2(v) => f64_bits(v).encode_to(dest),
+      Value::Float3(v) => f32_bits(v).encode_to(dest),
+      Value::Float4(v) => f32_bits(v).encode_to(dest),
+      Value::Seq(v) => v.encode_to(dest),
+      Value::Table(v) => v.encode_to(dest),
+    }
+  }
+}
+
+/// Decodes the elements of a sequence, reporting nesting and allocations to the input.
+fn decode_seq<I: Input>(
+  input: &mut I,
+  mut element: impl FnMut(&mut I) -> Result<Value, Error>,
+) -> Result<Vec<Value>, Error> {
+  let len = Compact::<u32>::decode(input)?.0;
+  input.descend_ref()?;
+  input.on_before_alloc_mem((len as usize).saturating_mul(core::mem::size_of::<Value>()))?;
+  let mut values = Vec::new();
+  for _ in 0..len {
+    values.push(element(input)?);
+  }
+  input.ascend_ref();
+  Ok(values)
+}
+
+/// Decodes the entries of a table, keys must be strictly ascending so every table has a single
+/// encoding.
+fn decode_table<I: Input>(
+  input: &mut I,
+  mut value: impl FnMut(&mut I, &String) -> Result<Value, Error>,
+) -> Result<BTreeMap<String, Value>, Error> {
+  let len = Compact::<u32>::decode(input)?.0;
+  input.descend_ref()?;
+  input
+    .on_before_alloc_mem((len as usize).saturating_mul(core::mem::size_of::<(String, Value)>()))?;
+  let mut values = BTreeMap::new();
+  let mut last: Option<String> = None;
+  for _ in 0..len {
+    let key = String::decode(input)?;
+    if last.as_ref().is_some_and(|last| *last >= key) {
+      return Err("Value table keys not in canonical order".into());
+    }
+    let entry = value(input, &key)?;
+    last = Some(key.clone());
+    values.insert(key, entry);
+  }
+  input.ascend_ref();
+  Ok(values)
+}
+
+fn decode_value<I: Input>(input: &mut I, depth: u32) -> Result<Value, Error> {
+  if depth > MAX_VALUE_DEPTH {
+    return Err("Value nested too deeply".into());
+  }
+  Ok(match input.read_byte()? {
+    0 => Value::None,
+    1 => Value::Bool(Decode::decode(input)?),
+    2 => Value::Color(Decode::decode(input)?),
+    3 => Value::Bytes(Decode::decode(input)?),
+    4 => Value::String(Decode::decode(input)?),
+    5 => Value::Enum(Compact::<u32>::decode(input)?.0),
+    6 => Value::Int(Decode::decode(input)?),
+    7 => Value::Int2(Decode::decode(input)?),
+    8 => Value::Int3(Decode::decode(input)?),
+    9 => Value::Int4(Decode::decode(input)?),
+    10 => Value::Int8(Decode::decode(input)?),
+    11 => Value::Int16(Decode::decode(input)?),
+    12 => Value::Float(f64::from_bits(Decode::decode(input)?)),
+    13 => Value::Float2(<[u64; 2]>::decode(input)?.map(f64::from_bits)),
+    14 => Value::Float3(<[u32; 3]>::decode(input)?.map(f32::from_bits)),
+    15 => Value::Float4(<[u32; 4]>::decode(input)?.map(f32::from_bits)),
+    16 => Value::Seq(decode_seq(input, |input| decode_value(input, depth + 1))?),
+    17 => Value::Table(decode_table(input, |input, _| {
+      decode_value(input, depth + 1)
+    })?),
+    18 => Value::Object(Decode::decode(input)?),
+    19 => Value::Code(Decode::decode(input)?),
+    _ => return Err("Invalid Value variant".into()),
+  })
+}
+
+impl Decode for Value {
+  fn decode<I: Input>(input: &mut I) -> Result<Self, Error> {
+    decode_value(input, 0)
+  }
+}
+
+// Schema-driven encoding: values of a known type are encoded without their variant index.
+//
+// Where several types are allowed (sequence elements, table values) the value is prefixed with
+// the index of its type when there is more than one, and self-describing when any type is.
+
+fn encode_alternatives<W: Output + ?Sized>(
+  value: &Value,
+  types: &[VariableType],
+  dest: &mut W,
+) -> Result<(), ValueError> {
+  match types {
+    [] => {
+      value.encode_to(dest);
+      Ok(())
+    }
+    [ty] => value.encode_typed_to(ty, dest),
+    types => {
+      let index = types
+        .iter()
+        .position(|ty| value.check(ty).is_ok())
+        .ok_or(ValueError::TypeMismatch)?;
+      dest.push_byte(index as u8);
+      value.encode_typed_to(&types[index], dest)
+    }
+  }
+}
+
+fn decode_alternatives<I: Input>(
+  types: &[VariableType],
+  input: &mut I,
+  depth: u32,
+) -> Result<Value, Error> {
+  match types {
+    [] => decode_value(input, depth),
+    [ty] => decode_typed(ty, input, depth),
+    types => {
+      let ty = types
+        .get(input.read_byte()? as usize)
+        .ok_or("Invalid Value type index")?;
+      decode_typed(ty, input, depth)
+    }
+  }
+}
+
+impl Value {
+  /// Encodes the value as a value of type `ty`, without describing its type.
+  ///
+  /// The value is checked against `ty` first, as the encoding is only meaningful to holders of
+  /// the same type.
+  pub fn encode_typed(&self, ty: &VariableType) -> Result<Vec<u8>, ValueError> {
+    self.check(ty)?;
+    let mut dest = Vec::new();
+    self.encode_typed_to(ty, &mut dest)?;
+    Ok(dest)
+  }
+
+  pub(crate) fn encode_typed_to<W: Output + ?Sized>(
+    &self,
+    ty: &VariableType,
+    dest: &mut W,
+  ) -> Result<(), ValueError> {
+    match (ty, self) {
+      (VariableType::Any, value) => value.encode_to(dest),
+      (VariableType::Seq { types, .. }, Value::Seq(values)) => {
+        Compact(values.len() as u32).encode_to(dest);
+        for value in values {
+          encode_alternatives(value, types, dest)?;
+        }
+      }
+      (VariableType::Table(_) | VariableType::TableV2(_), Value::Table(values)) => {
+        Compact(values.len() as u32).encode_to(dest);
+        for (key, value) in values {
+          key.encode_to(dest);
+          let types = ty
+            .table_key_types(key)
+            .ok_or_else(|| ValueError::UnknownKey(key.clone()))?;
+          encode_alternatives(value, types, dest)?;
+        }
+      }
+      (_, Value::Seq(_)) | (_, Value::Table(_)) => return Err(ValueError::TypeMismatch),
+      // every other value has the same payload in both encodings
+      (_, value) => {
+        let mut encoded = value.encode();
+        encoded.remove(0);
+        dest.write(&encoded);
+      }
+    }
+    Ok(())
+  }
+
+  /// Decodes a value of type `ty` encoded by [`Value::encode_typed`].
+  pub fn decode_typed(ty: &VariableType, input: &mut &[u8]) -> Result<Value, Error> {
+    decode_typed(ty, input, 0)
+  }
+}
+
+pub(crate) fn decode_typed<I: Input>(
+  ty: &VariableType,
+  input: &mut I,
+  depth: u32,
+) -> Result<Value, Error> {
+  if depth > MAX_VALUE_DEPTH {
+    return Err("Value nested too deeply".into());
+  }
+  let index = match ty {
+    VariableType::Any => return decode_value(input, depth),
+    VariableType::None
+    | VariableType::Channel(_)
+    | VariableType::Event(_)
+    | VariableType::ChannelV2(_)
+    | VariableType::EventV2(_) => 0,
+    VariableType::Bool => 1,
+    VariableType::Color => 2,
+    VariableType::Bytes(_) | VariableType::Image | VariableType::Audio | VariableType::Mesh => 3,
+    VariableType::String(_) => 4,
+    VariableType::Enum { .. } => 5,
+    VariableType::Int(_) => 6,
+    VariableType::Int2(_) => 7,
+    VariableType::Int3(_) => 8,
+    VariableType::Int4(_) => 9,
+    VariableType::Int8(_) => 10,
+    VariableType::Int16(_) => 11,
+    VariableType::Float(_) => 12,
+    VariableType::Float2(_) => 13,
+    VariableType::Float3(_) => 14,
+    VariableType::Float4(_) => 15,
+    VariableType::Seq { types, .. } => {
+      return Ok(Value::Seq(decode_seq(input, |input| {
+        decode_alternatives(types, input, depth + 1)
+      })?))
+    }
+    VariableType::Table(_) | VariableType::TableV2(_) => {
+      return Ok(Value::Table(decode_table(input, |input, key| {
+        let types = ty.table_key_types(key).ok_or("Unknown Value table key")?;
+        decode_alternatives(types, input, depth + 1)
+      })?))
+    }
+    VariableType::Object { .. } => 18,
+    VariableType::Code(_) => 19,
+  };
+  // the payload is the same as the self-describing one
+  decode_value(
+    &mut PrefixedInput {
+      prefix: Some(index),
+      input,
+    },
+    depth,
+  )
+}
+
+/// Feeds a variant index before the actual input, to reuse the self-describing decoder.
+struct PrefixedInput<'a, I> {
+  prefix: Option<u8>,
+  input: &'a mut I,
+}
+
+impl<I: Input> Input for PrefixedInput<'_, I> {
+  fn remaining_len(&mut self) -> Result<Option<usize>, Error> {
+    Ok(
+      self
+        .input
+        .remaining_len()?
+        .map(|len| len + self.prefix.is_some() as usize),
+    )
+  }
+
+  fn read(&mut self, into: &mut [u8]) -> Result<(), Error> {
+    match (self.prefix.take(), into.split_first_mut()) {
+      (Some(prefix), Some((first, rest))) => {
+        *first = prefix;
+        self.input.read(rest)
+      }
+      (prefix, _) => {
+        self.prefix = prefix;
+        self.input.read(into)
+      }
+    }
+  }
+
+  fn descend_ref(&mut self) -> Result<(), Error> {
+    self.input.descend_ref()
+  }
+
+  fn ascend_ref(&mut self) {
+    self.input.ascend_ref()
+  }
+
+  fn on_before_alloc_mem(&mut self, size: usize) -> Result<(), Error> {
+    self.input.on_before_alloc_mem(size)
+  }
+}
+
+macro_rules! impl_try_from_value {
+  ($($variant:ident => $type:ty),* $(,)?) => {
+    $(
+      impl TryFrom<&Value> for $type {
+        type Error = ValueError;
+
+        fn try_from(value: &Value) -> Result<Self, ValueError> {
+          match value {
+            Value::$variant(v) => Ok(v.clone()),
+            _ => Err(ValueError::TypeMismatch),
+          }
+        }
+      }
+    )*
+  };
+}
+
+impl_try_from_value! {
+  Bool => bool,
+  Color => [u8; 4],
+  Int => i64,
+  Int2 => [i64; 2],
+  Int3 => [i32; 3],
+  Int4 => [i32; 4],
+  Int8 => [i16; 8],
+  Int16 => [i8; 16],
+  Float => f64,
+  Float2 => [f64; 2],
+  Float3 => [f32; 3],
+  Float4 => [f32; 4],
+  Bytes => Vec<u8>,
+}
+
+// under no_std `String` is `Vec<u8>`, already converted from `Bytes`
+#[cfg(feature = "std")]
+impl_try_from_value! {
+  String => String,
+}
+
+#[cfg(feature = "std")]
+mod json {
+  use super::*;
+  use crate::hex::HexBytes;
+  use serde_json::Value as Json;
+
+  fn int(json: &Json) -> Result<i64, ValueError> {
+    json.as_i64().ok_or(ValueError::TypeMismatch)
+  }
+
+  fn float(json: &Json) -> Result<f64, ValueError> {
+    json.as_f64().ok_or(ValueError::TypeMismatch)
+  }
+
+  /// A JSON array of exactly `N` elements.
+  fn array<T: Copy + Default, const N: usize>(
+    json: &Json,
+    lane: impl Fn(&Json) -> Result<T, ValueError>,
+  ) -> Result<[T; N], ValueError> {
+    let items = json.as_array().ok_or(ValueError::TypeMismatch)?;
+    if items.len() != N {
+      return Err(ValueError::TypeMismatch);
+    }
+    let mut lanes = [T::default(); N];
+    for (lane_value, item) in lanes.iter_mut().zip(items) {
+      *lane_value = lane(item)?;
+    }
+    Ok(lanes)
+  }
+
+  /// An integer lane narrower than 64 bits.
+  fn narrow<T: TryFrom<i64>>(json: &Json) -> Result<T, ValueError> {
+    T::try_from(int(json)?).map_err(|_| ValueError::OutOfLimits)
+  }
+
+  /// Bytes are written as a `0x` prefixed hex string or an array of bytes.
+  fn bytes(json: &Json) -> Result<Vec<u8>, ValueError> {
+    match json {
+      Json::String(hex) => {
+        let hex = hex.strip_prefix("0x").ok_or(ValueError::TypeMismatch)?;
+        if hex.len() % 2 != 0 {
+          return Err(ValueError::TypeMismatch);
+        }
+        // by bytes, slicing the string could split a multi-byte character
+        let nibble = |c: u8| (c as char).to_digit(16).ok_or(ValueError::TypeMismatch);
+        hex
+          .as_bytes()
+          .chunks(2)
+          .map(|pair| Ok((nibble(pair[0])? << 4 | nibble(pair[1])?) as u8))
+          .collect()
+      }
+      Json::Array(items) => items.iter().map(narrow::<u8>).collect(),
+      _ => Err(ValueError::TypeMismatch),
+    }
+  }
+
+  fn alternatives(json: &Json, types: &[VariableType]) -> Result<Value, ValueError> {
+    if types.is_empty() {
+      return Value::from_json(&VariableType::Any, json);
+    }
+    let mut error = ValueError::TypeMismatch;
+    for ty in types {
+      match Value::from_json(ty, json) {
+        Ok(value) => return Ok(value),
+        Err(ValueError::TypeMismatch) => {}
+        Err(e) => error = e,
+      }
+    }
+    Err(error)
+  }
+
+  impl Value {
+    /// Reads a value of type `ty` from its human-readable JSON form and checks it against `ty`.
+    ///
+    /// Numbers and booleans map to JSON numbers and booleans, vectors and colors to arrays, bytes
+    /// (and images, audio, meshes, objects and code) to `0x` hex strings, sequences to arrays,
+    /// tables to objects and `None`, channels and events to `null`. Values of type `Any` are
+    /// inferred from the JSON.
+    pub fn from_json(ty: &VariableType, json: &Json) -> Result<Value, ValueError> {
+      let value = match ty {
+        VariableType::Any => match json {
+          Json::Null => Value::None,
+          Json::Bool(b) => Value::Bool(*b),
+          Json::Number(n) => match n.as_i64() {
+            Some(i) => Value::Int(i),
+            None => Value::Float(float(json)?),
+          },
+          Json::String(s) => Value::String(s.clone()),
+          Json::Array(items) => Value::Seq(
+            items
+              .iter()
+              .map(|item| Value::from_json(ty, item))
+              .collect::<Result<_, _>>()?,
+          ),
+          Json::Object(entries) => Value::Table(
+            entries
+              .iter()
+              .map(|(k, v)| Ok((k.clone(), Value::from_json(ty, v)?)))
+              .collect::<Result<_, ValueError>>()?,
+          ),
+        },
+        VariableType::None
+        | VariableType::Channel(_)
+        | VariableType::Event(_)
+        | VariableType::ChannelV2(_)
+        | VariableType::EventV2(_) => match json {
+          Json::Null => Value::None,
+          _ => return Err(ValueError::TypeMismatch),
+        },
+        VariableType::Bool => Value::Bool(json.as_bool().ok_or(ValueError::TypeMismatch)?),
+        VariableType::Color => Value::Color(array(json, narrow::<u8>)?),
+        VariableType::Bytes(_) | VariableType::Image | VariableType::Audio | VariableType::Mesh => {
+          Value::Bytes(bytes(json)?)
+        }
+        VariableType::Object { .. } => Value::Object(bytes(json)?),
+        VariableType::Code(_) => Value::Code(bytes(json)?),
+        VariableType::String(_) => {
+          Value::String(json.as_str().ok_or(ValueError::TypeMismatch)?.to_string())
+        }
+        VariableType::Enum { .. } => Value::Enum(narrow(json)?),
+        VariableType::Int(_) => Value::Int(int(json)?),
+        VariableType::Int2(_) => Value::Int2(array(json, int)?),
+        VariableType::Int3(_) => Value::Int3(array(json, narrow)?),
+        VariableType::Int4(_) => Value::Int4(array(json, narrow)?),
+        VariableType::Int8(_) => Value::Int8(array(json, narrow)?),
+        VariableType::Int16(_) => Value::Int16(array(json, narrow)?),
+        VariableType::Float(_) => Value::Float(float(json)?),
+        VariableType::Float2(_) => Value::Float2(array(json, float)?),
+        VariableType::Float3(_) => Value::Float3(array(json, |j| float(j).map(|f| f as f32))?),
+        VariableType::Float4(_) => Value::Float4(array(json, |j| float(j).map(|f| f as f32))?),
+        VariableType::Seq { types, .. } => Value::Seq(
+          json
+            .as_array()
+            .ok_or(ValueError::TypeMismatch)?
+            .iter()
+            .map(|item| alternatives(item, types))
+            .collect::<Result<_, _>>()?,
+        ),
+        VariableType::Table(_) | VariableType::TableV2(_) => Value::Table(
+          json
+            .as_object()
+            .ok_or(ValueError::TypeMismatch)?
+            .iter()
+            .map(|(key, item)| {
+              let types = ty
+                .table_key_types(key)
+                .ok_or_else(|| ValueError::UnknownKey(key.clone()))?;
+              Ok((key.clone(), alternatives(item, types)?))
+            })
+            .collect::<Result<_, ValueError>>()?,
+        ),
+      };
+      value.check(ty)?;
+      Ok(value)
+    }
+  }
+
+  fn hex(bytes: &[u8]) -> Json {
+    Json::String(HexBytes(bytes).to_string())
+  }
+
+  fn floats(lanes: impl IntoIterator<Item = f64>) -> Json {
+    Json::Array(lanes.into_iter().map(Json::from).collect())
+  }
+
+  impl Value {
+    /// The human-readable JSON form of the value, as read by [`Value::from_json`].
+    ///
+    /// Non-finite floats have no JSON representation and become `null`.
+    pub fn to_json(&self) -> Json {
+      match self {
+        Value::None => Json::Null,
+        Value::Bool(b) => Json::Bool(*b),
+        Value::Color(c) => Json::from(c.to_vec()),
+        Value::Bytes(b) | Value::Object(b) | Value::Code(b) => hex(b),
+        Value::String(s) => Json::String(s.clone()),
+        Value::Enum(e) => Json::from(*e),
+        Value::Int(i) => Json::from(*i),
+        Value::Int2(v) => Json::from(v.to_vec()),
+        Value::Int3(v) => Json::from(v.to_vec()),
+        Value::Int4(v) => Json::from(v.to_vec()),
+        Value::Int8(v) => Json::from(v.to_vec()),
+        Value::Int16(v) => Json::from(v.to_vec()),
+        Value::Float(f) => Json::from(*f),
+        Value::Float2(v) => floats(*v),
+        Value::Float3(v) => floats(v.map(f64::from)),
+        Value::Float4(v) => floats(v.map(f64::from)),
+        Value::Seq(values) => Json::Array(values.iter().map(Value::to_json).collect()),
+        Value::Table(values) => Json::Object(
+          values
+            .iter()
+            .map(|(k, v)| (k.clone(), v.to_json()))
+            .collect(),
+        ),
+      }
+    }
+  }
+
+  /// Decodes SCALE bytes of a value of type `ty` (see [`Value::encode_typed`]) into its JSON
+  /// form, e.g. to render `VariableTypeInfo::default`.
+  pub fn decode_to_json(ty: &VariableType, bytes: &[u8]) -> Result<Json, Error> {
+    let mut input = bytes;
+    let value = Value::decode_typed(ty, &mut input)?;
+    if !input.is_empty() {
+      return Err("Trailing Value bytes".into());
+    }
+    Ok(value.to_json())
+  }
+
+  /// Encodes the JSON form of a value of type `ty` into the SCALE bytes the runtime expects
+  /// (see [`Value::encode_typed`]), e.g. to fill `VariableTypeInfo::default`.
+  pub fn encode_json(ty: &VariableType, json: &Json) -> Result<Vec<u8>, ValueError> {
+    Value::from_json(ty, json)?.encode_typed(ty)
+  }
+}
+
+#[cfg(feature = "std")]
+pub use json::{decode_to_json, encode_json};
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::traits::TableInfo;
+
+  fn limits(min: i64, max: i64, scale: u32) -> Option<Limits> {
+    Some(Limits { min, max, scale })
+  }
+
+  #[test]
+  fn check_types() {
+    assert_eq!(Value::Bool(true).check(&VariableType::Bool), Ok(()));
+    assert_eq!(Value::Bool(true).check(&VariableType::Any), Ok(()));
+    assert_eq!(
+      Value::Int(1).check(&VariableType::Float(None)),
+      Err(ValueError::TypeMismatch)
+    );
+    assert_eq!(
+      Value::Int2([0, 11]).check(&VariableType::Int2([None, limits(0, 10, 0)].into())),
+      Err(ValueError::OutOfLimits)
+    );
+    // 1.5 with one decimal is 15
+    let float = VariableType::Float(limits(0, 15, 1));
+    assert_eq!(Value::Float(1.5).check(&float), Ok(()));
+    assert_eq!(
+      Value::Float(1.6).check(&float),
+      Err(ValueError::OutOfLimits)
+    );
+
+    let seq = VariableType::Seq {
+      types: vec![VariableType::Bool, VariableType::Int(limits(0, 1, 0))],
+      length_limits: limits(0, 2, 0),
+    };
+    assert_eq!(
+      Value::Seq(vec![Value::Bool(false), Value::Int(1)]).check(&seq),
+      Ok(())
+    );
+    assert_eq!(
+      Value::Seq(vec![Value::Int(2)]).check(&seq),
+      Err(ValueError::OutOfLimits)
+    );
+    assert_eq!(
+      Value::Seq(vec![Value::None; 3]).check(&seq),
+      Err(ValueError::LengthOutOfLimits)
+    );
+
+    let table = VariableType::Table(TableInfo {
+      keys: vec!["name".to_string()],
+      types: vec![vec![VariableType::String(None)]],
+    });
+    let mut values = BTreeMap::new();
+    values.insert("name".to_string(), Value::String("frag".to_string()));
+    assert_eq!(Value::Table(values.clone()).check(&table), Ok(()));
+    values.insert("other".to_string(), Value::None);
+    assert_eq!(
+      Value::Table(values).check(&table),
+      Err(ValueError::UnknownKey("other".to_string()))
+    );
+  }
+
+  #[test]
+  fn encoding() {
+    let mut table = BTreeMap::new();
+    table.insert("b".to_string(), Value::Float3([1.0, -2.5, 0.0]));
+    table.insert("a".to_string(), Value::Seq(vec![Value::Int16([-1; 16])]));
+    let value = Value::Table(table);
+    let encoded = value.encode();
+    assert_eq!(Value::decode(&mut &encoded[..]).unwrap(), value);
+
+    assert_eq!(Value::Float(1.0).encode()[1..], 1f64.to_bits().encode()[..]);
+
+    // keys out of order
+    let mut unordered = vec![17, 8];
+    unordered.extend(("b".to_string(), Value::None).encode());
+    unordered.extend(("a".to_string(), Value::None).encode());
+    assert!(Value::decode(&mut &unordered[..]).is_err());
+
+    let mut deep = [16, 4].repeat(MAX_VALUE_DEPTH as usize + 1);
+    deep.push(0);
+    assert!(Value::decode(&mut &deep[..]).is_err());
+  }
+
+  #[test]
+  fn typed_encoding() {
+    let seq = VariableType::Seq {
+      types: vec![VariableType::Bool, VariableType::Int(None)],
+      length_limits: None,
+    };
+    let value = Value::Seq(vec![Value::Int(-1), Value::Bool(true)]);
+    let encoded = value.encode_typed(&seq).unwrap();
+    let mut expected = vec![8, 1];
+    expected.extend((-1i64).encode());
+    expected.extend([0, 1]);
+    assert_eq!(encoded, expected);
+    assert_eq!(Value::decode_typed(&seq, &mut &encoded[..]).unwrap(), value);
+
+    // no type index for a single type, self-describing values for Any
+    let color = Value::Color([1, 2, 3, 4]);
+    assert_eq!(
+      color.encode_typed(&VariableType::Color).unwrap(),
+      vec![1, 2, 3, 4]
+    );
+    assert_eq!(
+      color.encode_typed(&VariableType::Any).unwrap(),
+      color.encode()
+    );
+
+    let table = VariableType::Table(TableInfo {
+      keys: vec!["".to_string()],
+      types: vec![vec![VariableType::Float2(Default::default())]],
+    });
+    let mut values = BTreeMap::new();
+    values.insert("x".to_string(), Value::Float2([0.5, 1.0]));
+    let value = Value::Table(values);
+    let encoded = value.encode_typed(&table).unwrap();
+    assert_eq!(encoded.len(), 1 + 2 + 16);
+    assert_eq!(
+      Value::decode_typed(&table, &mut &encoded[..]).unwrap(),
+      value
+    );
+
+    assert_eq!(
+      Value::Bool(true).encode_typed(&VariableType::Int(None)),
+      Err(ValueError::TypeMismatch)
+    );
+  }
+
+  #[cfg(feature = "std")]
+  #[test]
+  fn json_to_scale() {
+    use serde_json::json;
+
+    assert_eq!(
+      encode_json(&VariableType::Int3(Default::default()), &json!([1, -2, 3])).unwrap(),
+      [1i32, -2, 3].encode()
+    );
+    assert_eq!(
+      encode_json(&VariableType::Bytes(None), &json!("0x0aff")).unwrap(),
+      vec![0x0au8, 0xff].encode()
+    );
+    assert_eq!(
+      encode_json(&VariableType::Bytes(None), &json!("0x€a")),
+      Err(ValueError::TypeMismatch)
+    );
+    assert_eq!(
+      encode_json(
+        &VariableType::Int16(Default::default()),
+        &serde_json::Value::from(vec![128; 16])
+      ),
+      Err(ValueError::OutOfLimits)
+    );
+    assert_eq!(
+      encode_json(&VariableType::Float2(Default::default()), &json!([1.0])),
+      Err(ValueError::TypeMismatch)
+    );
+
+    let seq = VariableType::Seq {
+      types: vec![VariableType::Bool, VariableType::String(None)],
+      length_limits: None,
+    };
+    let value = Value::from_json(&seq, &json!([true, "a"])).unwrap();
+    assert_eq!(
+      value,
+      Value::Seq(vec![Value::Bool(true), Value::String("a".to_string())])
+    );
+    assert_eq!(
+      encode_json(&seq, &json!([true, "a"])).unwrap(),
+      value.encode_typed(&seq).unwrap()
+    );
+
+    let any = Value::from_json(&VariableType::Any, &json!({"a": [1, 0.5, null]})).unwrap();
+    let mut table = BTreeMap::new();
+    table.insert(
+      "a".to_string(),
+      Value::Seq(vec![Value::Int(1), Value::Float(0.5), Value::None]),
+    );
+    assert_eq!(any, Value::Table(table));
+  }
+
+  #[cfg(feature = "std")]
+  #[test]
+  fn scale_to_json() {
+    use serde_json::json;
+
+    let ty = VariableType::Seq {
+      types: vec![
+        VariableType::Float3(Default::default()),
+        VariableType::Bytes(None),
+        VariableType::Table(TableInfo {
+          keys: vec!["id".to_string()],
+          types: vec![vec![VariableType::Enum {
+            vendor_id: 1,
+            type_id: 2,
+          }]],
+        }),
+      ],
+      length_limits: None,
+    };
+    let json = json!([[0.5, 1.0, -2.0], "0x00ff", {"id": 3}]);
+    let bytes = encode_json(&ty, &json).unwrap();
+    assert_eq!(decode_to_json(&ty, &bytes).unwrap(), json);
+
+    assert!(decode_to_json(&ty, &bytes[..bytes.len() - 1]).is_err());
+    let mut trailing = bytes;
+    trailing.push(0);
+    assert!(decode_to_json(&ty, &trailing).is_err());
+    assert_eq!(Value::Float(f64::NAN).to_json(), serde_json::Value::Null);
+  }
+}